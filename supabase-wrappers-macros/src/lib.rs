@@ -89,12 +89,12 @@ pub fn wrappers_fdw(attr: TokenStream, item: TokenStream) -> TokenStream {
 
             #[pg_extern(create_or_replace)]
             fn #fn_ident() -> supabase_wrappers::FdwRoutine {
-                #ident::fdw_routine()
+                <#ident as ForeignDataWrapper<super::#error_type_ident>>::fdw_routine()
             }
 
             #[pg_extern(create_or_replace)]
             fn #fn_validator_ident(options: Vec<Option<String>>, catalog: Option<pg_sys::Oid>) {
-                #ident::validator(options, catalog)
+                <#ident as ForeignDataWrapper<super::#error_type_ident>>::validator(options, catalog)
                     .map_err(|e| <super::#error_type_ident as Into<ErrorReport>>::into(e))
                     .unwrap_or_report();
             }
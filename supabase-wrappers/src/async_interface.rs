@@ -0,0 +1,490 @@
+//! Provides async interface trait to develop Postgres foreign data wrapper
+//!
+
+use crate::instance::ForeignServer;
+use crate::interface::{
+    Cell, Column, ConflictAction, ForeignDataWrapper, Limit, Qual, Row, ScanCapabilities, Sort,
+};
+use crate::interrupt::{Interrupted, block_on_interruptible};
+use crate::utils::{CreateRuntimeError, ReportableError};
+use pgrx::pg_sys::{Oid, panic::ErrorReport};
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+
+// the async runtime shared by all async FDW instances in this backend
+static ASYNC_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Get the async runtime owned by Wrappers
+///
+/// The runtime is created on first use and lives as long as the Postgres
+/// backend. It has one worker thread, so tasks spawned on it, like fetching
+/// the next page of results, keep making progress while Postgres is consuming
+/// the current rows.
+///
+/// Spawned tasks run outside the Postgres backend thread and must not call
+/// any Postgres functions.
+pub fn async_runtime() -> Result<&'static Runtime, CreateRuntimeError> {
+    if let Some(rt) = ASYNC_RUNTIME.get() {
+        return Ok(rt);
+    }
+    let rt = Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    Ok(ASYNC_RUNTIME.get_or_init(|| rt))
+}
+
+// run a future to completion on the Wrappers async runtime, the future is
+// dropped if the statement is canceled
+fn block_on<T, E: From<Interrupted>>(future: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    block_on_interruptible(async_runtime().report_unwrap(), future)?
+}
+
+/// A queue of rows fetched by [`next_batch`](AsyncForeignDataWrapper::next_batch)
+/// but not yet returned to Postgres
+///
+/// The FDW owns the batch and exposes it through
+/// [`row_batch`](AsyncForeignDataWrapper::row_batch), Wrappers fills and
+/// drains it during the scan.
+#[derive(Debug, Clone, Default)]
+pub struct RowBatch {
+    rows: VecDeque<Row>,
+}
+
+impl RowBatch {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append rows to the end of this batch
+    pub fn extend(&mut self, rows: Vec<Row>) {
+        self.rows.extend(rows);
+    }
+
+    /// Take the first row from this batch
+    pub fn pop(&mut self) -> Option<Row> {
+        self.rows.pop_front()
+    }
+
+    /// Return the number of rows in this batch
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Check if this batch has no rows
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Remove all rows from this batch
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+}
+
+/// The async Foreign Data Wrapper trait
+///
+/// This is the async counterpart of [`ForeignDataWrapper`]. Instead of
+/// returning one row at a time, the FDW returns rows in batches, e.g. one page
+/// of an API response, and Wrappers drives the futures on its own runtime, so
+/// the FDW doesn't need to create a [`Runtime`] and call `block_on` by itself.
+///
+/// Required functions are listed below, all the others are optional.
+///
+/// 1. new
+/// 2. row_batch
+/// 3. begin_scan
+/// 4. next_batch
+/// 5. end_scan
+///
+/// Every type implements this trait also implements [`ForeignDataWrapper`]
+/// through a blanket adapter, so it can be used with the
+/// [`wrappers_fdw`](crate::wrappers_fdw) macro in the same way. Because both
+/// traits have methods with the same names, use the fully qualified syntax,
+/// e.g. `AsyncForeignDataWrapper::end_scan(self)`, if you need to call them
+/// directly.
+///
+/// Futures are driven on the backend thread one at a time, so they don't
+/// need to be `Send`. They are dropped when the statement is canceled or
+/// timed out, and an [`Interrupted`] error is returned instead, that's why
+/// the error type must be convertible from it.
+#[allow(async_fn_in_trait)]
+pub trait AsyncForeignDataWrapper<E: Into<ErrorReport> + From<Interrupted>> {
+    /// Create a FDW instance
+    ///
+    /// See [`ForeignDataWrapper::new`] for more details.
+    fn new(server: ForeignServer) -> Result<Self, E>
+    where
+        Self: Sized;
+
+    /// Return the batch used to buffer rows between [`next_batch`](Self::next_batch)
+    /// calls
+    ///
+    /// Usually this is just a [`RowBatch`] field in the FDW struct.
+    fn row_batch(&mut self) -> &mut RowBatch;
+
+    /// Obtain relation size estimates for a foreign table
+    ///
+    /// See [`ForeignDataWrapper::get_rel_size`] for more details.
+    async fn get_rel_size(
+        &mut self,
+        _quals: &[Qual],
+        _columns: &[Column],
+        _sorts: &[Sort],
+        _limit: &Option<Limit>,
+        _options: &HashMap<String, String>,
+    ) -> Result<(i64, i32), E> {
+        Ok((0, 0))
+    }
+
+    /// Declare which of the pushed down quals, sorts and limit are honored
+    ///
+    /// See [`ForeignDataWrapper::scan_capabilities`] for more details.
+    fn scan_capabilities(
+        &mut self,
+        _quals: &[Qual],
+        _columns: &[Column],
+        _sorts: &[Sort],
+        _limit: &Option<Limit>,
+        _options: &HashMap<String, String>,
+    ) -> Result<ScanCapabilities, E> {
+        Ok(ScanCapabilities::default())
+    }
+
+    /// Called when begin executing a foreign scan
+    ///
+    /// See [`ForeignDataWrapper::begin_scan`] for more details.
+    async fn begin_scan(
+        &mut self,
+        quals: &[Qual],
+        columns: &[Column],
+        sorts: &[Sort],
+        limit: &Option<Limit>,
+        options: &HashMap<String, String>,
+    ) -> Result<(), E>;
+
+    /// Called when fetch next batch of rows from the foreign source
+    ///
+    /// Return `None` if there are no more rows to read. An empty batch doesn't
+    /// stop the scan, this function will be called again.
+    ///
+    /// This function is only called when all rows in the previous batch have
+    /// been consumed by Postgres. To overlap network I/O with row processing,
+    /// spawn the request for the next page on [`async_runtime`] before
+    /// returning the current batch, and await its handle in the next call.
+    async fn next_batch(&mut self) -> Result<Option<Vec<Row>>, E>;
+
+    /// Called when restart the scan from the beginning
+    ///
+    /// Rows left in the batch are discarded before this function is called.
+    async fn re_scan(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when end the scan
+    async fn end_scan(&mut self) -> Result<(), E>;
+
+    /// Called when begin executing a foreign table modification operation
+    ///
+    /// See [`ForeignDataWrapper::begin_modify`] for more details.
+    async fn begin_modify(&mut self, _options: &HashMap<String, String>) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when insert one row into the foreign table
+    async fn insert(&mut self, _row: &Row) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Obtain the max number of rows inserted by one batch
+    ///
+    /// See [`ForeignDataWrapper::get_modify_batch_size`] for more details.
+    fn get_modify_batch_size(&mut self) -> usize {
+        1
+    }
+
+    /// Called when insert a batch of rows into the foreign table
+    ///
+    /// See [`ForeignDataWrapper::insert_batch`] for more details.
+    async fn insert_batch(&mut self, rows: &[Row]) -> Result<(), E> {
+        for row in rows {
+            AsyncForeignDataWrapper::insert(self, row).await?;
+        }
+        Ok(())
+    }
+
+    /// Check if this FDW supports `INSERT ... ON CONFLICT`
+    ///
+    /// See [`ForeignDataWrapper::upsert_supported`] for more details.
+    fn upsert_supported() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Called when insert one row with an `ON CONFLICT` clause into the
+    /// foreign table
+    ///
+    /// See [`ForeignDataWrapper::upsert`] for more details.
+    async fn upsert(
+        &mut self,
+        _row: &Row,
+        _conflict_columns: &[String],
+        _action: &ConflictAction,
+    ) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when update one row into the foreign table
+    async fn update(&mut self, _rowid: &Cell, _new_row: &Row) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when delete one row into the foreign table
+    async fn delete(&mut self, _rowid: &Cell) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when end the table update
+    async fn end_modify(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Check if this FDW supports transaction hooks
+    ///
+    /// See [`ForeignDataWrapper::transaction_hooks_supported`] for more details.
+    fn transaction_hooks_supported(&self) -> bool {
+        false
+    }
+
+    /// Called before the transaction which modified the foreign table commits
+    ///
+    /// See [`ForeignDataWrapper::pre_commit`] for more details.
+    async fn pre_commit(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when the transaction which modified the foreign table aborts
+    ///
+    /// See [`ForeignDataWrapper::abort`] for more details.
+    async fn abort(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when a remote call is interrupted by query cancellation
+    ///
+    /// See [`ForeignDataWrapper::cancel`] for more details.
+    async fn cancel(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Obtain a list of foreign table creation commands
+    ///
+    /// See [`ForeignDataWrapper::import_foreign_schema`] for more details.
+    async fn import_foreign_schema(
+        &mut self,
+        _stmt: crate::import_foreign_schema::ImportForeignSchemaStmt,
+    ) -> Result<Vec<String>, E> {
+        Ok(Vec::new())
+    }
+
+    /// Validator function for validating options
+    ///
+    /// See [`ForeignDataWrapper::validator`] for more details.
+    fn validator(_options: Vec<Option<String>>, _catalog: Option<Oid>) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+// blanket adapter which drives an async FDW through the sync interface
+impl<E, T> ForeignDataWrapper<E> for T
+where
+    E: Into<ErrorReport> + From<Interrupted>,
+    T: AsyncForeignDataWrapper<E>,
+{
+    fn new(server: ForeignServer) -> Result<Self, E> {
+        <T as AsyncForeignDataWrapper<E>>::new(server)
+    }
+
+    fn get_rel_size(
+        &mut self,
+        quals: &[Qual],
+        columns: &[Column],
+        sorts: &[Sort],
+        limit: &Option<Limit>,
+        options: &HashMap<String, String>,
+    ) -> Result<(i64, i32), E> {
+        block_on(AsyncForeignDataWrapper::get_rel_size(
+            self, quals, columns, sorts, limit, options,
+        ))
+    }
+
+    fn scan_capabilities(
+        &mut self,
+        quals: &[Qual],
+        columns: &[Column],
+        sorts: &[Sort],
+        limit: &Option<Limit>,
+        options: &HashMap<String, String>,
+    ) -> Result<ScanCapabilities, E> {
+        AsyncForeignDataWrapper::scan_capabilities(self, quals, columns, sorts, limit, options)
+    }
+
+    fn begin_scan(
+        &mut self,
+        quals: &[Qual],
+        columns: &[Column],
+        sorts: &[Sort],
+        limit: &Option<Limit>,
+        options: &HashMap<String, String>,
+    ) -> Result<(), E> {
+        self.row_batch().clear();
+        block_on(AsyncForeignDataWrapper::begin_scan(
+            self, quals, columns, sorts, limit, options,
+        ))
+    }
+
+    fn iter_scan(&mut self, row: &mut Row) -> Result<Option<()>, E> {
+        while self.row_batch().is_empty() {
+            match block_on(self.next_batch())? {
+                Some(rows) => self.row_batch().extend(rows),
+                None => return Ok(None),
+            }
+        }
+
+        if let Some(src) = self.row_batch().pop() {
+            row.replace_with(src);
+        }
+        Ok(Some(()))
+    }
+
+    fn re_scan(&mut self) -> Result<(), E> {
+        self.row_batch().clear();
+        block_on(AsyncForeignDataWrapper::re_scan(self))
+    }
+
+    fn end_scan(&mut self) -> Result<(), E> {
+        self.row_batch().clear();
+        block_on(AsyncForeignDataWrapper::end_scan(self))
+    }
+
+    fn begin_modify(&mut self, options: &HashMap<String, String>) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::begin_modify(self, options))
+    }
+
+    fn insert(&mut self, row: &Row) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::insert(self, row))
+    }
+
+    fn get_modify_batch_size(&mut self) -> usize {
+        AsyncForeignDataWrapper::get_modify_batch_size(self)
+    }
+
+    fn insert_batch(&mut self, rows: &[Row]) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::insert_batch(self, rows))
+    }
+
+    fn upsert_supported() -> bool {
+        <T as AsyncForeignDataWrapper<E>>::upsert_supported()
+    }
+
+    fn upsert(
+        &mut self,
+        row: &Row,
+        conflict_columns: &[String],
+        action: &ConflictAction,
+    ) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::upsert(
+            self,
+            row,
+            conflict_columns,
+            action,
+        ))
+    }
+
+    fn update(&mut self, rowid: &Cell, new_row: &Row) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::update(self, rowid, new_row))
+    }
+
+    fn delete(&mut self, rowid: &Cell) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::delete(self, rowid))
+    }
+
+    fn end_modify(&mut self) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::end_modify(self))
+    }
+
+    fn transaction_hooks_supported(&self) -> bool {
+        AsyncForeignDataWrapper::transaction_hooks_supported(self)
+    }
+
+    fn pre_commit(&mut self) -> Result<(), E> {
+        block_on(AsyncForeignDataWrapper::pre_commit(self))
+    }
+
+    fn abort(&mut self) -> Result<(), E> {
+        // the transaction is already aborting, so don't use the interruptible
+        // block_on here
+        async_runtime()
+            .report_unwrap()
+            .block_on(AsyncForeignDataWrapper::abort(self))
+    }
+
+    fn cancel(&mut self) -> Result<(), E> {
+        // cancellation is already pending, so don't use the interruptible
+        // block_on here
+        async_runtime()
+            .report_unwrap()
+            .block_on(AsyncForeignDataWrapper::cancel(self))
+    }
+
+    fn import_foreign_schema(
+        &mut self,
+        stmt: crate::import_foreign_schema::ImportForeignSchemaStmt,
+    ) -> Result<Vec<String>, E> {
+        block_on(AsyncForeignDataWrapper::import_foreign_schema(self, stmt))
+    }
+
+    fn validator(options: Vec<Option<String>>, catalog: Option<Oid>) -> Result<(), E> {
+        <T as AsyncForeignDataWrapper<E>>::validator(options, catalog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_row(id: i64) -> Row {
+        let mut row = Row::new();
+        row.push("id", Some(Cell::I64(id)));
+        row
+    }
+
+    #[test]
+    fn test_row_batch_fifo() {
+        let mut batch = RowBatch::new();
+        assert!(batch.is_empty());
+
+        batch.extend(vec![make_row(1), make_row(2)]);
+        batch.extend(vec![make_row(3)]);
+        assert_eq!(batch.len(), 3);
+
+        for id in 1..=3 {
+            let row = batch.pop().unwrap();
+            assert!(matches!(row.cells[0], Some(Cell::I64(v)) if v == id));
+        }
+        assert!(batch.pop().is_none());
+    }
+
+    #[test]
+    fn test_row_batch_clear() {
+        let mut batch = RowBatch::new();
+        batch.extend(vec![make_row(1), make_row(2)]);
+        batch.clear();
+        assert!(batch.is_empty());
+        assert!(batch.pop().is_none());
+    }
+}
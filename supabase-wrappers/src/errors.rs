@@ -0,0 +1,330 @@
+//! Standard error categories for foreign data wrappers
+//!
+
+use crate::options::OptionsError;
+use crate::utils::CreateRuntimeError;
+use pgrx::PgSqlErrorCode;
+use pgrx::pg_sys::panic::ErrorReport;
+use std::fmt;
+
+/// Category of a foreign data wrapper error
+///
+/// Each category is mapped to a SQLSTATE code, so client code can tell what
+/// went wrong without parsing the error message. Transient categories are
+/// retryable, which means the same query may succeed if it is run again later.
+///
+/// The SQLSTATE codes of the retryable categories are not used by any other
+/// category, so clients can tell if an error is retryable by its SQLSTATE
+/// alone, which is one of `53000`, `58000`, `HV00N` and `08006`.
+///
+/// | Category           | SQLSTATE | Retryable |
+/// | ------------------ | -------- | --------- |
+/// | `Auth`             | `28000`  | no        |
+/// | `PermissionDenied` | `42501`  | no        |
+/// | `NotFound`         | `42P01`  | no        |
+/// | `InvalidOption`    | `HV00D`  | no        |
+/// | `InvalidData`      | `22000`  | no        |
+/// | `Unsupported`      | `0A000`  | no        |
+/// | `LimitExceeded`    | `54000`  | no        |
+/// | `RateLimited`      | `53000`  | yes       |
+/// | `Timeout`          | `58000`  | yes       |
+/// | `Unavailable`      | `HV00N`  | yes       |
+/// | `Network`          | `08006`  | yes       |
+/// | `Remote`           | `HV000`  | no        |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Authentication with the remote source failed, e.g. invalid or expired
+    /// credentials
+    Auth,
+    /// The credentials are valid but not allowed to access the remote object
+    PermissionDenied,
+    /// The remote table or object doesn't exist
+    NotFound,
+    /// A server or foreign table option is missing or invalid
+    InvalidOption,
+    /// The remote data cannot be converted, or the local data cannot be sent
+    InvalidData,
+    /// The operation or data type is not supported by the FDW
+    Unsupported,
+    /// A resource limit set on the FDW is exceeded, e.g. a memory limit
+    LimitExceeded,
+    /// The remote source rejected the request because of rate limiting
+    RateLimited,
+    /// The remote request timed out, which is not `57014` (query canceled)
+    /// as the query itself was not canceled by the user
+    Timeout,
+    /// The remote source is temporarily unavailable, e.g. HTTP 503
+    Unavailable,
+    /// Transient network error, e.g. connection refused or reset
+    Network,
+    /// Any other error returned by the remote source
+    Remote,
+}
+
+impl ErrorCategory {
+    /// Get the SQLSTATE code of this category
+    pub fn sqlstate(&self) -> PgSqlErrorCode {
+        match self {
+            Self::Auth => PgSqlErrorCode::ERRCODE_INVALID_AUTHORIZATION_SPECIFICATION,
+            Self::PermissionDenied => PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE,
+            Self::NotFound => PgSqlErrorCode::ERRCODE_UNDEFINED_TABLE,
+            Self::InvalidOption => PgSqlErrorCode::ERRCODE_FDW_INVALID_OPTION_NAME,
+            Self::InvalidData => PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+            Self::Unsupported => PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+            Self::LimitExceeded => PgSqlErrorCode::ERRCODE_PROGRAM_LIMIT_EXCEEDED,
+            Self::RateLimited => PgSqlErrorCode::ERRCODE_INSUFFICIENT_RESOURCES,
+            Self::Timeout => PgSqlErrorCode::ERRCODE_SYSTEM_ERROR,
+            Self::Unavailable => PgSqlErrorCode::ERRCODE_FDW_UNABLE_TO_ESTABLISH_CONNECTION,
+            Self::Network => PgSqlErrorCode::ERRCODE_CONNECTION_FAILURE,
+            Self::Remote => PgSqlErrorCode::ERRCODE_FDW_ERROR,
+        }
+    }
+
+    /// Check if the failed request can be retried later
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited | Self::Timeout | Self::Unavailable | Self::Network
+        )
+    }
+
+    /// Get the category from a HTTP response status code
+    ///
+    /// `404` and `410` are mapped to `Remote`, because the missing resource
+    /// can be a single row rather than the table, use
+    /// [`from_table_http_status`](Self::from_table_http_status) for the
+    /// endpoints representing a whole remote table.
+    ///
+    /// For example,
+    ///
+    /// ```rust
+    /// # use supabase_wrappers::prelude::ErrorCategory;
+    /// assert_eq!(ErrorCategory::from_http_status(401), ErrorCategory::Auth);
+    /// assert_eq!(ErrorCategory::from_http_status(404), ErrorCategory::Remote);
+    /// assert!(ErrorCategory::from_http_status(429).is_retryable());
+    /// ```
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            401 => Self::Auth,
+            403 => Self::PermissionDenied,
+            408 | 504 => Self::Timeout,
+            429 => Self::RateLimited,
+            502 | 503 => Self::Unavailable,
+            400 | 422 => Self::InvalidData,
+            501 => Self::Unsupported,
+            _ => Self::Remote,
+        }
+    }
+
+    /// Get the category from a HTTP response status code of a table-level
+    /// endpoint, e.g. the list endpoint of a remote collection
+    ///
+    /// It is the same as [`from_http_status`](Self::from_http_status) except
+    /// `404` and `410` are mapped to `NotFound`, as the remote table doesn't
+    /// exist.
+    ///
+    /// ```rust
+    /// # use supabase_wrappers::prelude::ErrorCategory;
+    /// assert_eq!(ErrorCategory::from_table_http_status(404), ErrorCategory::NotFound);
+    /// assert_eq!(ErrorCategory::from_table_http_status(401), ErrorCategory::Auth);
+    /// ```
+    pub fn from_table_http_status(status: u16) -> Self {
+        match status {
+            404 | 410 => Self::NotFound,
+            _ => Self::from_http_status(status),
+        }
+    }
+
+    /// Create an [`ErrorReport`] with the SQLSTATE code of this category
+    ///
+    /// The category name and retryable flag are also put in the error detail
+    /// for humans, e.g. `category: rate_limited, retryable: true`. Clients
+    /// should check the SQLSTATE code instead of parsing the detail.
+    pub fn error_report(&self, message: impl Into<String>) -> ErrorReport {
+        ErrorReport::new(self.sqlstate(), message, "").set_detail(format!(
+            "category: {self}, retryable: {}",
+            self.is_retryable()
+        ))
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Auth => "auth",
+            Self::PermissionDenied => "permission_denied",
+            Self::NotFound => "not_found",
+            Self::InvalidOption => "invalid_option",
+            Self::InvalidData => "invalid_data",
+            Self::Unsupported => "unsupported",
+            Self::LimitExceeded => "limit_exceeded",
+            Self::RateLimited => "rate_limited",
+            Self::Timeout => "timeout",
+            Self::Unavailable => "unavailable",
+            Self::Network => "network",
+            Self::Remote => "remote",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Error type which can be classified into an [`ErrorCategory`]
+///
+/// Implement this trait for the FDW error type and use
+/// [`ErrorCategory::error_report`] to convert it into [`ErrorReport`].
+///
+/// For example,
+///
+/// ```rust,no_run
+/// # use supabase_wrappers::prelude::*;
+/// # use pgrx::pg_sys::panic::ErrorReport;
+/// #[derive(Debug, thiserror::Error)]
+/// enum MyFdwError {
+///     #[error("request failed with status {0}")]
+///     HttpStatus(u16),
+///     #[error("{0}")]
+///     OptionsError(#[from] OptionsError),
+/// }
+///
+/// impl CategorizedError for MyFdwError {
+///     fn category(&self) -> ErrorCategory {
+///         match self {
+///             MyFdwError::HttpStatus(status) => ErrorCategory::from_http_status(*status),
+///             MyFdwError::OptionsError(e) => e.category(),
+///         }
+///     }
+/// }
+///
+/// impl From<MyFdwError> for ErrorReport {
+///     fn from(value: MyFdwError) -> Self {
+///         value.category().error_report(format!("{value}"))
+///     }
+/// }
+/// ```
+pub trait CategorizedError {
+    /// Get the category of this error
+    fn category(&self) -> ErrorCategory;
+}
+
+impl CategorizedError for OptionsError {
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::InvalidOption
+    }
+}
+
+impl CategorizedError for CreateRuntimeError {
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Remote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status_category() {
+        assert_eq!(ErrorCategory::from_http_status(401), ErrorCategory::Auth);
+        assert_eq!(
+            ErrorCategory::from_http_status(403),
+            ErrorCategory::PermissionDenied
+        );
+        assert_eq!(ErrorCategory::from_http_status(404), ErrorCategory::Remote);
+        assert_eq!(ErrorCategory::from_http_status(410), ErrorCategory::Remote);
+        assert_eq!(ErrorCategory::from_http_status(504), ErrorCategory::Timeout);
+        assert_eq!(
+            ErrorCategory::from_http_status(429),
+            ErrorCategory::RateLimited
+        );
+        assert_eq!(
+            ErrorCategory::from_http_status(503),
+            ErrorCategory::Unavailable
+        );
+        assert_eq!(ErrorCategory::from_http_status(500), ErrorCategory::Remote);
+    }
+
+    #[test]
+    fn test_table_http_status_category() {
+        assert_eq!(
+            ErrorCategory::from_table_http_status(404),
+            ErrorCategory::NotFound
+        );
+        assert_eq!(
+            ErrorCategory::from_table_http_status(410),
+            ErrorCategory::NotFound
+        );
+        assert_eq!(
+            ErrorCategory::from_table_http_status(403),
+            ErrorCategory::PermissionDenied
+        );
+        assert_eq!(
+            ErrorCategory::from_table_http_status(500),
+            ErrorCategory::Remote
+        );
+    }
+
+    #[test]
+    fn test_retryable_category() {
+        assert!(ErrorCategory::RateLimited.is_retryable());
+        assert!(ErrorCategory::Network.is_retryable());
+        assert!(!ErrorCategory::Auth.is_retryable());
+        assert!(!ErrorCategory::Remote.is_retryable());
+    }
+
+    #[test]
+    fn test_category_sqlstate() {
+        assert_eq!(
+            ErrorCategory::Auth.sqlstate(),
+            PgSqlErrorCode::ERRCODE_INVALID_AUTHORIZATION_SPECIFICATION
+        );
+        assert_eq!(
+            ErrorCategory::NotFound.sqlstate(),
+            PgSqlErrorCode::ERRCODE_UNDEFINED_TABLE
+        );
+        assert_eq!(
+            ErrorCategory::Timeout.sqlstate(),
+            PgSqlErrorCode::ERRCODE_SYSTEM_ERROR
+        );
+        assert_eq!(ErrorCategory::RateLimited.to_string(), "rate_limited");
+    }
+
+    #[test]
+    fn test_retryable_sqlstate() {
+        let all = [
+            ErrorCategory::Auth,
+            ErrorCategory::PermissionDenied,
+            ErrorCategory::NotFound,
+            ErrorCategory::InvalidOption,
+            ErrorCategory::InvalidData,
+            ErrorCategory::Unsupported,
+            ErrorCategory::LimitExceeded,
+            ErrorCategory::RateLimited,
+            ErrorCategory::Timeout,
+            ErrorCategory::Unavailable,
+            ErrorCategory::Network,
+            ErrorCategory::Remote,
+        ];
+
+        // every category has its own SQLSTATE, so the retryable flag can be
+        // told by the SQLSTATE
+        for (i, a) in all.iter().enumerate() {
+            for b in &all[i + 1..] {
+                assert_ne!(a.sqlstate(), b.sqlstate(), "{a} and {b}");
+            }
+        }
+        let retryable: Vec<_> = all
+            .iter()
+            .filter(|c| c.is_retryable())
+            .map(|c| c.sqlstate())
+            .collect();
+        assert_eq!(
+            retryable,
+            vec![
+                PgSqlErrorCode::ERRCODE_INSUFFICIENT_RESOURCES,
+                PgSqlErrorCode::ERRCODE_SYSTEM_ERROR,
+                PgSqlErrorCode::ERRCODE_FDW_UNABLE_TO_ESTABLISH_CONNECTION,
+                PgSqlErrorCode::ERRCODE_CONNECTION_FAILURE,
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Hooks to observe requests made by foreign data wrappers
+//!
+
+use pgrx::pg_sys::{self, Oid};
+use std::cell::Cell;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// The kind of a foreign table request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    /// A foreign table scan, from `get_rel_size` to `end_scan`
+    Scan,
+    /// A foreign table modification, from `begin_modify` to `end_modify`
+    Modify,
+}
+
+/// The foreign table a request is made against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForeignTableRef {
+    pub server_oid: Oid,
+    pub foreigntable_oid: Oid,
+}
+
+/// Summary of a finished foreign table request
+#[derive(Debug, Clone, Copy)]
+pub struct RequestInfo {
+    pub table: ForeignTableRef,
+    pub kind: RequestKind,
+    /// Total time spent in the FDW callbacks of this request
+    pub elapsed: Duration,
+    /// Number of rows returned by a scan, or inserted, updated and deleted by
+    /// a modification
+    pub rows: u64,
+    /// Whether any FDW callback of this request returned an error
+    pub is_error: bool,
+    /// Whether this scan reads the target rows of an `UPDATE` or `DELETE`,
+    /// in which case the statement is also reported by a `Modify` request
+    pub is_modify_target: bool,
+}
+
+/// Function called when a foreign table request is finished
+///
+/// It is called in the backend, so it must not raise Postgres errors or
+/// block for long.
+pub type RequestHook = fn(&RequestInfo);
+
+static REQUEST_HOOK: OnceLock<RequestHook> = OnceLock::new();
+
+thread_local! {
+    static CURRENT_TABLE: Cell<Option<ForeignTableRef>> = const { Cell::new(None) };
+}
+
+/// Set the hook to be called when a foreign table request is finished
+///
+/// Only the first hook set in a backend takes effect, it returns false if a
+/// hook has already been set.
+pub fn set_request_hook(hook: RequestHook) -> bool {
+    REQUEST_HOOK.set(hook).is_ok()
+}
+
+/// Get the foreign table of the FDW callback currently running
+///
+/// Returns `None` if it is called outside of a foreign table scan or
+/// modification, for example in `import_foreign_schema`. FDWs can use it to
+/// attribute their own metrics, like bytes transferred, to the foreign table.
+pub fn current_foreign_table() -> Option<ForeignTableRef> {
+    CURRENT_TABLE.with(|t| t.get())
+}
+
+// tracks time spent and rows processed by one foreign table request, the
+// request hook is called when it is dropped
+pub(crate) struct RequestTracker {
+    info: RequestInfo,
+    started: bool,
+}
+
+impl RequestTracker {
+    pub(crate) unsafe fn new(foreigntable_oid: Oid, kind: RequestKind) -> Self {
+        let server_oid = unsafe { (*pg_sys::GetForeignTable(foreigntable_oid)).serverid };
+        Self {
+            info: RequestInfo {
+                table: ForeignTableRef {
+                    server_oid,
+                    foreigntable_oid,
+                },
+                kind,
+                elapsed: Duration::ZERO,
+                rows: 0,
+                is_error: false,
+                is_modify_target: false,
+            },
+            started: false,
+        }
+    }
+
+    // mark this scan as reading the target rows of a modification
+    pub(crate) fn set_modify_target(&mut self) {
+        self.info.is_modify_target = true;
+    }
+
+    // run a FDW callback as part of this request
+    pub(crate) fn track<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let prev = CURRENT_TABLE.with(|t| t.replace(Some(self.info.table)));
+        let start = Instant::now();
+        let result = f();
+        self.info.elapsed += start.elapsed();
+        CURRENT_TABLE.with(|t| t.set(prev));
+
+        self.started = true;
+        if result.is_err() {
+            self.info.is_error = true;
+        }
+        result
+    }
+
+    pub(crate) fn add_rows(&mut self, rows: u64) {
+        self.info.rows += rows;
+    }
+
+    // run a closure with the foreign table set as current, without timing it
+    pub(crate) fn enter<T>(&self, f: impl FnOnce() -> T) -> T {
+        let prev = CURRENT_TABLE.with(|t| t.replace(Some(self.info.table)));
+        let ret = f();
+        CURRENT_TABLE.with(|t| t.set(prev));
+        ret
+    }
+}
+
+impl Drop for RequestTracker {
+    fn drop(&mut self) {
+        if self.started
+            && let Some(hook) = REQUEST_HOOK.get()
+        {
+            hook(&self.info);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tracker() -> RequestTracker {
+        RequestTracker {
+            info: RequestInfo {
+                table: ForeignTableRef {
+                    server_oid: Oid::from(1u32),
+                    foreigntable_oid: Oid::from(2u32),
+                },
+                kind: RequestKind::Scan,
+                elapsed: Duration::ZERO,
+                rows: 0,
+                is_error: false,
+                is_modify_target: false,
+            },
+            started: false,
+        }
+    }
+
+    #[test]
+    fn test_request_tracker_track() {
+        let mut tracker = make_tracker();
+        let table = tracker.info.table;
+        assert!(current_foreign_table().is_none());
+
+        let result: Result<_, ()> = tracker.track(|| {
+            assert_eq!(current_foreign_table(), Some(table));
+            Ok(42)
+        });
+        assert_eq!(result, Ok(42));
+        assert!(current_foreign_table().is_none());
+        assert!(tracker.started);
+        assert!(!tracker.info.is_error);
+
+        let result: Result<(), _> = tracker.track(|| Err("failed"));
+        assert!(result.is_err());
+        assert!(tracker.info.is_error);
+
+        tracker.add_rows(3);
+        tracker.add_rows(2);
+        assert_eq!(tracker.info.rows, 5);
+    }
+
+    #[test]
+    fn test_request_tracker_enter() {
+        let mut tracker = make_tracker();
+        let table = tracker.info.table;
+
+        tracker.enter(|| assert_eq!(current_foreign_table(), Some(table)));
+        assert!(current_foreign_table().is_none());
+        assert!(!tracker.started);
+
+        tracker.set_modify_target();
+        assert!(tracker.info.is_modify_target);
+    }
+}
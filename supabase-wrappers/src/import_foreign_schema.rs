@@ -44,6 +44,193 @@ pub struct ImportForeignSchemaStmt {
     pub options: std::collections::HashMap<String, String>,
 }
 
+impl ImportForeignSchemaStmt {
+    /// Check if a remote table is selected by the `LIMIT TO` or `EXCEPT` clause
+    pub fn is_table_selected(&self, table_name: &str) -> bool {
+        let listed = self.table_list.iter().any(|t| t == table_name);
+        match self.list_type {
+            ImportSchemaType::FdwImportSchemaAll => true,
+            ImportSchemaType::FdwImportSchemaLimitTo => listed,
+            ImportSchemaType::FdwImportSchemaExcept => !listed,
+        }
+    }
+
+    /// Render foreign table definitions to `CREATE FOREIGN TABLE` statements
+    ///
+    /// Tables not selected by the `LIMIT TO` or `EXCEPT` clause and tables
+    /// without any columns are skipped. All tables are created on the server
+    /// in this statement.
+    ///
+    /// For example,
+    ///
+    /// ```rust,no_run
+    /// # use supabase_wrappers::prelude::*;
+    /// # fn import(stmt: ImportForeignSchemaStmt) -> Vec<String> {
+    /// let table = ForeignTableDef::new("users")
+    ///     .column(ColumnDef::new("id", "bigint").not_null(true))
+    ///     .column(ColumnDef::new("created_at", "timestamp").option("column_name", "Created At"))
+    ///     .option("object", "users")
+    ///     .option("rowid_column", "id");
+    /// stmt.build_ddl([table])
+    /// # }
+    /// ```
+    pub fn build_ddl(&self, tables: impl IntoIterator<Item = ForeignTableDef>) -> Vec<String> {
+        tables
+            .into_iter()
+            .filter(|tbl| !tbl.columns.is_empty() && self.is_table_selected(&tbl.name))
+            .map(|tbl| tbl.to_sql(&self.server_name))
+            .collect()
+    }
+}
+
+// quote an identifier, so it can be used in DDL as-is
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+// quote a string literal
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+// render an option list, e.g. options (key 'value')
+fn render_options(options: &[(String, String)]) -> String {
+    if options.is_empty() {
+        return String::default();
+    }
+    let opts = options
+        .iter()
+        .map(|(k, v)| format!("{} {}", quote_ident(k), quote_literal(v)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(" options ({opts})")
+}
+
+/// A column definition in [`ForeignTableDef`]
+#[derive(Debug, Clone, Default)]
+pub struct ColumnDef {
+    /// column name
+    pub name: String,
+
+    /// column type, e.g. `text` or `numeric(10, 2)`, which is not quoted
+    pub type_name: String,
+
+    /// if the column has `not null` constraint
+    pub not_null: bool,
+
+    /// column options
+    pub options: Vec<(String, String)>,
+}
+
+impl ColumnDef {
+    pub fn new(name: impl Into<String>, type_name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            type_name: type_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the `not null` constraint
+    pub fn not_null(mut self, not_null: bool) -> Self {
+        self.not_null = not_null;
+        self
+    }
+
+    /// Add a column option, e.g. `column_name`
+    pub fn option(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.push((name.into(), value.into()));
+        self
+    }
+
+    fn to_sql(&self) -> String {
+        format!(
+            "{} {}{}{}",
+            quote_ident(&self.name),
+            self.type_name,
+            if self.not_null { " not null" } else { "" },
+            render_options(&self.options),
+        )
+    }
+}
+
+/// A foreign table definition used in [`import_foreign_schema`](crate::interface::ForeignDataWrapper::import_foreign_schema)
+///
+/// Use [`ImportForeignSchemaStmt::build_ddl`] to render it to a
+/// `CREATE FOREIGN TABLE` statement. Identifiers are always quoted and option
+/// values are escaped.
+#[derive(Debug, Clone)]
+pub struct ForeignTableDef {
+    /// table name, which is matched against the `LIMIT TO` or `EXCEPT` clause
+    pub name: String,
+
+    /// column definitions
+    pub columns: Vec<ColumnDef>,
+
+    /// table options
+    pub options: Vec<(String, String)>,
+
+    /// if use `if not exists`, default is true
+    pub if_not_exists: bool,
+}
+
+impl ForeignTableDef {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            columns: Vec::new(),
+            options: Vec::new(),
+            if_not_exists: true,
+        }
+    }
+
+    /// Add a column
+    pub fn column(mut self, column: ColumnDef) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Add a list of columns
+    pub fn columns(mut self, columns: impl IntoIterator<Item = ColumnDef>) -> Self {
+        self.columns.extend(columns);
+        self
+    }
+
+    /// Add a table option
+    pub fn option(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set if use `if not exists` in the statement
+    pub fn if_not_exists(mut self, if_not_exists: bool) -> Self {
+        self.if_not_exists = if_not_exists;
+        self
+    }
+
+    /// Render the `CREATE FOREIGN TABLE` statement on the server
+    pub fn to_sql(&self, server_name: &str) -> String {
+        let cols = self
+            .columns
+            .iter()
+            .map(|col| col.to_sql())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "create foreign table {}{} ({}) server {}{}",
+            if self.if_not_exists {
+                "if not exists "
+            } else {
+                ""
+            },
+            quote_ident(&self.name),
+            cols,
+            quote_ident(server_name),
+            render_options(&self.options),
+        )
+    }
+}
+
 #[pg_guard]
 pub(super) extern "C-unwind" fn import_foreign_schema<
     E: Into<ErrorReport>,
@@ -127,3 +314,63 @@ pub(super) extern "C-unwind" fn import_foreign_schema<
         ret.into_ptr()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_stmt(list_type: ImportSchemaType, table_list: &[&str]) -> ImportForeignSchemaStmt {
+        ImportForeignSchemaStmt {
+            server_name: "my server".to_string(),
+            remote_schema: "public".to_string(),
+            local_schema: "public".to_string(),
+            list_type,
+            table_list: table_list.iter().map(|t| t.to_string()).collect(),
+            options: Default::default(),
+        }
+    }
+
+    fn make_table(name: &str) -> ForeignTableDef {
+        ForeignTableDef::new(name).column(ColumnDef::new("id", "bigint"))
+    }
+
+    #[test]
+    fn test_foreign_table_def_to_sql() {
+        let tbl = ForeignTableDef::new("my\"table")
+            .column(ColumnDef::new("id", "bigint").not_null(true))
+            .column(ColumnDef::new("created_at", "timestamp").option("column_name", "Created At"))
+            .column(ColumnDef::new("amount", "numeric(10, 2)"))
+            .option("object", "o'brien")
+            .option("rowid_column", "id");
+        assert_eq!(
+            tbl.to_sql("my_server"),
+            r#"create foreign table if not exists "my""table" ("id" bigint not null, "created_at" timestamp options ("column_name" 'Created At'), "amount" numeric(10, 2)) server "my_server" options ("object" 'o''brien', "rowid_column" 'id')"#
+        );
+
+        let tbl = make_table("t").if_not_exists(false);
+        assert_eq!(
+            tbl.to_sql("s"),
+            r#"create foreign table "t" ("id" bigint) server "s""#
+        );
+    }
+
+    #[test]
+    fn test_build_ddl_filtering() {
+        let tables = || vec![make_table("a"), make_table("b"), ForeignTableDef::new("c")];
+
+        let stmt = make_stmt(ImportSchemaType::FdwImportSchemaAll, &[]);
+        let ddl = stmt.build_ddl(tables());
+        assert_eq!(ddl.len(), 2);
+        assert!(ddl[0].contains(r#"server "my server""#));
+
+        let stmt = make_stmt(ImportSchemaType::FdwImportSchemaLimitTo, &["b", "c"]);
+        let ddl = stmt.build_ddl(tables());
+        assert_eq!(ddl.len(), 1);
+        assert!(ddl[0].contains(r#"table if not exists "b""#));
+
+        let stmt = make_stmt(ImportSchemaType::FdwImportSchemaExcept, &["b"]);
+        let ddl = stmt.build_ddl(tables());
+        assert_eq!(ddl.len(), 1);
+        assert!(ddl[0].contains(r#"table if not exists "a""#));
+    }
+}
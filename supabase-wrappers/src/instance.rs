@@ -14,6 +14,21 @@ pub struct ForeignServer {
     pub options: HashMap<String, String>,
 }
 
+impl ForeignServer {
+    /// Get options of the foreign data wrapper this server belongs to
+    ///
+    /// Those options are set by `CREATE FOREIGN DATA WRAPPER ... OPTIONS` and
+    /// can only be changed by the owner of the foreign data wrapper, so they
+    /// are suitable for settings which server owners must not override.
+    pub fn fdw_options(&self) -> Result<HashMap<String, String>, OptionsError> {
+        unsafe {
+            let fserver = pg_sys::GetForeignServer(self.server_oid);
+            let fdw = pg_sys::GetForeignDataWrapper((*fserver).fdwid);
+            options_to_hashmap((*fdw).options)
+        }
+    }
+}
+
 // create a fdw instance from its id
 pub(super) unsafe fn create_fdw_instance_from_server_id<
     E: Into<ErrorReport>,
@@ -379,6 +379,27 @@ impl FromDatum for Cell {
 
 pub trait CellFormatter {
     fn fmt_cell(&mut self, cell: &Cell) -> String;
+
+    /// Format an identifier, such as a column name, for the remote source
+    ///
+    /// By default, the identifier is used as-is without quoting. Override it
+    /// to quote identifiers by the rules of the remote source, usually only
+    /// when it is not a [plain identifier](is_plain_ident), so the deparsed
+    /// SQL of the existing plain column names stays the same.
+    fn fmt_ident(&mut self, ident: &str) -> String {
+        ident.to_owned()
+    }
+}
+
+/// Check if an identifier can be used without quoting in most SQL dialects,
+/// which starts with an ASCII letter or underscore and contains only ASCII
+/// letters, digits and underscores
+pub fn is_plain_ident(ident: &str) -> bool {
+    ident
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 struct DefaultFormatter {}
@@ -458,6 +479,18 @@ pub struct Column {
     /// column name
     pub name: String,
 
+    /// column name in the remote source, from the `column_name` column option
+    ///
+    /// It is same as `name` if the `column_name` option is not specified. For example,
+    ///
+    /// ```sql
+    /// create foreign table my_table (
+    ///   created_at timestamp options (column_name 'Created At')
+    /// )
+    ///   server my_server;
+    /// ```
+    pub remote_name: String,
+
     /// 1-based column number
     pub num: usize,
 
@@ -465,6 +498,25 @@ pub struct Column {
     pub type_oid: Oid,
 }
 
+impl Column {
+    /// Create a column whose remote name is same as its name
+    pub fn new(name: impl Into<String>, num: usize, type_oid: Oid) -> Self {
+        let name = name.into();
+        Self {
+            remote_name: name.clone(),
+            name,
+            num,
+            type_oid,
+        }
+    }
+
+    /// Set the column name in the remote source
+    pub fn remote_name(mut self, remote_name: impl Into<String>) -> Self {
+        self.remote_name = remote_name.into();
+        self
+    }
+}
+
 /// A restiction value used in [`Qual`], either a [`Cell`] or an array of [`Cell`]
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -544,9 +596,15 @@ pub struct Param {
 /// --   Qual { field: "col", operator: "=", value: Cell(String("foo")), use_or: false }
 /// -- ]
 /// ```
+///
+/// The `remote_field` is the field name in the remote source, which is the
+/// `column_name` column option if it is specified, otherwise same as `field`.
+/// It is used when deparsing the qual, formatted by the formatter's
+/// [`fmt_ident`](CellFormatter::fmt_ident).
 #[derive(Debug, Clone)]
 pub struct Qual {
     pub field: String,
+    pub remote_field: String,
     pub operator: String,
     pub value: Value,
     pub use_or: bool,
@@ -554,21 +612,44 @@ pub struct Qual {
 }
 
 impl Qual {
+    /// Create a qual whose remote field is same as its field
+    pub fn new(
+        field: impl Into<String>,
+        operator: impl Into<String>,
+        value: Value,
+        use_or: bool,
+    ) -> Self {
+        let field = field.into();
+        Self {
+            remote_field: field.clone(),
+            field,
+            operator: operator.into(),
+            value,
+            use_or,
+            param: None,
+        }
+    }
+
+    /// Set the field name in the remote source
+    pub fn remote_field(mut self, remote_field: impl Into<String>) -> Self {
+        self.remote_field = remote_field.into();
+        self
+    }
+
     pub fn deparse(&self) -> String {
         let mut formatter = DefaultFormatter::new();
         self.deparse_with_fmt(&mut formatter)
     }
 
     pub fn deparse_with_fmt<T: CellFormatter>(&self, t: &mut T) -> String {
+        let field = t.fmt_ident(&self.remote_field);
         if self.use_or {
             match &self.value {
                 Value::Cell(_) => unreachable!(),
                 Value::Array(cells) => {
                     let conds: Vec<String> = cells
                         .iter()
-                        .map(|cell| {
-                            format!("{} {} {}", self.field, self.operator, t.fmt_cell(cell))
-                        })
+                        .map(|cell| format!("{} {} {}", field, self.operator, t.fmt_cell(cell)))
                         .collect();
                     conds.join(" or ")
                 }
@@ -578,13 +659,13 @@ impl Qual {
                 Value::Cell(cell) => match self.operator.as_str() {
                     "is" | "is not" => match cell {
                         Cell::String(cell) if cell == "null" => {
-                            format!("{} {} null", self.field, self.operator)
+                            format!("{} {} null", field, self.operator)
                         }
-                        _ => format!("{} {} {}", self.field, self.operator, t.fmt_cell(cell)),
+                        _ => format!("{} {} {}", field, self.operator, t.fmt_cell(cell)),
                     },
-                    "~~" => format!("{} like {}", self.field, t.fmt_cell(cell)),
-                    "!~~" => format!("{} not like {}", self.field, t.fmt_cell(cell)),
-                    _ => format!("{} {} {}", self.field, self.operator, t.fmt_cell(cell)),
+                    "~~" => format!("{} like {}", field, t.fmt_cell(cell)),
+                    "!~~" => format!("{} not like {}", field, t.fmt_cell(cell)),
+                    _ => format!("{} {} {}", field, self.operator, t.fmt_cell(cell)),
                 },
                 Value::Array(_) => unreachable!(),
             }
@@ -618,9 +699,13 @@ impl Qual {
 /// order by id collate "de_DE";
 /// -- [Sort { field: "col", field_no: 2, reversed: false, nulls_first: false, collate: Some("de_DE") }]
 /// ```
+///
+/// Like [`Qual`], the `remote_field` is the field name in the remote source
+/// and it is used when deparsing the sort.
 #[derive(Debug, Clone, Default)]
 pub struct Sort {
     pub field: String,
+    pub remote_field: String,
     pub field_no: usize,
     pub reversed: bool,
     pub nulls_first: bool,
@@ -628,8 +713,37 @@ pub struct Sort {
 }
 
 impl Sort {
+    /// Create a sort whose remote field is same as its field
+    pub fn new(
+        field: impl Into<String>,
+        field_no: usize,
+        reversed: bool,
+        nulls_first: bool,
+    ) -> Self {
+        let field = field.into();
+        Self {
+            remote_field: field.clone(),
+            field,
+            field_no,
+            reversed,
+            nulls_first,
+            collate: None,
+        }
+    }
+
+    /// Set the field name in the remote source
+    pub fn remote_field(mut self, remote_field: impl Into<String>) -> Self {
+        self.remote_field = remote_field.into();
+        self
+    }
+
     pub fn deparse(&self) -> String {
-        let mut sql = self.field.to_string();
+        let mut formatter = DefaultFormatter::new();
+        self.deparse_with_fmt(&mut formatter)
+    }
+
+    pub fn deparse_with_fmt<T: CellFormatter>(&self, t: &mut T) -> String {
+        let mut sql = t.fmt_ident(&self.remote_field);
 
         if self.reversed {
             sql.push_str(" desc");
@@ -682,6 +796,47 @@ impl Limit {
     }
 }
 
+/// Scan pushdown capabilities declared by a FDW
+///
+/// It tells the planner which of the pushed down quals, sorts and limit are
+/// actually honored by the foreign table scan, see
+/// [`ForeignDataWrapper::scan_capabilities`] for more details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanCapabilities {
+    /// All quals are fully evaluated by the foreign source, so Postgres doesn't
+    /// recheck them on the returned rows
+    pub quals: bool,
+    /// Rows are returned in the order of the pushed down sorts, so Postgres
+    /// doesn't sort them again
+    pub sorts: bool,
+    /// No more than `count + offset` rows are returned when a limit is pushed
+    /// down
+    pub limit: bool,
+}
+
+/// Action of an `INSERT ... ON CONFLICT` clause
+///
+/// ## Examples
+///
+/// ```sql
+/// insert into t (id, name) values (1, 'foo') on conflict do nothing;
+/// -- ConflictAction::DoNothing
+/// ```
+///
+/// ```sql
+/// insert into t (id, name, age) values (1, 'foo', 42)
+/// on conflict (id) do update set name = excluded.name;
+/// -- ConflictAction::DoUpdate { columns: vec!["name"] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// Skip the row if it conflicts with an existing row
+    DoNothing,
+    /// Update the listed columns of the existing row with the values from
+    /// the new row
+    DoUpdate { columns: Vec<String> },
+}
+
 /// The Foreign Data Wrapper trait
 ///
 /// This is the main interface for your foreign data wrapper. Required functions
@@ -733,6 +888,31 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
         Ok((0, 0))
     }
 
+    /// Declare which of the pushed down quals, sorts and limit are honored
+    ///
+    /// Called by the planner right after [`get_rel_size`](Self::get_rel_size)
+    /// with the same arguments. By default nothing is declared, so Postgres
+    /// always rechecks quals, sorts rows and applies limit on the scan result.
+    ///
+    /// - `quals` - only takes effect when all the `WHERE` conditions can be
+    ///   pushed down, otherwise all of them are still rechecked
+    /// - `sorts` - only takes effect when the whole `ORDER BY` clause can be
+    ///   pushed down
+    /// - `limit` - the scan cost is estimated by the limited rows
+    ///
+    /// <strong>Warning</strong>: declaring a capability not honored by the FDW
+    /// leads to wrong query results.
+    fn scan_capabilities(
+        &mut self,
+        _quals: &[Qual],
+        _columns: &[Column],
+        _sorts: &[Sort],
+        _limit: &Option<Limit>,
+        _options: &HashMap<String, String>,
+    ) -> Result<ScanCapabilities, E> {
+        Ok(ScanCapabilities::default())
+    }
+
     /// Called when begin executing a foreign scan
     ///
     /// - `quals` - `WHERE` clause pushed down
@@ -791,6 +971,11 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
     ///   );
     /// ```
     ///
+    /// Columns renamed by the `column_name` column option are passed to the
+    /// modify callbacks by their remote names, see [`Column::remote_name`].
+    /// This applies to the `rowid_column` option, the columns in the rows and
+    /// the conflict target and `DO UPDATE` columns of [`upsert`](Self::upsert).
+    ///
     /// [See more details](https://www.postgresql.org/docs/current/fdw-callbacks.html#FDW-CALLBACKS-UPDATE).
     fn begin_modify(&mut self, _options: &HashMap<String, String>) -> Result<(), E> {
         Ok(())
@@ -805,6 +990,70 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
         Ok(())
     }
 
+    /// Obtain the max number of rows inserted by one
+    /// [`insert_batch`](Self::insert_batch) call
+    ///
+    /// Called after [`begin_modify`](Self::begin_modify). Batching is disabled
+    /// if it returns 1, which is the default. It is also disabled for `INSERT`
+    /// with `RETURNING`, `ON CONFLICT`, row triggers or `WITH CHECK OPTION`
+    /// constraints. Batch insert requires Postgres 14 or later.
+    ///
+    /// [See more details](https://www.postgresql.org/docs/current/fdw-callbacks.html#FDW-CALLBACKS-UPDATE).
+    fn get_modify_batch_size(&mut self) -> usize {
+        1
+    }
+
+    /// Called when insert a batch of rows into the foreign table
+    ///
+    /// - rows - the new rows to be inserted, at most
+    ///   [`get_modify_batch_size`](Self::get_modify_batch_size) rows
+    ///
+    /// By default, [`insert`](Self::insert) is called for each row.
+    ///
+    /// [See more details](https://www.postgresql.org/docs/current/fdw-callbacks.html#FDW-CALLBACKS-UPDATE).
+    fn insert_batch(&mut self, rows: &[Row]) -> Result<(), E> {
+        rows.iter().try_for_each(|row| self.insert(row))
+    }
+
+    /// Check if this FDW supports `INSERT ... ON CONFLICT`
+    ///
+    /// Return true if [`upsert`](Self::upsert) is implemented. Otherwise,
+    /// `ON CONFLICT DO UPDATE` and `ON CONFLICT (...) DO NOTHING` are
+    /// rejected during planning.
+    fn upsert_supported() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Called when insert one row with an `ON CONFLICT` clause into the
+    /// foreign table
+    ///
+    /// - row - the new row to be inserted
+    /// - conflict_columns - the columns in the conflict target, e.g. `["id"]`
+    ///   for `ON CONFLICT (id)`, it is empty if no conflict target is given
+    /// - action - what to do when the new row conflicts with an existing row
+    ///
+    /// Only `DO UPDATE SET col = EXCLUDED.col` assignments without `WHERE`
+    /// condition are supported, they are passed in
+    /// [`ConflictAction::DoUpdate`]. Usually it maps to the native upsert of
+    /// the remote source, like `INSERT OR REPLACE` or `MERGE`.
+    ///
+    /// The FDW extension must call
+    /// [`install_planner_hook`](crate::on_conflict::install_planner_hook) in
+    /// its `_PG_init`, and its library must be loaded before the statement is
+    /// planned, e.g. by `shared_preload_libraries`, so Postgres doesn't
+    /// reject the conflict target on a foreign table.
+    fn upsert(
+        &mut self,
+        _row: &Row,
+        _conflict_columns: &[String],
+        _action: &ConflictAction,
+    ) -> Result<(), E> {
+        Ok(())
+    }
+
     /// Called when update one row into the foreign table
     ///
     /// - rowid - the `rowid_column` cell
@@ -831,6 +1080,51 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
         Ok(())
     }
 
+    /// Check if this FDW supports transaction hooks
+    ///
+    /// Return true if [`pre_commit`](Self::pre_commit) or
+    /// [`abort`](Self::abort) is implemented. It is checked at the end of
+    /// each modification, and the FDW instance is then kept until the end of
+    /// the transaction instead of being dropped, so its memory is held until
+    /// then as well.
+    fn transaction_hooks_supported(&self) -> bool {
+        false
+    }
+
+    /// Called before the transaction which modified the foreign table commits
+    ///
+    /// Use it to flush or commit writes buffered by the FDW, returning an
+    /// error aborts the transaction. `PREPARE TRANSACTION` is not supported
+    /// by the FDWs with transaction hooks.
+    fn pre_commit(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when the transaction, or the subtransaction, which modified the
+    /// foreign table aborts
+    ///
+    /// Use it to discard writes buffered by the FDW or roll back the remote
+    /// transaction. It cannot abort the transaction again, so its error is
+    /// reported as a warning.
+    fn abort(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Called when a remote call is interrupted by query cancellation
+    ///
+    /// This function is called when other functions returned an error while
+    /// the statement is being canceled, for example, after
+    /// [`block_on_interruptible`](crate::interrupt::block_on_interruptible)
+    /// returned [`Interrupted`](crate::interrupt::Interrupted). Use it to
+    /// cancel the jobs still running on the remote side, like a BigQuery job
+    /// or a Snowflake query.
+    ///
+    /// This function should return quickly, the cancellation error will be
+    /// reported to Postgres once it returns.
+    fn cancel(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
     /// Obtain a list of foreign table creation commands
     ///
     /// Return a list of string, each of which must contain a CREATE FOREIGN TABLE
@@ -853,6 +1147,7 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
     {
         unsafe {
             use crate::{import_foreign_schema, modify, scan};
+
             let mut fdw_routine =
                 FdwRoutine::<AllocatedByRust>::alloc_node(pg_sys::NodeTag::T_FdwRoutine);
 
@@ -881,6 +1176,15 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
             fdw_routine.ExecForeignUpdate = Some(modify::exec_foreign_update::<E, Self>);
             fdw_routine.EndForeignModify = Some(modify::end_foreign_modify::<E, Self>);
 
+            // batch insert, since pg14
+            #[cfg(not(feature = "pg13"))]
+            {
+                fdw_routine.GetForeignModifyBatchSize =
+                    Some(modify::get_foreign_modify_batch_size::<E, Self>);
+                fdw_routine.ExecForeignBatchInsert =
+                    Some(modify::exec_foreign_batch_insert::<E, Self>);
+            }
+
             Self::fdw_routine_hook(&mut fdw_routine);
             fdw_routine.into_pg_boxed()
         }
@@ -948,3 +1252,152 @@ pub trait ForeignDataWrapper<E: Into<ErrorReport>> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::OptionsError;
+
+    // quote identifiers with backticks, like BigQuery
+    struct BacktickFormatter;
+
+    impl CellFormatter for BacktickFormatter {
+        fn fmt_cell(&mut self, cell: &Cell) -> String {
+            format!("{cell}")
+        }
+
+        fn fmt_ident(&mut self, ident: &str) -> String {
+            format!("`{}`", ident.replace('`', "\\`"))
+        }
+    }
+
+    fn make_qual(remote_field: &str, operator: &str, value: Value, use_or: bool) -> Qual {
+        Qual::new("col", operator, value, use_or).remote_field(remote_field)
+    }
+
+    // a FDW recording the ids of inserted rows, which rejects rows without id
+    #[derive(Default)]
+    struct InsertRecorder {
+        ids: Vec<i32>,
+    }
+
+    impl ForeignDataWrapper<OptionsError> for InsertRecorder {
+        fn new(_server: ForeignServer) -> Result<Self, OptionsError> {
+            Ok(Self::default())
+        }
+
+        fn begin_scan(
+            &mut self,
+            _quals: &[Qual],
+            _columns: &[Column],
+            _sorts: &[Sort],
+            _limit: &Option<Limit>,
+            _options: &HashMap<String, String>,
+        ) -> Result<(), OptionsError> {
+            Ok(())
+        }
+
+        fn iter_scan(&mut self, _row: &mut Row) -> Result<Option<()>, OptionsError> {
+            Ok(None)
+        }
+
+        fn end_scan(&mut self) -> Result<(), OptionsError> {
+            Ok(())
+        }
+
+        fn insert(&mut self, row: &Row) -> Result<(), OptionsError> {
+            match row.cells.first() {
+                Some(Some(Cell::I32(id))) => {
+                    self.ids.push(*id);
+                    Ok(())
+                }
+                _ => Err(OptionsError::OptionNameNotFound("id".to_string())),
+            }
+        }
+    }
+
+    fn make_row(id: Option<i32>) -> Row {
+        let mut row = Row::new();
+        row.push("id", id.map(Cell::I32));
+        row
+    }
+
+    #[test]
+    fn test_default_insert_batch() {
+        let mut fdw = InsertRecorder::default();
+        assert_eq!(fdw.get_modify_batch_size(), 1);
+        assert!(!fdw.transaction_hooks_supported());
+
+        // rows are inserted one by one by default
+        let rows = vec![make_row(Some(1)), make_row(Some(2)), make_row(Some(3))];
+        assert!(fdw.insert_batch(&rows).is_ok());
+        assert_eq!(fdw.ids, vec![1, 2, 3]);
+
+        // the first error stops the batch
+        let rows = vec![make_row(Some(4)), make_row(None), make_row(Some(5))];
+        assert!(fdw.insert_batch(&rows).is_err());
+        assert_eq!(fdw.ids, vec![1, 2, 3, 4]);
+
+        assert!(fdw.insert_batch(&[]).is_ok());
+        assert_eq!(fdw.ids, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_default_fmt_ident() {
+        let mut fmt = DefaultFormatter::new();
+        assert_eq!(fmt.fmt_ident("id"), "id");
+        assert_eq!(fmt.fmt_ident("CreatedAt"), "CreatedAt");
+        assert_eq!(fmt.fmt_ident("Created At"), "Created At");
+    }
+
+    #[test]
+    fn test_is_plain_ident() {
+        assert!(is_plain_ident("id"));
+        assert!(is_plain_ident("_col_1"));
+        assert!(is_plain_ident("CreatedAt"));
+        assert!(!is_plain_ident("Created At"));
+        assert!(!is_plain_ident("1col"));
+        assert!(!is_plain_ident("a\"b"));
+        assert!(!is_plain_ident(""));
+    }
+
+    #[test]
+    fn test_qual_deparse_remote_field() {
+        let qual = make_qual("Created At", "=", Value::Cell(Cell::I32(1)), false);
+        assert_eq!(qual.deparse(), "Created At = 1");
+        assert_eq!(
+            qual.deparse_with_fmt(&mut BacktickFormatter),
+            "`Created At` = 1"
+        );
+
+        let qual = make_qual(
+            "name",
+            "is",
+            Value::Cell(Cell::String("null".into())),
+            false,
+        );
+        assert_eq!(qual.deparse(), "name is null");
+
+        let qual = make_qual(
+            "My`Col",
+            "=",
+            Value::Array(vec![Cell::I32(1), Cell::I32(2)]),
+            true,
+        );
+        assert_eq!(
+            qual.deparse_with_fmt(&mut BacktickFormatter),
+            "`My\\`Col` = 1 or `My\\`Col` = 2"
+        );
+    }
+
+    #[test]
+    fn test_sort_deparse_remote_field() {
+        let sort = Sort::new("col", 1, true, true).remote_field("Created At");
+        assert_eq!(sort.field, "col");
+        assert_eq!(sort.deparse(), "Created At desc nulls first");
+        assert_eq!(
+            sort.deparse_with_fmt(&mut BacktickFormatter),
+            "`Created At` desc nulls first"
+        );
+    }
+}
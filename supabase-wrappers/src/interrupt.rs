@@ -0,0 +1,330 @@
+//! Helpers to make remote calls interruptible by query cancellation and
+//! `statement_timeout`
+//!
+
+use crate::interface::ForeignDataWrapper;
+use crate::utils::report_warning;
+use pgrx::pg_sys::{self, panic::ErrorReport};
+use pgrx::{PgSqlErrorCode, check_for_interrupts};
+use std::pin::pin;
+use std::ptr;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+// how often the pending interrupts are checked while waiting for a future
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Error returned when a remote call is interrupted by query cancellation,
+/// `statement_timeout` or backend termination
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("canceling statement due to user request")]
+pub struct Interrupted;
+
+impl From<Interrupted> for ErrorReport {
+    fn from(value: Interrupted) -> Self {
+        ErrorReport::new(
+            PgSqlErrorCode::ERRCODE_QUERY_CANCELED,
+            format!("{value}"),
+            "",
+        )
+    }
+}
+
+/// Check if the current statement is going to be canceled
+///
+/// It returns true when Postgres received a query cancel request, e.g. by
+/// Ctrl-C or `pg_cancel_backend()`, the `statement_timeout` has expired, or
+/// the backend is being terminated.
+pub fn cancel_pending() -> bool {
+    unsafe {
+        ptr::read_volatile(&raw const pg_sys::InterruptPending) != 0
+            && (ptr::read_volatile(&raw const pg_sys::QueryCancelPending) != 0
+                || ptr::read_volatile(&raw const pg_sys::ProcDiePending) != 0)
+    }
+}
+
+/// Get the remaining time before the current statement hits `statement_timeout`
+///
+/// Returns `None` if `statement_timeout` is disabled. Use it to set timeout
+/// on remote jobs, so they can be stopped on the remote side as well.
+///
+/// For example,
+///
+/// ```rust,no_run
+/// # use supabase_wrappers::prelude::remaining_statement_timeout;
+/// let timeout_ms = remaining_statement_timeout().map(|t| t.as_millis() as u64);
+/// ```
+pub fn remaining_statement_timeout() -> Option<Duration> {
+    let timeout_ms = unsafe { pg_sys::StatementTimeout };
+    if timeout_ms <= 0 {
+        return None;
+    }
+
+    let (start, now) = unsafe {
+        (
+            pg_sys::GetCurrentStatementStartTimestamp(),
+            pg_sys::GetCurrentTimestamp(),
+        )
+    };
+    let elapsed_us = (now - start).max(0);
+    let remaining_us = (i64::from(timeout_ms) * 1000 - elapsed_us).max(0);
+    Some(Duration::from_micros(remaining_us as u64))
+}
+
+/// Run a future to completion, unless the statement is canceled
+///
+/// Unlike `Runtime::block_on`, this function checks pending interrupts
+/// periodically while awaiting the future. If the statement is canceled or
+/// timed out, the future is dropped and [`Interrupted`] is returned. Dropping
+/// the future aborts the in-flight request, like a HTTP request or a database
+/// query.
+///
+/// When a FDW callback returns an error while cancellation is pending,
+/// Wrappers calls the [`cancel`](ForeignDataWrapper::cancel) hook and then
+/// reports the cancellation error to Postgres, so the FDW can simply convert
+/// [`Interrupted`] into its own error type.
+///
+/// The runtime must have time driver enabled, which is the case for the
+/// runtime created by [`create_async_runtime`](crate::utils::create_async_runtime).
+///
+/// For example,
+///
+/// ```rust,no_run
+/// # use supabase_wrappers::interrupt::Interrupted;
+/// # fn main() -> Result<(), Interrupted> {
+/// # use supabase_wrappers::prelude::*;
+/// # struct Client {
+/// # }
+/// # impl Client {
+/// #     async fn query(&self, _sql: &str) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let client = Client {};
+/// # let sql = "";
+/// let rt = create_async_runtime().unwrap();
+///
+/// // client.query() is an async function returning a Result
+/// match block_on_interruptible(&rt, client.query(&sql))? {
+///     Ok(result) => { }
+///     Err(err) => { }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn block_on_interruptible<F: Future>(
+    rt: &Runtime,
+    future: F,
+) -> Result<F::Output, Interrupted> {
+    block_on_until(rt, future, cancel_pending)
+}
+
+// run a future to completion, unless is_canceled returns true at one of the
+// periodical checks
+fn block_on_until<F: Future>(
+    rt: &Runtime,
+    future: F,
+    is_canceled: impl Fn() -> bool,
+) -> Result<F::Output, Interrupted> {
+    let mut future = pin!(future);
+    loop {
+        if is_canceled() {
+            return Err(Interrupted);
+        }
+        // the timer must be created inside the runtime context
+        let wait = async { tokio::time::timeout(INTERRUPT_CHECK_INTERVAL, future.as_mut()).await };
+        if let Ok(output) = rt.block_on(wait) {
+            return Ok(output);
+        }
+    }
+}
+
+/// Run a blocking function, and interrupt it if the statement is canceled
+///
+/// It is for the clients which block the current thread but can be stopped
+/// from another thread, like an embedded database. While `f` is running, a
+/// watcher thread checks pending interrupts periodically and calls
+/// `interrupt` if the statement is canceled or timed out. `f` should then
+/// return an error, which is reported as the cancellation error by Wrappers.
+///
+/// For example,
+///
+/// ```rust,no_run
+/// # use supabase_wrappers::prelude::run_interruptible;
+/// # use std::sync::atomic::{AtomicBool, Ordering};
+/// # struct Database {
+/// #     stop: AtomicBool,
+/// # }
+/// # impl Database {
+/// #     fn query(&self, _sql: &str) -> Result<(), ()> { Ok(()) }
+/// #     fn interrupt(&self) { self.stop.store(true, Ordering::SeqCst); }
+/// # }
+/// # let db = Database { stop: AtomicBool::new(false) };
+/// # let sql = "";
+/// let result = run_interruptible(|| db.query(&sql), || db.interrupt());
+/// ```
+pub fn run_interruptible<T>(f: impl FnOnce() -> T, interrupt: impl FnOnce() + Send) -> T {
+    run_until(f, interrupt, cancel_pending)
+}
+
+// run a blocking function, and call interrupt if is_canceled returns true at
+// one of the periodical checks made by a watcher thread
+fn run_until<T>(
+    f: impl FnOnce() -> T,
+    interrupt: impl FnOnce() + Send,
+    is_canceled: impl Fn() -> bool + Send,
+) -> T {
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    thread::scope(|s| {
+        s.spawn(move || {
+            loop {
+                match done_rx.recv_timeout(INTERRUPT_CHECK_INTERVAL) {
+                    Err(RecvTimeoutError::Timeout) if is_canceled() => {
+                        interrupt();
+                        return;
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // f has returned
+                    _ => return,
+                }
+            }
+        });
+        let result = f();
+        drop(done_tx);
+        result
+    })
+}
+
+// call a FDW function, and call the cancel hook if it failed because of a
+// pending cancellation
+pub(crate) fn call_with_cancel<E, W, T, F>(instance: &mut W, f: F) -> Result<T, E>
+where
+    E: Into<ErrorReport>,
+    W: ForeignDataWrapper<E>,
+    F: FnOnce(&mut W) -> Result<T, E>,
+{
+    let result = f(instance);
+    if result.is_err()
+        && cancel_pending()
+        && let Err(err) = instance.cancel()
+    {
+        let err: ErrorReport = err.into();
+        report_warning(&format!(
+            "failed to cancel remote request: {}",
+            err.message()
+        ));
+    }
+    result
+}
+
+// report the Postgres cancellation error if it is pending, this function
+// doesn't return in that case
+pub(crate) fn process_pending_cancel() {
+    if cancel_pending() {
+        check_for_interrupts!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::runtime::Builder;
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().enable_all().build().unwrap()
+    }
+
+    // set the flag when dropped, to check the future is dropped on cancel
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_block_on_ready_future() {
+        let rt = runtime();
+        let result = block_on_until(&rt, async { 42 }, || false);
+        assert_eq!(result, Ok(42));
+    }
+
+    #[test]
+    fn test_block_on_future_longer_than_check_interval() {
+        let rt = runtime();
+        let checks = AtomicUsize::new(0);
+        let future = async {
+            tokio::time::sleep(INTERRUPT_CHECK_INTERVAL * 3).await;
+            "done"
+        };
+        let result = block_on_until(&rt, future, || {
+            checks.fetch_add(1, Ordering::SeqCst);
+            false
+        });
+        assert_eq!(result, Ok("done"));
+        assert!(checks.load(Ordering::SeqCst) > 1);
+    }
+
+    #[test]
+    fn test_block_on_canceled_before_start() {
+        let rt = runtime();
+        let polled = AtomicBool::new(false);
+        let future = async {
+            polled.store(true, Ordering::SeqCst);
+        };
+        let result = block_on_until(&rt, future, || true);
+        assert_eq!(result, Err(Interrupted));
+        assert!(!polled.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_block_on_canceled_while_waiting() {
+        let rt = runtime();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        let future = async move {
+            let _flag = flag;
+            std::future::pending::<()>().await;
+        };
+
+        // cancel at the third check
+        let checks = AtomicUsize::new(0);
+        let result = block_on_until(&rt, future, || checks.fetch_add(1, Ordering::SeqCst) >= 2);
+        assert_eq!(result, Err(Interrupted));
+        assert_eq!(checks.load(Ordering::SeqCst), 3);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_run_not_canceled() {
+        let interrupted = AtomicBool::new(false);
+        let result = run_until(
+            || 42,
+            || interrupted.store(true, Ordering::SeqCst),
+            || false,
+        );
+        assert_eq!(result, 42);
+        assert!(!interrupted.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_run_canceled_while_running() {
+        let interrupted = AtomicBool::new(false);
+        let result = run_until(
+            || {
+                // block until interrupted by the watcher thread
+                while !interrupted.load(Ordering::SeqCst) {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                "interrupted"
+            },
+            || interrupted.store(true, Ordering::SeqCst),
+            || true,
+        );
+        assert_eq!(result, "interrupted");
+    }
+}
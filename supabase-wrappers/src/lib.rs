@@ -43,6 +43,7 @@
 //!
 //! - Query planning phase
 //!   - [get_rel_size()](`interface::ForeignDataWrapper#method.get_rel_size`)
+//!   - [scan_capabilities()](`interface::ForeignDataWrapper#method.scan_capabilities`)
 //! - Scan phase
 //!   - [begin_scan()](`interface::ForeignDataWrapper#tymethod.begin_scan`) *required*
 //!   - [iter_scan()](`interface::ForeignDataWrapper#tymethod.iter_scan`) *required*
@@ -51,9 +52,13 @@
 //! - Modify phase
 //!   - [begin_modify()](`interface::ForeignDataWrapper#method.begin_modify`)
 //!   - [insert()](`interface::ForeignDataWrapper#method.insert`)
+//!   - [insert_batch()](`interface::ForeignDataWrapper#method.insert_batch`)
 //!   - [update()](`interface::ForeignDataWrapper#method.update`)
 //!   - [delete()](`interface::ForeignDataWrapper#method.delete`)
 //!   - [end_modify()](`interface::ForeignDataWrapper#method.end_modify`)
+//! - Transaction end
+//!   - [pre_commit()](`interface::ForeignDataWrapper#method.pre_commit`)
+//!   - [abort()](`interface::ForeignDataWrapper#method.abort`)
 //!
 //! To give different functionalities to your FDW, you can choose different callback functions to implement. The required ones are `begin_scan`, `iter_scan` and `end_scan`, all the others are optional. See [Postgres FDW document](https://www.postgresql.org/docs/current/fdw-callbacks.html) for more details about FDW development.
 //!
@@ -270,9 +275,9 @@
 //!          Sort Key: col
 //!          ->  Foreign Scan on hello  (cost=0.00..1.00 rows=1 width=0)
 //!                Filter: (id = 1)
-//!                Wrappers: quals = [Qual { field: "id", operator: "=", value: Cell(I32(1)), use_or: false, param: None }]
-//!                Wrappers: tgts = [Column { name: "id", num: 1, type_oid: 20 }, Column { name: "col", num: 2, type_oid: 25 }]
-//!                Wrappers: sorts = [Sort { field: "col", field_no: 2, reversed: false, nulls_first: false, collate: None }]
+//!                Wrappers: quals = [Qual { field: "id", remote_field: "id", operator: "=", value: Cell(I32(1)), use_or: false, param: None }]
+//!                Wrappers: tgts = [Column { name: "id", remote_name: "id", num: 1, type_oid: 20 }, Column { name: "col", remote_name: "col", num: 2, type_oid: 25 }]
+//!                Wrappers: sorts = [Sort { field: "col", remote_field: "col", field_no: 2, reversed: false, nulls_first: false, collate: None }]
 //!                Wrappers: limit = Some(Limit { count: 1, offset: 0 })
 //! (9 rows)
 //! ```
@@ -292,15 +297,23 @@
 //! - [SQL Server](https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/mssql_fdw): A FDW for [Microsoft SQL Server](https://www.microsoft.com/en-au/sql-server/) which supports data read only.
 //! - [Redis](https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/redis_fdw): A FDW for [Redis](https://redis.io/) which supports data read only.
 
+pub mod async_interface;
+pub mod errors;
+pub mod hooks;
 pub mod interface;
+pub mod interrupt;
+pub mod on_conflict;
 pub mod options;
 pub mod utils;
 
 /// The prelude includes all necessary imports to make Wrappers work
 pub mod prelude {
+    pub use crate::async_interface::*;
+    pub use crate::errors::*;
     pub use crate::import_foreign_schema::*;
     pub use crate::instance::ForeignServer;
     pub use crate::interface::*;
+    pub use crate::interrupt::*;
     pub use crate::options::*;
     pub use crate::utils::*;
     pub use crate::wrappers_fdw;
@@ -319,6 +332,7 @@ mod polyfill;
 mod qual;
 mod scan;
 mod sort;
+mod transaction;
 
 /// PgBox'ed `FdwRoutine`, used in [`fdw_routine`](interface::ForeignDataWrapper::fdw_routine)
 pub type FdwRoutine<A = AllocatedByPostgres> = PgBox<pg_sys::FdwRoutine, A>;
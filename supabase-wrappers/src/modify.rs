@@ -11,14 +11,20 @@ use pgrx::{
 use std::collections::HashMap;
 use std::ffi::c_void;
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 use std::os::raw::c_int;
 use std::ptr;
 
 use crate::prelude::*;
 
+use super::hooks::{RequestKind, RequestTracker};
 use super::instance;
+use super::interrupt;
 use super::memctx;
+use super::on_conflict;
+use super::options::get_remote_column_name;
 use super::polyfill;
+use super::transaction;
 use super::utils;
 
 /// Serializable data for fdw_private in modify operations.
@@ -35,6 +41,53 @@ struct FdwModifyPrivate {
     /// Update columns (pg13 only)
     #[cfg(feature = "pg13")]
     update_cols: Vec<String>,
+    /// Conflict target columns and action of `INSERT ... ON CONFLICT`
+    on_conflict: Option<(Vec<String>, ConflictAction)>,
+}
+
+// make a Const node holding an INT4 value
+unsafe fn int4_const(value: i32) -> *mut pg_sys::Const {
+    unsafe {
+        pg_sys::makeConst(
+            pg_sys::INT4OID,
+            -1,
+            pg_sys::InvalidOid,
+            4,
+            value.into_datum().unwrap(),
+            false,
+            true,
+        )
+    }
+}
+
+// make a Const node holding a TEXT value
+unsafe fn text_const(value: &str) -> *mut pg_sys::Const {
+    unsafe {
+        pg_sys::makeConst(
+            pg_sys::TEXTOID,
+            -1,
+            pg_sys::InvalidOid,
+            -1,
+            value.into_datum().unwrap(),
+            false,
+            false,
+        )
+    }
+}
+
+// read TEXT Const nodes prefixed by an INT4 count, and move to the next position
+unsafe fn get_text_list(list: &List<'_, *mut c_void>, pos: &mut usize) -> Option<Vec<String>> {
+    unsafe {
+        let cst = *(*list.get(*pos)? as *mut pg_sys::Const);
+        let count = i32::from_datum(cst.constvalue, cst.constisnull)? as usize;
+        let mut ret = Vec::with_capacity(count);
+        for i in 0..count {
+            let cst = *(*list.get(*pos + 1 + i)? as *mut pg_sys::Const);
+            ret.push(String::from_datum(cst.constvalue, cst.constisnull)?);
+        }
+        *pos += count + 1;
+        Some(ret)
+    }
 }
 
 impl FdwModifyPrivate {
@@ -45,6 +98,9 @@ impl FdwModifyPrivate {
     /// - [2] INT4: rowid_typid as i32
     /// - [3] INT4: update_cols_count (pg13 only)
     /// - [4..N] TEXT: update_cols entries (pg13 only)
+    /// - [N+1] INT4: on conflict action, 0: none, 1: do nothing, 2: do update
+    /// - [N+2] INT4: conflict_columns_count, followed by TEXT entries
+    /// - [M] INT4: do update columns count, followed by TEXT entries
     unsafe fn serialize_to_list(&self) -> *mut pg_sys::List {
         pgrx::memcx::current_context(|mcx| unsafe {
             let mut ret = List::<*mut c_void>::Nil;
@@ -114,6 +170,20 @@ impl FdwModifyPrivate {
                 }
             }
 
+            // on conflict action, conflict columns and do update columns
+            let (action, conflict_cols, set_cols) = match &self.on_conflict {
+                None => (0, &[][..], &[][..]),
+                Some((cols, ConflictAction::DoNothing)) => (1, &cols[..], &[][..]),
+                Some((cols, ConflictAction::DoUpdate { columns })) => (2, &cols[..], &columns[..]),
+            };
+            ret.unstable_push_in_context(int4_const(action) as _, mcx);
+            for cols in [conflict_cols, set_cols] {
+                ret.unstable_push_in_context(int4_const(cols.len() as i32) as _, mcx);
+                for col in cols {
+                    ret.unstable_push_in_context(text_const(col) as _, mcx);
+                }
+            }
+
             ret.into_ptr()
         })
     }
@@ -140,6 +210,12 @@ impl FdwModifyPrivate {
             let rowid_typid_i32 = i32::from_datum(cst.constvalue, cst.constisnull)?;
             let rowid_typid = Oid::from(rowid_typid_i32 as u32);
 
+            #[cfg(not(feature = "pg13"))]
+            let mut pos = 3;
+
+            #[cfg(feature = "pg13")]
+            let mut pos = 4;
+
             #[cfg(feature = "pg13")]
             let update_cols = {
                 // [3] update_cols_count
@@ -155,24 +231,70 @@ impl FdwModifyPrivate {
                     let col = String::from_datum(cst.constvalue, cst.constisnull)?;
                     cols.push(col);
                 }
+                pos += count;
                 cols
             };
 
+            // [N+1] on conflict action, followed by conflict columns and
+            // do update columns
+            let cst = *(*list.get(pos)? as *mut pg_sys::Const);
+            let action = i32::from_datum(cst.constvalue, cst.constisnull)?;
+            pos += 1;
+            let conflict_cols = get_text_list(&list, &mut pos)?;
+            let set_cols = get_text_list(&list, &mut pos)?;
+            let on_conflict = match action {
+                1 => Some((conflict_cols, ConflictAction::DoNothing)),
+                2 => Some((
+                    conflict_cols,
+                    ConflictAction::DoUpdate { columns: set_cols },
+                )),
+                _ => None,
+            };
+
             Some(FdwModifyPrivate {
                 foreigntableid,
                 rowid_name,
                 rowid_typid,
                 #[cfg(feature = "pg13")]
                 update_cols,
+                on_conflict,
             })
         })
     }
 }
 
+// foreign data wrapper instance of a modification
+enum ModifyInstance<W> {
+    Owned(W),
+    // owned by the transaction if the FDW supports transaction hooks, it is
+    // valid until the modification ends
+    Xact(*mut W),
+}
+
+impl<W> Deref for ModifyInstance<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        match self {
+            Self::Owned(instance) => instance,
+            Self::Xact(instance) => unsafe { &**instance },
+        }
+    }
+}
+
+impl<W> DerefMut for ModifyInstance<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        match self {
+            Self::Owned(instance) => instance,
+            Self::Xact(instance) => unsafe { &mut **instance },
+        }
+    }
+}
+
 // Fdw private state for modify
 struct FdwModifyState<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> {
     // foreign data wrapper instance
-    instance: Option<W>,
+    instance: Option<ModifyInstance<W>>,
 
     // row id attribute number and type id
     rowid_name: String,
@@ -182,9 +304,19 @@ struct FdwModifyState<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> {
     // foreign table options
     opts: HashMap<String, String>,
 
+    // local to remote column names, only for the columns renamed by the
+    // `column_name` column option
+    remote_names: HashMap<String, String>,
+
     // temporary memory context per foreign table, created under Wrappers root
     // memory context
     tmp_ctx: MemoryContext,
+
+    // request statistics of this modification
+    tracker: RequestTracker,
+
+    // conflict target columns and action of 'INSERT ... ON CONFLICT'
+    on_conflict: Option<(Vec<String>, ConflictAction)>,
     _phantom: PhantomData<E>,
 
     #[cfg(feature = "pg13")]
@@ -193,40 +325,120 @@ struct FdwModifyState<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> {
 
 impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwModifyState<E, W> {
     fn begin_modify(&mut self) -> Result<(), E> {
-        if let Some(ref mut instance) = self.instance {
-            instance.begin_modify(&self.opts)
+        if let Some(instance) = self.instance.as_deref_mut() {
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| instance.begin_modify(&self.opts))
+            })
         } else {
             Ok(())
         }
     }
 
+    // let the transaction own the FDW instance if it supports transaction
+    // hooks, which is known after begin_modify, so its pre_commit or abort
+    // is called even if end_modify is never reached
+    fn join_xact(&mut self) {
+        self.instance = match self.instance.take() {
+            Some(ModifyInstance::Owned(instance)) if instance.transaction_hooks_supported() => {
+                Some(ModifyInstance::Xact(transaction::join_xact::<E, W>(
+                    instance,
+                )))
+            }
+            instance => instance,
+        };
+    }
+
+    // rename the row's columns to their remote names
+    fn to_remote_row(&self, mut row: Row) -> Row {
+        for col in row.cols.iter_mut() {
+            if let Some(remote_name) = self.remote_names.get(col) {
+                *col = remote_name.clone();
+            }
+        }
+        row
+    }
+
     fn insert(&mut self, row: &Row) -> Result<(), E> {
-        if let Some(ref mut instance) = self.instance {
-            instance.insert(row)
+        if let Some(instance) = self.instance.as_deref_mut() {
+            self.tracker
+                .track(|| interrupt::call_with_cancel(instance, |instance| instance.insert(row)))?;
+            self.tracker.add_rows(1);
+            Ok(())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(not(feature = "pg13"))]
+    fn get_modify_batch_size(&mut self) -> usize {
+        if let Some(instance) = self.instance.as_deref_mut()
+            && self.on_conflict.is_none()
+        {
+            self.tracker
+                .enter(|| instance.get_modify_batch_size())
+                .max(1)
+        } else {
+            1
+        }
+    }
+
+    #[cfg(not(feature = "pg13"))]
+    fn insert_batch(&mut self, rows: &[Row]) -> Result<(), E> {
+        if let Some(instance) = self.instance.as_deref_mut() {
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| instance.insert_batch(rows))
+            })?;
+            self.tracker.add_rows(rows.len() as u64);
+            Ok(())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn upsert(&mut self, row: &Row) -> Result<(), E> {
+        if let Some(instance) = self.instance.as_deref_mut()
+            && let Some((conflict_columns, action)) = &self.on_conflict
+        {
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| {
+                    instance.upsert(row, conflict_columns, action)
+                })
+            })?;
+            self.tracker.add_rows(1);
+            Ok(())
         } else {
             Ok(())
         }
     }
 
     fn update(&mut self, rowid: &Cell, new_row: &Row) -> Result<(), E> {
-        if let Some(ref mut instance) = self.instance {
-            instance.update(rowid, new_row)
+        if let Some(instance) = self.instance.as_deref_mut() {
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| instance.update(rowid, new_row))
+            })?;
+            self.tracker.add_rows(1);
+            Ok(())
         } else {
             Ok(())
         }
     }
 
     fn delete(&mut self, rowid: &Cell) -> Result<(), E> {
-        if let Some(ref mut instance) = self.instance {
-            instance.delete(rowid)
+        if let Some(instance) = self.instance.as_deref_mut() {
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| instance.delete(rowid))
+            })?;
+            self.tracker.add_rows(1);
+            Ok(())
         } else {
             Ok(())
         }
     }
 
     fn end_modify(&mut self) -> Result<(), E> {
-        if let Some(ref mut instance) = self.instance {
-            instance.end_modify()
+        if let Some(instance) = self.instance.as_deref_mut() {
+            self.tracker
+                .track(|| interrupt::call_with_cancel(instance, |instance| instance.end_modify()))
         } else {
             Ok(())
         }
@@ -235,8 +447,12 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwModifyState<E, W> {
 
 impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> Drop for FdwModifyState<E, W> {
     fn drop(&mut self) {
-        // drop foreign data wrapper instance
-        self.instance.take();
+        // drop foreign data wrapper instance, it may still report its own
+        // statistics for the foreign table. The instance owned by the
+        // transaction is dropped when the transaction ends instead.
+        if let Some(ModifyInstance::Owned(instance)) = self.instance.take() {
+            self.tracker.enter(|| drop(instance));
+        }
 
         // remove the allocated memory context
         unsafe {
@@ -254,6 +470,42 @@ unsafe fn drop_fdw_modify_state<E: Into<ErrorReport>, W: ForeignDataWrapper<E>>(
     drop(boxed_fdw_state);
 }
 
+// drop the modify state when the executor memory context is freed, as
+// end_foreign_modify is not called if the statement is ended by an error
+// raised by Postgres. It does nothing if the state is already dropped.
+#[pg_guard]
+unsafe extern "C-unwind" fn reset_fdw_modify_state<
+    E: Into<ErrorReport>,
+    W: ForeignDataWrapper<E>,
+>(
+    arg: *mut c_void,
+) {
+    let rinfo = arg as *mut pg_sys::ResultRelInfo;
+    unsafe {
+        let fdw_state = (*rinfo).ri_FdwState as *mut FdwModifyState<E, W>;
+        if !fdw_state.is_null() {
+            (*rinfo).ri_FdwState = ptr::null::<FdwModifyState<E, W>>() as _;
+            drop_fdw_modify_state(fdw_state);
+        }
+    }
+}
+
+// get local to remote name map of the columns renamed by the `column_name`
+// column option
+unsafe fn get_remote_column_names(relid: Oid) -> HashMap<String, String> {
+    let rel = unsafe { PgRelation::with_lock(relid, pg_sys::NoLock as _) };
+    let tup_desc = PgTupleDesc::from_relation(&rel);
+    tup_desc
+        .iter()
+        .filter(|a| !a.attisdropped)
+        .filter_map(|attr| {
+            let name = pgrx::name_data_to_str(&attr.attname);
+            let remote_name = unsafe { get_remote_column_name(relid, attr.attnum, name) };
+            (remote_name != name).then(|| (name.to_string(), remote_name))
+        })
+        .collect()
+}
+
 // find rowid column in relation description
 unsafe fn find_rowid_column(
     target_relation: pg_sys::Relation,
@@ -386,6 +638,12 @@ pub(super) extern "C-unwind" fn plan_foreign_modify<
             let attname = pgrx::name_data_to_str(&attr.attname);
             if attname == rowid_name {
                 let foreigntableid = rel.oid();
+                let on_conflict = on_conflict::extract_on_conflict(
+                    plan,
+                    result_relation,
+                    foreigntableid,
+                    W::upsert_supported(),
+                );
 
                 // Collect update columns for pg13
                 #[cfg(feature = "pg13")]
@@ -414,6 +672,7 @@ pub(super) extern "C-unwind" fn plan_foreign_modify<
                     rowid_typid: attr.atttypid,
                     #[cfg(feature = "pg13")]
                     update_cols,
+                    on_conflict,
                 };
 
                 // Serialize the data to a PostgreSQL List.
@@ -487,14 +746,23 @@ pub(super) extern "C-unwind" fn begin_foreign_modify<
             (*ftable).relid.to_u32().to_string(),
         );
 
+        // the FDW sees remote column names only, including the rowid column
+        let remote_names = get_remote_column_names(private.foreigntableid);
+        if let Some(remote_name) = remote_names.get(&private.rowid_name) {
+            opts.insert("rowid_column".into(), remote_name.clone());
+        }
+
         // Create the FdwModifyState with fresh data
         let mut state = FdwModifyState::<E, W> {
-            instance: Some(fdw_instance),
+            instance: Some(ModifyInstance::Owned(fdw_instance)),
             rowid_name: private.rowid_name,
             rowid_attno: 0, // Will be set below
             rowid_typid: private.rowid_typid,
             opts,
+            remote_names,
             tmp_ctx,
+            tracker: RequestTracker::new(private.foreigntableid, RequestKind::Modify),
+            on_conflict: private.on_conflict,
             _phantom: PhantomData,
             #[cfg(feature = "pg13")]
             update_cols: private.update_cols,
@@ -518,8 +786,20 @@ pub(super) extern "C-unwind" fn begin_foreign_modify<
             drop_fdw_modify_state(state.as_ptr());
             result.report_unwrap();
         }
+        state.join_xact();
 
         (*rinfo).ri_FdwState = state.into_pg() as _;
+
+        // the result relation info is allocated in the executor memory
+        // context, so it is still valid when the callback is called
+        let query_ctx = (*(*mtstate).ps.state).es_query_cxt;
+        let callback = pg_sys::MemoryContextAllocZero(
+            query_ctx,
+            std::mem::size_of::<pg_sys::MemoryContextCallback>(),
+        ) as *mut pg_sys::MemoryContextCallback;
+        (*callback).func = Some(reset_fdw_modify_state::<E, W>);
+        (*callback).arg = rinfo as _;
+        pg_sys::MemoryContextRegisterResetCallback(query_ctx, callback);
     }
 }
 
@@ -540,8 +820,12 @@ pub(super) extern "C-unwind" fn exec_foreign_insert<
         );
 
         let result = PgMemoryContexts::For(state.tmp_ctx).switch_to(|_| {
-            let row = utils::tuple_table_slot_to_row(slot);
-            state.insert(&row)
+            let row = state.to_remote_row(utils::tuple_table_slot_to_row(slot));
+            if state.on_conflict.is_some() {
+                state.upsert(&row)
+            } else {
+                state.insert(&row)
+            }
         });
         if result.is_err() {
             drop_fdw_modify_state(state.as_ptr());
@@ -553,6 +837,73 @@ pub(super) extern "C-unwind" fn exec_foreign_insert<
     slot
 }
 
+#[cfg(not(feature = "pg13"))]
+#[pg_guard]
+pub(super) extern "C-unwind" fn get_foreign_modify_batch_size<
+    E: Into<ErrorReport>,
+    W: ForeignDataWrapper<E>,
+>(
+    rinfo: *mut pg_sys::ResultRelInfo,
+) -> c_int {
+    debug2!("---> get_foreign_modify_batch_size");
+    unsafe {
+        let fdw_state = (*rinfo).ri_FdwState as *mut FdwModifyState<E, W>;
+        if fdw_state.is_null() {
+            return 1;
+        }
+
+        // disable batching when rows need to be returned or checked one by
+        // one, same as postgres_fdw
+        let trig_desc = (*rinfo).ri_TrigDesc;
+        if !(*rinfo).ri_projectReturning.is_null()
+            || !(*rinfo).ri_WithCheckOptions.is_null()
+            || (!trig_desc.is_null()
+                && ((*trig_desc).trig_insert_before_row || (*trig_desc).trig_insert_after_row))
+        {
+            return 1;
+        }
+
+        let mut state = PgBox::<FdwModifyState<E, W>>::from_pg(fdw_state);
+        state.get_modify_batch_size().min(c_int::MAX as usize) as c_int
+    }
+}
+
+#[cfg(not(feature = "pg13"))]
+#[pg_guard]
+pub(super) extern "C-unwind" fn exec_foreign_batch_insert<
+    E: Into<ErrorReport>,
+    W: ForeignDataWrapper<E>,
+>(
+    _estate: *mut pg_sys::EState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    slots: *mut *mut pg_sys::TupleTableSlot,
+    _plan_slots: *mut *mut pg_sys::TupleTableSlot,
+    num_slots: *mut c_int,
+) -> *mut *mut pg_sys::TupleTableSlot {
+    debug2!("---> exec_foreign_batch_insert");
+    unsafe {
+        let mut state = PgBox::<FdwModifyState<E, W>>::from_pg(
+            (*rinfo).ri_FdwState as *mut FdwModifyState<E, W>,
+        );
+
+        let result = PgMemoryContexts::For(state.tmp_ctx).switch_to(|_| {
+            let slots = std::slice::from_raw_parts(slots, *num_slots as usize);
+            let rows: Vec<Row> = slots
+                .iter()
+                .map(|slot| state.to_remote_row(utils::tuple_table_slot_to_row(*slot)))
+                .collect();
+            state.insert_batch(&rows)
+        });
+        if result.is_err() {
+            drop_fdw_modify_state(state.as_ptr());
+            (*rinfo).ri_FdwState = ptr::null::<FdwModifyState<E, W>>() as _;
+            result.report_unwrap();
+        }
+    }
+
+    slots
+}
+
 unsafe fn get_rowid_cell<E: Into<ErrorReport>, W: ForeignDataWrapper<E>>(
     state: &FdwModifyState<E, W>,
     plan_slot: *mut pg_sys::TupleTableSlot,
@@ -639,6 +990,7 @@ pub(super) extern "C-unwind" fn exec_foreign_update<
                     }
                 });
 
+                let new_row = state.to_remote_row(new_row);
                 state.update(&rowid, &new_row)
             } else {
                 Ok(())
@@ -0,0 +1,225 @@
+//! Support `INSERT ... ON CONFLICT` on foreign tables
+//!
+//! Postgres resolves the conflict target, e.g. `ON CONFLICT (id)`, against
+//! unique indexes of the table while planning, but foreign tables cannot have
+//! any index. So a planner hook takes the conflict target out of the query
+//! for foreign tables handled by Wrappers, and saves its column names for
+//! `plan_foreign_modify` to pass them to the FDW.
+//!
+
+use pgrx::pg_sys::{self, Oid};
+use pgrx::{PgList, PgSqlErrorCode, is_a, pg_guard};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::Once;
+
+use crate::interface::ConflictAction;
+use crate::modify;
+use crate::options::get_remote_column_name;
+use crate::utils::report_error;
+
+static INSTALL_HOOK: Once = Once::new();
+static mut PREV_PLANNER_HOOK: pg_sys::planner_hook_type = None;
+
+thread_local! {
+    // conflict target columns taken out of the query, keyed by foreign table oid
+    static CONFLICT_TARGETS: RefCell<HashMap<Oid, Vec<String>>> = RefCell::new(HashMap::new());
+}
+
+/// Install the planner hook to support `INSERT ... ON CONFLICT` on foreign
+/// tables
+///
+/// Call it in the `_PG_init` of the FDW extension, so the hook is in place
+/// before any statement is planned in the session. Without it, Postgres
+/// rejects `INSERT ... ON CONFLICT` with a conflict target on foreign tables.
+/// It is safe to call this function more than once, the hook is installed
+/// only once.
+///
+/// ```rust,no_run
+/// # use pgrx::prelude::*;
+/// #[pg_guard]
+/// pub extern "C-unwind" fn _PG_init() {
+///     supabase_wrappers::on_conflict::install_planner_hook();
+/// }
+/// # fn main() {}
+/// ```
+pub fn install_planner_hook() {
+    INSTALL_HOOK.call_once(|| unsafe {
+        PREV_PLANNER_HOOK = pg_sys::planner_hook;
+        pg_sys::planner_hook = Some(on_conflict_planner);
+    });
+}
+
+#[pg_guard]
+unsafe extern "C-unwind" fn on_conflict_planner(
+    parse: *mut pg_sys::Query,
+    query_string: *const c_char,
+    cursor_options: c_int,
+    bound_params: pg_sys::ParamListInfo,
+) -> *mut pg_sys::PlannedStmt {
+    unsafe {
+        // start with no conflict targets, so the ones left by a statement
+        // which failed before plan_foreign_modify won't be picked up, and
+        // restore the outer statement's ones after a nested planning
+        let outer_targets = CONFLICT_TARGETS.with(|t| t.take());
+
+        take_conflict_target(parse);
+        let result = match PREV_PLANNER_HOOK {
+            Some(prev) => prev(parse, query_string, cursor_options, bound_params),
+            None => pg_sys::standard_planner(parse, query_string, cursor_options, bound_params),
+        };
+
+        CONFLICT_TARGETS.with(|t| *t.borrow_mut() = outer_targets);
+        result
+    }
+}
+
+// check if the foreign table is handled by a FDW built with Wrappers
+unsafe fn is_wrappers_foreign_table(relid: Oid) -> bool {
+    unsafe {
+        let routine = pg_sys::GetFdwRoutineByRelId(relid);
+        (*routine).AddForeignUpdateTargets.map(|f| f as usize)
+            == Some(modify::add_foreign_update_targets as usize)
+    }
+}
+
+// take the conflict target out of an INSERT query on a foreign table, so
+// Postgres won't search unique index for it
+unsafe fn take_conflict_target(parse: *mut pg_sys::Query) {
+    unsafe {
+        let on_conflict = (*parse).onConflict;
+        if (*parse).commandType != pg_sys::CmdType::CMD_INSERT
+            || on_conflict.is_null()
+            || (*on_conflict).arbiterElems.is_null()
+        {
+            return;
+        }
+
+        let rtable = PgList::<pg_sys::RangeTblEntry>::from_pg((*parse).rtable);
+        let Some(rte) = rtable.get_ptr(((*parse).resultRelation - 1) as usize) else {
+            return;
+        };
+        if (*rte).relkind as u8 != pg_sys::RELKIND_FOREIGN_TABLE
+            || !is_wrappers_foreign_table((*rte).relid)
+        {
+            return;
+        }
+
+        let mut columns = Vec::new();
+        let elems = PgList::<pg_sys::InferenceElem>::from_pg((*on_conflict).arbiterElems);
+        for elem in elems.iter_ptr() {
+            let var = (*elem).expr as *mut pg_sys::Var;
+            if !is_a(var as _, pg_sys::NodeTag::T_Var) || (*var).varattno < 1 {
+                report_error(
+                    PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+                    "only columns are supported in ON CONFLICT target of foreign table",
+                );
+                return;
+            }
+            let attname = pg_sys::get_attname((*rte).relid, (*var).varattno, false);
+            let attname = CStr::from_ptr(attname).to_str().unwrap();
+            columns.push(get_remote_column_name(
+                (*rte).relid,
+                (*var).varattno,
+                attname,
+            ));
+        }
+
+        CONFLICT_TARGETS.with(|t| t.borrow_mut().insert((*rte).relid, columns));
+        (*on_conflict).arbiterElems = std::ptr::null_mut();
+        (*on_conflict).arbiterWhere = std::ptr::null_mut();
+    }
+}
+
+// get the ON CONFLICT clause of an INSERT on foreign table, report error if
+// it cannot be forwarded to the FDW
+pub(crate) unsafe fn extract_on_conflict(
+    plan: *mut pg_sys::ModifyTable,
+    result_relation: pg_sys::Index,
+    relid: Oid,
+    upsert_supported: bool,
+) -> Option<(Vec<String>, ConflictAction)> {
+    unsafe {
+        let conflict_columns = CONFLICT_TARGETS
+            .with(|t| t.borrow_mut().remove(&relid))
+            .unwrap_or_default();
+
+        let action = (*plan).onConflictAction;
+        let action = if action == pg_sys::OnConflictAction::ONCONFLICT_NOTHING {
+            // plain 'DO NOTHING' is executed as a normal insert if the FDW
+            // doesn't support upsert, this is how it worked before
+            if !upsert_supported && conflict_columns.is_empty() {
+                return None;
+            }
+            ConflictAction::DoNothing
+        } else if action == pg_sys::OnConflictAction::ONCONFLICT_UPDATE {
+            if !(*plan).onConflictWhere.is_null() {
+                report_error(
+                    PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+                    "WHERE condition in ON CONFLICT DO UPDATE is not supported on foreign table",
+                );
+                return None;
+            }
+
+            let mut columns = Vec::new();
+            let tgts = PgList::<pg_sys::TargetEntry>::from_pg((*plan).onConflictSet);
+
+            // column number of each SET target entry, since PG14 resno is the
+            // entry's position in the list and the column number is kept in
+            // onConflictCols instead
+            #[cfg(not(feature = "pg13"))]
+            let attnos = PgList::<c_int>::from_pg((*plan).onConflictCols)
+                .iter_int()
+                .map(|attno| attno as pg_sys::AttrNumber)
+                .collect::<Vec<_>>();
+
+            #[cfg(feature = "pg13")]
+            let attnos = tgts
+                .iter_ptr()
+                .filter(|t| !(**t).resjunk)
+                .map(|t| (*t).resno)
+                .collect::<Vec<_>>();
+
+            let set_tgts = tgts.iter_ptr().filter(|t| !(**t).resjunk);
+            for (tgt, attno) in set_tgts.zip(attnos) {
+                let var = (*tgt).expr as *mut pg_sys::Var;
+                let is_same_col =
+                    attno > 0 && is_a(var as _, pg_sys::NodeTag::T_Var) && (*var).varattno == attno;
+
+                // 'SET col = EXCLUDED.col'
+                if is_same_col && (*var).varno as pg_sys::Index == (*plan).exclRelRTI {
+                    let attname = pg_sys::get_attname(relid, attno, false);
+                    let attname = CStr::from_ptr(attname).to_str().unwrap();
+                    columns.push(get_remote_column_name(relid, attno, attname));
+                    continue;
+                }
+
+                // column not in the SET clause, which keeps its existing value
+                if is_same_col && (*var).varno as pg_sys::Index == result_relation {
+                    continue;
+                }
+
+                report_error(
+                    PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+                    "only 'SET column = EXCLUDED.column' is supported in ON CONFLICT DO UPDATE on foreign table",
+                );
+                return None;
+            }
+            ConflictAction::DoUpdate { columns }
+        } else {
+            return None;
+        };
+
+        if !upsert_supported {
+            report_error(
+                PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+                "ON CONFLICT is not supported by this foreign data wrapper",
+            );
+            return None;
+        }
+
+        Some((conflict_columns, action))
+    }
+}
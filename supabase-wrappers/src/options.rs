@@ -140,6 +140,20 @@ pub(super) unsafe fn options_to_hashmap(
     })
 }
 
+// get remote column name from the `column_name` column option, or use the
+// local column name if it is not specified
+pub(super) unsafe fn get_remote_column_name(
+    relid: pg_sys::Oid,
+    attnum: pg_sys::AttrNumber,
+    name: &str,
+) -> String {
+    let options = unsafe { pg_sys::GetForeignColumnOptions(relid, attnum) };
+    unsafe { options_to_hashmap(options) }
+        .ok()
+        .and_then(|mut opts| opts.remove("column_name"))
+        .unwrap_or_else(|| name.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -15,6 +15,7 @@ use std::ptr;
 use std::sync::Mutex;
 
 use crate::interface::Param;
+use crate::options::get_remote_column_name;
 
 // create array of Cell from constant datum array
 pub(crate) unsafe fn form_array_from_datum(
@@ -206,6 +207,9 @@ pub(crate) unsafe fn extract_from_op_expr(
                         && (*left).varattno >= 1
                     {
                         let field = pg_sys::get_attname(baserel_id, (*left).varattno, false);
+                        let field = CStr::from_ptr(field).to_str().unwrap().to_string();
+                        let remote_field =
+                            get_remote_column_name(baserel_id, (*left).varattno, &field);
 
                         let (value, param) = if is_a(right, pg_sys::NodeTag::T_Const) {
                             let right = right as *mut pg_sys::Const;
@@ -242,7 +246,8 @@ pub(crate) unsafe fn extract_from_op_expr(
 
                         if let Some(value) = value {
                             let qual = Qual {
-                                field: CStr::from_ptr(field).to_str().unwrap().to_string(),
+                                field,
+                                remote_field,
                                 operator: pgrx::name_data_to_str(&(*opr).oprname).to_string(),
                                 value: Value::Cell(value),
                                 use_or: false,
@@ -274,6 +279,8 @@ pub(crate) unsafe fn extract_from_null_test(
         }
 
         let field = pg_sys::get_attname(baserel_id, (*var).varattno, false);
+        let field = CStr::from_ptr(field).to_str().unwrap().to_string();
+        let remote_field = get_remote_column_name(baserel_id, (*var).varattno, &field);
 
         let opname = if (*expr).nulltesttype == pg_sys::NullTestType::IS_NULL {
             "is".to_string()
@@ -282,7 +289,8 @@ pub(crate) unsafe fn extract_from_null_test(
         };
 
         let qual = Qual {
-            field: CStr::from_ptr(field).to_str().unwrap().to_string(),
+            field,
+            remote_field,
             operator: opname,
             value: Value::Cell(Cell::String("null".to_string())),
             use_or: false,
@@ -325,6 +333,9 @@ pub(crate) unsafe fn extract_from_scalar_array_op_expr(
                         && (*left).varattno >= 1
                     {
                         let field = pg_sys::get_attname(baserel_id, (*left).varattno, false);
+                        let field = CStr::from_ptr(field).to_str().unwrap().to_string();
+                        let remote_field =
+                            get_remote_column_name(baserel_id, (*left).varattno, &field);
 
                         let value: Option<Vec<Cell>> = form_array_from_datum(
                             (*right).constvalue,
@@ -333,7 +344,8 @@ pub(crate) unsafe fn extract_from_scalar_array_op_expr(
                         );
                         if let Some(value) = value {
                             let qual = Qual {
-                                field: CStr::from_ptr(field).to_str().unwrap().to_string(),
+                                field,
+                                remote_field,
                                 operator: pgrx::name_data_to_str(&(*opr).oprname).to_string(),
                                 value: Value::Array(value),
                                 use_or: (*expr).useOr,
@@ -369,9 +381,12 @@ pub(crate) unsafe fn extract_from_var(
         }
 
         let field = pg_sys::get_attname(baserel_id, (*var).varattno, false);
+        let field = CStr::from_ptr(field).to_str().unwrap().to_string();
+        let remote_field = get_remote_column_name(baserel_id, (*var).varattno, &field);
 
         let qual = Qual {
-            field: CStr::from_ptr(field).to_str().unwrap().to_string(),
+            field,
+            remote_field,
             operator: "=".to_string(),
             value: Value::Cell(Cell::Bool(true)),
             use_or: false,
@@ -404,9 +419,12 @@ pub(crate) unsafe fn extract_from_bool_expr(
                 }
 
                 let field = pg_sys::get_attname(baserel_id, (*var).varattno, false);
+                let field = CStr::from_ptr(field).to_str().unwrap().to_string();
+                let remote_field = get_remote_column_name(baserel_id, (*var).varattno, &field);
 
                 let qual = Qual {
-                    field: CStr::from_ptr(field).to_str().unwrap().to_string(),
+                    field,
+                    remote_field,
                     operator: "=".to_string(),
                     value: Value::Cell(Cell::Bool(false)),
                     use_or: false,
@@ -432,6 +450,8 @@ pub(crate) unsafe fn extract_from_boolean_test(
         }
 
         let field = pg_sys::get_attname(baserel_id, (*var).varattno, false);
+        let field = CStr::from_ptr(field).to_str().unwrap().to_string();
+        let remote_field = get_remote_column_name(baserel_id, (*var).varattno, &field);
 
         let (opname, value) = match (*expr).booltesttype {
             pg_sys::BoolTestType::IS_TRUE => ("is".to_string(), true),
@@ -442,7 +462,8 @@ pub(crate) unsafe fn extract_from_boolean_test(
         };
 
         let qual = Qual {
-            field: CStr::from_ptr(field).to_str().unwrap().to_string(),
+            field,
+            remote_field,
             operator: opname,
             value: Value::Cell(Cell::Bool(value)),
             use_or: false,
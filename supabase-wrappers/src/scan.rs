@@ -1,6 +1,6 @@
 use pgrx::FromDatum;
 use pgrx::{
-    IntoDatum, PgSqlErrorCode, debug2,
+    IntoDatum, PgList, PgSqlErrorCode, debug2,
     memcxt::PgMemoryContexts,
     pg_sys::{Datum, MemoryContext, MemoryContextData, Oid, ParamKind},
     prelude::*,
@@ -12,8 +12,10 @@ use pgrx::pg_sys::panic::ErrorReport;
 use std::os::raw::c_int;
 use std::ptr;
 
+use crate::hooks::{RequestKind, RequestTracker};
 use crate::instance;
-use crate::interface::{Cell, Column, Limit, Qual, Row, Sort, Value};
+use crate::interface::{Cell, Column, Limit, Qual, Row, ScanCapabilities, Sort, Value};
+use crate::interrupt;
 use crate::limit::*;
 use crate::memctx;
 use crate::options::options_to_hashmap;
@@ -40,6 +42,9 @@ struct FdwState<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> {
     // limit
     limit: Option<Limit>,
 
+    // pushdown capabilities declared by the FDW
+    caps: ScanCapabilities,
+
     // foreign table options
     opts: HashMap<String, String>,
 
@@ -47,6 +52,9 @@ struct FdwState<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> {
     // memory context
     tmp_ctx: MemoryContext,
 
+    // request statistics of this scan
+    tracker: RequestTracker,
+
     // query result list
     values: Vec<Datum>,
     nulls: Vec<bool>,
@@ -62,8 +70,10 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwState<E, W> {
             tgts: Vec::new(),
             sorts: Vec::new(),
             limit: None,
+            caps: ScanCapabilities::default(),
             opts: HashMap::new(),
             tmp_ctx,
+            tracker: unsafe { RequestTracker::new(foreigntableid, RequestKind::Scan) },
             values: Vec::new(),
             nulls: Vec::new(),
             row: Row::new(),
@@ -74,28 +84,51 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwState<E, W> {
     #[inline]
     fn get_rel_size(&mut self) -> Result<(i64, i32), E> {
         if let Some(ref mut instance) = self.instance {
-            instance.get_rel_size(
-                &self.quals,
-                &self.tgts,
-                &self.sorts,
-                &self.limit,
-                &self.opts,
-            )
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| {
+                    instance.get_rel_size(
+                        &self.quals,
+                        &self.tgts,
+                        &self.sorts,
+                        &self.limit,
+                        &self.opts,
+                    )
+                })
+            })
         } else {
             Ok((0, 0))
         }
     }
 
     #[inline]
-    fn begin_scan(&mut self) -> Result<(), E> {
+    fn scan_capabilities(&mut self) -> Result<ScanCapabilities, E> {
         if let Some(ref mut instance) = self.instance {
-            instance.begin_scan(
+            instance.scan_capabilities(
                 &self.quals,
                 &self.tgts,
                 &self.sorts,
                 &self.limit,
                 &self.opts,
             )
+        } else {
+            Ok(ScanCapabilities::default())
+        }
+    }
+
+    #[inline]
+    fn begin_scan(&mut self) -> Result<(), E> {
+        if let Some(ref mut instance) = self.instance {
+            self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| {
+                    instance.begin_scan(
+                        &self.quals,
+                        &self.tgts,
+                        &self.sorts,
+                        &self.limit,
+                        &self.opts,
+                    )
+                })
+            })
         } else {
             Ok(())
         }
@@ -104,7 +137,13 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwState<E, W> {
     #[inline]
     fn iter_scan(&mut self) -> Result<Option<()>, E> {
         if let Some(ref mut instance) = self.instance {
-            instance.iter_scan(&mut self.row)
+            let result = self.tracker.track(|| {
+                interrupt::call_with_cancel(instance, |instance| instance.iter_scan(&mut self.row))
+            });
+            if let Ok(Some(_)) = result {
+                self.tracker.add_rows(1);
+            }
+            result
         } else {
             Ok(None)
         }
@@ -113,7 +152,8 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwState<E, W> {
     #[inline]
     fn re_scan(&mut self) -> Result<(), E> {
         if let Some(ref mut instance) = self.instance {
-            instance.re_scan()
+            self.tracker
+                .track(|| interrupt::call_with_cancel(instance, |instance| instance.re_scan()))
         } else {
             Ok(())
         }
@@ -122,7 +162,8 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> FdwState<E, W> {
     #[inline]
     fn end_scan(&mut self) -> Result<(), E> {
         if let Some(ref mut instance) = self.instance {
-            instance.end_scan()
+            self.tracker
+                .track(|| interrupt::call_with_cancel(instance, |instance| instance.end_scan()))
         } else {
             Ok(())
         }
@@ -133,8 +174,9 @@ impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> utils::SerdeList for FdwSta
 
 impl<E: Into<ErrorReport>, W: ForeignDataWrapper<E>> Drop for FdwState<E, W> {
     fn drop(&mut self) {
-        // drop foreign data wrapper instance
-        self.instance.take();
+        // drop foreign data wrapper instance, it may still report its own
+        // statistics for the foreign table
+        self.tracker.enter(|| self.instance.take());
 
         // remove the allocated memory context
         unsafe {
@@ -170,6 +212,11 @@ pub(super) extern "C-unwind" fn get_foreign_rel_size<
         // create scan state
         let mut state = FdwState::<E, W>::new(foreigntableid, ctx);
 
+        // the scan of an UPDATE or DELETE target is a part of the modification
+        if (*(*root).parse).resultRelation == (*baserel).relid as i32 {
+            state.tracker.set_modify_target();
+        }
+
         PgMemoryContexts::For(state.tmp_ctx).switch_to(|_| {
             // extract qual list
             state.quals = extract_quals(root, baserel, foreigntableid);
@@ -203,6 +250,9 @@ pub(super) extern "C-unwind" fn get_foreign_rel_size<
         (*baserel).rows = rows as f64;
         (*(*baserel).reltarget).width = width;
 
+        // get pushdown capabilities declared by the FDW
+        state.caps = state.scan_capabilities().report_unwrap();
+
         // save the state for following callbacks
         (*baserel).fdw_private = Box::leak(Box::new(state)) as *mut FdwState<E, W> as _;
     }
@@ -232,7 +282,25 @@ pub(super) extern "C-unwind" fn get_foreign_paths<
                 }
             })
             .unwrap_or(0.0);
-        let total_cost = startup_cost + (*baserel).rows;
+
+        // only the limited rows are fetched if the FDW honors the limit
+        let fetched_rows = match &state.limit {
+            Some(limit) if state.caps.limit => (*baserel)
+                .rows
+                .min(limit.count.saturating_add(limit.offset) as f64),
+            _ => (*baserel).rows,
+        };
+        let total_cost = startup_cost + fetched_rows;
+
+        // the path is sorted if the FDW honors the whole ORDER BY clause
+        let pathkeys = if state.caps.sorts
+            && !state.sorts.is_empty()
+            && state.sorts.len() == PgList::<pg_sys::PathKey>::from_pg((*root).query_pathkeys).len()
+        {
+            (*root).query_pathkeys
+        } else {
+            ptr::null_mut()
+        };
 
         // create a ForeignPath node and add it as the only possible path
         let path = pg_sys::create_foreignscan_path(
@@ -244,7 +312,7 @@ pub(super) extern "C-unwind" fn get_foreign_paths<
             0, // disabled_nodes
             startup_cost,
             total_cost,
-            ptr::null_mut(), // no pathkeys
+            pathkeys,
             ptr::null_mut(), // no outer rel either
             ptr::null_mut(), // no extra plan
             #[cfg(any(feature = "pg17", feature = "pg18"))]
@@ -269,8 +337,15 @@ pub(super) extern "C-unwind" fn get_foreign_plan<E: Into<ErrorReport>, W: Foreig
     unsafe {
         let state = PgBox::<FdwState<E, W>>::from_pg((*baserel).fdw_private as _);
 
-        // make foreign scan plan
-        let scan_clauses = pg_sys::extract_actual_clauses(scan_clauses, false);
+        // make foreign scan plan, quals are not rechecked if the FDW honors
+        // all of them
+        let all_quals_pushed = state.quals.len()
+            == PgList::<pg_sys::RestrictInfo>::from_pg((*baserel).baserestrictinfo).len();
+        let scan_clauses = if state.caps.quals && all_quals_pushed {
+            ptr::null_mut()
+        } else {
+            pg_sys::extract_actual_clauses(scan_clauses, false)
+        };
 
         // 'serialize' state to list, basically what we're doing here is to store
         // the state pointer as an integer constant in the list, so it can be
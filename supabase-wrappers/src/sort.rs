@@ -1,4 +1,5 @@
 use crate::interface::Sort;
+use crate::options::get_remote_column_name;
 use pgrx::list::List;
 use pgrx::{is_a, pg_sys};
 use std::ffi::CStr;
@@ -13,8 +14,10 @@ pub(crate) unsafe fn create_sort(
         let attno = (*var).varattno;
         let attname = pg_sys::get_attname(baserel_id, attno, true);
         if !attname.is_null() {
+            let field = CStr::from_ptr(attname).to_str().unwrap().to_owned();
             let sort = Sort {
-                field: CStr::from_ptr(attname).to_str().unwrap().to_owned(),
+                remote_field: get_remote_column_name(baserel_id, attno, &field),
+                field,
                 field_no: attno as usize,
                 #[cfg(feature = "pg18")]
                 reversed: (*pathkey).pk_cmptype == pg_sys::BTGreaterStrategyNumber,
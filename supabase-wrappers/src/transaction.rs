@@ -0,0 +1,273 @@
+//! Deliver transaction end events to FDW instances
+//!
+//! A FDW instance is created for each foreign table modification and normally
+//! dropped when the modification ends. If the FDW supports transaction hooks,
+//! the instance is owned by the transaction from the beginning of the
+//! modification instead, so its `pre_commit` or `abort` is always called to
+//! flush or discard buffered writes, even if the modification is ended by an
+//! error raised by Postgres.
+//!
+
+use pgrx::pg_sys::{self, panic::ErrorReport};
+use pgrx::{PgSqlErrorCode, pg_guard};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::Once;
+
+use crate::interface::ForeignDataWrapper;
+use crate::utils::{ReportableError, report_error, report_warning};
+
+static REGISTER_CALLBACKS: Once = Once::new();
+
+// a FDW instance waiting for the end of transaction, its type is erased so
+// instances of different FDWs can be kept together
+struct Participant {
+    instance: *mut c_void,
+    // transaction nesting level the instance belongs to
+    level: i32,
+    pre_commit: unsafe fn(*mut c_void) -> Result<(), ErrorReport>,
+    abort: unsafe fn(*mut c_void) -> Result<(), ErrorReport>,
+    drop: unsafe fn(*mut c_void),
+}
+
+impl Drop for Participant {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(self.instance) };
+    }
+}
+
+thread_local! {
+    // instances waiting for the end of current transaction, in the order of
+    // their modifications
+    static PARTICIPANTS: RefCell<VecDeque<Participant>> = const { RefCell::new(VecDeque::new()) };
+}
+
+unsafe fn pre_commit_instance<E: Into<ErrorReport>, W: ForeignDataWrapper<E>>(
+    instance: *mut c_void,
+) -> Result<(), ErrorReport> {
+    let instance = unsafe { &mut *(instance as *mut W) };
+    instance.pre_commit().map_err(Into::into)
+}
+
+unsafe fn abort_instance<E: Into<ErrorReport>, W: ForeignDataWrapper<E>>(
+    instance: *mut c_void,
+) -> Result<(), ErrorReport> {
+    let instance = unsafe { &mut *(instance as *mut W) };
+    instance.abort().map_err(Into::into)
+}
+
+unsafe fn drop_instance<W>(instance: *mut c_void) {
+    drop(unsafe { Box::from_raw(instance as *mut W) });
+}
+
+// let the current transaction own a FDW instance until it ends, the returned
+// pointer stays valid until then, or until the subtransaction it belongs to
+// aborts
+pub(crate) fn join_xact<E: Into<ErrorReport>, W: ForeignDataWrapper<E>>(instance: W) -> *mut W {
+    REGISTER_CALLBACKS.call_once(|| unsafe {
+        pg_sys::RegisterXactCallback(Some(xact_callback), ptr::null_mut());
+        pg_sys::RegisterSubXactCallback(Some(subxact_callback), ptr::null_mut());
+    });
+
+    let instance = Box::into_raw(Box::new(instance));
+    let participant = Participant {
+        instance: instance as *mut c_void,
+        level: unsafe { pg_sys::GetCurrentTransactionNestLevel() },
+        pre_commit: pre_commit_instance::<E, W>,
+        abort: abort_instance::<E, W>,
+        drop: drop_instance::<W>,
+    };
+    PARTICIPANTS.with(|p| p.borrow_mut().push_back(participant));
+    instance
+}
+
+// call pre_commit of each instance, the first error aborts the transaction
+fn pre_commit_all() {
+    while let Some(participant) = PARTICIPANTS.with(|p| p.borrow_mut().pop_front()) {
+        let result = unsafe { (participant.pre_commit)(participant.instance) };
+        if result.is_err() {
+            // put it back so it will be aborted with the rest of instances
+            PARTICIPANTS.with(|p| p.borrow_mut().push_front(participant));
+            result.report_unwrap();
+        }
+    }
+}
+
+// call abort of each instance at or below the nesting level, errors are
+// reported as warnings as the transaction is already aborting
+fn abort_all(level: i32) {
+    let participants = PARTICIPANTS.with(|p| {
+        let mut p = p.borrow_mut();
+        let (aborted, kept): (VecDeque<_>, VecDeque<_>) =
+            p.drain(..).partition(|pt| pt.level >= level);
+        *p = kept;
+        aborted
+    });
+    for participant in participants {
+        if let Err(err) = unsafe { (participant.abort)(participant.instance) } {
+            report_warning(&format!(
+                "failed to abort foreign table modification: {}",
+                err.message()
+            ));
+        }
+    }
+}
+
+// instances of the committed subtransaction at the nesting level, and of its
+// committed subtransactions, now belong to its parent
+fn reparent_all(level: i32) {
+    PARTICIPANTS.with(|p| {
+        p.borrow_mut()
+            .iter_mut()
+            .filter(|pt| pt.level >= level)
+            .for_each(|pt| pt.level = level - 1)
+    });
+}
+
+#[pg_guard]
+unsafe extern "C-unwind" fn xact_callback(event: pg_sys::XactEvent::Type, _arg: *mut c_void) {
+    match event {
+        pg_sys::XactEvent::XACT_EVENT_PRE_COMMIT => pre_commit_all(),
+        pg_sys::XactEvent::XACT_EVENT_PRE_PREPARE => {
+            if PARTICIPANTS.with(|p| !p.borrow().is_empty()) {
+                report_error(
+                    PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+                    "cannot prepare a transaction that modified foreign tables with transaction hooks",
+                );
+            }
+        }
+        pg_sys::XactEvent::XACT_EVENT_ABORT => abort_all(0),
+        _ => {}
+    }
+}
+
+#[pg_guard]
+unsafe extern "C-unwind" fn subxact_callback(
+    event: pg_sys::SubXactEvent::Type,
+    _my_subid: pg_sys::SubTransactionId,
+    _parent_subid: pg_sys::SubTransactionId,
+    _arg: *mut c_void,
+) {
+    let level = unsafe { pg_sys::GetCurrentTransactionNestLevel() };
+    match event {
+        pg_sys::SubXactEvent::SUBXACT_EVENT_COMMIT_SUB => reparent_all(level),
+        pg_sys::SubXactEvent::SUBXACT_EVENT_ABORT_SUB => abort_all(level),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    thread_local! {
+        // hook calls made by the test instances, in the order of calls
+        static CALLS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(call: &str, instance: *mut c_void) {
+        let id = unsafe { *(instance as *mut i32) };
+        CALLS.with(|c| c.borrow_mut().push(format!("{call} {id}")));
+    }
+
+    unsafe fn test_pre_commit(instance: *mut c_void) -> Result<(), ErrorReport> {
+        record("pre_commit", instance);
+        Ok(())
+    }
+
+    unsafe fn test_abort(instance: *mut c_void) -> Result<(), ErrorReport> {
+        record("abort", instance);
+        Ok(())
+    }
+
+    unsafe fn test_drop(instance: *mut c_void) {
+        record("drop", instance);
+        drop(unsafe { Box::from_raw(instance as *mut i32) });
+    }
+
+    // add a test instance identified by id at the nesting level
+    fn add_participant(id: i32, level: i32) {
+        let participant = Participant {
+            instance: Box::into_raw(Box::new(id)) as *mut c_void,
+            level,
+            pre_commit: test_pre_commit,
+            abort: test_abort,
+            drop: test_drop,
+        };
+        PARTICIPANTS.with(|p| p.borrow_mut().push_back(participant));
+    }
+
+    fn levels() -> Vec<i32> {
+        PARTICIPANTS.with(|p| p.borrow().iter().map(|pt| pt.level).collect())
+    }
+
+    fn take_calls() -> Vec<String> {
+        CALLS.with(|c| c.take())
+    }
+
+    #[test]
+    fn test_pre_commit_all() {
+        add_participant(1, 1);
+        add_participant(2, 1);
+        pre_commit_all();
+        assert!(levels().is_empty());
+        assert_eq!(
+            take_calls(),
+            vec!["pre_commit 1", "drop 1", "pre_commit 2", "drop 2"]
+        );
+    }
+
+    #[test]
+    fn test_abort_subxact() {
+        add_participant(1, 1);
+        add_participant(2, 2);
+        add_participant(3, 3);
+        add_participant(4, 1);
+
+        // rolling back to a savepoint only aborts instances of it and its
+        // subtransactions
+        abort_all(2);
+        assert_eq!(levels(), vec![1, 1]);
+        assert_eq!(take_calls(), vec!["abort 2", "drop 2", "abort 3", "drop 3"]);
+
+        abort_all(0);
+        assert!(levels().is_empty());
+        assert_eq!(take_calls(), vec!["abort 1", "drop 1", "abort 4", "drop 4"]);
+    }
+
+    #[test]
+    fn test_commit_subxact() {
+        add_participant(1, 1);
+        add_participant(2, 2);
+        add_participant(3, 3);
+
+        // releasing a savepoint moves its instances to the parent level
+        reparent_all(3);
+        assert_eq!(levels(), vec![1, 2, 2]);
+        abort_all(3);
+        assert_eq!(levels(), vec![1, 2, 2]);
+        assert!(take_calls().is_empty());
+
+        reparent_all(2);
+        assert_eq!(levels(), vec![1, 1, 1]);
+
+        // they are now committed or aborted with the top level transaction
+        abort_all(2);
+        assert_eq!(levels(), vec![1, 1, 1]);
+        pre_commit_all();
+        assert!(levels().is_empty());
+        assert_eq!(
+            take_calls(),
+            vec![
+                "pre_commit 1",
+                "drop 1",
+                "pre_commit 2",
+                "drop 2",
+                "pre_commit 3",
+                "drop 3"
+            ]
+        );
+    }
+}
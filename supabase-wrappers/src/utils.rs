@@ -2,6 +2,7 @@
 //!
 
 use crate::interface::{Cell, Column, Row};
+use crate::options::get_remote_column_name;
 use pgrx::{
     IntoDatum,
     list::List,
@@ -481,8 +482,10 @@ pub(super) unsafe fn extract_target_columns(
                         }
 
                         let type_oid = pg_sys::get_atttype((*rte).relid, attno);
+                        let name = CStr::from_ptr(attname).to_str().unwrap().to_owned();
                         ret.push(Column {
-                            name: CStr::from_ptr(attname).to_str().unwrap().to_owned(),
+                            remote_name: get_remote_column_name((*rte).relid, attno, &name),
+                            name,
                             num: attno as usize,
                             type_oid,
                         });
@@ -550,7 +553,13 @@ impl<T, E: Into<ErrorReport>> ReportableError for Result<T, E> {
     type Output = T;
 
     fn report_unwrap(self) -> Self::Output {
-        self.map_err(|e| e.into()).unwrap_or_report()
+        self.map_err(|e| {
+            // report the cancellation error instead if the error is caused by
+            // query cancellation or statement timeout
+            crate::interrupt::process_pending_cancel();
+            e.into()
+        })
+        .unwrap_or_report()
     }
 }
 
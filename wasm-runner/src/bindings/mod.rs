@@ -0,0 +1,2 @@
+pub(super) mod v2;
+pub(super) mod v3;
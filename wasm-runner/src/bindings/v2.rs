@@ -0,0 +1,176 @@
+use wasmtime::component::bindgen;
+
+use super::super::runner::cell_text;
+use super::v3::supabase::wrappers::{
+    stats::Metric,
+    types::{Cell, ImportForeignSchemaStmt, ImportSchemaType, TypeOid, Value},
+};
+
+bindgen!({
+    world: "wrappers",
+    path: "../wasm-wrappers/wit/v2",
+    additional_derives: [PartialEq],
+});
+
+use self::supabase::wrappers::{
+    stats::Metric as GuestMetric,
+    types::{
+        Cell as GuestCell, ImportForeignSchemaStmt as GuestImportForeignSchemaStmt,
+        ImportSchemaType as GuestImportSchemaType, TypeOid as GuestTypeOid, Value as GuestValue,
+    },
+};
+
+impl From<GuestCell> for Cell {
+    fn from(value: GuestCell) -> Self {
+        match value {
+            GuestCell::Bool(v) => Self::Bool(v),
+            GuestCell::I8(v) => Self::I8(v),
+            GuestCell::I16(v) => Self::I16(v),
+            GuestCell::F32(v) => Self::F32(v),
+            GuestCell::I32(v) => Self::I32(v),
+            GuestCell::F64(v) => Self::F64(v),
+            GuestCell::I64(v) => Self::I64(v),
+            GuestCell::Numeric(v) => Self::Numeric(v.to_string()),
+            GuestCell::String(v) => Self::String(v),
+            GuestCell::Date(v) => Self::Date(v),
+            GuestCell::Timestamp(v) => Self::Timestamp(v),
+            GuestCell::Timestamptz(v) => Self::Timestamptz(v),
+            GuestCell::Json(v) => Self::Json(v),
+            GuestCell::Uuid(v) => Self::Uuid(v),
+            GuestCell::Other(v) => Self::Other(v),
+        }
+    }
+}
+
+impl From<&Cell> for GuestCell {
+    fn from(value: &Cell) -> Self {
+        match value {
+            Cell::Bool(v) => Self::Bool(*v),
+            Cell::I8(v) => Self::I8(*v),
+            Cell::I16(v) => Self::I16(*v),
+            Cell::F32(v) => Self::F32(*v),
+            Cell::I32(v) => Self::I32(*v),
+            Cell::F64(v) => Self::F64(*v),
+            Cell::I64(v) => Self::I64(*v),
+            Cell::Numeric(v) => v
+                .parse()
+                .map(Self::Numeric)
+                .unwrap_or_else(|_| Self::Other(v.clone())),
+            Cell::String(v) => Self::String(v.clone()),
+            Cell::Date(v) => Self::Date(*v),
+            Cell::Timestamp(v) => Self::Timestamp(*v),
+            Cell::Timestamptz(v) => Self::Timestamptz(*v),
+            Cell::Json(v) => Self::Json(v.clone()),
+            Cell::Uuid(v) => Self::Uuid(v.clone()),
+            Cell::Other(v) => Self::Other(v.clone()),
+            // the types added in WIT 0.3.0 are passed in text format
+            _ => Self::Other(cell_text(value)),
+        }
+    }
+}
+
+impl From<&TypeOid> for GuestTypeOid {
+    fn from(value: &TypeOid) -> Self {
+        match value {
+            TypeOid::Bool => Self::Bool,
+            TypeOid::I8 => Self::I8,
+            TypeOid::I16 => Self::I16,
+            TypeOid::F32 => Self::F32,
+            TypeOid::I32 => Self::I32,
+            TypeOid::F64 => Self::F64,
+            TypeOid::I64 => Self::I64,
+            TypeOid::Numeric => Self::Numeric,
+            TypeOid::String => Self::String,
+            TypeOid::Date => Self::Date,
+            TypeOid::Timestamp => Self::Timestamp,
+            TypeOid::Timestamptz => Self::Timestamptz,
+            TypeOid::Json => Self::Json,
+            TypeOid::Uuid => Self::Uuid,
+            TypeOid::Time => Self::Other("time".to_string()),
+            TypeOid::Interval => Self::Other("interval".to_string()),
+            TypeOid::Bytea => Self::Other("bytea".to_string()),
+            TypeOid::BoolArray => Self::Other("bool[]".to_string()),
+            TypeOid::I16Array => Self::Other("smallint[]".to_string()),
+            TypeOid::I32Array => Self::Other("integer[]".to_string()),
+            TypeOid::I64Array => Self::Other("bigint[]".to_string()),
+            TypeOid::F32Array => Self::Other("real[]".to_string()),
+            TypeOid::F64Array => Self::Other("double precision[]".to_string()),
+            TypeOid::StringArray => Self::Other("text[]".to_string()),
+            TypeOid::Other(v) => Self::Other(v.clone()),
+        }
+    }
+}
+
+impl From<&Value> for GuestValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Cell(c) => Self::Cell(GuestCell::from(c)),
+            Value::Array(a) => Self::Array(a.iter().map(GuestCell::from).collect()),
+        }
+    }
+}
+
+impl From<&ImportForeignSchemaStmt> for GuestImportForeignSchemaStmt {
+    fn from(value: &ImportForeignSchemaStmt) -> Self {
+        Self {
+            server_name: value.server_name.clone(),
+            remote_schema: value.remote_schema.clone(),
+            local_schema: value.local_schema.clone(),
+            list_type: match value.list_type {
+                ImportSchemaType::All => GuestImportSchemaType::All,
+                ImportSchemaType::LimitTo => GuestImportSchemaType::LimitTo,
+                ImportSchemaType::Except => GuestImportSchemaType::Except,
+            },
+            table_list: value.table_list.clone(),
+        }
+    }
+}
+
+impl From<GuestMetric> for Metric {
+    fn from(value: GuestMetric) -> Self {
+        match value {
+            GuestMetric::CreateTimes => Self::CreateTimes,
+            GuestMetric::RowsIn => Self::RowsIn,
+            GuestMetric::RowsOut => Self::RowsOut,
+            GuestMetric::BytesIn => Self::BytesIn,
+            GuestMetric::BytesOut => Self::BytesOut,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interval;
+
+    #[test]
+    fn test_cell_conversion() {
+        assert_eq!(
+            Cell::from(GuestCell::Numeric(1.5)),
+            Cell::Numeric("1.5".to_string())
+        );
+        assert_eq!(
+            GuestCell::from(&Cell::Numeric("1.50".to_string())),
+            GuestCell::Numeric(1.5)
+        );
+        assert_eq!(
+            GuestCell::from(&Cell::Numeric("NaN-ish".to_string())),
+            GuestCell::Other("NaN-ish".to_string())
+        );
+
+        // the types added in WIT 0.3.0 are passed in text format
+        let interval = Cell::Interval(Interval {
+            months: 0,
+            days: 1,
+            micros: 0,
+        });
+        assert_eq!(
+            GuestCell::from(&interval),
+            GuestCell::Other("0 mons 1 days 0 microseconds".to_string())
+        );
+        assert_eq!(
+            GuestTypeOid::from(&TypeOid::StringArray),
+            GuestTypeOid::Other("text[]".to_string())
+        );
+    }
+}
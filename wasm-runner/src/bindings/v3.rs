@@ -0,0 +1,7 @@
+use wasmtime::component::bindgen;
+
+bindgen!({
+    world: "wrappers",
+    path: "../wasm-wrappers/wit/v3",
+    additional_derives: [PartialEq],
+});
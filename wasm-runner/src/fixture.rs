@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{RunnerError, RunnerResult};
+
+// environment variable to turn on recording mode
+const RECORD_ENV: &str = "WRAPPERS_WASM_RECORD";
+
+/// A recorded HTTP request
+///
+/// Request headers are not recorded and not used for matching, so
+/// credentials are never saved to the fixture files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+}
+
+/// A recorded HTTP response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureResponse {
+    pub status_code: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub body: String,
+}
+
+/// A recorded HTTP request/response pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: FixtureRequest,
+    pub response: FixtureResponse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Serve requests from the recorded interactions
+    Replay,
+    /// Send requests to the remote server and record the interactions
+    Record,
+}
+
+/// HTTP fixtures of a test case
+///
+/// In replay mode, a request is matched to the first unused interaction
+/// which has the same method, URL and body. If all the matching interactions
+/// have been used, the last one is used again, so repeated requests like a
+/// rescan can be served by a single recorded interaction. An unmatched
+/// request fails with a HTTP error returned to the Wasm FDW.
+#[derive(Debug)]
+pub struct Fixtures {
+    path: Option<PathBuf>,
+    mode: FixtureMode,
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+impl Fixtures {
+    /// Create fixtures without any interactions, for the Wasm FDWs which
+    /// don't make HTTP requests
+    pub fn empty() -> Self {
+        Self::from_interactions(Vec::new())
+    }
+
+    /// Create fixtures from in-memory interactions
+    pub fn from_interactions(interactions: Vec<Interaction>) -> Self {
+        Self {
+            path: None,
+            mode: FixtureMode::Replay,
+            used: vec![false; interactions.len()],
+            interactions,
+        }
+    }
+
+    /// Load fixtures from a JSON file
+    ///
+    /// When `WRAPPERS_WASM_RECORD=1` is set, the file doesn't need to exist
+    /// and it will be overwritten by the recorded interactions.
+    pub fn load(path: impl AsRef<Path>) -> RunnerResult<Self> {
+        let path = path.as_ref();
+        if env::var(RECORD_ENV).is_ok_and(|v| v == "1") {
+            return Ok(Self {
+                path: Some(path.to_path_buf()),
+                mode: FixtureMode::Record,
+                interactions: Vec::new(),
+                used: Vec::new(),
+            });
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            RunnerError::Fixture(format!("cannot read fixture {}: {e}", path.display()))
+        })?;
+        let interactions: Vec<Interaction> = serde_json::from_str(&content)?;
+        let mut fixtures = Self::from_interactions(interactions);
+        fixtures.path = Some(path.to_path_buf());
+        Ok(fixtures)
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    pub fn interactions(&self) -> &[Interaction] {
+        &self.interactions
+    }
+
+    /// Save recorded interactions to the fixture file
+    pub fn save(&self) -> RunnerResult<()> {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let content = serde_json::to_string_pretty(&self.interactions)?;
+            fs::write(path, content + "\n")?;
+        }
+        Ok(())
+    }
+
+    /// Serve a HTTP request, returns the response or a HTTP error message
+    pub(super) fn serve(
+        &mut self,
+        request: FixtureRequest,
+        headers: &[(String, String)],
+    ) -> Result<FixtureResponse, String> {
+        match self.mode {
+            FixtureMode::Replay => self.replay(&request),
+            FixtureMode::Record => {
+                let response = send_request(&request, headers)?;
+                self.interactions.push(Interaction {
+                    request,
+                    response: response.clone(),
+                });
+                self.used.push(true);
+                Ok(response)
+            }
+        }
+    }
+
+    fn replay(&mut self, request: &FixtureRequest) -> Result<FixtureResponse, String> {
+        let matched: Vec<usize> = self
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| &i.request == request)
+            .map(|(idx, _)| idx)
+            .collect();
+        let idx = matched
+            .iter()
+            .find(|idx| !self.used[**idx])
+            .or(matched.last())
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "no recorded fixture for request {} {}",
+                    request.method.to_uppercase(),
+                    request.url
+                )
+            })?;
+        self.used[idx] = true;
+        Ok(self.interactions[idx].response.clone())
+    }
+}
+
+// send request to the remote server in recording mode
+fn send_request(
+    request: &FixtureRequest,
+    headers: &[(String, String)],
+) -> Result<FixtureResponse, String> {
+    let client = reqwest::blocking::Client::builder()
+        .gzip(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let method = reqwest::Method::from_bytes(request.method.to_uppercase().as_bytes())
+        .map_err(|e| e.to_string())?;
+    let mut builder = client.request(method, &request.url);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    if !request.body.is_empty() {
+        builder = builder.body(request.body.clone());
+    }
+    let resp = builder.send().map_err(|e| e.to_string())?;
+
+    let status_code = resp.status().as_u16();
+    let headers = resp
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|v| (name.as_str().to_owned(), v.to_owned()))
+        })
+        .collect();
+    let body = resp.text().map_err(|e| e.to_string())?;
+
+    Ok(FixtureResponse {
+        status_code,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interaction(url: &str, body: &str) -> Interaction {
+        Interaction {
+            request: FixtureRequest {
+                method: "get".to_string(),
+                url: url.to_string(),
+                body: String::default(),
+            },
+            response: FixtureResponse {
+                status_code: 200,
+                headers: Vec::new(),
+                body: body.to_string(),
+            },
+        }
+    }
+
+    fn request(url: &str) -> FixtureRequest {
+        FixtureRequest {
+            method: "get".to_string(),
+            url: url.to_string(),
+            body: String::default(),
+        }
+    }
+
+    #[test]
+    fn test_replay_in_order() {
+        let mut fixtures = Fixtures::from_interactions(vec![
+            interaction("https://api.example.com/users", "page1"),
+            interaction("https://api.example.com/users", "page2"),
+        ]);
+        let url = "https://api.example.com/users";
+
+        let resp = fixtures.serve(request(url), &[]).unwrap();
+        assert_eq!(resp.body, "page1");
+        let resp = fixtures.serve(request(url), &[]).unwrap();
+        assert_eq!(resp.body, "page2");
+
+        // the last matched interaction is reused after all are used
+        let resp = fixtures.serve(request(url), &[]).unwrap();
+        assert_eq!(resp.body, "page2");
+    }
+
+    #[test]
+    fn test_replay_unmatched() {
+        let mut fixtures =
+            Fixtures::from_interactions(vec![interaction("https://api.example.com/users", "")]);
+
+        let err = fixtures
+            .serve(request("https://api.example.com/groups"), &[])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "no recorded fixture for request GET https://api.example.com/groups"
+        );
+    }
+
+    #[test]
+    fn test_fixture_format() {
+        let content = r#"[
+            {
+                "request": { "method": "get", "url": "https://api.example.com/users" },
+                "response": { "status_code": 200, "body": "[]" }
+            }
+        ]"#;
+        let interactions: Vec<Interaction> = serde_json::from_str(content).unwrap();
+        assert_eq!(
+            interactions,
+            vec![interaction("https://api.example.com/users", "[]")]
+        );
+    }
+}
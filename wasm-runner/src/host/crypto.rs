@@ -0,0 +1,112 @@
+use hmac::{Hmac, Mac};
+use rsa::{
+    RsaPrivateKey,
+    pkcs1::DecodeRsaPrivateKey,
+    pkcs1v15::SigningKey as RsaSigningKey,
+    pkcs8::DecodePrivateKey,
+    signature::{SignatureEncoding, Signer},
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use super::RunnerHost;
+
+// sign data and return the encoded signature bytes
+fn sign_with<K, S>(key: &K, data: &[u8]) -> Result<Vec<u8>, String>
+where
+    K: Signer<S>,
+    S: SignatureEncoding,
+{
+    key.try_sign(data)
+        .map(|sig| sig.to_vec())
+        .map_err(|e| e.to_string())
+}
+
+// parse RSA private key in PKCS#8 or PKCS#1 PEM format
+fn rsa_private_key(pem: &str) -> Result<RsaPrivateKey, String> {
+    RsaPrivateKey::from_pkcs8_pem(pem)
+        .ok()
+        .or_else(|| RsaPrivateKey::from_pkcs1_pem(pem).ok())
+        .ok_or("invalid RSA private key".to_string())
+}
+
+// compute digest of the data
+fn digest(algo: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    match algo {
+        "sha256" => Ok(Sha256::digest(data).to_vec()),
+        "sha384" => Ok(Sha384::digest(data).to_vec()),
+        "sha512" => Ok(Sha512::digest(data).to_vec()),
+        _ => Err(format!("digest algorithm {algo} not implemented")),
+    }
+}
+
+// compute HMAC of the data
+fn hmac(algo: &str, key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    // HMAC accepts keys of any length, so creating it never fails
+    let ret = match algo {
+        "sha256" => Hmac::<Sha256>::new_from_slice(key)
+            .map(|mac| mac.chain_update(data).finalize().into_bytes().to_vec()),
+        "sha384" => Hmac::<Sha384>::new_from_slice(key)
+            .map(|mac| mac.chain_update(data).finalize().into_bytes().to_vec()),
+        "sha512" => Hmac::<Sha512>::new_from_slice(key)
+            .map(|mac| mac.chain_update(data).finalize().into_bytes().to_vec()),
+        _ => return Err(format!("hmac algorithm {algo} not implemented")),
+    };
+    ret.map_err(|e| e.to_string())
+}
+
+// sign the data with a private key in PEM format
+fn sign(algo: &str, pem_key: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    match algo {
+        "RS256" => {
+            let key = RsaSigningKey::<Sha256>::new(rsa_private_key(pem_key)?);
+            sign_with::<_, rsa::pkcs1v15::Signature>(&key, data)
+        }
+        "RS384" => {
+            let key = RsaSigningKey::<Sha384>::new(rsa_private_key(pem_key)?);
+            sign_with::<_, rsa::pkcs1v15::Signature>(&key, data)
+        }
+        "RS512" => {
+            let key = RsaSigningKey::<Sha512>::new(rsa_private_key(pem_key)?);
+            sign_with::<_, rsa::pkcs1v15::Signature>(&key, data)
+        }
+        "ES256" => {
+            // accept both PKCS#8 and SEC1 PEM formats
+            let key = p256::ecdsa::SigningKey::from_pkcs8_pem(pem_key)
+                .ok()
+                .or_else(|| p256::SecretKey::from_sec1_pem(pem_key).ok().map(Into::into))
+                .ok_or("invalid P-256 private key".to_string())?;
+            sign_with::<_, p256::ecdsa::Signature>(&key, data)
+        }
+        "ES384" => {
+            let key = p384::ecdsa::SigningKey::from_pkcs8_pem(pem_key)
+                .ok()
+                .or_else(|| p384::SecretKey::from_sec1_pem(pem_key).ok().map(Into::into))
+                .ok_or("invalid P-384 private key".to_string())?;
+            sign_with::<_, p384::ecdsa::Signature>(&key, data)
+        }
+        "EdDSA" => {
+            let keypair = ed25519_compact::KeyPair::from_pem(pem_key)
+                .map_err(|_| "invalid Ed25519 private key".to_string())?;
+            Ok(keypair.sk.sign(data, None).to_vec())
+        }
+        _ => Err(format!("signing algorithm {algo} not implemented")),
+    }
+}
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::crypto;
+
+    impl crypto::Host for RunnerHost {
+        fn digest(&mut self, algo: String, data: Vec<u8>) -> crypto::CryptoResult {
+            digest(&algo, &data)
+        }
+
+        fn hmac(&mut self, algo: String, key: Vec<u8>, data: Vec<u8>) -> crypto::CryptoResult {
+            hmac(&algo, &key, &data)
+        }
+
+        fn sign(&mut self, algo: String, pem_key: String, data: Vec<u8>) -> crypto::CryptoResult {
+            sign(&algo, &pem_key, &data)
+        }
+    }
+};
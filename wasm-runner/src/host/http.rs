@@ -0,0 +1,264 @@
+use wasmtime::Result as WasmResult;
+use wasmtime::component::Resource;
+
+use super::super::fixture::{FixtureRequest, FixtureResponse};
+use super::RunnerHost;
+
+// raise error for http status code
+fn error_for_status(status_code: u16, url: &str) -> Result<(), String> {
+    let status = reqwest::StatusCode::from_u16(status_code).map_err(|e| e.to_string())?;
+    if status.is_client_error() || status.is_server_error() {
+        Err(format!("HTTP status error ({status}) for url ({url})"))
+    } else {
+        Ok(())
+    }
+}
+
+// a http response served from fixtures whose body is read incrementally by
+// the guest
+pub(super) struct HttpStream {
+    url: String,
+    resp: FixtureResponse,
+    // body data not read by the guest yet
+    buf: Vec<u8>,
+}
+
+impl HttpStream {
+    fn read_chunk(&mut self) -> Option<Vec<u8>> {
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.buf))
+        }
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        // the last line may not have line ending
+        let mut line: Vec<u8> = match self.buf.iter().position(|b| *b == b'\n') {
+            Some(end) => {
+                let mut line: Vec<u8> = self.buf.drain(..=end).collect();
+                line.pop();
+                line
+            }
+            None => std::mem::take(&mut self.buf),
+        };
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        String::from_utf8(line).map(Some).map_err(|e| e.to_string())
+    }
+}
+
+impl RunnerHost {
+    // serve a http request from fixtures
+    fn serve_request(
+        &mut self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: String,
+    ) -> Result<FixtureResponse, String> {
+        let fixture_req = FixtureRequest {
+            method: method.to_owned(),
+            url: url.to_owned(),
+            body,
+        };
+        self.fixtures.serve(fixture_req, headers)
+    }
+}
+
+const _: () = {
+    use super::super::bindings::v2::supabase::wrappers::http;
+
+    impl RunnerHost {
+        fn http_request(&mut self, method: &str, req: http::Request) -> http::HttpResult {
+            let resp = self.serve_request(method, &req.url, &req.headers, req.body)?;
+            Ok(http::Response {
+                url: req.url,
+                status_code: resp.status_code,
+                headers: resp.headers,
+                body: resp.body,
+            })
+        }
+    }
+
+    impl http::Host for RunnerHost {
+        fn get(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request("get", req)
+        }
+
+        fn post(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request("post", req)
+        }
+
+        fn put(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request("put", req)
+        }
+
+        fn patch(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request("patch", req)
+        }
+
+        fn delete(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request("delete", req)
+        }
+
+        fn error_for_status(&mut self, resp: http::Response) -> Result<(), http::HttpError> {
+            error_for_status(resp.status_code, &resp.url)
+        }
+    }
+};
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::http;
+
+    fn method_name(method: &http::Method) -> &'static str {
+        match method {
+            http::Method::Get => "get",
+            http::Method::Post => "post",
+            http::Method::Put => "put",
+            http::Method::Patch => "patch",
+            http::Method::Delete => "delete",
+        }
+    }
+
+    impl RunnerHost {
+        // serve a http request with binary body, fixtures only have text
+        // bodies, so the request body must be valid UTF-8
+        fn serve_request_bytes(&mut self, req: &http::Request) -> Result<FixtureResponse, String> {
+            let body = String::from_utf8(req.body.clone()).map_err(|_| {
+                "request body is not valid UTF-8 and cannot be recorded".to_string()
+            })?;
+            self.serve_request(method_name(&req.method), &req.url, &req.headers, body)
+        }
+
+        fn http_request_bytes(&mut self, req: http::Request) -> http::HttpResult {
+            let resp = self.serve_request_bytes(&req)?;
+            Ok(http::Response {
+                url: req.url,
+                status_code: resp.status_code,
+                headers: resp.headers,
+                body: resp.body.into_bytes(),
+            })
+        }
+
+        fn http_stream(&mut self, rep: &Resource<http::ResponseStream>) -> &mut HttpStream {
+            self.http_streams
+                .get_mut(&rep.rep())
+                .expect("response stream should exist")
+        }
+    }
+
+    impl http::HostResponseStream for RunnerHost {
+        fn url(&mut self, rep: Resource<http::ResponseStream>) -> String {
+            self.http_stream(&rep).url.clone()
+        }
+
+        fn status_code(&mut self, rep: Resource<http::ResponseStream>) -> u16 {
+            self.http_stream(&rep).resp.status_code
+        }
+
+        fn headers(&mut self, rep: Resource<http::ResponseStream>) -> http::Headers {
+            self.http_stream(&rep).resp.headers.clone()
+        }
+
+        fn read_chunk(
+            &mut self,
+            rep: Resource<http::ResponseStream>,
+        ) -> Result<Option<http::Body>, http::HttpError> {
+            Ok(self.http_stream(&rep).read_chunk())
+        }
+
+        fn read_line(
+            &mut self,
+            rep: Resource<http::ResponseStream>,
+        ) -> Result<Option<String>, http::HttpError> {
+            self.http_stream(&rep).read_line()
+        }
+
+        fn drop(&mut self, rep: Resource<http::ResponseStream>) -> WasmResult<()> {
+            self.http_streams.remove(&rep.rep());
+            Ok(())
+        }
+    }
+
+    impl http::Host for RunnerHost {
+        fn get(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn post(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn put(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn patch(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn delete(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn send_stream(
+            &mut self,
+            req: http::Request,
+        ) -> Result<Resource<http::ResponseStream>, http::HttpError> {
+            let resp = self.serve_request_bytes(&req)?;
+            let stream = HttpStream {
+                url: req.url,
+                buf: resp.body.clone().into_bytes(),
+                resp,
+            };
+            let rep = self.next_stream_rep;
+            self.next_stream_rep = rep.wrapping_add(1);
+            self.http_streams.insert(rep, stream);
+            Ok(Resource::new_own(rep))
+        }
+
+        fn error_for_status(&mut self, resp: http::Response) -> Result<(), http::HttpError> {
+            error_for_status(resp.status_code, &resp.url)
+        }
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(body: &str) -> HttpStream {
+        HttpStream {
+            url: "https://api.example.com/events".to_string(),
+            resp: FixtureResponse {
+                status_code: 200,
+                headers: Vec::new(),
+                body: body.to_string(),
+            },
+            buf: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_stream_read_line() {
+        let mut s = stream("line1\r\nline2\n\nline3");
+        assert_eq!(s.read_line(), Ok(Some("line1".to_string())));
+        assert_eq!(s.read_line(), Ok(Some("line2".to_string())));
+        assert_eq!(s.read_line(), Ok(Some(String::new())));
+        assert_eq!(s.read_line(), Ok(Some("line3".to_string())));
+        assert_eq!(s.read_line(), Ok(None));
+    }
+
+    #[test]
+    fn test_stream_read_chunk() {
+        let mut s = stream("line1\nline2");
+        assert_eq!(s.read_line(), Ok(Some("line1".to_string())));
+        assert_eq!(s.read_chunk(), Some(b"line2".to_vec()));
+        assert_eq!(s.read_chunk(), None);
+    }
+}
@@ -0,0 +1,107 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use jwt_simple::prelude::*;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::RunnerHost;
+
+// encode and sign a token with the claims in payload
+fn encode_token(
+    payload: Vec<(String, String)>,
+    algo: &str,
+    key: &str,
+    ttl_hours: u32,
+) -> Result<String, String> {
+    let mut claims = Claims::create(Duration::from_hours(ttl_hours as u64));
+    for (claim, value) in payload {
+        match claim.as_str() {
+            "iss" => {
+                claims = claims.with_issuer(value);
+            }
+            "sub" => {
+                claims = claims.with_subject(value);
+            }
+            _ => return Err(format!("claim {claim} not implemented")),
+        }
+    }
+
+    match algo {
+        "RS256" => RS256KeyPair::from_pem(key),
+        _ => return Err(format!("algorithm {algo} not implemented")),
+    }
+    .and_then(|keypair| keypair.sign(claims))
+    .map_err(|e| e.to_string())
+}
+
+type VerifiedClaims = JWTClaims<JsonMap<String, JsonValue>>;
+
+// decode token payload as JSON string without verifying it
+fn decode_token(token: &str) -> Result<String, String> {
+    let payload = token
+        .split('.')
+        .nth(1)
+        .ok_or("invalid token format".to_string())?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| "invalid token payload encoding".to_string())?;
+    let claims: JsonValue =
+        serde_json::from_slice(&payload).map_err(|_| "invalid token payload".to_string())?;
+    Ok(claims.to_string())
+}
+
+// verify token signature and time claims, returns claims as JSON string
+fn verify_token(token: &str, algo: &str, key: &str) -> Result<String, String> {
+    let claims: Result<VerifiedClaims, _> = match algo {
+        "HS256" => HS256Key::from_bytes(key.as_bytes()).verify_token(token, None),
+        "HS384" => HS384Key::from_bytes(key.as_bytes()).verify_token(token, None),
+        "HS512" => HS512Key::from_bytes(key.as_bytes()).verify_token(token, None),
+        "RS256" => RS256PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "RS384" => RS384PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "RS512" => RS512PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "ES256" => ES256PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "ES384" => ES384PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "EdDSA" => Ed25519PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        _ => return Err(format!("algorithm {algo} not implemented")),
+    };
+    let claims = claims.map_err(|e| e.to_string())?;
+    serde_json::to_string(&claims).map_err(|e| e.to_string())
+}
+
+const _: () = {
+    use super::super::bindings::v2::supabase::wrappers::jwt;
+
+    impl jwt::Host for RunnerHost {
+        fn encode(
+            &mut self,
+            payload: Vec<(String, String)>,
+            algo: String,
+            key: String,
+            ttl_hours: u32,
+        ) -> jwt::JwtResult {
+            encode_token(payload, &algo, &key, ttl_hours)
+        }
+    }
+};
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::jwt;
+
+    impl jwt::Host for RunnerHost {
+        fn encode(
+            &mut self,
+            payload: Vec<(String, String)>,
+            algo: String,
+            key: String,
+            ttl_hours: u32,
+        ) -> jwt::JwtResult {
+            encode_token(payload, &algo, &key, ttl_hours)
+        }
+
+        fn decode(&mut self, token: String) -> jwt::JwtResult {
+            decode_token(&token)
+        }
+
+        fn verify(&mut self, token: String, algo: String, key: String) -> jwt::JwtResult {
+            verify_token(&token, &algo, &key)
+        }
+    }
+};
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use super::RunnerHost;
+
+// a key-value pair kept in memory for the lifetime of the runner
+pub(super) struct KvEntry {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl KvEntry {
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|t| t <= Instant::now())
+    }
+}
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::kv;
+
+    impl kv::Host for RunnerHost {
+        fn get(&mut self, key: String) -> Result<Option<String>, kv::KvError> {
+            if self.kv.get(&key).is_some_and(|e| e.is_expired()) {
+                self.kv.remove(&key);
+            }
+            Ok(self.kv.get(&key).map(|e| e.value.clone()))
+        }
+
+        fn set(
+            &mut self,
+            key: String,
+            value: String,
+            ttl_secs: Option<u32>,
+        ) -> Result<(), kv::KvError> {
+            let expires_at = ttl_secs.map(|t| Instant::now() + Duration::from_secs(t as u64));
+            self.kv.insert(key, KvEntry { value, expires_at });
+            Ok(())
+        }
+
+        fn delete(&mut self, key: String) -> Result<(), kv::KvError> {
+            self.kv.remove(&key);
+            Ok(())
+        }
+    }
+};
@@ -0,0 +1,34 @@
+use super::RunnerHost;
+
+// format a field value, it is quoted if it contains spaces, quotes or '='
+fn format_value(value: &str) -> String {
+    if value.is_empty() || value.contains([' ', '"', '=']) {
+        format!("{value:?}")
+    } else {
+        value.to_owned()
+    }
+}
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::log::{self, Level};
+
+    impl log::Host for RunnerHost {
+        fn write(&mut self, level: Level, msg: String, fields: Vec<(String, String)>) {
+            let level = match level {
+                Level::Debug5 => "DEBUG5",
+                Level::Debug4 => "DEBUG4",
+                Level::Debug3 => "DEBUG3",
+                Level::Debug2 => "DEBUG2",
+                Level::Debug1 => "DEBUG1",
+                Level::Log => "LOG",
+                Level::Info => "INFO",
+                Level::Warning => "WARNING",
+            };
+            let mut line = format!("{level}: {msg}");
+            for (key, value) in &fields {
+                line.push_str(&format!(" {}={}", key, format_value(value)));
+            }
+            self.messages.push(line);
+        }
+    }
+};
@@ -0,0 +1,622 @@
+mod crypto;
+mod http;
+mod jwt;
+mod kv;
+mod log;
+mod stats;
+mod tcp;
+mod time;
+mod utils;
+
+use std::collections::HashMap;
+use wasmtime::Result as WasmResult;
+use wasmtime::component::*;
+
+use super::bindings::v3::supabase::wrappers::stats::Metric;
+use super::fixture::Fixtures;
+use super::runner::{Column, Limit, Qual, Row, Sort};
+use http::HttpStream;
+use kv::KvEntry;
+
+// get option value, the error message is the same as the Wasm FDW host
+fn require_option(key: &str, opts: &HashMap<String, String>) -> Result<String, String> {
+    opts.get(key)
+        .cloned()
+        .ok_or(format!("required option `{key}` is not specified"))
+}
+
+// get option value of an options resource, the error message is the same as
+// the Wasm FDW host when the options type is not supported
+fn require_options_value(
+    key: &str,
+    opts: Option<&HashMap<String, String>>,
+) -> Result<String, String> {
+    let Some(opts) = opts else {
+        return Err(format!(
+            "required option '{key}' is not found, the options type is not supported"
+        ));
+    };
+    require_option(key, opts)
+}
+
+pub(super) struct RunnerHost {
+    pub fixtures: Fixtures,
+    pub secrets: HashMap<String, String>,
+    pub messages: Vec<String>,
+    pub svr_opts: HashMap<String, String>,
+    pub tbl_opts: HashMap<String, String>,
+    pub import_schema_opts: HashMap<String, String>,
+    pub row: Row,
+    pub columns: Vec<Column>,
+    pub quals: Vec<Qual>,
+    pub sorts: Vec<Sort>,
+    pub limit: Option<Limit>,
+    stats: HashMap<(String, &'static str), i64>,
+    metadata: HashMap<String, String>,
+    kv: HashMap<String, KvEntry>,
+    // open streaming http responses, keyed by resource rep
+    http_streams: HashMap<u32, HttpStream>,
+    next_stream_rep: u32,
+}
+
+impl RunnerHost {
+    const CTX_REP: u32 = 1000;
+    const SVR_OPTS_REP: u32 = 2000;
+    const TBL_OPTS_REP: u32 = 3000;
+    const IMPORT_SCHEMA_OPTS_REP: u32 = 3100;
+    const OTHER_OPTS_REP: u32 = 3200;
+    const ROW_REP: u32 = 4000;
+    const COLUMN_REP: u32 = 5000;
+    const QUAL_REP: u32 = 6000;
+    const SORT_REP: u32 = 7000;
+    const LIMIT_REP: u32 = 8000;
+
+    pub(super) fn new(fixtures: Fixtures) -> Self {
+        Self {
+            fixtures,
+            secrets: HashMap::new(),
+            messages: Vec::new(),
+            svr_opts: HashMap::new(),
+            tbl_opts: HashMap::new(),
+            import_schema_opts: HashMap::new(),
+            row: Row::default(),
+            columns: Vec::new(),
+            quals: Vec::new(),
+            sorts: Vec::new(),
+            limit: None,
+            stats: HashMap::new(),
+            metadata: HashMap::new(),
+            kv: HashMap::new(),
+            http_streams: HashMap::new(),
+            next_stream_rep: 0,
+        }
+    }
+
+    pub(super) fn stats(&self, fdw_name: &str, metric: Metric) -> i64 {
+        self.stats
+            .get(&(fdw_name.to_owned(), stats::metric_name(metric)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // get the options of an options resource, the unsupported 'other' options
+    // type has no options
+    fn options(&self, rep: u32) -> Option<&HashMap<String, String>> {
+        match rep {
+            Self::SVR_OPTS_REP => Some(&self.svr_opts),
+            Self::TBL_OPTS_REP => Some(&self.tbl_opts),
+            Self::IMPORT_SCHEMA_OPTS_REP => Some(&self.import_schema_opts),
+            _ => None,
+        }
+    }
+}
+
+const _: () = {
+    use super::bindings::v2::{
+        exports::supabase::wrappers::routines::Context,
+        supabase::wrappers::types::{
+            Cell as GuestCell, Column as GuestColumn, FdwError as GuestFdwError, Host, HostColumn,
+            HostContext, HostLimit, HostOptions, HostQual, HostRow, HostSort, Limit as GuestLimit,
+            Options, OptionsType, Param as GuestParam, Qual as GuestQual, Row as GuestRow,
+            Sort as GuestSort, TypeOid, Value as GuestValue,
+        },
+    };
+
+    impl HostRow for RunnerHost {
+        fn new(&mut self) -> Resource<GuestRow> {
+            Resource::new_own(Self::ROW_REP)
+        }
+
+        fn cols(&mut self, _rep: Resource<GuestRow>) -> Vec<String> {
+            self.row.cols.clone()
+        }
+
+        fn cells(&mut self, _rep: Resource<GuestRow>) -> Vec<Option<GuestCell>> {
+            self.row
+                .cells
+                .iter()
+                .map(|c| c.as_ref().map(GuestCell::from))
+                .collect()
+        }
+
+        fn push(&mut self, _rep: Resource<GuestRow>, cell: Option<GuestCell>) {
+            let idx = self.row.cols.len();
+            let col = &self.columns[idx];
+            self.row.push(&col.name, cell.map(Into::into));
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestRow>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostColumn for RunnerHost {
+        fn new(&mut self, index: u32) -> Resource<GuestColumn> {
+            Resource::new_own(Self::COLUMN_REP + index)
+        }
+
+        fn name(&mut self, rep: Resource<GuestColumn>) -> String {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            self.columns[index].name.clone()
+        }
+
+        fn num(&mut self, rep: Resource<GuestColumn>) -> u32 {
+            rep.rep() - Self::COLUMN_REP + 1
+        }
+
+        fn type_oid(&mut self, rep: Resource<GuestColumn>) -> TypeOid {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            TypeOid::from(&self.columns[index].type_oid)
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestColumn>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostQual for RunnerHost {
+        fn new(&mut self, index: u32) -> Resource<GuestQual> {
+            Resource::new_own(Self::QUAL_REP + index)
+        }
+
+        fn field(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].field.clone()
+        }
+
+        fn operator(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].operator.clone()
+        }
+
+        fn value(&mut self, rep: Resource<GuestQual>) -> GuestValue {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            GuestValue::from(&self.quals[index].value)
+        }
+
+        fn use_or(&mut self, rep: Resource<GuestQual>) -> bool {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].use_or
+        }
+
+        fn param(&mut self, _rep: Resource<GuestQual>) -> Option<GuestParam> {
+            None
+        }
+
+        fn deparse(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].deparse()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestQual>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostSort for RunnerHost {
+        fn new(&mut self, index: u32) -> Resource<GuestSort> {
+            Resource::new_own(Self::SORT_REP + index)
+        }
+
+        fn field(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].field.clone()
+        }
+
+        fn field_no(&mut self, rep: Resource<GuestSort>) -> u32 {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].field_no as u32
+        }
+
+        fn reversed(&mut self, rep: Resource<GuestSort>) -> bool {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].reversed
+        }
+
+        fn nulls_first(&mut self, rep: Resource<GuestSort>) -> bool {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].nulls_first
+        }
+
+        fn collate(&mut self, rep: Resource<GuestSort>) -> Option<String> {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].collate.clone()
+        }
+
+        fn deparse(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].deparse()
+        }
+
+        fn deparse_with_collate(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].deparse_with_collate()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestSort>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostLimit for RunnerHost {
+        fn new(&mut self) -> Resource<GuestLimit> {
+            Resource::new_own(Self::LIMIT_REP)
+        }
+
+        fn count(&mut self, _rep: Resource<GuestLimit>) -> i64 {
+            self.limit.as_ref().map(|a| a.count).unwrap()
+        }
+
+        fn offset(&mut self, _rep: Resource<GuestLimit>) -> i64 {
+            self.limit.as_ref().map(|a| a.offset).unwrap()
+        }
+
+        fn deparse(&mut self, _rep: Resource<GuestLimit>) -> String {
+            self.limit.as_ref().map(|a| a.deparse()).unwrap()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestLimit>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostOptions for RunnerHost {
+        fn new(&mut self, options_type: OptionsType) -> Resource<Options> {
+            let opt_type = match options_type {
+                OptionsType::Server => Self::SVR_OPTS_REP,
+                OptionsType::Table => Self::TBL_OPTS_REP,
+                OptionsType::ImportSchema => Self::IMPORT_SCHEMA_OPTS_REP,
+                // not supported, an error is returned when its option is required
+                OptionsType::Other(_) => Self::OTHER_OPTS_REP,
+            };
+            Resource::new_own(opt_type)
+        }
+
+        fn get(&mut self, rep: Resource<Options>, key: String) -> Option<String> {
+            self.options(rep.rep())
+                .and_then(|opts| opts.get(&key))
+                .cloned()
+        }
+
+        fn require(
+            &mut self,
+            rep: Resource<Options>,
+            key: String,
+        ) -> Result<String, GuestFdwError> {
+            require_options_value(&key, self.options(rep.rep()))
+        }
+
+        fn require_or(&mut self, rep: Resource<Options>, key: String, default: String) -> String {
+            HostOptions::get(self, rep, key).unwrap_or(default)
+        }
+
+        fn drop(&mut self, _rep: Resource<Options>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostContext for RunnerHost {
+        fn new(&mut self) -> Resource<Context> {
+            Resource::new_borrow(Self::CTX_REP)
+        }
+
+        fn get_options(
+            &mut self,
+            _rep: Resource<Context>,
+            options_type: OptionsType,
+        ) -> Resource<Options> {
+            HostOptions::new(self, options_type)
+        }
+
+        fn get_quals(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestQual>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.quals.len() {
+                ret.push(HostQual::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_columns(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestColumn>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.columns.len() {
+                ret.push(HostColumn::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_sorts(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestSort>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.sorts.len() {
+                ret.push(HostSort::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_limit(&mut self, _rep: Resource<Context>) -> Option<Resource<GuestLimit>> {
+            if self.limit.is_some() {
+                Some(HostLimit::new(self))
+            } else {
+                None
+            }
+        }
+
+        fn drop(&mut self, _rep: Resource<Context>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Host for RunnerHost {}
+};
+
+const _: () = {
+    use super::bindings::v3::{
+        exports::supabase::wrappers::routines::Context,
+        supabase::wrappers::types::{
+            Cell, Column as GuestColumn, FdwError as GuestFdwError, Host, HostColumn, HostContext,
+            HostLimit, HostOptions, HostQual, HostRow, HostSort, Limit as GuestLimit, Options,
+            OptionsType, Param as GuestParam, Qual as GuestQual, Row as GuestRow,
+            Sort as GuestSort, TypeOid, Value,
+        },
+    };
+
+    impl HostRow for RunnerHost {
+        fn new(&mut self) -> Resource<GuestRow> {
+            Resource::new_own(Self::ROW_REP)
+        }
+
+        fn cols(&mut self, _rep: Resource<GuestRow>) -> Vec<String> {
+            self.row.cols.clone()
+        }
+
+        fn cells(&mut self, _rep: Resource<GuestRow>) -> Vec<Option<Cell>> {
+            self.row.cells.clone()
+        }
+
+        fn push(&mut self, _rep: Resource<GuestRow>, cell: Option<Cell>) {
+            let idx = self.row.cols.len();
+            let col = &self.columns[idx];
+            self.row.push(&col.name, cell);
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestRow>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostColumn for RunnerHost {
+        fn new(&mut self, index: u32) -> Resource<GuestColumn> {
+            Resource::new_own(Self::COLUMN_REP + index)
+        }
+
+        fn name(&mut self, rep: Resource<GuestColumn>) -> String {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            self.columns[index].name.clone()
+        }
+
+        fn num(&mut self, rep: Resource<GuestColumn>) -> u32 {
+            rep.rep() - Self::COLUMN_REP + 1
+        }
+
+        fn type_oid(&mut self, rep: Resource<GuestColumn>) -> TypeOid {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            self.columns[index].type_oid.clone()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestColumn>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostQual for RunnerHost {
+        fn new(&mut self, index: u32) -> Resource<GuestQual> {
+            Resource::new_own(Self::QUAL_REP + index)
+        }
+
+        fn field(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].field.clone()
+        }
+
+        fn operator(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].operator.clone()
+        }
+
+        fn value(&mut self, rep: Resource<GuestQual>) -> Value {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].value.clone()
+        }
+
+        fn use_or(&mut self, rep: Resource<GuestQual>) -> bool {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].use_or
+        }
+
+        fn param(&mut self, _rep: Resource<GuestQual>) -> Option<GuestParam> {
+            None
+        }
+
+        fn deparse(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].deparse()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestQual>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostSort for RunnerHost {
+        fn new(&mut self, index: u32) -> Resource<GuestSort> {
+            Resource::new_own(Self::SORT_REP + index)
+        }
+
+        fn field(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].field.clone()
+        }
+
+        fn field_no(&mut self, rep: Resource<GuestSort>) -> u32 {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].field_no as u32
+        }
+
+        fn reversed(&mut self, rep: Resource<GuestSort>) -> bool {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].reversed
+        }
+
+        fn nulls_first(&mut self, rep: Resource<GuestSort>) -> bool {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].nulls_first
+        }
+
+        fn collate(&mut self, rep: Resource<GuestSort>) -> Option<String> {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].collate.clone()
+        }
+
+        fn deparse(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].deparse()
+        }
+
+        fn deparse_with_collate(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].deparse_with_collate()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestSort>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostLimit for RunnerHost {
+        fn new(&mut self) -> Resource<GuestLimit> {
+            Resource::new_own(Self::LIMIT_REP)
+        }
+
+        fn count(&mut self, _rep: Resource<GuestLimit>) -> i64 {
+            self.limit.as_ref().map(|a| a.count).unwrap()
+        }
+
+        fn offset(&mut self, _rep: Resource<GuestLimit>) -> i64 {
+            self.limit.as_ref().map(|a| a.offset).unwrap()
+        }
+
+        fn deparse(&mut self, _rep: Resource<GuestLimit>) -> String {
+            self.limit.as_ref().map(|a| a.deparse()).unwrap()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestLimit>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostOptions for RunnerHost {
+        fn new(&mut self, options_type: OptionsType) -> Resource<Options> {
+            let opt_type = match options_type {
+                OptionsType::Server => Self::SVR_OPTS_REP,
+                OptionsType::Table => Self::TBL_OPTS_REP,
+                OptionsType::ImportSchema => Self::IMPORT_SCHEMA_OPTS_REP,
+                // not supported, an error is returned when its option is required
+                OptionsType::Other(_) => Self::OTHER_OPTS_REP,
+            };
+            Resource::new_own(opt_type)
+        }
+
+        fn get(&mut self, rep: Resource<Options>, key: String) -> Option<String> {
+            self.options(rep.rep())
+                .and_then(|opts| opts.get(&key))
+                .cloned()
+        }
+
+        fn require(
+            &mut self,
+            rep: Resource<Options>,
+            key: String,
+        ) -> Result<String, GuestFdwError> {
+            require_options_value(&key, self.options(rep.rep()))
+        }
+
+        fn require_or(&mut self, rep: Resource<Options>, key: String, default: String) -> String {
+            HostOptions::get(self, rep, key).unwrap_or(default)
+        }
+
+        fn drop(&mut self, _rep: Resource<Options>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostContext for RunnerHost {
+        fn new(&mut self) -> Resource<Context> {
+            Resource::new_borrow(Self::CTX_REP)
+        }
+
+        fn get_options(
+            &mut self,
+            _rep: Resource<Context>,
+            options_type: OptionsType,
+        ) -> Resource<Options> {
+            HostOptions::new(self, options_type)
+        }
+
+        fn get_quals(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestQual>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.quals.len() {
+                ret.push(HostQual::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_columns(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestColumn>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.columns.len() {
+                ret.push(HostColumn::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_sorts(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestSort>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.sorts.len() {
+                ret.push(HostSort::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_limit(&mut self, _rep: Resource<Context>) -> Option<Resource<GuestLimit>> {
+            if self.limit.is_some() {
+                Some(HostLimit::new(self))
+            } else {
+                None
+            }
+        }
+
+        fn drop(&mut self, _rep: Resource<Context>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Host for RunnerHost {}
+};
@@ -0,0 +1,56 @@
+use super::super::bindings::v3::supabase::wrappers::stats::Metric;
+use super::RunnerHost;
+
+// name of a stats metric
+pub(super) fn metric_name(metric: Metric) -> &'static str {
+    match metric {
+        Metric::CreateTimes => "create_times",
+        Metric::RowsIn => "rows_in",
+        Metric::RowsOut => "rows_out",
+        Metric::BytesIn => "bytes_in",
+        Metric::BytesOut => "bytes_out",
+    }
+}
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::stats;
+
+    impl stats::Host for RunnerHost {
+        fn inc_stats(&mut self, fdw_name: String, metric: stats::Metric, inc: i64) {
+            *self
+                .stats
+                .entry((fdw_name, metric_name(metric)))
+                .or_default() += inc;
+        }
+
+        fn get_metadata(&mut self, fdw_name: String) -> Option<String> {
+            self.metadata.get(&fdw_name).cloned()
+        }
+
+        fn set_metadata(&mut self, fdw_name: String, metadata: Option<String>) {
+            match metadata {
+                Some(m) => self.metadata.insert(fdw_name, m),
+                None => self.metadata.remove(&fdw_name),
+            };
+        }
+    }
+};
+
+const _: () = {
+    use super::super::bindings::v2::supabase::wrappers::stats;
+    use super::super::bindings::v3::supabase::wrappers::stats as stats_v3;
+
+    impl stats::Host for RunnerHost {
+        fn inc_stats(&mut self, fdw_name: String, metric: stats::Metric, inc: i64) {
+            stats_v3::Host::inc_stats(self, fdw_name, metric.into(), inc);
+        }
+
+        fn get_metadata(&mut self, fdw_name: String) -> Option<String> {
+            stats_v3::Host::get_metadata(self, fdw_name)
+        }
+
+        fn set_metadata(&mut self, fdw_name: String, metadata: Option<String>) {
+            stats_v3::Host::set_metadata(self, fdw_name, metadata);
+        }
+    }
+};
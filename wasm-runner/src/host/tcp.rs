@@ -0,0 +1,56 @@
+use wasmtime::Result as WasmResult;
+use wasmtime::component::Resource;
+
+use super::RunnerHost;
+
+// raw tcp traffic can't be served from the recorded http fixtures
+const NOT_SUPPORTED: &str = "tcp connection is not supported by the test runner";
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::tcp;
+
+    // no connection can be created, so these are never called
+    impl tcp::HostConnection for RunnerHost {
+        fn read(
+            &mut self,
+            _rep: Resource<tcp::Connection>,
+            _max_bytes: u32,
+        ) -> Result<Vec<u8>, tcp::TcpError> {
+            Err(NOT_SUPPORTED.to_owned())
+        }
+
+        fn write(
+            &mut self,
+            _rep: Resource<tcp::Connection>,
+            _data: Vec<u8>,
+        ) -> Result<(), tcp::TcpError> {
+            Err(NOT_SUPPORTED.to_owned())
+        }
+
+        fn upgrade_tls(
+            &mut self,
+            _rep: Resource<tcp::Connection>,
+            _server_name: String,
+        ) -> Result<(), tcp::TcpError> {
+            Err(NOT_SUPPORTED.to_owned())
+        }
+
+        fn close(&mut self, _rep: Resource<tcp::Connection>) -> Result<(), tcp::TcpError> {
+            Err(NOT_SUPPORTED.to_owned())
+        }
+
+        fn drop(&mut self, _rep: Resource<tcp::Connection>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl tcp::Host for RunnerHost {
+        fn connect(
+            &mut self,
+            _host: String,
+            _port: u16,
+        ) -> Result<Resource<tcp::Connection>, tcp::TcpError> {
+            Err(NOT_SUPPORTED.to_owned())
+        }
+    }
+};
@@ -0,0 +1,74 @@
+use super::super::runner::fmt_cell;
+use super::RunnerHost;
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::{types::Cell, utils};
+
+    impl utils::Host for RunnerHost {
+        fn report_info(&mut self, msg: String) {
+            self.messages.push(format!("INFO: {msg}"));
+        }
+
+        fn report_notice(&mut self, msg: String) {
+            self.messages.push(format!("NOTICE: {msg}"));
+        }
+
+        fn report_warning(&mut self, msg: String) {
+            self.messages.push(format!("WARNING: {msg}"));
+        }
+
+        fn report_error(&mut self, msg: String) {
+            self.messages.push(format!("ERROR: {msg}"));
+        }
+
+        fn cell_to_string(&mut self, cell: Option<Cell>) -> String {
+            match cell {
+                Some(c) => fmt_cell(&c),
+                None => "null".to_string(),
+            }
+        }
+
+        fn get_vault_secret(&mut self, secret_id: String) -> Option<String> {
+            self.secrets.get(&secret_id).cloned()
+        }
+
+        fn get_vault_secret_by_name(&mut self, secret_name: String) -> Option<String> {
+            self.secrets.get(&secret_name).cloned()
+        }
+    }
+};
+
+const _: () = {
+    use super::super::bindings::v2::supabase::wrappers::{types::Cell as GuestCell, utils};
+    use super::super::bindings::v3::supabase::wrappers::utils as utils_v3;
+
+    impl utils::Host for RunnerHost {
+        fn report_info(&mut self, msg: String) {
+            utils_v3::Host::report_info(self, msg);
+        }
+
+        fn report_notice(&mut self, msg: String) {
+            utils_v3::Host::report_notice(self, msg);
+        }
+
+        fn report_warning(&mut self, msg: String) {
+            utils_v3::Host::report_warning(self, msg);
+        }
+
+        fn report_error(&mut self, msg: String) {
+            utils_v3::Host::report_error(self, msg);
+        }
+
+        fn cell_to_string(&mut self, cell: Option<GuestCell>) -> String {
+            utils_v3::Host::cell_to_string(self, cell.map(Into::into))
+        }
+
+        fn get_vault_secret(&mut self, secret_id: String) -> Option<String> {
+            utils_v3::Host::get_vault_secret(self, secret_id)
+        }
+
+        fn get_vault_secret_by_name(&mut self, secret_name: String) -> Option<String> {
+            utils_v3::Host::get_vault_secret_by_name(self, secret_name)
+        }
+    }
+};
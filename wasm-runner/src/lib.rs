@@ -0,0 +1,79 @@
+//! Local test runner for Wasm foreign data wrappers
+//!
+//! It loads a Wasm FDW component outside Postgres, feeds it with server
+//! options, table options, columns, quals, sorts and limit, and collects the
+//! rows it produces. HTTP requests made by the Wasm FDW are served from
+//! recorded request/response [`Fixtures`], so the tests are hermetic and can
+//! run with plain `cargo test`.
+//!
+//! Wasm FDWs built against WIT version `0.2.0` or `0.3.0` are supported. The
+//! cells, type OIDs and values are always in WIT version `0.3.0` types, they
+//! are converted for the Wasm FDWs built against `0.2.0`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wrappers_wasm_runner::*;
+//!
+//! let fixtures = Fixtures::load("tests/fixtures/users.json").unwrap();
+//! let mut runner = Runner::new("target/wasm32-unknown-unknown/release/example_fdw.wasm", fixtures).unwrap();
+//! runner.add_secret("api_key_id", "test-api-key");
+//! runner.init(&[("api_key_id", "api_key_id")]).unwrap();
+//!
+//! let scan = Scan {
+//!     options: options(&[("object", "users")]),
+//!     columns: vec![Column::new("id", TypeOid::I64), Column::new("name", TypeOid::String)],
+//!     quals: vec![Qual::new("id", "=", Cell::I64(42))],
+//!     ..Default::default()
+//! };
+//! let rows = runner.scan(&scan).unwrap();
+//! assert_eq!(rows[0].get("name"), Some(&Cell::String("foo".to_string())));
+//! runner.finish().unwrap();
+//! ```
+//!
+//! # Recording fixtures
+//!
+//! Set the `WRAPPERS_WASM_RECORD` environment variable to `1` to send the
+//! requests to the real remote API and save the request/response pairs to
+//! the fixture file by [`Runner::finish`]. Request headers are not
+//! recorded, so credentials passed by headers are not saved to fixtures.
+
+mod bindings;
+mod fixture;
+mod host;
+mod runner;
+
+pub use bindings::v3::supabase::wrappers::{
+    stats::Metric,
+    types::{Cell, ImportForeignSchemaStmt, ImportSchemaType, Interval, TypeOid, Value},
+};
+pub use fixture::{FixtureMode, FixtureRequest, FixtureResponse, Fixtures, Interaction};
+pub use runner::{Column, Limit, Modify, Qual, Row, Runner, Scan, Sort, options};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RunnerError {
+    #[error("guest fdw error: {0}")]
+    GuestFdw(String),
+
+    #[error("wasmtime error: {0}")]
+    Wasmtime(#[from] wasmtime::Error),
+
+    #[error("fixture error: {0}")]
+    Fixture(String),
+
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<String> for RunnerError {
+    fn from(value: String) -> Self {
+        Self::GuestFdw(value)
+    }
+}
+
+pub type RunnerResult<T> = Result<T, RunnerError>;
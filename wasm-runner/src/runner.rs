@@ -0,0 +1,739 @@
+use chrono::{DateTime, Duration, NaiveTime};
+use std::collections::HashMap;
+use std::path::Path;
+use wasmtime::component::*;
+use wasmtime::{Engine, Store};
+
+use super::RunnerResult;
+use super::bindings::{
+    v2::{
+        Wrappers as WrappersV2,
+        supabase::wrappers::types::{
+            Cell as GuestCellV2, HostContext as HostContextV2, HostRow as HostRowV2,
+            ImportForeignSchemaStmt as GuestImportForeignSchemaStmtV2,
+        },
+    },
+    v3::{
+        Wrappers as WrappersV3,
+        supabase::wrappers::{
+            stats::Metric,
+            types::{
+                Cell, HostContext as HostContextV3, HostRow as HostRowV3, ImportForeignSchemaStmt,
+                TypeOid, Value,
+            },
+        },
+    },
+};
+use super::fixture::{FixtureMode, Fixtures};
+use super::host::RunnerHost;
+
+// max number of rows fetched by each iter-scan-batch call, the same as the
+// Wasm FDW host
+const SCAN_BATCH_SIZE: u32 = 100;
+
+/// Build an options map from key-value pairs
+pub fn options(opts: &[(&str, &str)]) -> HashMap<String, String> {
+    opts.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+// format array elements as text, null elements are formatted as `null`
+fn fmt_array<T: ToString>(array: &[Option<T>]) -> String {
+    let elems: Vec<String> = array
+        .iter()
+        .map(|e| match e {
+            Some(v) => v.to_string(),
+            None => "null".to_owned(),
+        })
+        .collect();
+    format!("[{}]", elems.join(","))
+}
+
+// format a cell value as text without quotes
+pub(super) fn cell_text(cell: &Cell) -> String {
+    match cell {
+        Cell::Bool(v) => v.to_string(),
+        Cell::I8(v) => v.to_string(),
+        Cell::I16(v) => v.to_string(),
+        Cell::F32(v) => v.to_string(),
+        Cell::I32(v) => v.to_string(),
+        Cell::F64(v) => v.to_string(),
+        Cell::I64(v) => v.to_string(),
+        Cell::Numeric(v) | Cell::String(v) | Cell::Json(v) | Cell::Uuid(v) | Cell::Other(v) => {
+            v.clone()
+        }
+        Cell::Date(v) => DateTime::from_timestamp(*v, 0)
+            .map(|ts| ts.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        Cell::Timestamp(v) => DateTime::from_timestamp_micros(*v)
+            .map(|ts| ts.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+            .unwrap_or_default(),
+        Cell::Timestamptz(v) => DateTime::from_timestamp_micros(*v)
+            .map(|ts| format!("{}+00", ts.format("%Y-%m-%d %H:%M:%S%.f")))
+            .unwrap_or_default(),
+        Cell::Time(v) => NaiveTime::MIN
+            .overflowing_add_signed(Duration::microseconds(*v))
+            .0
+            .format("%H:%M:%S%.f")
+            .to_string(),
+        Cell::Interval(v) => format!(
+            "{} mons {} days {} microseconds",
+            v.months, v.days, v.micros
+        ),
+        Cell::Bytea(v) => {
+            let hex: String = v.iter().map(|b| format!("{b:02X}")).collect();
+            format!("\\x{hex}")
+        }
+        Cell::BoolArray(v) => fmt_array(v),
+        Cell::I16Array(v) => fmt_array(v),
+        Cell::I32Array(v) => fmt_array(v),
+        Cell::I64Array(v) => fmt_array(v),
+        Cell::F32Array(v) => fmt_array(v),
+        Cell::F64Array(v) => fmt_array(v),
+        Cell::StringArray(v) => fmt_array(v),
+    }
+}
+
+// format a cell like it is formatted by the Wasm FDW host
+pub(super) fn fmt_cell(cell: &Cell) -> String {
+    match cell {
+        Cell::Bool(_)
+        | Cell::I8(_)
+        | Cell::I16(_)
+        | Cell::F32(_)
+        | Cell::I32(_)
+        | Cell::F64(_)
+        | Cell::I64(_)
+        | Cell::Numeric(_)
+        | Cell::Interval(_)
+        | Cell::BoolArray(_)
+        | Cell::I16Array(_)
+        | Cell::I32Array(_)
+        | Cell::I64Array(_)
+        | Cell::F32Array(_)
+        | Cell::F64Array(_)
+        | Cell::StringArray(_) => cell_text(cell),
+        Cell::Bytea(v) if v.is_empty() => "''".to_owned(),
+        _ => format!("'{}'", cell_text(cell)),
+    }
+}
+
+/// A foreign table column
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub type_oid: TypeOid,
+}
+
+impl Column {
+    pub fn new(name: &str, type_oid: TypeOid) -> Self {
+        Self {
+            name: name.to_owned(),
+            type_oid,
+        }
+    }
+}
+
+/// A query condition pushed down to the Wasm FDW, e.g. `id = 42`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Qual {
+    pub field: String,
+    pub operator: String,
+    pub value: Value,
+    pub use_or: bool,
+}
+
+impl Qual {
+    pub fn new(field: &str, operator: &str, cell: Cell) -> Self {
+        Self {
+            field: field.to_owned(),
+            operator: operator.to_owned(),
+            value: Value::Cell(cell),
+            use_or: false,
+        }
+    }
+
+    pub fn deparse(&self) -> String {
+        let field = &self.field;
+        match &self.value {
+            Value::Cell(cell) => match self.operator.as_str() {
+                "~~" => format!("{} like {}", field, fmt_cell(cell)),
+                "!~~" => format!("{} not like {}", field, fmt_cell(cell)),
+                _ => format!("{} {} {}", field, self.operator, fmt_cell(cell)),
+            },
+            Value::Array(cells) => {
+                let conds: Vec<String> = cells
+                    .iter()
+                    .map(|cell| format!("{} {} {}", field, self.operator, fmt_cell(cell)))
+                    .collect();
+                conds.join(if self.use_or { " or " } else { " and " })
+            }
+        }
+    }
+}
+
+/// A query sort pushed down to the Wasm FDW, e.g. `order by id desc`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sort {
+    pub field: String,
+    pub field_no: usize,
+    pub reversed: bool,
+    pub nulls_first: bool,
+    pub collate: Option<String>,
+}
+
+impl Sort {
+    pub fn deparse(&self) -> String {
+        let mut sql = self.field.to_string();
+
+        if self.reversed {
+            sql.push_str(" desc");
+        } else {
+            sql.push_str(" asc");
+        }
+
+        if self.nulls_first {
+            sql.push_str(" nulls first")
+        } else {
+            sql.push_str(" nulls last")
+        }
+
+        sql
+    }
+
+    pub fn deparse_with_collate(&self) -> String {
+        let mut sql = self.deparse();
+
+        if let Some(collate) = &self.collate {
+            sql.push_str(&format!(" collate {collate}"));
+        }
+
+        sql
+    }
+}
+
+/// A query limit pushed down to the Wasm FDW, e.g. `limit 42 offset 7`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Limit {
+    pub count: i64,
+    pub offset: i64,
+}
+
+impl Limit {
+    pub fn deparse(&self) -> String {
+        format!("limit {} offset {}", self.count, self.offset)
+    }
+}
+
+/// A row produced by or sent to the Wasm FDW
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    pub cols: Vec<String>,
+    pub cells: Vec<Option<Cell>>,
+}
+
+impl Row {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, col: &str, cell: Option<Cell>) {
+        self.cols.push(col.to_owned());
+        self.cells.push(cell);
+    }
+
+    /// Get cell by column name, `None` if the column doesn't exist or the
+    /// cell is null
+    pub fn get(&self, col: &str) -> Option<&Cell> {
+        self.cols
+            .iter()
+            .position(|c| c == col)
+            .and_then(|idx| self.cells[idx].as_ref())
+    }
+
+    pub fn clear(&mut self) {
+        self.cols.clear();
+        self.cells.clear();
+    }
+}
+
+/// A foreign table scan, a.k.a `select` query
+#[derive(Debug, Clone, Default)]
+pub struct Scan {
+    pub options: HashMap<String, String>,
+    pub columns: Vec<Column>,
+    pub quals: Vec<Qual>,
+    pub sorts: Vec<Sort>,
+    pub limit: Option<Limit>,
+}
+
+/// A foreign table modification, a.k.a `insert`, `update` or `delete` query
+#[derive(Debug, Clone)]
+pub enum Modify {
+    Insert(Row),
+    Update(Cell, Row),
+    Delete(Cell),
+}
+
+enum Bindings {
+    V2(WrappersV2),
+    V3(WrappersV3),
+}
+
+/// Runner of a Wasm FDW component outside Postgres
+///
+/// It follows the same call sequence as the Wasm FDW host running in
+/// Postgres, for example, [`Runner::scan`] calls `begin-scan`, `iter-scan`
+/// (or `iter-scan-batch` for WIT version `0.3.0`) until it returns no more
+/// rows and then `end-scan`.
+///
+/// Call [`Runner::finish`] at the end of a test, so the fixtures recorded
+/// with `WRAPPERS_WASM_RECORD=1` are saved.
+pub struct Runner {
+    store: Store<RunnerHost>,
+    bindings: Bindings,
+}
+
+impl Runner {
+    /// Load and instantiate a Wasm FDW component from file, it can be built
+    /// against WIT version `0.2.0` or `0.3.0`
+    pub fn new(path: impl AsRef<Path>, fixtures: Fixtures) -> RunnerResult<Self> {
+        let engine = Engine::default();
+        let component = Component::from_file(&engine, path)?;
+
+        let mut linker = Linker::new(&engine);
+        WrappersV2::add_to_linker::<_, HasSelf<_>>(&mut linker, |host: &mut RunnerHost| host)?;
+        WrappersV3::add_to_linker::<_, HasSelf<_>>(&mut linker, |host: &mut RunnerHost| host)?;
+
+        let mut store = Store::new(&engine, RunnerHost::new(fixtures));
+        let bindings = WrappersV2::instantiate(&mut store, &component, &linker)
+            .map(Bindings::V2)
+            .or_else(|_| {
+                WrappersV3::instantiate(&mut store, &component, &linker).map(Bindings::V3)
+            })?;
+
+        Ok(Self { store, bindings })
+    }
+
+    /// Add a Vault secret which can be fetched by either its ID or name
+    pub fn add_secret(&mut self, id: &str, secret: &str) {
+        self.store
+            .data_mut()
+            .secrets
+            .insert(id.to_owned(), secret.to_owned());
+    }
+
+    /// Get the host version requirement declared by the Wasm FDW
+    pub fn host_version_requirement(&mut self) -> RunnerResult<String> {
+        let ret = match &self.bindings {
+            Bindings::V2(b) => b
+                .supabase_wrappers_routines()
+                .call_host_version_requirement(&mut self.store)?,
+            Bindings::V3(b) => b
+                .supabase_wrappers_routines()
+                .call_host_version_requirement(&mut self.store)?,
+        };
+        Ok(ret)
+    }
+
+    /// Initialize the Wasm FDW with foreign server options
+    pub fn init(&mut self, server_options: &[(&str, &str)]) -> RunnerResult<()> {
+        self.store.data_mut().svr_opts = options(server_options);
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_init(&mut self.store, ctx)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_init(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    /// Scan a foreign table and return all the rows produced
+    pub fn scan(&mut self, scan: &Scan) -> RunnerResult<Vec<Row>> {
+        let host = self.store.data_mut();
+        host.tbl_opts.clone_from(&scan.options);
+        host.columns.clone_from(&scan.columns);
+        host.quals.clone_from(&scan.quals);
+        host.sorts.clone_from(&scan.sorts);
+        host.limit.clone_from(&scan.limit);
+
+        self.call_begin_scan()?;
+
+        let mut rows = Vec::new();
+        loop {
+            let batch = self.call_iter_scan()?;
+            if batch.is_empty() {
+                break;
+            }
+            rows.extend(batch);
+        }
+
+        self.call_end_scan()?;
+
+        Ok(rows)
+    }
+
+    /// Modify a foreign table with a sequence of insert, update or delete
+    ///
+    /// For WIT version `0.3.0`, if the Wasm FDW declares the
+    /// `transaction-hooks` capability, `pre-commit` is called after
+    /// `end-modify` like the modify is in an auto-commit transaction, and
+    /// `abort` is called if any of them fails.
+    pub fn modify(
+        &mut self,
+        table_options: &[(&str, &str)],
+        columns: &[Column],
+        ops: Vec<Modify>,
+    ) -> RunnerResult<()> {
+        let host = self.store.data_mut();
+        host.tbl_opts = options(table_options);
+        host.columns = columns.to_vec();
+
+        self.call_begin_modify()?;
+        let transaction_hooks = self.call_modify_capabilities()?;
+
+        let ret = self.modify_rows(ops).and_then(|_| {
+            if transaction_hooks {
+                self.call_pre_commit()
+            } else {
+                Ok(())
+            }
+        });
+        if transaction_hooks
+            && ret.is_err()
+            && let Err(err) = self.call_abort()
+        {
+            // report it as a warning like the Wasm FDW host, as the
+            // transaction is already aborting
+            self.store.data_mut().messages.push(format!(
+                "WARNING: failed to abort foreign table modification: {err}"
+            ));
+        }
+        ret
+    }
+
+    /// Import foreign schema and return the `create foreign table` statements
+    pub fn import_foreign_schema(
+        &mut self,
+        stmt: &ImportForeignSchemaStmt,
+        options: &[(&str, &str)],
+    ) -> RunnerResult<Vec<String>> {
+        self.store.data_mut().import_schema_opts = self::options(options);
+        let ret = match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                let stmt = GuestImportForeignSchemaStmtV2::from(stmt);
+                b.supabase_wrappers_routines().call_import_foreign_schema(
+                    &mut self.store,
+                    ctx,
+                    &stmt,
+                )??
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines().call_import_foreign_schema(
+                    &mut self.store,
+                    ctx,
+                    stmt,
+                )??
+            }
+        };
+        Ok(ret)
+    }
+
+    /// Get the accumulated value of a stats metric reported by the Wasm FDW
+    pub fn stats(&self, fdw_name: &str, metric: Metric) -> i64 {
+        self.store.data().stats(fdw_name, metric)
+    }
+
+    /// Get the messages reported by the Wasm FDW through `utils` and `log`
+    /// interfaces
+    pub fn messages(&self) -> &[String] {
+        &self.store.data().messages
+    }
+
+    /// Finish the test, the recorded fixtures are saved to the fixture file
+    /// in recording mode
+    pub fn finish(self) -> RunnerResult<()> {
+        let fixtures = &self.store.data().fixtures;
+        if fixtures.mode() == FixtureMode::Record {
+            fixtures.save()?;
+        }
+        Ok(())
+    }
+
+    fn modify_rows(&mut self, ops: Vec<Modify>) -> RunnerResult<()> {
+        for op in ops {
+            match op {
+                Modify::Insert(row) => {
+                    self.store.data_mut().row = row;
+                    self.call_insert()?;
+                }
+                Modify::Update(rowid, row) => {
+                    self.store.data_mut().row = row;
+                    self.call_update(&rowid)?;
+                }
+                Modify::Delete(rowid) => self.call_delete(&rowid)?,
+            }
+        }
+        self.call_end_modify()
+    }
+
+    fn call_begin_scan(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_begin_scan(&mut self.store, ctx)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_begin_scan(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    // fetch next rows, returns an empty list if there are no more rows
+    fn call_iter_scan(&mut self) -> RunnerResult<Vec<Row>> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                self.store.data_mut().row.clear();
+                let ctx = HostContextV2::new(self.store.data_mut());
+                let row = HostRowV2::new(self.store.data_mut());
+                let ret =
+                    b.supabase_wrappers_routines()
+                        .call_iter_scan(&mut self.store, ctx, row)??;
+                Ok(match ret {
+                    Some(_) => vec![self.store.data().row.clone()],
+                    None => Vec::new(),
+                })
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let batch = b.supabase_wrappers_routines().call_iter_scan_batch(
+                    &mut self.store,
+                    ctx,
+                    SCAN_BATCH_SIZE,
+                )??;
+
+                // each row has cells in the order of target columns
+                let columns = &self.store.data().columns;
+                batch
+                    .into_iter()
+                    .map(|cells| {
+                        if cells.len() != columns.len() {
+                            return Err(format!(
+                                "batch row has {} cells but {} columns are expected",
+                                cells.len(),
+                                columns.len()
+                            )
+                            .into());
+                        }
+                        let mut row = Row::new();
+                        for (col, cell) in columns.iter().zip(cells) {
+                            row.push(&col.name, cell);
+                        }
+                        Ok(row)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn call_end_scan(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_end_scan(&mut self.store, ctx)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_end_scan(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_begin_modify(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_begin_modify(&mut self.store, ctx)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_begin_modify(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_modify_capabilities(&mut self) -> RunnerResult<bool> {
+        let ret = match &self.bindings {
+            // transaction hooks are only available since v3
+            Bindings::V2(_) => false,
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_get_modify_capabilities(&mut self.store, ctx)??
+                    .transaction_hooks
+            }
+        };
+        Ok(ret)
+    }
+
+    fn call_insert(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                let row = HostRowV2::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_insert(&mut self.store, ctx, row)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let row = HostRowV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_insert(&mut self.store, ctx, row)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_update(&mut self, rowid: &Cell) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                let row = HostRowV2::new(self.store.data_mut());
+                let rowid = GuestCellV2::from(rowid);
+                b.supabase_wrappers_routines()
+                    .call_update(&mut self.store, ctx, &rowid, row)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let row = HostRowV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_update(&mut self.store, ctx, rowid, row)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_delete(&mut self, rowid: &Cell) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                let rowid = GuestCellV2::from(rowid);
+                b.supabase_wrappers_routines()
+                    .call_delete(&mut self.store, ctx, &rowid)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_delete(&mut self.store, ctx, rowid)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_end_modify(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(b) => {
+                let ctx = HostContextV2::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_end_modify(&mut self.store, ctx)??;
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_end_modify(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_pre_commit(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            // transaction hooks are only available since v3
+            Bindings::V2(_) => {}
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_pre_commit(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_abort(&mut self) -> RunnerResult<()> {
+        match &self.bindings {
+            Bindings::V2(_) => {}
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_abort(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interval;
+
+    #[test]
+    fn test_fmt_cell() {
+        assert_eq!(fmt_cell(&Cell::I64(42)), "42");
+        assert_eq!(fmt_cell(&Cell::Numeric("1.50".to_string())), "1.50");
+        assert_eq!(fmt_cell(&Cell::String("foo".to_string())), "'foo'");
+        assert_eq!(fmt_cell(&Cell::Date(1_700_000_000)), "'2023-11-14'");
+        assert_eq!(
+            fmt_cell(&Cell::Timestamptz(1_700_000_000_000_000)),
+            "'2023-11-14 22:13:20+00'"
+        );
+        assert_eq!(fmt_cell(&Cell::Time(45_296_000_000)), "'12:34:56'");
+        assert_eq!(
+            fmt_cell(&Cell::Interval(Interval {
+                months: 1,
+                days: 2,
+                micros: 3
+            })),
+            "1 mons 2 days 3 microseconds"
+        );
+        assert_eq!(fmt_cell(&Cell::Bytea(vec![0xde, 0xad])), "'\\xDEAD'");
+        assert_eq!(fmt_cell(&Cell::Bytea(Vec::new())), "''");
+        assert_eq!(
+            fmt_cell(&Cell::I32Array(vec![Some(1), None, Some(3)])),
+            "[1,null,3]"
+        );
+    }
+
+    #[test]
+    fn test_qual_deparse() {
+        let qual = Qual::new("Name", "~~", Cell::String("foo%".to_string()));
+        assert_eq!(qual.deparse(), "Name like 'foo%'");
+
+        let qual = Qual {
+            field: "id".to_string(),
+            operator: "=".to_string(),
+            value: Value::Array(vec![Cell::I64(1), Cell::I64(2)]),
+            use_or: true,
+        };
+        assert_eq!(qual.deparse(), "id = 1 or id = 2");
+    }
+}
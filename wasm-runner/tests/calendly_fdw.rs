@@ -0,0 +1,97 @@
+mod common;
+
+use wrappers_wasm_runner::*;
+
+const FDW: &str = "calendly_fdw";
+
+fn new_runner(fixture: &str) -> Runner {
+    let fixtures = Fixtures::load(common::fixture_path(FDW, fixture)).unwrap();
+    let mut runner = Runner::new(common::wasm_path(FDW), fixtures).unwrap();
+    runner.add_secret("calendly_key_id", "1234567890");
+    runner
+        .init(&[
+            ("organization", "https://api.calendly.com/organizations/xxx"),
+            ("api_key_id", "calendly_key_id"),
+        ])
+        .unwrap();
+    runner
+}
+
+#[test]
+fn calendly_fdw_scan_event_types() {
+    let mut runner = new_runner("event_types");
+
+    let scan = Scan {
+        options: options(&[("object", "event_types")]),
+        columns: vec![
+            Column::new("uri", TypeOid::String),
+            Column::new("created_at", TypeOid::Timestamp),
+            Column::new("attrs", TypeOid::Json),
+        ],
+        ..Default::default()
+    };
+    let rows = runner.scan(&scan).unwrap();
+
+    // both pages are fetched
+    let uris: Vec<_> = rows.iter().map(|r| r.get("uri").cloned()).collect();
+    assert_eq!(
+        uris,
+        vec![
+            Some(Cell::String(
+                "https://api.calendly.com/event_types/158ecbf6-79bb-4205-a5fc-a7fefa5883a2"
+                    .to_string()
+            )),
+            Some(Cell::String(
+                "https://api.calendly.com/event_types/6a0d2a3e-0b8e-4c4a-9f0e-1f1f0c7a2b3d"
+                    .to_string()
+            )),
+        ]
+    );
+    assert_eq!(
+        rows[0].get("created_at"),
+        Some(&Cell::Timestamp(1_730_877_775_937_829))
+    );
+    assert!(matches!(rows[1].get("attrs"), Some(Cell::Json(_))));
+
+    assert_eq!(runner.stats("CalendlyFdw", Metric::CreateTimes), 1);
+    assert_eq!(runner.stats("CalendlyFdw", Metric::RowsIn), 2);
+
+    runner.finish().unwrap();
+}
+
+#[test]
+fn calendly_fdw_unauthenticated() {
+    let mut runner = new_runner("unauthenticated");
+
+    let scan = Scan {
+        options: options(&[("object", "current_user")]),
+        columns: vec![Column::new("uri", TypeOid::String)],
+        ..Default::default()
+    };
+    let err = runner.scan(&scan).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("HTTP status error (401 Unauthorized)")
+    );
+
+    runner.finish().unwrap();
+}
+
+#[test]
+fn calendly_fdw_import_foreign_schema() {
+    let mut runner = new_runner("event_types");
+
+    let stmt = ImportForeignSchemaStmt {
+        server_name: "calendly_server".to_string(),
+        remote_schema: "calendly".to_string(),
+        local_schema: "public".to_string(),
+        list_type: ImportSchemaType::All,
+        table_list: Vec::new(),
+        options: Vec::new(),
+    };
+    let sqls = runner.import_foreign_schema(&stmt, &[]).unwrap();
+    assert_eq!(sqls.len(), 5);
+    assert!(sqls.iter().all(|s| s.contains("server calendly_server")));
+
+    runner.finish().unwrap();
+}
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+// path of a Wasm FDW component built in 'wasm-wrappers/fdw'
+pub fn wasm_path(fdw: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../wasm-wrappers/fdw/target/wasm32-unknown-unknown/release")
+        .join(format!("{fdw}.wasm"));
+    assert!(
+        path.exists(),
+        "{} not found, build Wasm FDWs first by running \
+         `cargo component build --release --target wasm32-unknown-unknown` in wasm-wrappers/fdw",
+        path.display()
+    );
+    path
+}
+
+// path of a fixture file of a Wasm FDW
+#[allow(dead_code)]
+pub fn fixture_path(fdw: &str, name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../wasm-wrappers/fdw")
+        .join(fdw)
+        .join("tests/fixtures")
+        .join(format!("{name}.json"))
+}
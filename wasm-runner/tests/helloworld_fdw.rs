@@ -0,0 +1,41 @@
+mod common;
+
+use wrappers_wasm_runner::*;
+
+#[test]
+fn helloworld_fdw_scan() {
+    let mut runner = Runner::new(common::wasm_path("helloworld_fdw"), Fixtures::empty()).unwrap();
+    runner.init(&[]).unwrap();
+
+    let scan = Scan {
+        columns: vec![
+            Column::new("id", TypeOid::I64),
+            Column::new("col", TypeOid::String),
+        ],
+        ..Default::default()
+    };
+    let rows = runner.scan(&scan).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get("id"), Some(&Cell::I64(42)));
+    assert_eq!(
+        rows[0].get("col"),
+        Some(&Cell::String("Hello world".to_string()))
+    );
+
+    // scan again should produce the same rows
+    assert_eq!(runner.scan(&scan).unwrap(), rows);
+}
+
+#[test]
+fn helloworld_fdw_modify() {
+    let mut runner = Runner::new(common::wasm_path("helloworld_fdw"), Fixtures::empty()).unwrap();
+    runner.init(&[]).unwrap();
+
+    let err = runner
+        .modify(&[], &[], vec![Modify::Delete(Cell::I64(42))])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "guest fdw error: modify on foreign table is not supported"
+    );
+}
@@ -1,18 +1,6 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid,
-        },
-        utils,
-    },
-};
+use wrappers_wasm_sdk::prelude::*;
 
 #[derive(Debug, Default)]
 struct CalFdw {
@@ -23,19 +11,19 @@ struct CalFdw {
     src_idx: usize,
 }
 
-static mut INSTANCE: *mut CalFdw = std::ptr::null_mut::<CalFdw>();
+static INSTANCE: Instance<CalFdw> = Instance::new();
 static FDW_NAME: &str = "CalFdw";
 
+// max number of retries when got rate limited error
+static MAX_RETRIES: u32 = 3;
+
 impl CalFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // convert Cal response data field to a cell
@@ -55,42 +43,10 @@ impl CalFdw {
             ));
         }
 
-        let src = src_row
-            .as_object()
-            .and_then(|v| v.get(&tgt_col_name))
+        let src = json_path(src_row, &tgt_col_name)
             .ok_or(format!("source column '{tgt_col_name}' not found"))?;
 
-        // column type mapping
-        let cell = match tgt_col.type_oid() {
-            TypeOid::Bool => src.as_bool().map(Cell::Bool),
-            TypeOid::F64 => src.as_f64().map(Cell::F64),
-            TypeOid::I64 => src.as_i64().map(Cell::I64),
-            TypeOid::String => src.as_str().map(|v| Cell::String(v.to_owned())),
-            TypeOid::Timestamp => {
-                if let Some(s) = src.as_str() {
-                    let ts = time::parse_from_rfc3339(s)?;
-                    Some(Cell::Timestamp(ts))
-                } else {
-                    None
-                }
-            }
-            TypeOid::Timestamptz => {
-                if let Some(s) = src.as_str() {
-                    let ts = time::parse_from_rfc3339(s)?;
-                    Some(Cell::Timestamptz(ts))
-                } else {
-                    None
-                }
-            }
-            TypeOid::Json => src.as_object().map(|_| Cell::Json(src.to_string())),
-            _ => {
-                return Err(format!(
-                    "target column '{tgt_col_name}' type is not supported"
-                ));
-            }
-        };
-
-        Ok(cell)
+        json_to_cell(src, tgt_col)
     }
 
     // create a request instance
@@ -131,7 +87,8 @@ impl CalFdw {
             method: http::Method::Get,
             url,
             headers,
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         })
     }
 
@@ -140,48 +97,85 @@ impl CalFdw {
         self.src_rows.clear();
         self.src_idx = 0;
 
-        loop {
-            // create a request and send it
-            let req = self.create_request()?;
-            let resp = match req.method {
-                http::Method::Get => http::get(&req)?,
-                _ => unreachable!("invalid request method"),
-            };
-
-            // idle for a while for retry when got rate limited error
-            // ref: https://cal.com/docs/api-reference/v1/rate-limit
-            if resp.status_code == 429 {
-                if let Some(retry) = resp.headers.iter().find(|h| h.0 == "x-ratelimit-reset") {
-                    let delay_secs = retry.1.parse::<u64>().map_err(|e| e.to_string())?;
-                    time::sleep(delay_secs * 1000);
-                    continue;
+        // create a request and send it, it will be retried when got rate limited error
+        // ref: https://cal.com/docs/api-reference/v1/rate-limit
+        let req = self.create_request()?;
+        let resp = send_request(&req, MAX_RETRIES)?;
+
+        // transform response to json
+        let resp_json: JsonValue = serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
+
+        // unify response object to array and save source rows
+        let resp_data = resp_json
+            .pointer("/data")
+            .and_then(|v| {
+                if v.is_array() {
+                    v.as_array().cloned()
+                } else {
+                    Some(vec![v.clone()])
                 }
-            }
+            })
+            .ok_or("cannot get query result data")?;
+        self.src_rows.extend(resp_data);
 
-            // transform response to json
-            let resp_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
-
-            // check for errors
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
-
-            // unify response object to array and save source rows
-            let resp_data = resp_json
-                .pointer("/data")
-                .and_then(|v| {
-                    if v.is_array() {
-                        v.as_array().cloned()
-                    } else {
-                        Some(vec![v.clone()])
-                    }
-                })
-                .ok_or("cannot get query result data")?;
-            self.src_rows.extend(resp_data);
-
-            stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
-            break;
+        stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
+
+        Ok(())
+    }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all source rows are consumed
+        if self.src_idx >= self.src_rows.len() {
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+
+            // release consumed rows, so they are not counted again when the
+            // host asks for another batch after the last one
+            self.src_rows.clear();
+            self.src_idx = 0;
+            return Ok(None);
         }
 
+        // convert Cal row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
+
+    // create a booking from the JSON cell in the first column
+    fn insert_row(&self, cells: &[Option<Cell>]) -> FdwResult {
+        if let Some(Some(cell)) = cells.first() {
+            match cell {
+                Cell::Json(body) => {
+                    let url = format!("{}/{}", self.base_url, self.object);
+                    let mut headers = self.headers.clone();
+                    headers.push(("cal-api-version".to_owned(), "2024-08-13".to_owned()));
+                    let req = http::Request {
+                        method: http::Method::Post,
+                        url,
+                        headers,
+                        body: body.as_bytes().to_vec(),
+                        timeout_ms: None,
+                    };
+                    let resp = http::post(&req)?;
+                    http::error_for_status(&resp).map_err(|err| {
+                        format!("{}: {}", err, String::from_utf8_lossy(&resp.body))
+                    })?;
+                    stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
+                }
+                _ => {
+                    return Err("column type other than JSON is not supported".to_owned());
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -189,7 +183,7 @@ impl CalFdw {
 impl Guest for CalFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -221,6 +215,20 @@ impl Guest for CalFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -228,26 +236,9 @@ impl Guest for CalFdw {
         this.fetch_source_data()
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all source rows are consumed
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-            return Ok(None);
-        }
-
-        // convert Cal row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(_ctx: &Context) -> FdwResult {
@@ -272,31 +263,26 @@ impl Guest for CalFdw {
         Ok(())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, row: &Row) -> FdwResult {
         let this = Self::this_mut();
-        if let Some(cell) = &row.cells()[0] {
-            match cell {
-                Cell::Json(body) => {
-                    let url = format!("{}/{}", this.base_url, this.object);
-                    let mut headers = this.headers.clone();
-                    headers.push(("cal-api-version".to_owned(), "2024-08-13".to_owned()));
-                    let req = http::Request {
-                        method: http::Method::Post,
-                        url,
-                        headers,
-                        body: body.to_owned(),
-                    };
-                    let resp = http::post(&req)?;
-                    http::error_for_status(&resp)
-                        .map_err(|err| format!("{}: {}", err, resp.body))?;
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
-                }
-                _ => {
-                    return Err("column type other than JSON is not supported".to_owned());
-                }
-            }
-        }
-        Ok(())
+        this.insert_row(&row.cells())
+    }
+
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        let this = Self::this_mut();
+        rows.iter().try_for_each(|row| this.insert_row(row))
     }
 
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
@@ -311,6 +297,14 @@ impl Guest for CalFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -382,4 +376,4 @@ impl Guest for CalFdw {
     }
 }
 
-bindings::export!(CalFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(CalFdw with_types_in wrappers_wasm_sdk::bindings);
@@ -1,18 +1,6 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid,
-        },
-        utils,
-    },
-};
+use wrappers_wasm_sdk::prelude::*;
 
 #[derive(Debug, Default)]
 struct CalendlyFdw {
@@ -20,23 +8,34 @@ struct CalendlyFdw {
     headers: Vec<(String, String)>,
     org: String,
     object: String,
-    src_rows: Vec<JsonValue>,
-    src_idx: usize,
+    scanner: RestScanner,
 }
 
-static mut INSTANCE: *mut CalendlyFdw = std::ptr::null_mut::<CalendlyFdw>();
+#[derive(FromOptions)]
+struct ServerOptions {
+    organization: String,
+    #[option(name = "api_url", default = "https://api.calendly.com")]
+    base_url: String,
+}
+
+#[derive(FromOptions)]
+struct TableOptions {
+    object: String,
+}
+
+static INSTANCE: Instance<CalendlyFdw> = Instance::new();
 static FDW_NAME: &str = "CalendlyFdw";
 
+// max number of retries when got rate limited error
+static MAX_RETRIES: u32 = 3;
+
 impl CalendlyFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // convert Calendly response data field to a cell
@@ -59,46 +58,14 @@ impl CalendlyFdw {
             ));
         }
 
-        let src = src_row
-            .as_object()
-            .and_then(|v| v.get(&tgt_col_name))
+        let src = json_path(src_row, &tgt_col_name)
             .ok_or(format!("source column '{tgt_col_name}' not found"))?;
 
-        // column type mapping
-        let cell = match tgt_col.type_oid() {
-            TypeOid::Bool => src.as_bool().map(Cell::Bool),
-            TypeOid::F64 => src.as_f64().map(Cell::F64),
-            TypeOid::I64 => src.as_i64().map(Cell::I64),
-            TypeOid::String => src.as_str().map(|v| Cell::String(v.to_owned())),
-            TypeOid::Timestamp => {
-                if let Some(s) = src.as_str() {
-                    let ts = time::parse_from_rfc3339(s)?;
-                    Some(Cell::Timestamp(ts))
-                } else {
-                    None
-                }
-            }
-            TypeOid::Timestamptz => {
-                if let Some(s) = src.as_str() {
-                    let ts = time::parse_from_rfc3339(s)?;
-                    Some(Cell::Timestamptz(ts))
-                } else {
-                    None
-                }
-            }
-            TypeOid::Json => src.as_object().map(|_| Cell::Json(src.to_string())),
-            _ => {
-                return Err(format!(
-                    "target column '{tgt_col_name}' type is not supported"
-                ));
-            }
-        };
-
-        Ok(cell)
+        json_to_cell(src, tgt_col)
     }
 
     // create a request instance
-    fn create_request(&self, page_token: &Option<String>) -> Result<http::Request, FdwError> {
+    fn create_request(&self, page_token: Option<&str>) -> Result<http::Request, FdwError> {
         let url = match self.object.as_str() {
             "current_user" => format!("{}/users/me", self.base_url),
             "event_types" | "groups" | "organization_memberships" | "scheduled_events" => {
@@ -121,74 +88,72 @@ impl CalendlyFdw {
             method: http::Method::Get,
             url,
             headers: self.headers.clone(),
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         })
     }
 
-    // make request to Calendly API, including following pagination requests
-    fn fetch_source_data(&mut self) -> FdwResult {
-        let mut page_token: Option<String> = None;
-
-        self.src_rows.clear();
-        self.src_idx = 0;
-
-        loop {
-            // create a request and send it
-            let req = self.create_request(&page_token)?;
-            let resp = match req.method {
-                http::Method::Get => http::get(&req)?,
-                _ => unreachable!("invalid request method"),
-            };
-
-            // idle for a while for retry when got rate limited error
-            // ref: https://developer.calendly.com/api-docs/edca8074633f8-upcoming-changes
-            if resp.status_code == 429 {
-                if let Some(retry) = resp.headers.iter().find(|h| h.0 == "x-ratelimit-reset") {
-                    let delay_secs = retry.1.parse::<u64>().map_err(|e| e.to_string())?;
-                    time::sleep(delay_secs * 1000);
-                    continue;
-                }
-            }
+    // make request to Calendly API to fetch one page of source rows
+    fn fetch_page(&self) -> Result<Page, FdwError> {
+        // create a request and send it, it will be retried when got rate limited error
+        // ref: https://developer.calendly.com/api-docs/edca8074633f8-upcoming-changes
+        let req = self.create_request(self.scanner.cursor())?;
+        let resp = send_request(&req, MAX_RETRIES)?;
+
+        // transform response to json
+        let resp_json: JsonValue = serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
+
+        // unify response object to array of source rows
+        let rows = if let Some(collection) = resp_json.pointer("/collection") {
+            collection
+                .as_array()
+                .cloned()
+                .ok_or("cannot get query result data")?
+        } else if let Some(resource) = resp_json.pointer("/resource") {
+            vec![resource.clone()]
+        } else {
+            return Err("response format is not supported".to_string());
+        };
 
-            // transform response to json
-            let resp_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
-
-            // check for errors
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
-
-            // unify response object to array and save source rows
-            let resp_data = if resp_json.pointer("/collection").is_some() {
-                resp_json
-                    .pointer("/collection")
-                    .and_then(|v| v.as_array().cloned())
-                    .ok_or("cannot get query result data")?
-            } else if resp_json.pointer("/resource").is_some() {
-                vec![resp_json.pointer("/resource").unwrap().clone()]
-            } else {
-                return Err("response format is not supported".to_string());
-            };
-            self.src_rows.extend(resp_data);
-
-            stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
-
-            // deal with pagination to save next page cursor
-            page_token = resp_json
-                .pointer("/pagination/next_page_token")
-                .and_then(|v| v.as_str().map(|s| s.to_owned()));
-            if page_token.is_none() {
-                break;
-            }
+        stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
+        // rows are counted per page, as the host may ask for another batch
+        // after all rows are consumed
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, rows.len() as i64);
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, rows.len() as i64);
+
+        // get next page cursor
+        let next_cursor = resp_json
+            .pointer("/pagination/next_page_token")
+            .and_then(|v| v.as_str().map(|s| s.to_owned()));
+
+        Ok(Page { rows, next_cursor })
+    }
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // fetch next page if all source rows in current page are consumed
+        while self.scanner.needs_page() {
+            let page = self.fetch_page()?;
+            self.scanner.push_page(page);
         }
 
-        Ok(())
+        // if all source rows are consumed
+        let Some(src_row) = self.scanner.next_row() else {
+            return Ok(None);
+        };
+
+        // convert Calendly row to Postgres row
+        ctx.get_columns()
+            .iter()
+            .map(|tgt_col| self.src_to_cell(&src_row, tgt_col))
+            .collect::<Result<_, _>>()
+            .map(Some)
     }
 }
 
 impl Guest for CalendlyFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -197,15 +162,10 @@ impl Guest for CalendlyFdw {
 
         // get foreign server options
         let opts = ctx.get_options(&OptionsType::Server);
-        this.org = opts.require("organization")?;
-        this.base_url = opts.require_or("api_url", "https://api.calendly.com");
-        let api_key = match opts.get("api_key") {
-            Some(key) => key,
-            None => {
-                let key_id = opts.require("api_key_id")?;
-                utils::get_vault_secret(&key_id).unwrap_or_default()
-            }
-        };
+        let server_opts = ServerOptions::from_options(&opts)?;
+        this.org = server_opts.organization;
+        this.base_url = server_opts.base_url;
+        let api_key = require_secret(&opts, "api_key", "api_key_id")?;
 
         // Calendly api authentication
         // ref: https://developer.calendly.com/api-docs/d7755e2f9e5fe-calendly-api
@@ -221,43 +181,42 @@ impl Guest for CalendlyFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
-        this.object = opts.require("object")?;
-        this.fetch_source_data()
+        this.object = TableOptions::from_options(&opts)?.object;
+        this.scanner.reset();
+        Ok(())
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all source rows are consumed
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-            return Ok(None);
-        }
-
-        // convert Calendly row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(_ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
-        this.fetch_source_data()
+        this.scanner.reset();
+        Ok(())
     }
 
     fn end_scan(_ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
-        this.src_rows.clear();
+        this.scanner.reset();
         Ok(())
     }
 
@@ -265,10 +224,26 @@ impl Guest for CalendlyFdw {
         Err("modify on foreign table is not supported".to_owned())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Ok(())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Ok(())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Ok(())
     }
@@ -281,6 +256,14 @@ impl Guest for CalendlyFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -352,4 +335,4 @@ impl Guest for CalendlyFdw {
     }
 }
 
-bindings::export!(CalendlyFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(CalendlyFdw with_types_in wrappers_wasm_sdk::bindings);
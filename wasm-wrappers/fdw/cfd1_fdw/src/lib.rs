@@ -1,17 +1,19 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
 
 #[derive(Debug, Default)]
@@ -25,19 +27,16 @@ struct Cfd1Fdw {
     src_idx: usize,
 }
 
-static mut INSTANCE: *mut Cfd1Fdw = std::ptr::null_mut::<Cfd1Fdw>();
+static INSTANCE: Instance<Cfd1Fdw> = Instance::new();
 static FDW_NAME: &str = "Cfd1Fdw";
 
 impl Cfd1Fdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // convert Cloudflare API response data field to a cell
@@ -194,7 +193,8 @@ impl Cfd1Fdw {
             method,
             url,
             headers: self.headers.clone(),
-            body,
+            body: body.into_bytes(),
+            timeout_ms: None,
         })
     }
 
@@ -228,10 +228,11 @@ impl Cfd1Fdw {
 
             // transform response to json
             let resp_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
             // check for HTTP errors
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+            http::error_for_status(&resp)
+                .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
             // check for API request errors
             if let Some(success) = resp_json["success"].as_bool() {
@@ -274,16 +275,85 @@ impl Cfd1Fdw {
             method: http::Method::Post,
             url: format!("{}/{}/query", self.base_url, self.database_id),
             headers: self.headers.clone(),
-            body: format!(r#"{{ "params": {params:?}, "sql": "{sql}" }}"#),
+            body: format!(r#"{{ "params": {params:?}, "sql": "{sql}" }}"#).into_bytes(),
+            timeout_ms: None,
         };
         self.fetch_source_data(req)
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all source rows are consumed
+        if self.src_idx >= self.src_rows.len() {
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+
+            // release consumed rows, so they are not counted again when the
+            // host asks for another batch after the last one
+            self.src_rows.clear();
+            self.src_idx = 0;
+            return Ok(None);
+        }
+
+        // convert source row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
+
+    // insert a row of the given columns
+    fn insert_row(&mut self, cols: &[String], cells: &[Option<Cell>]) -> FdwResult {
+        // make query parameter and colmn name&value list
+        let (params, cols): (Vec<String>, Vec<(String, String)>) = cols
+            .iter()
+            .zip(cells.iter())
+            .filter(|(col, cell)| *col != "_attrs" && cell.is_some())
+            .map(|(col, cell)| {
+                let mut param = utils::cell_to_string(cell.as_ref());
+                if let Some(Cell::String(_)) = cell {
+                    // if cell is string, strip the leading and trailing quote
+                    param = param
+                        .as_str()
+                        .strip_prefix("'")
+                        .and_then(|s| s.strip_suffix("'"))
+                        .map(|s| s.to_owned())
+                        .unwrap_or_default();
+                }
+                let col_name = col.to_owned();
+                let col_value = "?".to_owned();
+                (param, (col_name, col_value))
+            })
+            .unzip();
+
+        // deparse sql query
+        let (col_names, col_values): (Vec<String>, Vec<String>) = cols.iter().cloned().unzip();
+        let sql = format!(
+            "insert into {} ({}) values ({})",
+            self.table,
+            col_names.join(","),
+            col_values.join(",")
+        );
+
+        // send modify request
+        self.modify_source_data(&params, &sql)?;
+
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
+
+        Ok(())
+    }
 }
 
 impl Guest for Cfd1Fdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -320,6 +390,20 @@ impl Guest for Cfd1Fdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -328,26 +412,9 @@ impl Guest for Cfd1Fdw {
         this.fetch_source_data(req)
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all source rows are consumed
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-            return Ok(None);
-        }
-
-        // convert source row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -371,47 +438,27 @@ impl Guest for Cfd1Fdw {
         Ok(())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, row: &Row) -> FdwResult {
         let this = Self::this_mut();
+        this.insert_row(&row.cols(), &row.cells())
+    }
 
-        // make query parameter and colmn name&value list
-        let (params, cols): (Vec<String>, Vec<(String, String)>) = row
-            .cols()
-            .iter()
-            .zip(row.cells().iter())
-            .filter(|(col, cell)| *col != "_attrs" && cell.is_some())
-            .map(|(col, cell)| {
-                let mut param = utils::cell_to_string(cell.as_ref());
-                if let Some(Cell::String(_)) = cell {
-                    // if cell is string, strip the leading and trailing quote
-                    param = param
-                        .as_str()
-                        .strip_prefix("'")
-                        .and_then(|s| s.strip_suffix("'"))
-                        .map(|s| s.to_owned())
-                        .unwrap_or_default();
-                }
-                let col_name = col.to_owned();
-                let col_value = "?".to_owned();
-                (param, (col_name, col_value))
-            })
-            .unzip();
-
-        // deparse sql query
-        let (col_names, col_values): (Vec<String>, Vec<String>) = cols.iter().cloned().unzip();
-        let sql = format!(
-            "insert into {} ({}) values ({})",
-            this.table,
-            col_names.join(","),
-            col_values.join(",")
-        );
-
-        // send modify request
-        this.modify_source_data(&params, &sql)?;
-
-        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
-
-        Ok(())
+    fn insert_batch(
+        _ctx: &Context,
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        let this = Self::this_mut();
+        rows.iter()
+            .try_for_each(|row| this.insert_row(&columns, row))
     }
 
     fn update(_ctx: &Context, rowid: Cell, row: &Row) -> FdwResult {
@@ -479,6 +526,14 @@ impl Guest for Cfd1Fdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -502,4 +557,4 @@ impl Guest for Cfd1Fdw {
     }
 }
 
-bindings::export!(Cfd1Fdw with_types_in bindings);
+wrappers_wasm_sdk::export!(Cfd1Fdw with_types_in wrappers_wasm_sdk::bindings);
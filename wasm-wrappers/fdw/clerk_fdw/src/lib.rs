@@ -1,17 +1,19 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
 
 #[derive(Debug, Default)]
@@ -27,7 +29,7 @@ struct ClerkFdw {
     sub_obj_value: String,
 }
 
-static mut INSTANCE: *mut ClerkFdw = std::ptr::null_mut::<ClerkFdw>();
+static INSTANCE: Instance<ClerkFdw> = Instance::new();
 static FDW_NAME: &str = "ClerkFdw";
 
 // max number of rows returned per request
@@ -35,14 +37,11 @@ static BATCH_SIZE: usize = 500;
 
 impl ClerkFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // convert Clerk response data field to a cell
@@ -280,7 +279,8 @@ impl ClerkFdw {
             method: http::Method::Get,
             url,
             headers,
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         })
     }
 
@@ -308,11 +308,12 @@ impl ClerkFdw {
             }
 
             // check for errors
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+            http::error_for_status(&resp)
+                .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
             // transform response to json
             let resp_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
             stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
 
@@ -347,12 +348,75 @@ impl ClerkFdw {
 
         Ok(())
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all rows in local batch buffer are consumed
+        while self.src_idx >= self.src_rows.len() {
+            let consumed_cnt = self.src_rows.len();
+
+            // For parameterized billing endpoints, don't paginate (scoped to specific resource)
+            let is_parameterized = matches!(
+                self.object.as_str(),
+                "users/billing/subscription"
+                    | "organizations/billing/subscription"
+                    | "billing/statement"
+                    | "billing/payment_attempts"
+            );
+
+            // local batch buffer isn't fully filled, means no more source records on remote,
+            // stop the iteration scan
+            if consumed_cnt < BATCH_SIZE || is_parameterized {
+                return Ok(None);
+            }
+
+            // otherwise, make a new request for the next batch
+            self.src_offset += consumed_cnt;
+            self.fetch_source_data(ctx)?;
+
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, consumed_cnt as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, consumed_cnt as i64);
+        }
+
+        // convert Clerk row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
+
+    // create an object from the 'attrs' JSONB column
+    fn insert_row(&self, cells: &[Option<Cell>]) -> FdwResult {
+        // we assume 'attrs' is defined as the last column
+        if let Some(Some(Cell::Json(body))) = cells.last() {
+            let url = format!("{}/{}", self.base_url, self.object);
+            let headers = self.headers.clone();
+            let req = http::Request {
+                method: http::Method::Post,
+                url,
+                headers,
+                body: body.as_bytes().to_vec(),
+                timeout_ms: None,
+            };
+            let resp = http::post(&req)?;
+            http::error_for_status(&resp)
+                .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
+            return Ok(());
+        }
+        Err("cannot find 'attrs' JSONB column to insert".to_owned())
+    }
 }
 
 impl Guest for ClerkFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -387,6 +451,20 @@ impl Guest for ClerkFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -398,45 +476,9 @@ impl Guest for ClerkFdw {
         this.fetch_source_data(ctx)
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all rows in local batch buffer are consumed
-        while this.src_idx >= this.src_rows.len() {
-            let consumed_cnt = this.src_rows.len();
-
-            // For parameterized billing endpoints, don't paginate (scoped to specific resource)
-            let is_parameterized = matches!(
-                this.object.as_str(),
-                "users/billing/subscription"
-                    | "organizations/billing/subscription"
-                    | "billing/statement"
-                    | "billing/payment_attempts"
-            );
-
-            // local batch buffer isn't fully filled, means no more source records on remote,
-            // stop the iteration scan
-            if consumed_cnt < BATCH_SIZE || is_parameterized {
-                return Ok(None);
-            }
-
-            // otherwise, make a new request for the next batch
-            this.src_offset += consumed_cnt;
-            this.fetch_source_data(ctx)?;
-
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, consumed_cnt as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, consumed_cnt as i64);
-        }
-
-        // convert Clerk row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -461,24 +503,26 @@ impl Guest for ClerkFdw {
         Ok(())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, row: &Row) -> FdwResult {
         let this = Self::this_mut();
-        // we assume 'attrs' is defined as the last column
-        if let Some(Some(Cell::Json(body))) = row.cells().last() {
-            let url = format!("{}/{}", this.base_url, this.object);
-            let headers = this.headers.clone();
-            let req = http::Request {
-                method: http::Method::Post,
-                url,
-                headers,
-                body: body.to_owned(),
-            };
-            let resp = http::post(&req)?;
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
-            return Ok(());
-        }
-        Err("cannot find 'attrs' JSONB column to insert".to_owned())
+        this.insert_row(&row.cells())
+    }
+
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        let this = Self::this_mut();
+        rows.iter().try_for_each(|row| this.insert_row(row))
     }
 
     fn update(_ctx: &Context, rowid: Cell, row: &Row) -> FdwResult {
@@ -492,10 +536,12 @@ impl Guest for ClerkFdw {
                     method: http::Method::Patch,
                     url,
                     headers,
-                    body: body.to_owned(),
+                    body: body.as_bytes().to_vec(),
+                    timeout_ms: None,
                 };
                 let resp = http::patch(&req)?;
-                http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+                http::error_for_status(&resp)
+                    .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
                 stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
                 Ok(())
             } else {
@@ -515,10 +561,12 @@ impl Guest for ClerkFdw {
                 method: http::Method::Delete,
                 url,
                 headers,
-                body: String::default(),
+                body: Vec::new(),
+                timeout_ms: None,
             };
             let resp = http::delete(&req)?;
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+            http::error_for_status(&resp)
+                .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
             Ok(())
         } else {
             Err("no rowid column specified for delete".to_owned())
@@ -529,6 +577,14 @@ impl Guest for ClerkFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -842,4 +898,4 @@ impl Guest for ClerkFdw {
     }
 }
 
-bindings::export!(ClerkFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(ClerkFdw with_types_in wrappers_wasm_sdk::bindings);
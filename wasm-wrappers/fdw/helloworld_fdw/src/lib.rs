@@ -1,10 +1,4 @@
-#[allow(warnings)]
-mod bindings;
-
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::types::{Cell, Context, FdwError, FdwResult, ImportForeignSchemaStmt, Row},
-};
+use wrappers_wasm_sdk::prelude::*;
 
 #[derive(Debug, Default)]
 struct HelloWorldFdw {
@@ -12,25 +6,43 @@ struct HelloWorldFdw {
     row_cnt: i32,
 }
 
-static mut INSTANCE: *mut HelloWorldFdw = std::ptr::null_mut::<HelloWorldFdw>();
+static INSTANCE: Instance<HelloWorldFdw> = Instance::new();
 
 impl HelloWorldFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
+    }
+
+    // make the next row, returns 'None' when there are no more rows
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        if self.row_cnt >= 1 {
+            return Ok(None);
+        }
+
+        let row = ctx
+            .get_columns()
+            .iter()
+            .map(|tgt_col| match tgt_col.name().as_str() {
+                "id" => Some(Cell::I64(42)),
+                "col" => Some(Cell::String("Hello world".to_string())),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        self.row_cnt += 1;
+
+        Ok(Some(row))
     }
 }
 
 impl Guest for HelloWorldFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(_ctx: &Context) -> FdwResult {
@@ -38,6 +50,20 @@ impl Guest for HelloWorldFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // there is only one row
+        Ok((1, 16))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // nothing is pushed down, Postgres rechecks quals, sorts and limit
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(_ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
 
@@ -47,30 +73,11 @@ impl Guest for HelloWorldFdw {
         Ok(())
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
 
-        if this.row_cnt >= 1 {
-            // return 'None' to stop data scan
-            return Ok(None);
-        }
-
-        for tgt_col in &ctx.get_columns() {
-            match tgt_col.name().as_str() {
-                "id" => {
-                    row.push(Some(&Cell::I64(42)));
-                }
-                "col" => {
-                    row.push(Some(&Cell::String("Hello world".to_string())));
-                }
-                _ => unreachable!(),
-            }
-        }
-
-        this.row_cnt += 1;
-
-        // return Some(_) to Postgres and continue data scan
-        Ok(Some(0))
+        // return an empty batch to stop data scan
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(_ctx: &Context) -> FdwResult {
@@ -88,10 +95,26 @@ impl Guest for HelloWorldFdw {
         Err("modify on foreign table is not supported".to_owned())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Ok(())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Ok(())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Ok(())
     }
@@ -104,6 +127,14 @@ impl Guest for HelloWorldFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -122,4 +153,4 @@ impl Guest for HelloWorldFdw {
     }
 }
 
-bindings::export!(HelloWorldFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(HelloWorldFdw with_types_in wrappers_wasm_sdk::bindings);
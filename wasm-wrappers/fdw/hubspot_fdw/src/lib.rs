@@ -1,17 +1,19 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch, eq_qual_value,
 };
 
 #[derive(Debug, Default)]
@@ -27,7 +29,7 @@ struct HubspotFdw {
     consumed_row_cnt: i64,
 }
 
-static mut INSTANCE: *mut HubspotFdw = std::ptr::null_mut::<HubspotFdw>();
+static INSTANCE: Instance<HubspotFdw> = Instance::new();
 static FDW_NAME: &str = "HubspotFdw";
 
 // max number of rows returned per request
@@ -35,14 +37,11 @@ static BATCH_SIZE: usize = 100;
 
 impl HubspotFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // convert HubSpot response data field to a cell
@@ -70,13 +69,14 @@ impl HubspotFdw {
             TypeOid::Bool => src.as_bool().map(Cell::Bool),
             TypeOid::F64 => src.as_f64().map(Cell::F64),
             TypeOid::I64 => src.as_i64().map(Cell::I64),
+            // keep the exact decimal string of the source number
             TypeOid::Numeric => src
-                .as_f64()
+                .as_number()
+                .map(|v| v.to_string())
                 .or_else(|| {
                     src.as_str()
-                        .map(|v| v.parse::<f64>())
-                        .transpose()
-                        .unwrap_or_default()
+                        .filter(|v| v.parse::<f64>().is_ok())
+                        .map(|v| v.to_owned())
                 })
                 .map(Cell::Numeric),
             TypeOid::String => src.as_str().map(|v| Cell::String(v.to_owned())),
@@ -113,21 +113,9 @@ impl HubspotFdw {
         let quals = ctx.get_quals();
 
         // set request url, it is in `<objects>/<id>` form if `id = <string>` qual is specified
-        let url = if let Some(q) = quals.iter().find(|q| {
-            if (q.field() == "id") && (q.operator() == "=") {
-                if let Value::Cell(Cell::String(_)) = q.value() {
-                    return true;
-                }
-            }
-            false
-        }) {
+        let url = if let Some(id) = eq_qual_value(&quals, "id") {
             // push down `id = <string>` clause
-            match q.value() {
-                Value::Cell(Cell::String(id)) => {
-                    format!("{}/{}/{}?{}", self.base_url, self.object, id, qs.join("&"))
-                }
-                _ => unreachable!(),
-            }
+            format!("{}/{}/{}?{}", self.base_url, self.object, id, qs.join("&"))
         } else {
             // otherwise, the request is to get object list
             if let Some(ref sc) = self.src_cursor {
@@ -148,7 +136,8 @@ impl HubspotFdw {
             method: http::Method::Get,
             url,
             headers: self.headers.clone(),
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         })
     }
 
@@ -165,10 +154,11 @@ impl HubspotFdw {
         }
 
         // check for errors
-        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+        http::error_for_status(&resp)
+            .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
         // transform response to json
-        let resp_json: JsonValue = serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+        let resp_json: JsonValue = serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
         stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
 
@@ -206,12 +196,46 @@ impl HubspotFdw {
 
         Ok(())
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all rows in local batch buffer are consumed
+        while self.src_idx >= self.src_rows.len() {
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+
+            // release consumed rows, so they are not counted again when the
+            // host asks for another batch after the last one
+            self.src_rows.clear();
+            self.src_idx = 0;
+
+            // no more source records on remote or consumed records exceeds limit, stop the iteration scan
+            if self.src_cursor.is_none() || (Some(self.consumed_row_cnt) >= self.src_limit) {
+                return Ok(None);
+            }
+
+            // otherwise, make a new request for the next batch
+            self.fetch_source_data(ctx)?;
+        }
+
+        // convert HubSpot row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+        self.src_idx += 1;
+        self.consumed_row_cnt += 1;
+
+        Ok(Some(row))
+    }
 }
 
 impl Guest for HubspotFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -247,6 +271,20 @@ impl Guest for HubspotFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -267,33 +305,9 @@ impl Guest for HubspotFdw {
         this.fetch_source_data(ctx)
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all rows in local batch buffer are consumed
-        while this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-
-            // no more source records on remote or consumed records exceeds limit, stop the iteration scan
-            if this.src_cursor.is_none() || (Some(this.consumed_row_cnt) >= this.src_limit) {
-                return Ok(None);
-            }
-
-            // otherwise, make a new request for the next batch
-            this.fetch_source_data(ctx)?;
-        }
-
-        // convert HubSpot row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-        this.src_idx += 1;
-        this.consumed_row_cnt += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -313,10 +327,26 @@ impl Guest for HubspotFdw {
         Err("modify on foreign table is not supported".to_owned())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Ok(())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Ok(())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Ok(())
     }
@@ -329,6 +359,14 @@ impl Guest for HubspotFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -466,4 +504,4 @@ impl Guest for HubspotFdw {
     }
 }
 
-bindings::export!(HubspotFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(HubspotFdw with_types_in wrappers_wasm_sdk::bindings);
@@ -2,21 +2,22 @@
 //!
 //! Supports querying Ethereum, Polygon, and other EVM-compatible chains.
 
-#[allow(warnings)]
-mod bindings;
-
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use serde_json::Value as JsonValue;
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
-use serde_json::Value as JsonValue;
 
 #[derive(Debug, Default, Clone)]
 enum Resource {
@@ -57,19 +58,16 @@ struct InfuraFdw {
     block_hash: Option<String>,
 }
 
-static mut INSTANCE: *mut InfuraFdw = std::ptr::null_mut::<InfuraFdw>();
+static INSTANCE: Instance<InfuraFdw> = Instance::new();
 static FDW_NAME: &str = "InfuraFdw";
 
 impl InfuraFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     fn default_url(&self) -> String {
@@ -92,7 +90,8 @@ impl InfuraFdw {
             method: http::Method::Post,
             url: self.base_url.clone(),
             headers: self.headers.clone(),
-            body: body.to_string(),
+            body: body.to_string().into_bytes(),
+            timeout_ms: None,
         };
 
         // Retry logic with exponential backoff for rate limiting
@@ -117,13 +116,14 @@ impl InfuraFdw {
             if resp.status_code != 200 {
                 return Err(format!(
                     "Infura API error: status {}, body: {}",
-                    resp.status_code, resp.body
+                    resp.status_code,
+                    String::from_utf8_lossy(&resp.body)
                 ));
             }
 
             stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
 
-            let json: JsonValue = serde_json::from_str(&resp.body)
+            let json: JsonValue = serde_json::from_slice(&resp.body)
                 .map_err(|e| format!("Failed to parse response: {e}"))?;
 
             if let Some(error) = json.get("error") {
@@ -250,13 +250,13 @@ impl InfuraFdw {
         Ok(())
     }
 
-    /// Parse hex string to f64 (for PostgreSQL numeric type)
-    fn hex_to_numeric(hex: &str) -> Option<f64> {
+    /// Parse hex string to decimal string (for PostgreSQL numeric type)
+    fn hex_to_numeric(hex: &str) -> Option<String> {
         let hex = hex.strip_prefix("0x").unwrap_or(hex);
         if hex.is_empty() {
-            return Some(0.0);
+            return Some("0".to_string());
         }
-        u64::from_str_radix(hex, 16).map(|n| n as f64).ok()
+        u128::from_str_radix(hex, 16).map(|n| n.to_string()).ok()
     }
 
     /// Convert hex balance string to ETH string representation
@@ -538,11 +538,27 @@ impl InfuraFdw {
         };
         Ok(cell)
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        let Some(src_row) = self.src_rows.get(self.src_idx) else {
+            return Ok(None);
+        };
+
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+
+        self.src_idx += 1;
+        Ok(Some(row))
+    }
 }
 
 impl Guest for InfuraFdw {
     fn host_version_requirement() -> String {
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -584,6 +600,20 @@ impl Guest for InfuraFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         this.src_rows.clear();
@@ -606,7 +636,10 @@ impl Guest for InfuraFdw {
             let value = match qual.value() {
                 Value::Cell(Cell::String(s)) => s.clone(),
                 Value::Cell(Cell::I64(n)) => format!("0x{n:x}"),
-                Value::Cell(Cell::Numeric(n)) => format!("0x{:x}", n as u64),
+                Value::Cell(Cell::Numeric(n)) => match n.parse::<u128>() {
+                    Ok(n) => format!("0x{n:x}"),
+                    Err(_) => continue,
+                },
                 _ => continue,
             };
 
@@ -639,23 +672,14 @@ impl Guest for InfuraFdw {
         Ok(())
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
+        let rows = collect_batch(max_rows, || this.next_row(ctx))?;
 
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-            return Ok(None);
-        }
-
-        let src_row = &this.src_rows[this.src_idx];
-
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
+        // rows are counted per batch, as rows are kept for re-scan
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, rows.len() as i64);
 
-        this.src_idx += 1;
-        Ok(Some(0))
+        Ok(rows)
     }
 
     fn re_scan(_ctx: &Context) -> FdwResult {
@@ -674,10 +698,26 @@ impl Guest for InfuraFdw {
         Err("Infura FDW is read-only. Blockchain data cannot be modified.".to_string())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Ok(())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Ok(())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Ok(())
     }
@@ -690,6 +730,14 @@ impl Guest for InfuraFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -779,4 +827,4 @@ impl Guest for InfuraFdw {
     }
 }
 
-bindings::export!(InfuraFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(InfuraFdw with_types_in wrappers_wasm_sdk::bindings);
@@ -1,17 +1,19 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
 
 #[derive(Debug, Default)]
@@ -23,19 +25,16 @@ struct NotionFdw {
     src_idx: usize,
 }
 
-static mut INSTANCE: *mut NotionFdw = std::ptr::null_mut::<NotionFdw>();
+static INSTANCE: Instance<NotionFdw> = Instance::new();
 static FDW_NAME: &str = "NotionFdw";
 
 impl NotionFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // convert Notion response data field to a cell
@@ -212,7 +211,8 @@ impl NotionFdw {
             method,
             url,
             headers: self.headers.clone(),
-            body,
+            body: body.into_bytes(),
+            timeout_ms: None,
         })
     }
 
@@ -247,7 +247,7 @@ impl NotionFdw {
 
             // transform response to json
             let resp_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
             // if the 404 is caused by no object found, we shouldn't take it as an error
             if resp.status_code == 404
@@ -257,7 +257,8 @@ impl NotionFdw {
             }
 
             // check for errors
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+            http::error_for_status(&resp)
+                .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
             // unify response object to array and save source rows
             let resp_data = if resp_json.pointer("/object").and_then(|v| v.as_str()) == Some("list")
@@ -391,12 +392,39 @@ impl NotionFdw {
 
         Ok(())
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all source rows are consumed
+        if self.src_idx >= self.src_rows.len() {
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+
+            // release consumed rows, so they are not counted again when the
+            // host asks for another batch after the last one
+            self.src_rows.clear();
+            self.src_idx = 0;
+            return Ok(None);
+        }
+
+        // convert Notion row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
 }
 
 impl Guest for NotionFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -431,6 +459,20 @@ impl Guest for NotionFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -439,26 +481,9 @@ impl Guest for NotionFdw {
         this.fetch_source_data(ctx)
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all source rows are consumed
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-            return Ok(None);
-        }
-
-        // convert Notion row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -476,10 +501,26 @@ impl Guest for NotionFdw {
         Err("modify on foreign table is not supported".to_owned())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Ok(())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Ok(())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Ok(())
     }
@@ -492,6 +533,14 @@ impl Guest for NotionFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -558,4 +607,4 @@ impl Guest for NotionFdw {
     }
 }
 
-bindings::export!(NotionFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(NotionFdw with_types_in wrappers_wasm_sdk::bindings);
@@ -6,26 +6,28 @@
 // Allow usize->i64 casts for stats (expected to fit on 64-bit systems)
 #![allow(clippy::cast_possible_wrap)]
 
-#[allow(warnings)]
-mod bindings;
 mod schema;
 mod spec;
 
 use serde_json::{Map as JsonMap, Value as JsonValue};
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, ImportSchemaType,
-            OptionsType, Row, TypeOid, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    build_ddl, collect_batch,
 };
 
-use schema::generate_all_tables;
+use schema::build_all_tables;
 use spec::OpenApiSpec;
 
 /// The `OpenAPI` FDW state
@@ -68,36 +70,17 @@ struct OpenApiFdw {
     consumed_row_cnt: i64,
 }
 
-/// Global FDW instance pointer.
-///
-/// # Safety
-///
-/// This `static mut` is safe because Wasm execution is single-threaded:
-/// - No concurrent access is possible (no data races)
-/// - Initialized once in `init()` before any scan/modify methods are called
-/// - All access goes through `this_mut()` which returns exclusive `&mut` reference
-static mut INSTANCE: *mut OpenApiFdw = std::ptr::null_mut::<OpenApiFdw>();
+/// Global FDW instance, accessed through `this_mut()`
+static INSTANCE: Instance<OpenApiFdw> = Instance::new();
 static FDW_NAME: &str = "OpenApiFdw";
 
 impl OpenApiFdw {
     fn init() {
-        let instance = Self::default();
-        // SAFETY: Wasm is single-threaded, no concurrent access possible.
-        // Box::leak intentionally leaks memory to create a stable 'static pointer
-        // that lives for the entire FDW lifetime (until Postgres unloads).
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        // SAFETY: INSTANCE is initialized in init() before any scan/modify
-        // methods are called. Wasm is single-threaded, so only one &mut
-        // reference exists at a time (no aliasing).
-        unsafe {
-            debug_assert!(!INSTANCE.is_null(), "OpenApiFdw not initialized");
-            &mut (*INSTANCE)
-        }
+        INSTANCE.get_mut()
     }
 
     /// Fetch and parse the `OpenAPI` spec
@@ -107,14 +90,20 @@ impl OpenApiFdw {
                 method: http::Method::Get,
                 url: url.clone(),
                 headers: self.headers.clone(),
-                body: String::default(),
+                body: Vec::new(),
+                timeout_ms: None,
             };
             let resp = http::get(&req)?;
-            http::error_for_status(&resp)
-                .map_err(|err| format!("Failed to fetch OpenAPI spec: {}: {}", err, resp.body))?;
+            http::error_for_status(&resp).map_err(|err| {
+                format!(
+                    "Failed to fetch OpenAPI spec: {}: {}",
+                    err,
+                    String::from_utf8_lossy(&resp.body)
+                )
+            })?;
 
             let spec_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
             self.spec = Some(OpenApiSpec::from_json(&spec_json)?);
 
             // Use base_url from spec if not explicitly set
@@ -132,7 +121,9 @@ impl OpenApiFdw {
     }
 
     /// Extract a qual value as a string
-    fn qual_value_to_string(qual: &bindings::supabase::wrappers::types::Qual) -> Option<String> {
+    fn qual_value_to_string(
+        qual: &wrappers_wasm_sdk::bindings::supabase::wrappers::types::Qual,
+    ) -> Option<String> {
         if qual.operator() != "=" {
             return None;
         }
@@ -330,7 +321,8 @@ impl OpenApiFdw {
             method: http::Method::Get,
             url,
             headers: self.headers.clone(),
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         };
 
         // Retry loop for rate limiting (HTTP 429)
@@ -375,10 +367,11 @@ impl OpenApiFdw {
             return Ok(());
         }
 
-        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+        http::error_for_status(&resp)
+            .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
         let mut resp_json: JsonValue =
-            serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+            serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
         stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
 
@@ -536,7 +529,10 @@ impl OpenApiFdw {
                 #[allow(clippy::cast_possible_truncation)]
                 TypeOid::F32 => value.parse::<f64>().ok().map(|v| Cell::F32(v as f32)),
                 TypeOid::F64 => value.parse::<f64>().ok().map(Cell::F64),
-                TypeOid::Numeric => value.parse::<f64>().ok().map(Cell::Numeric),
+                TypeOid::Numeric => value
+                    .parse::<f64>()
+                    .ok()
+                    .map(|_| Cell::Numeric(value.to_owned())),
                 TypeOid::Date => time::parse_from_rfc3339(value)
                     .ok()
                     .map(|ts| Cell::Date(ts / 1_000_000)),
@@ -591,7 +587,7 @@ impl OpenApiFdw {
             #[allow(clippy::cast_possible_truncation)]
             TypeOid::F32 => src.as_f64().map(|v| Cell::F32(v as f32)),
             TypeOid::F64 => src.as_f64().map(Cell::F64),
-            TypeOid::Numeric => src.as_f64().map(Cell::Numeric),
+            TypeOid::Numeric => src.as_number().map(|v| Cell::Numeric(v.to_string())),
             TypeOid::String => {
                 // Handle both string and non-string JSON values
                 Some(Cell::String(
@@ -626,10 +622,63 @@ impl OpenApiFdw {
             TypeOid::Uuid => src.as_str().map(|v| Cell::String(v.to_owned())),
             // Json and unknown types: serialize to JSON string
             TypeOid::Json | TypeOid::Other(_) => Some(Cell::Json(src.to_string())),
+            // the other types added in WIT 0.3.0, like arrays
+            _ => wrappers_wasm_sdk::json_to_cell(src, tgt_col)?,
         };
 
         Ok(cell)
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // Check if we need to fetch more data
+        if self.src_idx >= self.src_rows.len() {
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+
+            // release consumed rows, so they are not counted again when the
+            // host asks for another batch after the last one
+            self.src_rows.clear();
+            self.src_idx = 0;
+
+            // No more pages to fetch
+            if self.next_cursor.is_none() && self.next_url.is_none() {
+                return Ok(None);
+            }
+
+            // Check if limit is satisfied - stop pagination early
+            if let Some(limit) = self.src_limit {
+                if self.consumed_row_cnt >= limit {
+                    return Ok(None);
+                }
+            }
+
+            // Fetch next page
+            self.make_request(ctx)?;
+
+            // If still no data after fetch, we're done
+            if self.src_rows.is_empty() {
+                return Ok(None);
+            }
+        }
+
+        // Convert current row (apply object_path if set, e.g., "/properties" for GeoJSON)
+        let src_row = &self.src_rows[self.src_idx];
+        let effective_row = self
+            .object_path
+            .as_ref()
+            .map_or(src_row, |path| src_row.pointer(path).unwrap_or(src_row));
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.json_to_cell(effective_row, &tgt_col)?;
+            row.push(cell);
+        }
+
+        self.src_idx += 1;
+        self.consumed_row_cnt += 1;
+
+        Ok(Some(row))
+    }
 }
 
 /// Convert `snake_case` to `camelCase`
@@ -653,7 +702,7 @@ fn to_camel_case(s: &str) -> String {
 
 impl Guest for OpenApiFdw {
     fn host_version_requirement() -> String {
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -782,6 +831,20 @@ impl Guest for OpenApiFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -825,50 +888,9 @@ impl Guest for OpenApiFdw {
         Ok(())
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // Check if we need to fetch more data
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-
-            // No more pages to fetch
-            if this.next_cursor.is_none() && this.next_url.is_none() {
-                return Ok(None);
-            }
-
-            // Check if limit is satisfied - stop pagination early
-            if let Some(limit) = this.src_limit {
-                if this.consumed_row_cnt >= limit {
-                    return Ok(None);
-                }
-            }
-
-            // Fetch next page
-            this.make_request(ctx)?;
-
-            // If still no data after fetch, we're done
-            if this.src_rows.is_empty() {
-                return Ok(None);
-            }
-        }
-
-        // Convert current row (apply object_path if set, e.g., "/properties" for GeoJSON)
-        let src_row = &this.src_rows[this.src_idx];
-        let effective_row = this
-            .object_path
-            .as_ref()
-            .map_or(src_row, |path| src_row.pointer(path).unwrap_or(src_row));
-        for tgt_col in ctx.get_columns() {
-            let cell = this.json_to_cell(effective_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-
-        this.src_idx += 1;
-        this.consumed_row_cnt += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -890,10 +912,26 @@ impl Guest for OpenApiFdw {
         Err("OpenAPI FDW is read-only".to_string())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Err("OpenAPI FDW is read-only".to_string())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Err("OpenAPI FDW is read-only".to_string())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Err("OpenAPI FDW is read-only".to_string())
     }
@@ -906,6 +944,14 @@ impl Guest for OpenApiFdw {
         Err("OpenAPI FDW is read-only".to_string())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -922,21 +968,12 @@ impl Guest for OpenApiFdw {
             .as_ref()
             .ok_or("No OpenAPI spec available. Set spec_url in server options.")?;
 
-        // Determine filter based on import statement
-        let (filter, exclude) = match stmt.list_type {
-            ImportSchemaType::All => (None, false),
-            ImportSchemaType::LimitTo => (Some(stmt.table_list.as_slice()), false),
-            ImportSchemaType::Except => (Some(stmt.table_list.as_slice()), true),
-        };
-
-        let tables =
-            generate_all_tables(spec, &stmt.server_name, filter, exclude, this.include_attrs);
-
-        Ok(tables)
+        let tables = build_all_tables(spec, this.include_attrs);
+        Ok(build_ddl(&stmt, tables))
     }
 }
 
-bindings::export!(OpenApiFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(OpenApiFdw with_types_in wrappers_wasm_sdk::bindings);
 
 #[cfg(test)]
 mod tests {
@@ -1,7 +1,9 @@
 //! Schema generation and type mapping for `OpenAPI` FDW
 //!
 //! This module handles mapping `OpenAPI` types to `PostgreSQL` types
-//! and generating CREATE FOREIGN TABLE statements.
+//! and building foreign table definitions.
+
+use wrappers_wasm_sdk::{ColumnDef, ForeignTableDef};
 
 use crate::spec::{EndpointInfo, OpenApiSpec, Schema};
 
@@ -37,14 +39,6 @@ pub fn openapi_to_pg_type(schema: &Schema, spec: &OpenApiSpec) -> &'static str {
     }
 }
 
-/// Column definition for a foreign table
-#[derive(Debug)]
-pub struct ColumnDef {
-    pub name: String,
-    pub pg_type: &'static str,
-    pub nullable: bool,
-}
-
 /// Extract column definitions from an `OpenAPI` response schema
 pub fn extract_columns(schema: &Schema, spec: &OpenApiSpec, include_attrs: bool) -> Vec<ColumnDef> {
     let mut columns = Vec::new();
@@ -66,11 +60,8 @@ pub fn extract_columns(schema: &Schema, spec: &OpenApiSpec, include_attrs: bool)
                 let pg_type = openapi_to_pg_type(prop_schema, spec);
                 let nullable = !schema.required.contains(name) || prop_schema.nullable;
 
-                columns.push(ColumnDef {
-                    name: sanitize_column_name(name),
-                    pg_type,
-                    nullable,
-                });
+                columns
+                    .push(ColumnDef::new(sanitize_column_name(name), pg_type).not_null(!nullable));
             }
         }
     }
@@ -84,11 +75,7 @@ pub fn extract_columns(schema: &Schema, spec: &OpenApiSpec, include_attrs: bool)
 
     // Add an 'attrs' column for the full JSON response, unless disabled or already exists
     if include_attrs && !columns.iter().any(|c| c.name == "attrs") {
-        columns.push(ColumnDef {
-            name: "attrs".to_string(),
-            pg_type: "jsonb",
-            nullable: true,
-        });
+        columns.push(ColumnDef::new("attrs", "jsonb"));
     }
 
     columns
@@ -117,113 +104,52 @@ fn sanitize_column_name(name: &str) -> String {
     result
 }
 
-/// Quote a PostgreSQL identifier (table name, column name, etc.)
-/// Doubles any internal double quotes and wraps in double quotes.
-fn quote_identifier(name: &str) -> String {
-    format!("\"{}\"", name.replace('"', "\"\""))
-}
-
-/// Generate a CREATE FOREIGN TABLE statement for an endpoint
-pub fn generate_foreign_table(
+/// Build the foreign table definition for an endpoint
+pub fn build_foreign_table(
     endpoint: &EndpointInfo,
     spec: &OpenApiSpec,
-    server_name: &str,
     include_attrs: bool,
-) -> String {
-    let table_name = endpoint.table_name();
-
+) -> ForeignTableDef {
     let columns = endpoint.response_schema.as_ref().map_or_else(
         || {
             // Default columns if no schema is available
-            let mut cols = vec![ColumnDef {
-                name: "id".to_string(),
-                pg_type: "text",
-                nullable: false,
-            }];
+            let mut cols = vec![ColumnDef::new("id", "text").not_null(true)];
             if include_attrs {
-                cols.push(ColumnDef {
-                    name: "attrs".to_string(),
-                    pg_type: "jsonb",
-                    nullable: true,
-                });
+                cols.push(ColumnDef::new("attrs", "jsonb"));
             }
             cols
         },
         |schema| extract_columns(schema, spec, include_attrs),
     );
 
-    let column_defs: Vec<String> = columns
-        .iter()
-        .map(|col| {
-            let not_null = if col.nullable { "" } else { " NOT NULL" };
-            format!(
-                "    {} {}{}",
-                quote_identifier(&col.name),
-                col.pg_type,
-                not_null
-            )
-        })
-        .collect();
-
     // Determine rowid_column:
     //  - Prefer an explicit 'id' column if available
     //  - Otherwise, prefer the first non-'attrs' column with a non-jsonb type
     //  - If no suitable column exists, omit rowid_column entirely
-    let rowid_col: Option<&str> = columns
+    let rowid_col: Option<String> = columns
         .iter()
         .find(|c| c.name == "id")
         .or_else(|| {
             columns
                 .iter()
-                .find(|c| c.name != "attrs" && c.pg_type != "jsonb")
+                .find(|c| c.name != "attrs" && c.type_name != "jsonb")
         })
-        .map(|c| c.name.as_str());
-
-    // Escape single quotes in option values for SQL
-    let escaped_endpoint = endpoint.path.replace('\'', "''");
-
-    let options = if let Some(rowid) = rowid_col {
-        let escaped_rowid = rowid.replace('\'', "''");
-        format!("    endpoint '{escaped_endpoint}',\n    rowid_column '{escaped_rowid}'")
-    } else {
-        format!("    endpoint '{escaped_endpoint}'")
-    };
+        .map(|c| c.name.clone());
 
-    format!(
-        r"CREATE FOREIGN TABLE IF NOT EXISTS {} (
-{}
-)
-SERVER {} OPTIONS (
-{}
-)",
-        quote_identifier(&table_name),
-        column_defs.join(",\n"),
-        quote_identifier(server_name),
-        options
-    )
+    let mut table = ForeignTableDef::new(endpoint.table_name())
+        .columns(columns)
+        .option("endpoint", &endpoint.path);
+    if let Some(rowid) = rowid_col {
+        table = table.option("rowid_column", rowid);
+    }
+    table
 }
 
-/// Generate CREATE FOREIGN TABLE statements for all endpoints in a spec
-pub fn generate_all_tables(
-    spec: &OpenApiSpec,
-    server_name: &str,
-    filter: Option<&[String]>,
-    exclude: bool,
-    include_attrs: bool,
-) -> Vec<String> {
-    let endpoints = spec.get_endpoints();
-
-    endpoints
+/// Build foreign table definitions for all endpoints in a spec
+pub fn build_all_tables(spec: &OpenApiSpec, include_attrs: bool) -> Vec<ForeignTableDef> {
+    spec.get_endpoints()
         .iter()
-        .filter(|e| {
-            let table_name = e.table_name();
-            match filter {
-                None => true,
-                Some(list) if exclude => !list.iter().any(|n| n == &table_name),
-                Some(list) => list.iter().any(|n| n == &table_name),
-            }
-        })
-        .map(|e| generate_foreign_table(e, spec, server_name, include_attrs))
+        .map(|e| build_foreign_table(e, spec, include_attrs))
         .collect()
 }
 
@@ -270,4 +196,47 @@ mod tests {
         };
         assert_eq!(openapi_to_pg_type(&int_schema, &spec), "integer");
     }
+
+    #[test]
+    fn test_build_foreign_table() {
+        let spec = OpenApiSpec::from_str(
+            r#"{
+            "openapi": "3.0.0",
+            "info": {"title": "Test"},
+            "paths": {
+                "/users": {
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "array",
+                                            "items": {
+                                                "type": "object",
+                                                "required": ["id"],
+                                                "properties": {
+                                                    "id": {"type": "string"},
+                                                    "createdAt": {"type": "string", "format": "date-time"}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }"#,
+        )
+        .unwrap();
+
+        let tables = build_all_tables(&spec, true);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(
+            tables[0].to_sql("api"),
+            r#"create foreign table if not exists "users" ("id" text not null, "created_at" timestamptz, "attrs" jsonb) server "api" options ("endpoint" '/users', "rowid_column" 'id')"#
+        );
+    }
 }
@@ -1,17 +1,19 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::{Map as JsonMap, Value as JsonValue, json};
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
 
 #[derive(Debug, Default)]
@@ -25,19 +27,16 @@ struct PaddleFdw {
     rowid_col: String,
 }
 
-static mut INSTANCE: *mut PaddleFdw = std::ptr::null_mut::<PaddleFdw>();
+static INSTANCE: Instance<PaddleFdw> = Instance::new();
 static FDW_NAME: &str = "PaddleFdw";
 
 impl PaddleFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // get object list maximum page size
@@ -90,10 +89,11 @@ impl PaddleFdw {
             method: http::Method::Get,
             url,
             headers: self.headers.clone(),
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         };
         let resp = http::get(&req)?;
-        let resp_json: JsonValue = serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+        let resp_json: JsonValue = serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
         // if the 404 is caused by no object found, we shouldn't take it as an error
         if resp.status_code == 404 && resp_json.pointer("/error/code") == Some(&json!("not_found"))
@@ -104,7 +104,8 @@ impl PaddleFdw {
             return Ok(());
         }
 
-        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+        http::error_for_status(&resp)
+            .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
         // save source rows
         self.src_rows = resp_json
@@ -167,7 +168,7 @@ impl PaddleFdw {
             TypeOid::I32 => src.as_i64().map(|v| Cell::I32(v as i32)),
             TypeOid::F64 => src.as_f64().map(Cell::F64),
             TypeOid::I64 => src.as_i64().map(Cell::I64),
-            TypeOid::Numeric => src.as_f64().map(Cell::Numeric),
+            TypeOid::Numeric => src.as_number().map(|v| Cell::Numeric(v.to_string())),
             TypeOid::String => src.as_str().map(|v| Cell::String(v.to_owned())),
             TypeOid::Date => {
                 if let Some(s) = src.as_str() {
@@ -205,10 +206,10 @@ impl PaddleFdw {
     }
 
     // convert a row to JSON string, which is used as request body for row update
-    fn row_to_body(&self, row: &Row) -> Result<String, FdwError> {
+    fn row_to_body(&self, cols: &[String], cells: &[Option<Cell>]) -> Result<String, FdwError> {
         let mut map = JsonMap::new();
 
-        for (col_name, cell) in row.cols().iter().zip(row.cells().iter()) {
+        for (col_name, cell) in cols.iter().zip(cells.iter()) {
             if let Some(cell) = cell {
                 let value = match cell {
                     Cell::Bool(v) => JsonValue::Bool(*v),
@@ -230,12 +231,64 @@ impl PaddleFdw {
 
         Ok(JsonValue::Object(map).to_string())
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all local rows are consumed
+        if self.src_idx >= self.src_rows.len() {
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+
+            // release consumed rows, so they are not counted again when the
+            // host asks for another batch after the last one
+            self.src_rows.clear();
+            self.src_idx = 0;
+
+            // if no more pages, stop the iter scan
+            if self.url.is_none() {
+                return Ok(None);
+            }
+
+            // otherwise, make another call to get next page data
+            self.make_request(ctx)?;
+        }
+
+        // convert Paddle row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
+
+    // create an object from a row of the given columns
+    fn insert_row(&mut self, cols: &[String], cells: &[Option<Cell>]) -> FdwResult {
+        let url = format!("{}/{}", self.base_url, self.object);
+        let body = self.row_to_body(cols, cells)?;
+        let req = http::Request {
+            method: http::Method::Post,
+            url,
+            headers: self.headers.clone(),
+            body: body.into_bytes(),
+            timeout_ms: None,
+        };
+        let resp = http::post(&req)?;
+        http::error_for_status(&resp)
+            .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
+        Ok(())
+    }
 }
 
 impl Guest for PaddleFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -268,6 +321,20 @@ impl Guest for PaddleFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -279,33 +346,9 @@ impl Guest for PaddleFdw {
         Ok(())
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all local rows are consumed
-        if this.src_idx >= this.src_rows.len() {
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-
-            // if no more pages, stop the iter scan
-            if this.url.is_none() {
-                return Ok(None);
-            }
-
-            // otherwise, make another call to get next page data
-            this.make_request(ctx)?;
-        }
-
-        // convert Paddle row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -328,20 +371,27 @@ impl Guest for PaddleFdw {
         Ok(())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, row: &Row) -> FdwResult {
         let this = Self::this_mut();
-        let url = format!("{}/{}", this.base_url, this.object);
-        let body = this.row_to_body(row)?;
-        let req = http::Request {
-            method: http::Method::Post,
-            url,
-            headers: this.headers.clone(),
-            body,
-        };
-        let resp = http::post(&req)?;
-        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
-        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
-        Ok(())
+        this.insert_row(&row.cols(), &row.cells())
+    }
+
+    fn insert_batch(
+        _ctx: &Context,
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        let this = Self::this_mut();
+        rows.iter()
+            .try_for_each(|row| this.insert_row(&columns, row))
     }
 
     fn update(_ctx: &Context, rowid: Cell, row: &Row) -> FdwResult {
@@ -351,15 +401,17 @@ impl Guest for PaddleFdw {
             _ => return Err("invalid rowid column value".to_string()),
         };
         let url = format!("{}/{}/{}", this.base_url, this.object, id);
-        let body = this.row_to_body(row)?;
+        let body = this.row_to_body(&row.cols(), &row.cells())?;
         let req = http::Request {
             method: http::Method::Patch,
             url,
             headers: this.headers.clone(),
-            body,
+            body: body.into_bytes(),
+            timeout_ms: None,
         };
         let resp = http::patch(&req)?;
-        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+        http::error_for_status(&resp)
+            .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
         stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
         Ok(())
     }
@@ -372,6 +424,14 @@ impl Guest for PaddleFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -430,4 +490,4 @@ impl Guest for PaddleFdw {
     }
 }
 
-bindings::export!(PaddleFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(PaddleFdw with_types_in wrappers_wasm_sdk::bindings);
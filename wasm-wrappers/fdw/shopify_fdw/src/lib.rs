@@ -1,20 +1,22 @@
-#[allow(warnings)]
-mod bindings;
 mod field_maps;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid, Value,
+use field_maps::get_field_map;
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
-use field_maps::get_field_map;
 
 #[derive(Debug, Default)]
 struct ShopifyFdw {
@@ -26,7 +28,7 @@ struct ShopifyFdw {
     src_cursor: Option<String>,
 }
 
-static mut INSTANCE: *mut ShopifyFdw = std::ptr::null_mut::<ShopifyFdw>();
+static INSTANCE: Instance<ShopifyFdw> = Instance::new();
 static FDW_NAME: &str = "ShopifyFdw";
 
 // max number of rows returned per request
@@ -34,14 +36,11 @@ static BATCH_SIZE: usize = 250;
 
 impl ShopifyFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     fn object_singular(&self) -> &str {
@@ -84,7 +83,7 @@ impl ShopifyFdw {
                 .as_i64()
                 .or_else(|| src.as_str().map(|v| v.parse::<i64>().unwrap_or_default()))
                 .map(Cell::I64),
-            TypeOid::Numeric => src.as_f64().map(Cell::Numeric),
+            TypeOid::Numeric => src.as_number().map(|v| Cell::Numeric(v.to_string())),
             TypeOid::String => src.as_str().map(|v| Cell::String(v.to_owned())),
             TypeOid::Date => {
                 if let Some(s) = src.as_str() {
@@ -247,7 +246,8 @@ impl ShopifyFdw {
             method: http::Method::Post,
             url: self.base_url.clone(),
             headers: self.headers.clone(),
-            body,
+            body: body.into_bytes(),
+            timeout_ms: None,
         })
     }
 
@@ -259,10 +259,11 @@ impl ShopifyFdw {
         // make request to remote endpoint
         let req = self.create_request(ctx)?;
         let resp = http::post(&req)?;
-        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+        http::error_for_status(&resp)
+            .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
         // transform response to json
-        let resp_json: JsonValue = serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+        let resp_json: JsonValue = serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
         // return when there are error messages in response
         if let Some(msg) = resp_json
@@ -304,12 +305,43 @@ impl ShopifyFdw {
 
         Ok(())
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all rows in local batch buffer are consumed
+        while self.src_idx >= self.src_rows.len() {
+            let consumed_cnt = self.src_rows.len();
+
+            // local batch buffer isn't fully filled or no next page cursor,
+            // means no more source records on remote, stop the iteration scan
+            if consumed_cnt < BATCH_SIZE || self.src_cursor.is_none() {
+                return Ok(None);
+            }
+
+            // otherwise, make a new request for the next page
+            self.fetch_source_data(ctx)?;
+
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, consumed_cnt as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, consumed_cnt as i64);
+        }
+
+        // convert source row to Postgres row
+        let src_row = &self.src_rows[self.src_idx];
+        let mut row = Vec::new();
+        for tgt_col in ctx.get_columns() {
+            let cell = self.src_field_to_cell(src_row, &tgt_col)?;
+            row.push(cell);
+        }
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
 }
 
 impl Guest for ShopifyFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -346,6 +378,20 @@ impl Guest for ShopifyFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -353,35 +399,9 @@ impl Guest for ShopifyFdw {
         this.fetch_source_data(ctx)
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all rows in local batch buffer are consumed
-        while this.src_idx >= this.src_rows.len() {
-            let consumed_cnt = this.src_rows.len();
-
-            // local batch buffer isn't fully filled or no next page cursor,
-            // means no more source records on remote, stop the iteration scan
-            if consumed_cnt < BATCH_SIZE || this.src_cursor.is_none() {
-                return Ok(None);
-            }
-
-            // otherwise, make a new request for the next page
-            this.fetch_source_data(ctx)?;
-
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, consumed_cnt as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, consumed_cnt as i64);
-        }
-
-        // convert source row to Postgres row
-        let src_row = &this.src_rows[this.src_idx];
-        for tgt_col in ctx.get_columns() {
-            let cell = this.src_field_to_cell(src_row, &tgt_col)?;
-            row.push(cell.as_ref());
-        }
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -399,10 +419,26 @@ impl Guest for ShopifyFdw {
         Err("modify on foreign table is not supported".to_owned())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Ok(())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Ok(())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Ok(())
     }
@@ -415,6 +451,14 @@ impl Guest for ShopifyFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -464,4 +508,4 @@ impl Guest for ShopifyFdw {
     }
 }
 
-bindings::export!(ShopifyFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(ShopifyFdw with_types_in wrappers_wasm_sdk::bindings);
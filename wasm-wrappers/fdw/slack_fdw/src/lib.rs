@@ -13,8 +13,6 @@
 //! - files: Query files shared in the workspace
 //! - team-info: Query information about the workspace
 
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
 // Slack FDW implementation modules
@@ -22,16 +20,20 @@ use serde_json::Value as JsonValue;
 mod api;
 pub mod models;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, stats, time,
-        types::{
-            Cell, Context, FdwError, FdwResult, ImportForeignSchemaStmt, Limit, OptionsType, Row,
-            Sort, Value,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, stats, time,
+            types::{
+                Cell, Context, FdwError, FdwResult, ImportForeignSchemaStmt, Limit,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, Sort, Value,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
 
 // Import all model types needed for the FDW
@@ -89,7 +91,7 @@ struct SlackFdw {
 }
 
 /// Global instance of the FDW as required by the PostgreSQL FDW API
-static mut INSTANCE: *mut SlackFdw = std::ptr::null_mut::<SlackFdw>();
+static INSTANCE: Instance<SlackFdw> = Instance::new();
 
 /// Name of the FDW for logging and metrics
 static FDW_NAME: &str = "SlackFdw";
@@ -99,78 +101,79 @@ static BATCH_SIZE: u32 = 100;
 
 impl SlackFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // Map Slack UserGroup to PostgreSQL Row
-    fn usergroup_to_row(&self, usergroup: &UserGroup, row: &Row) -> Result<(), FdwError> {
+    fn usergroup_to_row(
+        &self,
+        usergroup: &UserGroup,
+        row: &mut Vec<Option<Cell>>,
+    ) -> Result<(), FdwError> {
         // Basic information
-        row.push(Some(&Cell::String(usergroup.id.clone())));
-        row.push(Some(&Cell::String(usergroup.team_id.clone())));
-        row.push(Some(&Cell::String(usergroup.name.clone())));
-        row.push(Some(&Cell::String(usergroup.handle.clone())));
+        row.push(Some(Cell::String(usergroup.id.clone())));
+        row.push(Some(Cell::String(usergroup.team_id.clone())));
+        row.push(Some(Cell::String(usergroup.name.clone())));
+        row.push(Some(Cell::String(usergroup.handle.clone())));
 
         // Optional fields
         if let Some(description) = &usergroup.description {
-            row.push(Some(&Cell::String(description.clone())));
+            row.push(Some(Cell::String(description.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(is_external) = usergroup.is_external {
-            row.push(Some(&Cell::Bool(is_external)));
+            row.push(Some(Cell::Bool(is_external)));
         } else {
             row.push(None);
         }
 
         // Timestamps
-        row.push(Some(&Cell::I64(usergroup.date_create)));
-        row.push(Some(&Cell::I64(usergroup.date_update)));
+        row.push(Some(Cell::I64(usergroup.date_create)));
+        row.push(Some(Cell::I64(usergroup.date_update)));
 
         if let Some(date_delete) = usergroup.date_delete {
-            row.push(Some(&Cell::I64(date_delete)));
+            row.push(Some(Cell::I64(date_delete)));
         } else {
             row.push(None);
         }
 
         // Auto type
         if let Some(auto_type) = &usergroup.auto_type {
-            row.push(Some(&Cell::String(auto_type.clone())));
+            row.push(Some(Cell::String(auto_type.clone())));
         } else {
             row.push(None);
         }
 
         // User information
-        row.push(Some(&Cell::String(usergroup.created_by.clone())));
+        row.push(Some(Cell::String(usergroup.created_by.clone())));
 
         if let Some(updated_by) = &usergroup.updated_by {
-            row.push(Some(&Cell::String(updated_by.clone())));
+            row.push(Some(Cell::String(updated_by.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(deleted_by) = &usergroup.deleted_by {
-            row.push(Some(&Cell::String(deleted_by.clone())));
+            row.push(Some(Cell::String(deleted_by.clone())));
         } else {
             row.push(None);
         }
 
         // Counts
         if let Some(user_count) = usergroup.user_count {
-            row.push(Some(&Cell::I32(user_count)));
+            row.push(Some(Cell::I32(user_count)));
         } else {
             row.push(None);
         }
 
         if let Some(channel_count) = usergroup.channel_count {
-            row.push(Some(&Cell::I32(channel_count)));
+            row.push(Some(Cell::I32(channel_count)));
         } else {
             row.push(None);
         }
@@ -182,40 +185,44 @@ impl SlackFdw {
     fn usergroup_membership_to_row(
         &self,
         membership: &UserGroupMembership,
-        row: &Row,
+        row: &mut Vec<Option<Cell>>,
     ) -> Result<(), FdwError> {
-        row.push(Some(&Cell::String(membership.usergroup_id.clone())));
-        row.push(Some(&Cell::String(membership.usergroup_name.clone())));
-        row.push(Some(&Cell::String(membership.usergroup_handle.clone())));
-        row.push(Some(&Cell::String(membership.user_id.clone())));
+        row.push(Some(Cell::String(membership.usergroup_id.clone())));
+        row.push(Some(Cell::String(membership.usergroup_name.clone())));
+        row.push(Some(Cell::String(membership.usergroup_handle.clone())));
+        row.push(Some(Cell::String(membership.user_id.clone())));
         Ok(())
     }
 
     // Map Slack Message to PostgreSQL Row
-    fn message_to_row(&self, message: &Message, row: &Row) -> Result<(), FdwError> {
+    fn message_to_row(
+        &self,
+        message: &Message,
+        row: &mut Vec<Option<Cell>>,
+    ) -> Result<(), FdwError> {
         // Get channel_id
         let channel_id = self.current_channel_id.clone().unwrap_or_default();
 
         // Basic information
-        row.push(Some(&Cell::String(message.ts.clone())));
+        row.push(Some(Cell::String(message.ts.clone())));
 
         if let Some(user_id) = &message.user {
-            row.push(Some(&Cell::String(user_id.clone())));
+            row.push(Some(Cell::String(user_id.clone())));
         } else {
             row.push(None);
         }
 
-        row.push(Some(&Cell::String(channel_id)));
-        row.push(Some(&Cell::String(message.text.clone())));
+        row.push(Some(Cell::String(channel_id)));
+        row.push(Some(Cell::String(message.text.clone())));
 
         if let Some(thread_ts) = &message.thread_ts {
-            row.push(Some(&Cell::String(thread_ts.clone())));
+            row.push(Some(Cell::String(thread_ts.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(reply_count) = message.reply_count {
-            row.push(Some(&Cell::I32(reply_count)));
+            row.push(Some(Cell::I32(reply_count)));
         } else {
             row.push(None);
         }
@@ -224,245 +231,253 @@ impl SlackFdw {
     }
 
     // Map Slack Channel to PostgreSQL Row
-    fn channel_to_row(&self, channel: &Channel, row: &Row) -> Result<(), FdwError> {
-        row.push(Some(&Cell::String(channel.id.clone())));
-        row.push(Some(&Cell::String(channel.name.clone())));
-        row.push(Some(&Cell::Bool(channel.is_private)));
+    fn channel_to_row(
+        &self,
+        channel: &Channel,
+        row: &mut Vec<Option<Cell>>,
+    ) -> Result<(), FdwError> {
+        row.push(Some(Cell::String(channel.id.clone())));
+        row.push(Some(Cell::String(channel.name.clone())));
+        row.push(Some(Cell::Bool(channel.is_private)));
 
         // Convert unix timestamp to PostgreSQL timestamp
-        row.push(Some(&Cell::I64(channel.created)));
+        row.push(Some(Cell::I64(channel.created)));
 
-        row.push(Some(&Cell::String(channel.creator.clone())));
+        row.push(Some(Cell::String(channel.creator.clone())));
 
         Ok(())
     }
 
     // Map Slack File to PostgreSQL Row
-    fn file_to_row(&self, file: &File, row: &Row) -> Result<(), FdwError> {
-        row.push(Some(&Cell::String(file.id.clone())));
-        row.push(Some(&Cell::String(file.name.clone())));
-        row.push(Some(&Cell::String(file.title.clone())));
-        row.push(Some(&Cell::String(file.mimetype.clone())));
-        row.push(Some(&Cell::I64(file.size)));
+    fn file_to_row(&self, file: &File, row: &mut Vec<Option<Cell>>) -> Result<(), FdwError> {
+        row.push(Some(Cell::String(file.id.clone())));
+        row.push(Some(Cell::String(file.name.clone())));
+        row.push(Some(Cell::String(file.title.clone())));
+        row.push(Some(Cell::String(file.mimetype.clone())));
+        row.push(Some(Cell::I64(file.size)));
 
         if let Some(url) = &file.url_private {
-            row.push(Some(&Cell::String(url.clone())));
+            row.push(Some(Cell::String(url.clone())));
         } else {
             row.push(None);
         }
 
-        row.push(Some(&Cell::String(file.user.clone())));
+        row.push(Some(Cell::String(file.user.clone())));
 
         // Convert unix timestamp to PostgreSQL timestamp
-        row.push(Some(&Cell::I64(file.created)));
+        row.push(Some(Cell::I64(file.created)));
 
         Ok(())
     }
 
     // Map Slack TeamInfo to PostgreSQL Row
-    fn team_info_to_row(&self, team_info: &TeamInfo, row: &Row) -> Result<(), FdwError> {
-        row.push(Some(&Cell::String(team_info.id.clone())));
-        row.push(Some(&Cell::String(team_info.name.clone())));
-        row.push(Some(&Cell::String(team_info.domain.clone())));
-        row.push(Some(&Cell::String(team_info.email_domain.clone())));
+    fn team_info_to_row(
+        &self,
+        team_info: &TeamInfo,
+        row: &mut Vec<Option<Cell>>,
+    ) -> Result<(), FdwError> {
+        row.push(Some(Cell::String(team_info.id.clone())));
+        row.push(Some(Cell::String(team_info.name.clone())));
+        row.push(Some(Cell::String(team_info.domain.clone())));
+        row.push(Some(Cell::String(team_info.email_domain.clone())));
 
         Ok(())
     }
 
     // Map Slack User to PostgreSQL Row
-    fn user_to_row(&self, user: &User, row: &Row) -> Result<(), FdwError> {
+    fn user_to_row(&self, user: &User, row: &mut Vec<Option<Cell>>) -> Result<(), FdwError> {
         // Basic information
-        row.push(Some(&Cell::String(user.id.clone()))); // id
-        row.push(Some(&Cell::String(user.name.clone()))); // name
+        row.push(Some(Cell::String(user.id.clone()))); // id
+        row.push(Some(Cell::String(user.name.clone()))); // name
 
         // Name and profile fields
         if let Some(real_name) = &user.real_name {
-            row.push(Some(&Cell::String(real_name.clone())));
+            row.push(Some(Cell::String(real_name.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(display_name) = &user.profile.display_name {
-            row.push(Some(&Cell::String(display_name.clone())));
+            row.push(Some(Cell::String(display_name.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(display_name_normalized) = &user.profile.display_name_normalized {
-            row.push(Some(&Cell::String(display_name_normalized.clone())));
+            row.push(Some(Cell::String(display_name_normalized.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(real_name_normalized) = &user.profile.real_name_normalized {
-            row.push(Some(&Cell::String(real_name_normalized.clone())));
+            row.push(Some(Cell::String(real_name_normalized.clone())));
         } else {
             row.push(None);
         }
 
         // Contact information
         if let Some(email) = &user.profile.email {
-            row.push(Some(&Cell::String(email.clone())));
+            row.push(Some(Cell::String(email.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(phone) = &user.profile.phone {
-            row.push(Some(&Cell::String(phone.clone())));
+            row.push(Some(Cell::String(phone.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(skype) = &user.profile.skype {
-            row.push(Some(&Cell::String(skype.clone())));
+            row.push(Some(Cell::String(skype.clone())));
         } else {
             row.push(None);
         }
 
         // Role information
         if let Some(is_admin) = user.is_admin {
-            row.push(Some(&Cell::Bool(is_admin)));
+            row.push(Some(Cell::Bool(is_admin)));
         } else {
             row.push(None);
         }
 
         if let Some(is_owner) = user.is_owner {
-            row.push(Some(&Cell::Bool(is_owner)));
+            row.push(Some(Cell::Bool(is_owner)));
         } else {
             row.push(None);
         }
 
         if let Some(is_primary_owner) = user.is_primary_owner {
-            row.push(Some(&Cell::Bool(is_primary_owner)));
+            row.push(Some(Cell::Bool(is_primary_owner)));
         } else {
             row.push(None);
         }
 
-        row.push(Some(&Cell::Bool(user.is_bot))); // is_bot
+        row.push(Some(Cell::Bool(user.is_bot))); // is_bot
 
         if let Some(is_app_user) = user.is_app_user {
-            row.push(Some(&Cell::Bool(is_app_user)));
+            row.push(Some(Cell::Bool(is_app_user)));
         } else {
             row.push(None);
         }
 
         if let Some(is_restricted) = user.is_restricted {
-            row.push(Some(&Cell::Bool(is_restricted)));
+            row.push(Some(Cell::Bool(is_restricted)));
         } else {
             row.push(None);
         }
 
         if let Some(is_ultra_restricted) = user.is_ultra_restricted {
-            row.push(Some(&Cell::Bool(is_ultra_restricted)));
+            row.push(Some(Cell::Bool(is_ultra_restricted)));
         } else {
             row.push(None);
         }
 
-        row.push(Some(&Cell::Bool(user.deleted))); // deleted
+        row.push(Some(Cell::Bool(user.deleted))); // deleted
 
         // Status information
         if let Some(status_text) = &user.profile.status_text {
-            row.push(Some(&Cell::String(status_text.clone())));
+            row.push(Some(Cell::String(status_text.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(status_emoji) = &user.profile.status_emoji {
-            row.push(Some(&Cell::String(status_emoji.clone())));
+            row.push(Some(Cell::String(status_emoji.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(status_expiration) = user.profile.status_expiration {
-            row.push(Some(&Cell::I64(status_expiration)));
+            row.push(Some(Cell::I64(status_expiration)));
         } else {
             row.push(None);
         }
 
         if let Some(title) = &user.profile.title {
-            row.push(Some(&Cell::String(title.clone())));
+            row.push(Some(Cell::String(title.clone())));
         } else {
             row.push(None);
         }
 
         // Team information
         if let Some(team_id) = &user.team_id {
-            row.push(Some(&Cell::String(team_id.clone())));
+            row.push(Some(Cell::String(team_id.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(team) = &user.profile.team {
-            row.push(Some(&Cell::String(team.clone())));
+            row.push(Some(Cell::String(team.clone())));
         } else {
             row.push(None);
         }
 
         // Time zone information
         if let Some(tz) = &user.tz {
-            row.push(Some(&Cell::String(tz.clone())));
+            row.push(Some(Cell::String(tz.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(tz_label) = &user.tz_label {
-            row.push(Some(&Cell::String(tz_label.clone())));
+            row.push(Some(Cell::String(tz_label.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(tz_offset) = user.tz_offset {
-            row.push(Some(&Cell::I32(tz_offset)));
+            row.push(Some(Cell::I32(tz_offset)));
         } else {
             row.push(None);
         }
 
         if let Some(locale) = &user.locale {
-            row.push(Some(&Cell::String(locale.clone())));
+            row.push(Some(Cell::String(locale.clone())));
         } else {
             row.push(None);
         }
 
         // Avatar/image URLs
         if let Some(image_24) = &user.profile.image_24 {
-            row.push(Some(&Cell::String(image_24.clone())));
+            row.push(Some(Cell::String(image_24.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(image_48) = &user.profile.image_48 {
-            row.push(Some(&Cell::String(image_48.clone())));
+            row.push(Some(Cell::String(image_48.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(image_72) = &user.profile.image_72 {
-            row.push(Some(&Cell::String(image_72.clone())));
+            row.push(Some(Cell::String(image_72.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(image_192) = &user.profile.image_192 {
-            row.push(Some(&Cell::String(image_192.clone())));
+            row.push(Some(Cell::String(image_192.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(image_512) = &user.profile.image_512 {
-            row.push(Some(&Cell::String(image_512.clone())));
+            row.push(Some(Cell::String(image_512.clone())));
         } else {
             row.push(None);
         }
 
         // Miscellaneous
         if let Some(color) = &user.color {
-            row.push(Some(&Cell::String(color.clone())));
+            row.push(Some(Cell::String(color.clone())));
         } else {
             row.push(None);
         }
 
         if let Some(updated) = user.updated {
-            row.push(Some(&Cell::I64(updated)));
+            row.push(Some(Cell::I64(updated)));
         } else {
             row.push(None);
         }
@@ -492,7 +507,8 @@ impl SlackFdw {
             method: http::Method::Get,
             url,
             headers: self.headers.clone(),
-            body: String::default(),
+            body: Vec::new(),
+            timeout_ms: None,
         })
     }
 
@@ -514,11 +530,12 @@ impl SlackFdw {
             }
 
             // Check for errors
-            http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, resp.body))?;
+            http::error_for_status(&resp)
+                .map_err(|err| format!("{}: {}", err, String::from_utf8_lossy(&resp.body)))?;
 
             // Transform response to JSON
             let resp_json: JsonValue =
-                serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
             stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
 
@@ -1079,113 +1096,29 @@ impl SlackFdw {
             Err("Failed to parse users from response".to_string())
         }
     }
-}
-
-impl Guest for SlackFdw {
-    fn host_version_requirement() -> String {
-        // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
-    }
-
-    fn init(ctx: &Context) -> FdwResult {
-        Self::init();
-        let this = Self::this_mut();
-
-        // Get foreign server options
-        let opts = ctx.get_options(&OptionsType::Server);
-
-        // Get API token from options or vault
-        let api_token = match opts.get("api_token") {
-            Some(token) => token,
-            None => {
-                let token_id = opts.require("api_token_id")?;
-                utils::get_vault_secret(&token_id).unwrap_or_default()
-            }
-        };
 
-        // Validate token format
-        if !api_token.starts_with("xoxp-") && !api_token.starts_with("xoxb-") {
-            return Err("Invalid api_token format. Expected an OAuth token starting with 'xoxp-' or 'xoxb-'".to_string());
-        }
-
-        // Get optional parameters
-        let workspace = opts.get("workspace");
-
-        // Set up authorization headers
-        this.headers
-            .push(("user-agent".to_owned(), "Wrappers Slack FDW".to_string()));
-        this.headers
-            .push(("content-type".to_owned(), "application/json".to_string()));
-        this.headers
-            .push(("authorization".to_owned(), format!("Bearer {api_token}")));
-
-        // Store options in the instance
-        this.api_token = api_token;
-        this.workspace = workspace;
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        let mut row = Vec::new();
 
-        stats::inc_stats(FDW_NAME, stats::Metric::CreateTimes, 1);
-
-        Ok(())
-    }
-
-    fn begin_scan(ctx: &Context) -> FdwResult {
-        let this = Self::this_mut();
-
-        // Get resource from table options
-        let opts = ctx.get_options(&OptionsType::Table);
-        let resource = opts.require("resource")?;
-
-        // Reset pagination state
-        this.resource = resource.clone();
-        this.has_more = false;
-        this.next_cursor = None;
-        this.result_index = 0;
-        this.current_channel_id = None;
-
-        // Store the sort and limit information for query pushdown
-        this.sorts = ctx.get_sorts();
-        this.limit = ctx.get_limit();
-
-        // Fetch the appropriate resource data
-        match resource.as_str() {
-            "users" => this.fetch_users(ctx),
-            "usergroups" => this.fetch_user_groups(ctx),
-            "usergroup_members" => this.fetch_user_group_memberships(ctx),
-            "messages" => this.fetch_messages(ctx),
-            "channels" => this.fetch_channels(ctx),
-            "files" => this.fetch_files(ctx),
-            "team-info" => this.fetch_team_info(ctx),
-            _ => Err(format!(
-                "Unsupported resource type: {resource}. Supported resources are 'users', 'usergroups', 'usergroup_members', 'messages', 'channels', 'files', and 'team-info'."
-            )),
-        }
-    }
-
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
-        let this = Self::this_mut();
-
-        match this.resource.as_str() {
+        match self.resource.as_str() {
             "users" => {
                 // If we've reached the end of our current batch of users
-                if this.result_index >= this.users.len() {
-                    // Record metrics
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.users.len() as i64);
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.users.len() as i64);
-
+                if self.result_index >= self.users.len() {
                     // If there's a next cursor and we don't have a limit or haven't reached it yet, fetch the next batch
-                    if this.next_cursor.is_some() {
+                    if self.next_cursor.is_some() {
                         // If we have a limit, check if we've already reached it
-                        if let Some(limit) = &this.limit {
-                            if this.users.len() >= limit.count() as usize {
+                        if let Some(limit) = &self.limit {
+                            if self.users.len() >= limit.count() as usize {
                                 // We've already met our limit, don't fetch more
                                 return Ok(None);
                             }
                         }
 
-                        this.fetch_users(ctx)?;
+                        self.fetch_users(ctx)?;
 
                         // If the new batch is empty, we're done
-                        if this.users.is_empty() {
+                        if self.users.is_empty() {
                             return Ok(None);
                         }
                     } else {
@@ -1195,79 +1128,53 @@ impl Guest for SlackFdw {
                 }
 
                 // Get the user from the current batch
-                let user = &this.users[this.result_index];
+                let user = &self.users[self.result_index];
 
                 // Convert user to row
-                this.user_to_row(user, row)?;
+                self.user_to_row(user, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
             "usergroups" => {
                 // If we've reached the end of our user groups
-                if this.result_index >= this.user_groups.len() {
-                    // Record metrics
-                    stats::inc_stats(
-                        FDW_NAME,
-                        stats::Metric::RowsIn,
-                        this.user_groups.len() as i64,
-                    );
-                    stats::inc_stats(
-                        FDW_NAME,
-                        stats::Metric::RowsOut,
-                        this.user_groups.len() as i64,
-                    );
+                if self.result_index >= self.user_groups.len() {
                     return Ok(None);
                 }
 
                 // Get the user group from the current position
-                let usergroup = &this.user_groups[this.result_index];
+                let usergroup = &self.user_groups[self.result_index];
 
                 // Convert user group to row
-                this.usergroup_to_row(usergroup, row)?;
+                self.usergroup_to_row(usergroup, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
             "usergroup_members" => {
                 // If we've reached the end of our user group memberships
-                if this.result_index >= this.user_group_memberships.len() {
-                    // Record metrics
-                    stats::inc_stats(
-                        FDW_NAME,
-                        stats::Metric::RowsIn,
-                        this.user_group_memberships.len() as i64,
-                    );
-                    stats::inc_stats(
-                        FDW_NAME,
-                        stats::Metric::RowsOut,
-                        this.user_group_memberships.len() as i64,
-                    );
+                if self.result_index >= self.user_group_memberships.len() {
                     return Ok(None);
                 }
 
                 // Get the membership from the current position
-                let membership = &this.user_group_memberships[this.result_index];
+                let membership = &self.user_group_memberships[self.result_index];
 
                 // Convert membership to row
-                this.usergroup_membership_to_row(membership, row)?;
+                self.usergroup_membership_to_row(membership, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
             "messages" => {
                 // If we've reached the end of our messages
-                if this.result_index >= this.messages.len() {
-                    // Record metrics
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.messages.len() as i64);
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.messages.len() as i64);
-
+                if self.result_index >= self.messages.len() {
                     // If there's more messages and we have a pagination cursor, fetch the next batch
-                    if this.has_more && this.next_cursor.is_some() {
-                        this.fetch_messages(ctx)?;
+                    if self.has_more && self.next_cursor.is_some() {
+                        self.fetch_messages(ctx)?;
 
                         // If the new batch is empty, we're done
-                        if this.messages.is_empty() {
+                        if self.messages.is_empty() {
                             return Ok(None);
                         }
                     } else {
@@ -1277,27 +1184,23 @@ impl Guest for SlackFdw {
                 }
 
                 // Get the message from the current batch
-                let message = &this.messages[this.result_index];
+                let message = &self.messages[self.result_index];
 
                 // Convert message to row
-                this.message_to_row(message, row)?;
+                self.message_to_row(message, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
             "channels" => {
                 // If we've reached the end of our channels
-                if this.result_index >= this.channels.len() {
-                    // Record metrics
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.channels.len() as i64);
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.channels.len() as i64);
-
+                if self.result_index >= self.channels.len() {
                     // If there's a next cursor, fetch the next batch
-                    if this.next_cursor.is_some() {
-                        this.fetch_channels(ctx)?;
+                    if self.next_cursor.is_some() {
+                        self.fetch_channels(ctx)?;
 
                         // If the new batch is empty, we're done
-                        if this.channels.is_empty() {
+                        if self.channels.is_empty() {
                             return Ok(None);
                         }
                     } else {
@@ -1307,49 +1210,153 @@ impl Guest for SlackFdw {
                 }
 
                 // Get the channel from the current batch
-                let channel = &this.channels[this.result_index];
+                let channel = &self.channels[self.result_index];
 
                 // Convert channel to row
-                this.channel_to_row(channel, row)?;
+                self.channel_to_row(channel, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
             "files" => {
                 // If we've reached the end of our files
-                if this.result_index >= this.files.len() {
-                    // Record metrics
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.files.len() as i64);
-                    stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.files.len() as i64);
+                if self.result_index >= self.files.len() {
                     return Ok(None);
                 }
 
                 // Get the file from the current batch
-                let file = &this.files[this.result_index];
+                let file = &self.files[self.result_index];
 
                 // Convert file to row
-                this.file_to_row(file, row)?;
+                self.file_to_row(file, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
             "team-info" => {
                 // Team info has only one row
-                if this.result_index > 0 || this.team_info.is_none() {
+                if self.result_index > 0 || self.team_info.is_none() {
                     return Ok(None);
                 }
 
                 // Get the team info
-                let team_info = this.team_info.as_ref().unwrap();
+                let team_info = self.team_info.as_ref().unwrap();
 
                 // Convert team info to row
-                this.team_info_to_row(team_info, row)?;
+                self.team_info_to_row(team_info, &mut row)?;
 
-                this.result_index += 1;
-                Ok(Some(0))
+                self.result_index += 1;
+                Ok(Some(row))
             }
-            _ => Err(format!("Unsupported resource type: {}", this.resource)),
+            _ => Err(format!("Unsupported resource type: {}", self.resource)),
+        }
+    }
+}
+
+impl Guest for SlackFdw {
+    fn host_version_requirement() -> String {
+        // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
+        "^0.1.8".to_string()
+    }
+
+    fn init(ctx: &Context) -> FdwResult {
+        Self::init();
+        let this = Self::this_mut();
+
+        // Get foreign server options
+        let opts = ctx.get_options(&OptionsType::Server);
+
+        // Get API token from options or vault
+        let api_token = match opts.get("api_token") {
+            Some(token) => token,
+            None => {
+                let token_id = opts.require("api_token_id")?;
+                utils::get_vault_secret(&token_id).unwrap_or_default()
+            }
+        };
+
+        // Validate token format
+        if !api_token.starts_with("xoxp-") && !api_token.starts_with("xoxb-") {
+            return Err("Invalid api_token format. Expected an OAuth token starting with 'xoxp-' or 'xoxb-'".to_string());
         }
+
+        // Get optional parameters
+        let workspace = opts.get("workspace");
+
+        // Set up authorization headers
+        this.headers
+            .push(("user-agent".to_owned(), "Wrappers Slack FDW".to_string()));
+        this.headers
+            .push(("content-type".to_owned(), "application/json".to_string()));
+        this.headers
+            .push(("authorization".to_owned(), format!("Bearer {api_token}")));
+
+        // Store options in the instance
+        this.api_token = api_token;
+        this.workspace = workspace;
+
+        stats::inc_stats(FDW_NAME, stats::Metric::CreateTimes, 1);
+
+        Ok(())
+    }
+
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
+    fn begin_scan(ctx: &Context) -> FdwResult {
+        let this = Self::this_mut();
+
+        // Get resource from table options
+        let opts = ctx.get_options(&OptionsType::Table);
+        let resource = opts.require("resource")?;
+
+        // Reset pagination state
+        this.resource = resource.clone();
+        this.has_more = false;
+        this.next_cursor = None;
+        this.result_index = 0;
+        this.current_channel_id = None;
+
+        // Store the sort and limit information for query pushdown
+        this.sorts = ctx.get_sorts();
+        this.limit = ctx.get_limit();
+
+        // Fetch the appropriate resource data
+        match resource.as_str() {
+            "users" => this.fetch_users(ctx),
+            "usergroups" => this.fetch_user_groups(ctx),
+            "usergroup_members" => this.fetch_user_group_memberships(ctx),
+            "messages" => this.fetch_messages(ctx),
+            "channels" => this.fetch_channels(ctx),
+            "files" => this.fetch_files(ctx),
+            "team-info" => this.fetch_team_info(ctx),
+            _ => Err(format!(
+                "Unsupported resource type: {resource}. Supported resources are 'users', 'usergroups', 'usergroup_members', 'messages', 'channels', 'files', and 'team-info'."
+            )),
+        }
+    }
+
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
+        let this = Self::this_mut();
+        let rows = collect_batch(max_rows, || this.next_row(ctx))?;
+
+        // rows are counted per batch, as the host asks for another batch
+        // after the last one
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, rows.len() as i64);
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, rows.len() as i64);
+
+        Ok(rows)
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -1399,10 +1406,26 @@ impl Guest for SlackFdw {
         Err("Slack FDW is read-only".to_string())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, _row: &Row) -> FdwResult {
         Err("Slack FDW is read-only".to_string())
     }
 
+    fn insert_batch(
+        _ctx: &Context,
+        _columns: Vec<String>,
+        _rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        Err("Slack FDW is read-only".to_string())
+    }
+
     fn update(_ctx: &Context, _rowid: Cell, _row: &Row) -> FdwResult {
         Err("Slack FDW is read-only".to_string())
     }
@@ -1415,6 +1438,14 @@ impl Guest for SlackFdw {
         Err("Slack FDW is read-only".to_string())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         stmt: ImportForeignSchemaStmt,
@@ -1557,4 +1588,4 @@ impl Guest for SlackFdw {
     }
 }
 
-bindings::export!(SlackFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(SlackFdw with_types_in wrappers_wasm_sdk::bindings);
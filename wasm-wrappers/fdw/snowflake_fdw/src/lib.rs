@@ -1,17 +1,19 @@
-#[allow(warnings)]
-mod bindings;
 use serde_json::Value as JsonValue;
 
-use bindings::{
-    exports::supabase::wrappers::routines::Guest,
-    supabase::wrappers::{
-        http, jwt, stats, time,
-        types::{
-            Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt, OptionsType, Row,
-            TypeOid,
+use wrappers_wasm_sdk::{
+    Instance,
+    bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, jwt, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ModifyCapabilities, OptionsType, Row, ScanCapabilities, TypeOid,
+            },
+            utils,
         },
-        utils,
     },
+    collect_batch,
 };
 
 #[derive(Debug, Default)]
@@ -28,19 +30,16 @@ struct SnowflakeFdw {
     timeout_secs: i32,
 }
 
-static mut INSTANCE: *mut SnowflakeFdw = std::ptr::null_mut::<SnowflakeFdw>();
+static INSTANCE: Instance<SnowflakeFdw> = Instance::new();
 static FDW_NAME: &str = "SnowflakeFdw";
 
 impl SnowflakeFdw {
     fn init() {
-        let instance = Self::default();
-        unsafe {
-            INSTANCE = Box::leak(Box::new(instance));
-        }
+        INSTANCE.init(Self::default());
     }
 
     fn this_mut() -> &'static mut Self {
-        unsafe { &mut (*INSTANCE) }
+        INSTANCE.get_mut()
     }
 
     // make a request to Snowflake
@@ -54,14 +53,15 @@ impl SnowflakeFdw {
             method,
             url: url.to_owned(),
             headers: self.headers.clone(),
-            body: body.to_owned(),
+            body: body.as_bytes().to_vec(),
+            timeout_ms: None,
         };
         let resp = match method {
             http::Method::Get => http::get(&req),
             http::Method::Post => http::post(&req),
             _ => unreachable!(),
         }?;
-        let json_value = serde_json::from_str(&resp.body).map_err(|e| e.to_string())?;
+        let json_value = serde_json::from_slice(&resp.body).map_err(|e| e.to_string())?;
 
         stats::inc_stats(FDW_NAME, stats::Metric::BytesIn, resp.body.len() as i64);
         stats::inc_stats(FDW_NAME, stats::Metric::BytesOut, body.len() as i64);
@@ -117,8 +117,9 @@ impl SnowflakeFdw {
                 Some(Cell::I64(v))
             }
             TypeOid::Numeric => {
-                let v = src_str.parse::<f64>().map_err(|e| e.to_string())?;
-                Some(Cell::Numeric(v))
+                // validate the number but keep its exact decimal string
+                src_str.parse::<f64>().map_err(|e| e.to_string())?;
+                Some(Cell::Numeric(src_str.to_owned()))
             }
             TypeOid::String => {
                 let v = src_str.to_owned();
@@ -219,7 +220,8 @@ impl SnowflakeFdw {
             if resp.status_code != 200 && resp.status_code != 202 {
                 return Err(format!(
                     "request failed with status code {}: {}",
-                    resp.status_code, resp.body
+                    resp.status_code,
+                    String::from_utf8_lossy(&resp.body)
                 ));
             }
 
@@ -271,12 +273,87 @@ impl SnowflakeFdw {
 
         Ok(())
     }
+
+    // get the next row, returns 'None' if all source rows are consumed
+    fn next_row(&mut self, ctx: &Context) -> Result<Option<Vec<Option<Cell>>>, FdwError> {
+        // if all rows in local partition are consumed
+        if self.src_idx >= self.src_rows.len() {
+            self.partition_idx += 1;
+
+            // no more partitions, stop the iteration scan
+            if self.partition_idx >= self.partition_cnt {
+                return Ok(None);
+            }
+
+            // otherwise make a new request for the next partition
+            let url = format!(
+                "{}/{}?partition={}",
+                self.base_url, self.stmt_handle, self.partition_idx
+            );
+            let (_, resp_json) = self.make_get_request(&url)?;
+            self.src_rows = resp_json
+                .as_object()
+                .and_then(|v| v.get("data"))
+                .and_then(|v| v.as_array())
+                .map(|v| v.to_owned())
+                .ok_or("cannot get query result data")?;
+            self.src_idx = 0;
+
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, self.src_rows.len() as i64);
+            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, self.src_rows.len() as i64);
+        }
+
+        // convert a Snowflake row to Postgres row
+        let src_row = &self.src_rows[self.src_idx]
+            .as_array()
+            .ok_or("invalid source row")?;
+        let mut row = Vec::new();
+        for (idx, tgt_col) in ctx.get_columns().iter().enumerate() {
+            match src_row[idx] {
+                JsonValue::String(ref src_str) => {
+                    let cell = self.src_to_cell(src_str, tgt_col)?;
+                    row.push(cell);
+                }
+                JsonValue::Null => {
+                    row.push(None);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        self.src_idx += 1;
+
+        Ok(Some(row))
+    }
+
+    // insert rows using a single multi-row insert statement
+    fn insert_rows(&mut self, cols: &[String], rows: &[Vec<Option<Cell>>]) -> FdwResult {
+        let values: Vec<String> = rows
+            .iter()
+            .map(|cells| {
+                let col_values: Vec<String> = cells
+                    .iter()
+                    .map(|c| utils::cell_to_string(c.as_ref()))
+                    .collect();
+                format!("({})", col_values.join(","))
+            })
+            .collect();
+        let sql = format!(
+            "insert into {} ({}) values {}",
+            self.table,
+            cols.join(","),
+            values.join(",")
+        );
+        self.make_init_request(&sql)?;
+        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, rows.len() as i64);
+        Ok(())
+    }
 }
 
 impl Guest for SnowflakeFdw {
     fn host_version_requirement() -> String {
         // semver ref: https://docs.rs/semver/latest/semver/enum.Op.html
-        "^0.1.0".to_string()
+        "^0.1.8".to_string()
     }
 
     fn init(ctx: &Context) -> FdwResult {
@@ -341,6 +418,20 @@ impl Guest for SnowflakeFdw {
         Ok(())
     }
 
+    fn get_rel_size(_ctx: &Context) -> Result<(i64, i32), FdwError> {
+        // no estimate, the host uses its default
+        Ok((0, 0))
+    }
+
+    fn get_scan_capabilities(_ctx: &Context) -> Result<ScanCapabilities, FdwError> {
+        // pushed down quals, sorts and limit are rechecked by Postgres
+        Ok(ScanCapabilities {
+            quals: false,
+            sorts: false,
+            limit: false,
+        })
+    }
+
     fn begin_scan(ctx: &Context) -> FdwResult {
         let this = Self::this_mut();
         let opts = ctx.get_options(&OptionsType::Table);
@@ -350,56 +441,9 @@ impl Guest for SnowflakeFdw {
         this.make_init_request(&sql)
     }
 
-    fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+    fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
         let this = Self::this_mut();
-
-        // if all rows in local partition are consumed
-        if this.src_idx >= this.src_rows.len() {
-            this.partition_idx += 1;
-
-            // no more partitions, stop the iteration scan
-            if this.partition_idx >= this.partition_cnt {
-                return Ok(None);
-            }
-
-            // otherwise make a new request for the next partition
-            let url = format!(
-                "{}/{}?partition={}",
-                this.base_url, this.stmt_handle, this.partition_idx
-            );
-            let (_, resp_json) = this.make_get_request(&url)?;
-            this.src_rows = resp_json
-                .as_object()
-                .and_then(|v| v.get("data"))
-                .and_then(|v| v.as_array())
-                .map(|v| v.to_owned())
-                .ok_or("cannot get query result data")?;
-            this.src_idx = 0;
-
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsIn, this.src_rows.len() as i64);
-            stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, this.src_rows.len() as i64);
-        }
-
-        // convert a Snowflake row to Postgres row
-        let src_row = &this.src_rows[this.src_idx]
-            .as_array()
-            .ok_or("invalid source row")?;
-        for (idx, tgt_col) in ctx.get_columns().iter().enumerate() {
-            match src_row[idx] {
-                JsonValue::String(ref src_str) => {
-                    let cell = this.src_to_cell(src_str, tgt_col)?;
-                    row.push(cell.as_ref());
-                }
-                JsonValue::Null => {
-                    row.push(None);
-                }
-                _ => unreachable!(),
-            }
-        }
-
-        this.src_idx += 1;
-
-        Ok(Some(0))
+        collect_batch(max_rows, || this.next_row(ctx))
     }
 
     fn re_scan(ctx: &Context) -> FdwResult {
@@ -422,23 +466,26 @@ impl Guest for SnowflakeFdw {
         Ok(())
     }
 
+    fn get_modify_capabilities(_ctx: &Context) -> Result<ModifyCapabilities, FdwError> {
+        // pre-commit and abort do nothing, so no need to keep the instance
+        // until the end of transaction
+        Ok(ModifyCapabilities {
+            transaction_hooks: false,
+        })
+    }
+
     fn insert(_ctx: &Context, row: &Row) -> FdwResult {
         let this = Self::this_mut();
-        let col_names: Vec<String> = row.cols().iter().map(|c| c.to_owned()).collect();
-        let col_values: Vec<String> = row
-            .cells()
-            .iter()
-            .map(|c| utils::cell_to_string(c.as_ref()))
-            .collect();
-        let sql = format!(
-            "insert into {} ({}) values ({})",
-            this.table,
-            col_names.join(","),
-            col_values.join(",")
-        );
-        this.make_init_request(&sql)?;
-        stats::inc_stats(FDW_NAME, stats::Metric::RowsOut, 1);
-        Ok(())
+        this.insert_rows(&row.cols(), &[row.cells()])
+    }
+
+    fn insert_batch(
+        _ctx: &Context,
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<Cell>>>,
+    ) -> FdwResult {
+        let this = Self::this_mut();
+        this.insert_rows(&columns, &rows)
     }
 
     fn update(_ctx: &Context, rowid: Cell, row: &Row) -> FdwResult {
@@ -478,6 +525,14 @@ impl Guest for SnowflakeFdw {
         Ok(())
     }
 
+    fn pre_commit(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
+    fn abort(_ctx: &Context) -> FdwResult {
+        Ok(())
+    }
+
     fn import_foreign_schema(
         _ctx: &Context,
         _stmt: ImportForeignSchemaStmt,
@@ -486,4 +541,4 @@ impl Guest for SnowflakeFdw {
     }
 }
 
-bindings::export!(SnowflakeFdw with_types_in bindings);
+wrappers_wasm_sdk::export!(SnowflakeFdw with_types_in wrappers_wasm_sdk::bindings);
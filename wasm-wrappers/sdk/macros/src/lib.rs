@@ -0,0 +1,140 @@
+//! Procedural macros for [wrappers-wasm-sdk](https://github.com/supabase/wrappers/tree/main/wasm-wrappers/sdk)
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Type, parse_macro_input,
+};
+
+/// Derive `FromOptions` trait for a struct with named fields
+///
+/// Each field is mapped to an option with the same name, use the field
+/// attribute `#[option(name = "...", default = "...")]` to override the option
+/// name or to provide a default value.
+///
+/// ```rust,ignore
+/// #[derive(FromOptions)]
+/// struct TableOptions {
+///     object: String,
+///     #[option(name = "rowid_column")]
+///     rowid_col: Option<String>,
+///     #[option(default = "100")]
+///     page_size: u32,
+/// }
+/// ```
+#[proc_macro_derive(FromOptions, attributes(option))]
+pub fn from_options(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_from_options(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_from_options(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "FromOptions can only be derived for struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "FromOptions can only be derived for struct",
+            ));
+        }
+    };
+
+    let mut field_inits = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.as_ref().unwrap();
+        let mut name = field_ident.to_string();
+        let mut default: Option<LitStr> = None;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("option")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<LitStr>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported option attribute"))
+                }
+            })?;
+        }
+
+        let init = match (option_inner_type(&field.ty), default) {
+            (Some(inner_ty), _) => quote! {
+                match ::wrappers_wasm_sdk::OptionsSource::get_option(opts, #name) {
+                    Some(value) => Some(
+                        value
+                            .parse::<#inner_ty>()
+                            .map_err(|_| format!("invalid value for option '{}'", #name))?,
+                    ),
+                    None => None,
+                }
+            },
+            (None, Some(default)) => {
+                let ty = &field.ty;
+                quote! {
+                    ::wrappers_wasm_sdk::OptionsSource::require_option_or(opts, #name, #default)
+                        .parse::<#ty>()
+                        .map_err(|_| format!("invalid value for option '{}'", #name))?
+                }
+            }
+            (None, None) => {
+                let ty = &field.ty;
+                quote! {
+                    ::wrappers_wasm_sdk::OptionsSource::require_option(opts, #name)?
+                        .parse::<#ty>()
+                        .map_err(|_| format!("invalid value for option '{}'", #name))?
+                }
+            }
+        };
+        field_inits.push(quote! { #field_ident: #init });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::wrappers_wasm_sdk::FromOptions for #ident #ty_generics #where_clause {
+            fn from_options<__O: ::wrappers_wasm_sdk::OptionsSource + ?Sized>(
+                opts: &__O,
+            ) -> ::std::result::Result<
+                Self,
+                ::wrappers_wasm_sdk::bindings::supabase::wrappers::types::FdwError,
+            > {
+                Ok(Self {
+                    #(#field_inits,)*
+                })
+            }
+        }
+    })
+}
+
+// get the inner type T if the type is Option<T>
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,302 @@
+use serde_json::Value as JsonValue;
+
+use crate::bindings::supabase::wrappers::{
+    time,
+    types::{Cell, Column, FdwError, TypeOid},
+};
+
+/// Get a value from JSON by path
+///
+/// The path can be either a JSON pointer, like `/data/attributes/name`, or a
+/// dot-separated field path, like `data.attributes.name`. Array elements can
+/// be referenced by index, like `items.0.id`.
+pub fn json_path<'a>(src: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    if path.is_empty() {
+        return Some(src);
+    }
+    if path.starts_with('/') {
+        return src.pointer(path);
+    }
+    path.split('.').try_fold(src, |v, key| match v {
+        JsonValue::Object(obj) => obj.get(key),
+        JsonValue::Array(arr) => key.parse::<usize>().ok().and_then(|idx| arr.get(idx)),
+        _ => None,
+    })
+}
+
+/// Convert a JSON value to a cell according to the target column type
+///
+/// JSON `null` is converted to `None`. Date and timestamp columns expect an
+/// RFC3339 string, and a `json` or `jsonb` column accepts any JSON value.
+pub fn json_to_cell(src: &JsonValue, tgt_col: &Column) -> Result<Option<Cell>, FdwError> {
+    convert_json(src, &tgt_col.name(), tgt_col.type_oid())
+}
+
+// convert a JSON value to a cell of the type
+fn convert_json(
+    src: &JsonValue,
+    tgt_col_name: &str,
+    type_oid: TypeOid,
+) -> Result<Option<Cell>, FdwError> {
+    if src.is_null() {
+        return Ok(None);
+    }
+
+    let type_mismatch = || format!("source value for column '{tgt_col_name}' has mismatched type");
+
+    let cell = match type_oid {
+        TypeOid::Bool => src.as_bool().map(Cell::Bool),
+        TypeOid::I8 => src
+            .as_i64()
+            .and_then(|v| i8::try_from(v).ok())
+            .map(Cell::I8),
+        TypeOid::I16 => src
+            .as_i64()
+            .and_then(|v| i16::try_from(v).ok())
+            .map(Cell::I16),
+        TypeOid::I32 => src
+            .as_i64()
+            .and_then(|v| i32::try_from(v).ok())
+            .map(Cell::I32),
+        TypeOid::I64 => src.as_i64().map(Cell::I64),
+        TypeOid::F32 => src.as_f64().map(|v| Cell::F32(v as f32)),
+        TypeOid::F64 => src.as_f64().map(Cell::F64),
+        #[cfg(not(feature = "v3"))]
+        TypeOid::Numeric => src.as_f64().map(Cell::Numeric),
+        // keep the exact decimal string of the JSON number
+        #[cfg(feature = "v3")]
+        TypeOid::Numeric => src.as_number().map(|v| Cell::Numeric(v.to_string())),
+        TypeOid::String => Some(Cell::String(match src.as_str() {
+            Some(s) => s.to_owned(),
+            None => src.to_string(),
+        })),
+        TypeOid::Date => match src.as_str() {
+            Some(s) => Some(Cell::Date(time::parse_from_rfc3339(s)? / 1_000_000)),
+            None => None,
+        },
+        TypeOid::Timestamp => match src.as_str() {
+            Some(s) => Some(Cell::Timestamp(time::parse_from_rfc3339(s)?)),
+            None => None,
+        },
+        TypeOid::Timestamptz => match src.as_str() {
+            Some(s) => Some(Cell::Timestamptz(time::parse_from_rfc3339(s)?)),
+            None => None,
+        },
+        TypeOid::Json => Some(Cell::Json(src.to_string())),
+        TypeOid::Uuid => src.as_str().map(|v| Cell::Uuid(v.to_owned())),
+        #[cfg(feature = "v3")]
+        TypeOid::BoolArray => json_array(src, JsonValue::as_bool).map(Cell::BoolArray),
+        #[cfg(feature = "v3")]
+        TypeOid::I16Array => {
+            json_array(src, |v| v.as_i64().and_then(|v| i16::try_from(v).ok())).map(Cell::I16Array)
+        }
+        #[cfg(feature = "v3")]
+        TypeOid::I32Array => {
+            json_array(src, |v| v.as_i64().and_then(|v| i32::try_from(v).ok())).map(Cell::I32Array)
+        }
+        #[cfg(feature = "v3")]
+        TypeOid::I64Array => json_array(src, JsonValue::as_i64).map(Cell::I64Array),
+        #[cfg(feature = "v3")]
+        TypeOid::F32Array => json_array(src, |v| v.as_f64().map(|v| v as f32)).map(Cell::F32Array),
+        #[cfg(feature = "v3")]
+        TypeOid::F64Array => json_array(src, JsonValue::as_f64).map(Cell::F64Array),
+        #[cfg(feature = "v3")]
+        TypeOid::StringArray => json_array(src, |v| {
+            Some(match v.as_str() {
+                Some(s) => s.to_owned(),
+                None => v.to_string(),
+            })
+        })
+        .map(Cell::StringArray),
+        #[cfg(feature = "v3")]
+        TypeOid::Time | TypeOid::Interval | TypeOid::Bytea => {
+            return Err(format!(
+                "target column '{tgt_col_name}' type is not supported"
+            ));
+        }
+        TypeOid::Other(_) => {
+            return Err(format!(
+                "target column '{tgt_col_name}' type is not supported"
+            ));
+        }
+    };
+
+    cell.map(Some).ok_or_else(type_mismatch)
+}
+
+// convert a JSON array to array elements, JSON `null` elements are kept as
+// `None`, returns `None` if any of the other elements has mismatched type
+#[cfg(feature = "v3")]
+fn json_array<T>(src: &JsonValue, f: impl Fn(&JsonValue) -> Option<T>) -> Option<Vec<Option<T>>> {
+    src.as_array()?
+        .iter()
+        .map(|v| {
+            if v.is_null() {
+                Some(None)
+            } else {
+                f(v).map(Some)
+            }
+        })
+        .collect()
+}
+
+/// Get a value from JSON by path and convert it to a cell
+///
+/// A missing value is converted to `None`, see [`json_path`] for the path
+/// syntax and [`json_to_cell`] for the type mapping.
+pub fn json_path_to_cell(
+    src: &JsonValue,
+    path: &str,
+    tgt_col: &Column,
+) -> Result<Option<Cell>, FdwError> {
+    match json_path(src, path) {
+        Some(v) => json_to_cell(v, tgt_col),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn convert(src: &JsonValue, type_oid: TypeOid) -> Result<Option<Cell>, FdwError> {
+        convert_json(src, "col", type_oid)
+    }
+
+    #[test]
+    fn test_json_path() {
+        let src = json!({
+            "data": {
+                "attributes": {"name": "foo"},
+                "items": [{"id": 1}, {"id": 2}],
+            }
+        });
+
+        assert_eq!(json_path(&src, ""), Some(&src));
+        assert_eq!(json_path(&src, "data.attributes.name"), Some(&json!("foo")));
+        assert_eq!(
+            json_path(&src, "/data/attributes/name"),
+            Some(&json!("foo"))
+        );
+        assert_eq!(json_path(&src, "data.items.1.id"), Some(&json!(2)));
+        assert_eq!(json_path(&src, "/data/items/0/id"), Some(&json!(1)));
+        assert_eq!(json_path(&src, "data.items.2.id"), None);
+        assert_eq!(json_path(&src, "data.items.x"), None);
+        assert_eq!(json_path(&src, "data.attributes.name.first"), None);
+        assert_eq!(json_path(&src, "data.missing"), None);
+    }
+
+    #[test]
+    fn test_convert_json() {
+        assert_eq!(convert(&json!(null), TypeOid::I64), Ok(None));
+        assert_eq!(
+            convert(&json!(true), TypeOid::Bool),
+            Ok(Some(Cell::Bool(true)))
+        );
+        assert_eq!(convert(&json!(-8), TypeOid::I8), Ok(Some(Cell::I8(-8))));
+        assert_eq!(convert(&json!(300), TypeOid::I16), Ok(Some(Cell::I16(300))));
+        assert_eq!(convert(&json!(42), TypeOid::I32), Ok(Some(Cell::I32(42))));
+        assert_eq!(
+            convert(&json!(1_i64 << 40), TypeOid::I64),
+            Ok(Some(Cell::I64(1 << 40)))
+        );
+        assert_eq!(convert(&json!(1.5), TypeOid::F32), Ok(Some(Cell::F32(1.5))));
+        assert_eq!(convert(&json!(2), TypeOid::F64), Ok(Some(Cell::F64(2.0))));
+        assert_eq!(
+            convert(&json!("foo"), TypeOid::String),
+            Ok(Some(Cell::String("foo".to_string())))
+        );
+        // non-string value is converted to its JSON text
+        assert_eq!(
+            convert(&json!({"a": 1}), TypeOid::String),
+            Ok(Some(Cell::String(r#"{"a":1}"#.to_string())))
+        );
+        assert_eq!(
+            convert(&json!([1, "a"]), TypeOid::Json),
+            Ok(Some(Cell::Json(r#"[1,"a"]"#.to_string())))
+        );
+        assert_eq!(
+            convert(
+                &json!("6f0e3f5a-9b1c-4e0e-8f4a-2b8e1c3d4f5a"),
+                TypeOid::Uuid
+            ),
+            Ok(Some(Cell::Uuid(
+                "6f0e3f5a-9b1c-4e0e-8f4a-2b8e1c3d4f5a".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_convert_json_mismatch() {
+        let mismatch = Err("source value for column 'col' has mismatched type".to_string());
+        assert_eq!(convert(&json!("1"), TypeOid::I64), mismatch);
+        assert_eq!(convert(&json!(1.5), TypeOid::I32), mismatch);
+        assert_eq!(convert(&json!(128), TypeOid::I8), mismatch);
+        assert_eq!(convert(&json!(1), TypeOid::Bool), mismatch);
+        assert_eq!(convert(&json!(1), TypeOid::Uuid), mismatch);
+        assert_eq!(convert(&json!(1), TypeOid::Date), mismatch);
+        assert_eq!(
+            convert(&json!(1), TypeOid::Other("point".to_string())),
+            Err("target column 'col' type is not supported".to_string())
+        );
+    }
+
+    #[cfg(not(feature = "v3"))]
+    #[test]
+    fn test_convert_json_numeric() {
+        assert_eq!(
+            convert(&json!(123.45), TypeOid::Numeric),
+            Ok(Some(Cell::Numeric(123.45)))
+        );
+    }
+
+    #[cfg(feature = "v3")]
+    #[test]
+    fn test_convert_json_numeric() {
+        assert_eq!(
+            convert(&json!(123.45), TypeOid::Numeric),
+            Ok(Some(Cell::Numeric("123.45".to_string())))
+        );
+        assert_eq!(
+            convert(&json!(9007199254740993_u64), TypeOid::Numeric),
+            Ok(Some(Cell::Numeric("9007199254740993".to_string())))
+        );
+    }
+
+    #[cfg(feature = "v3")]
+    #[test]
+    fn test_convert_json_array() {
+        assert_eq!(
+            convert(&json!([1, null, 3]), TypeOid::I64Array),
+            Ok(Some(Cell::I64Array(vec![Some(1), None, Some(3)])))
+        );
+        assert_eq!(
+            convert(&json!([true, false]), TypeOid::BoolArray),
+            Ok(Some(Cell::BoolArray(vec![Some(true), Some(false)])))
+        );
+        assert_eq!(
+            convert(&json!(["a", 1]), TypeOid::StringArray),
+            Ok(Some(Cell::StringArray(vec![
+                Some("a".to_string()),
+                Some("1".to_string())
+            ])))
+        );
+        assert_eq!(
+            convert(&json!([1.5]), TypeOid::F64Array),
+            Ok(Some(Cell::F64Array(vec![Some(1.5)])))
+        );
+        assert_eq!(
+            convert(&json!([1, 70000]), TypeOid::I16Array),
+            Err("source value for column 'col' has mismatched type".to_string())
+        );
+        assert_eq!(
+            convert(&json!(1), TypeOid::I32Array),
+            Err("source value for column 'col' has mismatched type".to_string())
+        );
+        assert_eq!(
+            convert(&json!("00:00:01"), TypeOid::Time),
+            Err("target column 'col' type is not supported".to_string())
+        );
+    }
+}
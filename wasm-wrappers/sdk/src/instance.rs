@@ -0,0 +1,91 @@
+use std::cell::UnsafeCell;
+
+/// Holder of the FDW instance state across export calls
+///
+/// Wasm guests are single-threaded and the host never re-enters a guest
+/// while an export call is in progress, so a mutable reference handed out
+/// by [`Instance::get_mut`] cannot alias another one in practice. This
+/// replaces the `static mut` pointer each FDW used to declare by itself.
+///
+/// ```rust,ignore
+/// static INSTANCE: Instance<ExampleFdw> = Instance::new();
+///
+/// fn init(ctx: &Context) -> FdwResult {
+///     INSTANCE.init(ExampleFdw::default());
+///     let this = INSTANCE.get_mut();
+///     ...
+/// }
+/// ```
+pub struct Instance<T> {
+    inner: UnsafeCell<Option<T>>,
+}
+
+// SAFETY: Wasm guests run on a single thread and export calls are not re-entrant.
+unsafe impl<T> Sync for Instance<T> {}
+
+impl<T> Instance<T> {
+    pub const fn new() -> Self {
+        Self {
+            inner: UnsafeCell::new(None),
+        }
+    }
+
+    /// Set up the instance state, replacing the previous one if any
+    pub fn init(&self, value: T) {
+        unsafe {
+            *self.inner.get() = Some(value);
+        }
+    }
+
+    /// Check if the instance state has been set up
+    pub fn is_initialized(&self) -> bool {
+        unsafe { (*self.inner.get()).is_some() }
+    }
+
+    /// Get mutable reference to the instance state
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Instance::init`] has not been called yet.
+    #[allow(clippy::mut_from_ref)]
+    pub fn get_mut(&self) -> &mut T {
+        unsafe {
+            (*self.inner.get())
+                .as_mut()
+                .expect("FDW instance is not initialized")
+        }
+    }
+}
+
+impl<T> Default for Instance<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance() {
+        let instance: Instance<Vec<i32>> = Instance::new();
+        assert!(!instance.is_initialized());
+
+        instance.init(vec![1]);
+        assert!(instance.is_initialized());
+        instance.get_mut().push(2);
+        assert_eq!(instance.get_mut(), &vec![1, 2]);
+
+        // init again replaces the state
+        instance.init(Vec::new());
+        assert!(instance.get_mut().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "FDW instance is not initialized")]
+    fn test_instance_not_initialized() {
+        let instance: Instance<i32> = Instance::new();
+        instance.get_mut();
+    }
+}
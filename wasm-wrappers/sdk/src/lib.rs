@@ -0,0 +1,112 @@
+//! SDK for developing Wasm foreign data wrappers for [Supabase Wrappers](https://github.com/supabase/wrappers)
+//!
+//! It provides the guest bindings of the `supabase:wrappers` WIT package and
+//! helpers for the boilerplate most Wasm FDWs need:
+//!
+//! - [`Instance`] - holder of the FDW state across export calls
+//! - [`RestScanner`] - scanner of paginated REST API results
+//! - [`json_to_cell`] and [`json_path_to_cell`] - JSON to [`Cell`](bindings::supabase::wrappers::types::Cell) conversion
+//! - [`find_qual`], [`eq_qual_value`] and [`quals_to_query_params`] - qual pushdown helpers
+//! - [`FromOptions`] - derive macro to parse foreign server or table options
+//! - [`ForeignTableDef`] and [`build_ddl`] - foreign table DDL builder for `import_foreign_schema`
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use wrappers_wasm_sdk::prelude::*;
+//!
+//! #[derive(Debug, Default)]
+//! struct ExampleFdw {
+//!     row_cnt: i64,
+//! }
+//!
+//! static INSTANCE: Instance<ExampleFdw> = Instance::new();
+//!
+//! impl Guest for ExampleFdw {
+//!     fn init(_ctx: &Context) -> FdwResult {
+//!         INSTANCE.init(ExampleFdw::default());
+//!         Ok(())
+//!     }
+//!
+//!     fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+//!         let this = INSTANCE.get_mut();
+//!         ...
+//!     }
+//!
+//!     ...
+//! }
+//!
+//! wrappers_wasm_sdk::export!(ExampleFdw with_types_in wrappers_wasm_sdk::bindings);
+//! ```
+
+/// Guest bindings generated from the `supabase:wrappers` WIT package
+///
+/// They are generated from WIT version `0.2.0` by default, enable the `v3`
+/// feature to use WIT version `0.3.0` instead.
+#[allow(warnings)]
+pub mod bindings {
+    #[cfg(not(feature = "v3"))]
+    wit_bindgen::generate!({
+        path: "../wit/v2",
+        world: "wrappers",
+        pub_export_macro: true,
+        additional_derives: [PartialEq],
+        default_bindings_module: "wrappers_wasm_sdk::bindings",
+    });
+
+    #[cfg(feature = "v3")]
+    wit_bindgen::generate!({
+        path: "../wit/v3",
+        world: "wrappers",
+        pub_export_macro: true,
+        additional_derives: [PartialEq],
+        default_bindings_module: "wrappers_wasm_sdk::bindings",
+    });
+}
+
+mod cell;
+mod ddl;
+mod instance;
+mod options;
+mod qual;
+mod request;
+mod scanner;
+
+pub use bindings::export;
+pub use cell::{json_path, json_path_to_cell, json_to_cell};
+pub use ddl::{ColumnDef, ForeignTableDef, build_ddl, is_table_selected};
+pub use instance::Instance;
+pub use options::{FromOptions, OptionsSource, require_secret};
+pub use qual::{QualLike, cell_to_string, eq_qual_value, find_qual, quals_to_query_params};
+pub use request::send_request;
+#[cfg(feature = "v3")]
+pub use scanner::collect_batch;
+pub use scanner::{Page, RestScanner};
+
+pub use wrappers_wasm_sdk_macros::FromOptions;
+
+pub mod prelude {
+    #[cfg(feature = "v3")]
+    pub use crate::bindings::supabase::wrappers::{
+        crypto, kv, log, tcp,
+        types::{Interval, ModifyCapabilities, ScanCapabilities},
+    };
+    pub use crate::bindings::{
+        exports::supabase::wrappers::routines::Guest,
+        supabase::wrappers::{
+            http, jwt, stats, time,
+            types::{
+                Cell, Column, Context, FdwError, FdwResult, ImportForeignSchemaStmt,
+                ImportSchemaType, Limit, Options, OptionsType, Qual, Row, Sort, TypeOid, Value,
+            },
+            utils,
+        },
+    };
+    #[cfg(feature = "v3")]
+    pub use crate::collect_batch;
+    pub use crate::{
+        ColumnDef, ForeignTableDef, FromOptions, Instance, Page, RestScanner, build_ddl,
+        cell_to_string, eq_qual_value, find_qual, is_table_selected, json_path, json_path_to_cell,
+        json_to_cell, quals_to_query_params, require_secret, send_request,
+    };
+}
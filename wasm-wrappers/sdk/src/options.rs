@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+use crate::bindings::supabase::wrappers::{
+    types::{FdwError, Options},
+    utils,
+};
+
+/// Source of option values
+///
+/// It is implemented for the [`Options`] resource and for `HashMap`, so the
+/// option parsing can be tested without the host.
+pub trait OptionsSource {
+    /// Get an option value, `None` if it is not specified
+    fn get_option(&self, name: &str) -> Option<String>;
+
+    /// Get a required option value
+    fn require_option(&self, name: &str) -> Result<String, FdwError> {
+        self.get_option(name)
+            .ok_or_else(|| format!("required option `{name}` is not specified"))
+    }
+
+    /// Get an option value, or the default value if it is not specified
+    fn require_option_or(&self, name: &str, default: &str) -> String {
+        self.get_option(name).unwrap_or_else(|| default.to_owned())
+    }
+}
+
+impl OptionsSource for Options {
+    fn get_option(&self, name: &str) -> Option<String> {
+        self.get(name)
+    }
+
+    fn require_option(&self, name: &str) -> Result<String, FdwError> {
+        self.require(name)
+    }
+
+    fn require_option_or(&self, name: &str, default: &str) -> String {
+        self.require_or(name, default)
+    }
+}
+
+impl<S: BuildHasher> OptionsSource for HashMap<String, String, S> {
+    fn get_option(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// Parse a struct from foreign server, table or import schema options
+///
+/// This is usually implemented by `#[derive(FromOptions)]`, each field is
+/// mapped to an option with the same name and parsed by its [`FromStr`]
+/// implementation:
+///
+/// - `String` field is required unless a default value is given
+/// - `Option<T>` field is optional
+/// - other field is required unless a default value is given
+///
+/// The field attribute `#[option(name = "...", default = "...")]` can be used
+/// to override the option name and to provide a default value.
+///
+/// ```rust,ignore
+/// #[derive(FromOptions)]
+/// struct ServerOptions {
+///     #[option(name = "api_url", default = "https://api.example.com")]
+///     base_url: String,
+///     #[option(default = "100")]
+///     page_size: u32,
+///     org: Option<String>,
+/// }
+///
+/// let opts = ServerOptions::from_options(&ctx.get_options(&OptionsType::Server))?;
+/// ```
+///
+/// [`FromStr`]: std::str::FromStr
+pub trait FromOptions: Sized {
+    fn from_options<O: OptionsSource + ?Sized>(opts: &O) -> Result<Self, FdwError>;
+}
+
+/// Get a secret from options, either in plain text or from Vault
+///
+/// The secret is read from option `key` first, if it is not specified then
+/// option `key_id` is required and the secret is fetched from Vault by that ID.
+pub fn require_secret<O: OptionsSource + ?Sized>(
+    opts: &O,
+    key: &str,
+    key_id: &str,
+) -> Result<String, FdwError> {
+    match opts.get_option(key) {
+        Some(secret) => Ok(secret),
+        None => {
+            let secret_id = opts.require_option(key_id)?;
+            utils::get_vault_secret(&secret_id)
+                .ok_or(format!("cannot find secret '{secret_id}' in Vault"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_opts(opts: &[(&str, &str)]) -> HashMap<String, String> {
+        opts.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_options_source() {
+        let opts = make_opts(&[("api_url", "https://api.example.com")]);
+
+        assert_eq!(
+            opts.get_option("api_url").as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(opts.get_option("page_size"), None);
+
+        assert_eq!(
+            opts.require_option("api_url").as_deref(),
+            Ok("https://api.example.com")
+        );
+        assert_eq!(
+            opts.require_option("page_size"),
+            Err("required option `page_size` is not specified".to_string())
+        );
+
+        assert_eq!(opts.require_option_or("page_size", "100"), "100");
+        assert_eq!(
+            opts.require_option_or("api_url", "https://localhost"),
+            "https://api.example.com"
+        );
+    }
+
+    #[test]
+    fn test_require_secret() {
+        let opts = make_opts(&[("api_key", "sk-123")]);
+        assert_eq!(
+            require_secret(&opts, "api_key", "api_key_id").as_deref(),
+            Ok("sk-123")
+        );
+
+        // the secret ID option is required if the secret is not given in
+        // plain text
+        let opts = make_opts(&[]);
+        assert_eq!(
+            require_secret(&opts, "api_key", "api_key_id"),
+            Err("required option `api_key_id` is not specified".to_string())
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::bindings::supabase::wrappers::{
+    time,
+    types::{Cell, Qual, Value},
+};
+
+/// Fields of a qual used by the qual helpers
+///
+/// It is implemented for the [`Qual`] resource, and can be implemented for
+/// plain structs to test the qual pushdown logic without the host.
+pub trait QualLike {
+    fn field(&self) -> String;
+    fn operator(&self) -> String;
+    fn value(&self) -> Value;
+    fn use_or(&self) -> bool;
+}
+
+impl QualLike for Qual {
+    fn field(&self) -> String {
+        Qual::field(self)
+    }
+
+    fn operator(&self) -> String {
+        Qual::operator(self)
+    }
+
+    fn value(&self) -> Value {
+        Qual::value(self)
+    }
+
+    fn use_or(&self) -> bool {
+        Qual::use_or(self)
+    }
+}
+
+/// Find a single-value qual by its field name and operator
+///
+/// Quals combined with `OR`, like `field = any(array[...])`, are skipped.
+pub fn find_qual<'a, Q: QualLike>(quals: &'a [Q], field: &str, operator: &str) -> Option<&'a Q> {
+    quals.iter().find(|q| {
+        q.field() == field
+            && q.operator() == operator
+            && !q.use_or()
+            && matches!(q.value(), Value::Cell(_))
+    })
+}
+
+/// Get the raw string value of a `field = <value>` qual
+///
+/// This is the common case of pushing down an equality condition to a
+/// remote API, like `where id = 'abc'` to `GET /objects/abc`.
+pub fn eq_qual_value<Q: QualLike>(quals: &[Q], field: &str) -> Option<String> {
+    find_qual(quals, field, "=").and_then(|q| match q.value() {
+        Value::Cell(cell) => cell_to_string(&cell),
+        Value::Array(_) => None,
+    })
+}
+
+/// Convert a cell to its raw string value for a remote API request
+///
+/// Unlike `utils::cell_to_string`, strings are not quoted. Date and timestamp
+/// cells are formatted as RFC3339. Returns `None` if the cell cannot be
+/// represented as a plain value.
+pub fn cell_to_string(cell: &Cell) -> Option<String> {
+    let s = match cell {
+        Cell::Bool(v) => v.to_string(),
+        Cell::I8(v) => v.to_string(),
+        Cell::I16(v) => v.to_string(),
+        Cell::F32(v) => v.to_string(),
+        Cell::I32(v) => v.to_string(),
+        Cell::F64(v) => v.to_string(),
+        Cell::I64(v) => v.to_string(),
+        Cell::Numeric(v) => v.to_string(),
+        Cell::String(v) | Cell::Uuid(v) | Cell::Json(v) => v.clone(),
+        Cell::Date(v) => time::epoch_ms_to_rfc3339(v * 1_000_000).ok()?,
+        Cell::Timestamp(v) | Cell::Timestamptz(v) => time::epoch_ms_to_rfc3339(*v).ok()?,
+        #[cfg(feature = "v3")]
+        Cell::Time(_)
+        | Cell::Interval(_)
+        | Cell::Bytea(_)
+        | Cell::BoolArray(_)
+        | Cell::I16Array(_)
+        | Cell::I32Array(_)
+        | Cell::I64Array(_)
+        | Cell::F32Array(_)
+        | Cell::F64Array(_)
+        | Cell::StringArray(_) => return None,
+        Cell::Other(_) => return None,
+    };
+    Some(s)
+}
+
+/// Build query string parameters from equality quals
+///
+/// Each `(field, param)` pair maps a column name to a query parameter name,
+/// and produces `param=value` if a `field = <value>` qual exists.
+pub fn quals_to_query_params<Q: QualLike>(quals: &[Q], mappings: &[(&str, &str)]) -> Vec<String> {
+    mappings
+        .iter()
+        .filter_map(|(field, param)| {
+            eq_qual_value(quals, field).map(|value| format!("{param}={value}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestQual {
+        field: &'static str,
+        operator: &'static str,
+        value: Value,
+        use_or: bool,
+    }
+
+    impl QualLike for TestQual {
+        fn field(&self) -> String {
+            self.field.to_string()
+        }
+
+        fn operator(&self) -> String {
+            self.operator.to_string()
+        }
+
+        fn value(&self) -> Value {
+            self.value.clone()
+        }
+
+        fn use_or(&self) -> bool {
+            self.use_or
+        }
+    }
+
+    fn make_qual(field: &'static str, operator: &'static str, cell: Cell) -> TestQual {
+        TestQual {
+            field,
+            operator,
+            value: Value::Cell(cell),
+            use_or: false,
+        }
+    }
+
+    fn make_quals() -> Vec<TestQual> {
+        vec![
+            make_qual("id", "=", Cell::String("abc".to_string())),
+            make_qual("amount", ">", Cell::I64(100)),
+            make_qual("status", "=", Cell::Bool(true)),
+            // status = any(array['a', 'b'])
+            TestQual {
+                field: "state",
+                operator: "=",
+                value: Value::Array(vec![
+                    Cell::String("a".to_string()),
+                    Cell::String("b".to_string()),
+                ]),
+                use_or: true,
+            },
+            TestQual {
+                field: "kind",
+                operator: "=",
+                value: Value::Cell(Cell::String("x".to_string())),
+                use_or: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_find_qual() {
+        let quals = make_quals();
+
+        let qual = find_qual(&quals, "amount", ">").unwrap();
+        assert_eq!(qual.value(), Value::Cell(Cell::I64(100)));
+        assert!(find_qual(&quals, "amount", "=").is_none());
+        assert!(find_qual(&quals, "name", "=").is_none());
+
+        // quals combined with OR are skipped
+        assert!(find_qual(&quals, "state", "=").is_none());
+        assert!(find_qual(&quals, "kind", "=").is_none());
+    }
+
+    #[test]
+    fn test_eq_qual_value() {
+        let quals = make_quals();
+        assert_eq!(eq_qual_value(&quals, "id").as_deref(), Some("abc"));
+        assert_eq!(eq_qual_value(&quals, "status").as_deref(), Some("true"));
+        assert_eq!(eq_qual_value(&quals, "amount"), None);
+        assert_eq!(eq_qual_value(&quals, "state"), None);
+    }
+
+    #[test]
+    fn test_quals_to_query_params() {
+        let quals = make_quals();
+        let params = quals_to_query_params(
+            &quals,
+            &[("id", "object_id"), ("status", "active"), ("name", "name")],
+        );
+        assert_eq!(params, vec!["object_id=abc", "active=true"]);
+    }
+
+    #[test]
+    fn test_cell_to_string() {
+        assert_eq!(cell_to_string(&Cell::I32(-42)).as_deref(), Some("-42"));
+        assert_eq!(cell_to_string(&Cell::F64(1.5)).as_deref(), Some("1.5"));
+        assert_eq!(
+            cell_to_string(&Cell::String("it's".to_string())).as_deref(),
+            Some("it's")
+        );
+        assert_eq!(
+            cell_to_string(&Cell::Json(r#"{"a":1}"#.to_string())).as_deref(),
+            Some(r#"{"a":1}"#)
+        );
+        assert_eq!(cell_to_string(&Cell::Other("x".to_string())), None);
+    }
+
+    #[cfg(not(feature = "v3"))]
+    #[test]
+    fn test_numeric_cell_to_string() {
+        assert_eq!(
+            cell_to_string(&Cell::Numeric(12.5)).as_deref(),
+            Some("12.5")
+        );
+    }
+
+    #[cfg(feature = "v3")]
+    #[test]
+    fn test_numeric_cell_to_string() {
+        assert_eq!(
+            cell_to_string(&Cell::Numeric("123.4500".to_string())).as_deref(),
+            Some("123.4500")
+        );
+        assert_eq!(cell_to_string(&Cell::Bytea(vec![1, 2])), None);
+        assert_eq!(cell_to_string(&Cell::I64Array(vec![Some(1)])), None);
+    }
+}
@@ -0,0 +1,55 @@
+use std::borrow::Cow;
+
+use crate::bindings::supabase::wrappers::{
+    http::{self, Method, Request, Response},
+    time,
+    types::FdwError,
+};
+
+/// Send a HTTP request and check its response status
+///
+/// When the response status is `429 Too Many Requests`, the request will be
+/// retried after the delay given by `retry-after` or `x-ratelimit-reset`
+/// header, or 1 second if neither is present, up to `max_retries` times.
+/// Response body is included in the error message if the request failed.
+pub fn send_request(req: &Request, max_retries: u32) -> Result<Response, FdwError> {
+    let mut retries = 0;
+
+    loop {
+        let resp = match req.method {
+            Method::Get => http::get(req)?,
+            Method::Post => http::post(req)?,
+            Method::Put => http::put(req)?,
+            Method::Patch => http::patch(req)?,
+            Method::Delete => http::delete(req)?,
+        };
+
+        if resp.status_code == 429 && retries < max_retries {
+            let delay_secs = resp
+                .headers
+                .iter()
+                .find(|h| h.0 == "retry-after" || h.0 == "x-ratelimit-reset")
+                .and_then(|h| h.1.parse::<u64>().ok())
+                .unwrap_or(1);
+            time::sleep(delay_secs * 1000);
+            retries += 1;
+            continue;
+        }
+
+        http::error_for_status(&resp).map_err(|err| format!("{}: {}", err, body_text(&resp)))?;
+
+        return Ok(resp);
+    }
+}
+
+// response body as text, for the error message
+#[cfg(not(feature = "v3"))]
+fn body_text(resp: &Response) -> Cow<'_, str> {
+    Cow::Borrowed(&resp.body)
+}
+
+// response body as text, for the error message
+#[cfg(feature = "v3")]
+fn body_text(resp: &Response) -> Cow<'_, str> {
+    String::from_utf8_lossy(&resp.body)
+}
@@ -0,0 +1,189 @@
+use serde_json::Value as JsonValue;
+use std::collections::VecDeque;
+
+#[cfg(feature = "v3")]
+use crate::bindings::supabase::wrappers::types::{Cell, FdwError};
+
+/// A page of source rows returned from a remote API
+#[derive(Debug, Default)]
+pub struct Page {
+    /// Source rows in this page
+    pub rows: Vec<JsonValue>,
+
+    /// Cursor to fetch the next page, `None` if this is the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Scanner of paginated REST API results
+///
+/// It buffers source rows of the current page and keeps track of the cursor
+/// to the next page, so a page is only fetched when all of the previous
+/// page's rows are consumed.
+///
+/// ```rust,ignore
+/// fn iter_scan(ctx: &Context, row: &Row) -> Result<Option<u32>, FdwError> {
+///     let this = INSTANCE.get_mut();
+///
+///     while this.scanner.needs_page() {
+///         let page = this.fetch_page(this.scanner.cursor())?;
+///         this.scanner.push_page(page);
+///     }
+///
+///     match this.scanner.next_row() {
+///         Some(src_row) => { ... Ok(Some(0)) }
+///         None => Ok(None),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RestScanner {
+    rows: VecDeque<JsonValue>,
+    next_cursor: Option<String>,
+    has_more: bool,
+    rows_fetched: usize,
+}
+
+impl Default for RestScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RestScanner {
+    pub fn new() -> Self {
+        Self {
+            rows: VecDeque::new(),
+            next_cursor: None,
+            has_more: true,
+            rows_fetched: 0,
+        }
+    }
+
+    /// Reset the scanner to start from the first page
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Check if a page needs to be fetched before getting the next row
+    pub fn needs_page(&self) -> bool {
+        self.rows.is_empty() && self.has_more
+    }
+
+    /// Cursor to fetch the next page, `None` for the first page
+    pub fn cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+
+    /// Total number of source rows fetched so far
+    pub fn rows_fetched(&self) -> usize {
+        self.rows_fetched
+    }
+
+    /// Save a fetched page
+    pub fn push_page(&mut self, page: Page) {
+        self.rows_fetched += page.rows.len();
+        self.rows.extend(page.rows);
+        self.has_more = page.next_cursor.is_some();
+        self.next_cursor = page.next_cursor;
+    }
+
+    /// Get the next source row, `None` if the current page is consumed
+    pub fn next_row(&mut self) -> Option<JsonValue> {
+        self.rows.pop_front()
+    }
+}
+
+/// Collect a batch of rows for `iter_scan_batch`
+///
+/// `next_row` is called until it returns `None` or `max_rows` rows are
+/// collected, and an empty batch ends the scan.
+///
+/// ```rust,ignore
+/// fn iter_scan_batch(ctx: &Context, max_rows: u32) -> Result<Vec<Vec<Option<Cell>>>, FdwError> {
+///     let this = INSTANCE.get_mut();
+///     collect_batch(max_rows, || this.next_row(ctx))
+/// }
+/// ```
+#[cfg(feature = "v3")]
+pub fn collect_batch<F>(max_rows: u32, mut next_row: F) -> Result<Vec<Vec<Option<Cell>>>, FdwError>
+where
+    F: FnMut() -> Result<Option<Vec<Option<Cell>>>, FdwError>,
+{
+    let mut rows = Vec::new();
+    while rows.len() < max_rows as usize {
+        match next_row()? {
+            Some(row) => rows.push(row),
+            None => break,
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rest_scanner() {
+        let mut scanner = RestScanner::new();
+        assert!(scanner.needs_page());
+        assert_eq!(scanner.cursor(), None);
+        assert_eq!(scanner.next_row(), None);
+
+        scanner.push_page(Page {
+            rows: vec![json!({"id": 1}), json!({"id": 2})],
+            next_cursor: Some("page2".to_string()),
+        });
+        assert!(!scanner.needs_page());
+        assert_eq!(scanner.cursor(), Some("page2"));
+        assert_eq!(scanner.rows_fetched(), 2);
+        assert_eq!(scanner.next_row(), Some(json!({"id": 1})));
+        assert_eq!(scanner.next_row(), Some(json!({"id": 2})));
+
+        // the next page is needed only after the current page is consumed
+        assert!(scanner.needs_page());
+        assert_eq!(scanner.next_row(), None);
+
+        // an empty page with a cursor keeps fetching
+        scanner.push_page(Page {
+            rows: vec![],
+            next_cursor: Some("page3".to_string()),
+        });
+        assert!(scanner.needs_page());
+        assert_eq!(scanner.cursor(), Some("page3"));
+
+        // the last page
+        scanner.push_page(Page {
+            rows: vec![json!({"id": 3})],
+            next_cursor: None,
+        });
+        assert_eq!(scanner.rows_fetched(), 3);
+        assert_eq!(scanner.next_row(), Some(json!({"id": 3})));
+        assert!(!scanner.needs_page());
+        assert_eq!(scanner.next_row(), None);
+
+        scanner.reset();
+        assert!(scanner.needs_page());
+        assert_eq!(scanner.cursor(), None);
+        assert_eq!(scanner.rows_fetched(), 0);
+    }
+
+    #[cfg(feature = "v3")]
+    #[test]
+    fn test_collect_batch() {
+        let mut ids = 0..5;
+        let mut next_row = || Ok(ids.next().map(|id| vec![Some(Cell::I64(id))]));
+
+        let batch = collect_batch(2, &mut next_row).unwrap();
+        assert_eq!(
+            batch,
+            vec![vec![Some(Cell::I64(0))], vec![Some(Cell::I64(1))]]
+        );
+        assert_eq!(collect_batch(10, &mut next_row).unwrap().len(), 3);
+        assert!(collect_batch(10, &mut next_row).unwrap().is_empty());
+
+        let err = collect_batch(10, || Err("request failed".to_string()));
+        assert_eq!(err, Err("request failed".to_string()));
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use wrappers_wasm_sdk::FromOptions;
+
+#[derive(Debug, PartialEq, FromOptions)]
+struct ServerOptions {
+    #[option(name = "api_url", default = "https://api.example.com")]
+    base_url: String,
+    #[option(default = "100")]
+    page_size: u32,
+    org: Option<String>,
+    max_retries: Option<u32>,
+    object: String,
+}
+
+fn make_opts(opts: &[(&str, &str)]) -> HashMap<String, String> {
+    opts.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_from_options() {
+    let opts = make_opts(&[("object", "users")]);
+    assert_eq!(
+        ServerOptions::from_options(&opts),
+        Ok(ServerOptions {
+            base_url: "https://api.example.com".to_string(),
+            page_size: 100,
+            org: None,
+            max_retries: None,
+            object: "users".to_string(),
+        })
+    );
+
+    let opts = make_opts(&[
+        ("api_url", "https://localhost:8080"),
+        ("page_size", "20"),
+        ("org", "acme"),
+        ("max_retries", "3"),
+        ("object", "orders"),
+        ("unknown", "ignored"),
+    ]);
+    assert_eq!(
+        ServerOptions::from_options(&opts),
+        Ok(ServerOptions {
+            base_url: "https://localhost:8080".to_string(),
+            page_size: 20,
+            org: Some("acme".to_string()),
+            max_retries: Some(3),
+            object: "orders".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_from_options_error() {
+    // field is renamed, so its own name is not used
+    let opts = make_opts(&[("base_url", "https://localhost"), ("page_size", "20")]);
+    assert_eq!(
+        ServerOptions::from_options(&opts),
+        Err("required option `object` is not specified".to_string())
+    );
+
+    let opts = make_opts(&[("object", "users"), ("page_size", "-1")]);
+    assert_eq!(
+        ServerOptions::from_options(&opts),
+        Err("invalid value for option 'page_size'".to_string())
+    );
+
+    let opts = make_opts(&[("object", "users"), ("max_retries", "many")]);
+    assert_eq!(
+        ServerOptions::from_options(&opts),
+        Err("invalid value for option 'max_retries'".to_string())
+    );
+}
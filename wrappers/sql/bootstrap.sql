@@ -22,6 +22,32 @@ COMMENT ON COLUMN wrappers_fdw_stats.bytes_in IS 'Total bytes input from origin'
 COMMENT ON COLUMN wrappers_fdw_stats.bytes_out IS 'Total bytes output to Postgres';
 COMMENT ON COLUMN wrappers_fdw_stats.metadata IS 'Metadata specific for the FDW';
 
+DROP TABLE IF EXISTS wrappers_fdw_kv;
+
+CREATE TABLE wrappers_fdw_kv (
+  server_oid        oid NOT NULL,
+  user_id           oid NOT NULL,
+  key               text NOT NULL,
+  value             text NOT NULL,
+  expires_at        timestamptz NULL,
+  created_at        timestamptz NOT NULL DEFAULT timezone('utc'::text, now()),
+  updated_at        timestamptz NOT NULL DEFAULT timezone('utc'::text, now()),
+  PRIMARY KEY (server_oid, user_id, key)
+);
+
+-- each user can only access its own key-value pairs, the privileges on this
+-- table need to be granted to the users of Wasm FDW servers with 'kv_persist'
+ALTER TABLE wrappers_fdw_kv ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY wrappers_fdw_kv_owner ON wrappers_fdw_kv
+  USING (user_id = (SELECT oid FROM pg_catalog.pg_roles WHERE rolname = current_user))
+  WITH CHECK (user_id = (SELECT oid FROM pg_catalog.pg_roles WHERE rolname = current_user));
+
+COMMENT ON TABLE wrappers_fdw_kv IS 'Wrappers Wasm Foreign Data Wrapper persisted key-value pairs, values are stored in plain text';
+COMMENT ON COLUMN wrappers_fdw_kv.server_oid IS 'OID of the foreign server the key-value pair belongs to';
+COMMENT ON COLUMN wrappers_fdw_kv.user_id IS 'OID of the user the key-value pair belongs to';
+COMMENT ON COLUMN wrappers_fdw_kv.expires_at IS 'Time when the key-value pair expires, never expires if it is null';
+
 -- The operator '<==>' is defined in s3vectors_fdw since v0.5.6, drop it here
 -- to avoid conflict when upgrading wrappers extension.
 DROP OPERATOR IF EXISTS <==> (jsonb, jsonb);
@@ -1,2 +1,28 @@
 -- SQL statements are intended to go after all other generated SQL.
 
+
+CREATE VIEW wrappers_stat_foreign_tables AS
+SELECT
+  s.srvname AS server_name,
+  n.nspname AS schema_name,
+  c.relname AS table_name,
+  st.requests,
+  st.errors,
+  st.rows_in,
+  st.rows_out,
+  st.bytes_in,
+  st.bytes_out,
+  st.total_time_ms,
+  st.latency_histogram
+FROM wrappers_stat_foreign_tables() st
+  JOIN pg_catalog.pg_foreign_server s ON s.oid = st.server_oid
+  JOIN pg_catalog.pg_class c ON c.oid = st.foreigntable_oid
+  JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace;
+
+COMMENT ON VIEW wrappers_stat_foreign_tables IS 'Wrappers per foreign table statistics, requires wrappers in shared_preload_libraries';
+COMMENT ON COLUMN wrappers_stat_foreign_tables.requests IS 'Total number of scans and modifications';
+COMMENT ON COLUMN wrappers_stat_foreign_tables.errors IS 'Total number of scans and modifications failed';
+COMMENT ON COLUMN wrappers_stat_foreign_tables.total_time_ms IS 'Total time spent in the FDW, in milliseconds';
+COMMENT ON COLUMN wrappers_stat_foreign_tables.latency_histogram IS 'Number of requests by latency, in buckets of <= 1, 5, 10, 50, 100, 500, 1000, 5000 and > 5000 milliseconds';
+
+REVOKE ALL ON FUNCTION wrappers_stat_reset() FROM PUBLIC;
@@ -31,7 +31,7 @@ fn create_client(api_key: &str) -> Result<ClientWithMiddleware, AirtableFdwError
 }
 
 #[wrappers_fdw(
-    version = "0.1.5",
+    version = "0.1.6",
     author = "Ankur Goyal",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/airtable_fdw",
     error_type = "AirtableFdwError"
@@ -162,11 +162,10 @@ impl ForeignDataWrapper<AirtableFdwError> for AirtableFdw {
                 // state so starting with the simpler solution.
                 let url = self.set_limit_offset(&url, None, offset.as_deref())?;
 
-                let body = self.rt.block_on(client.get(&url).send()).and_then(|resp| {
-                    resp.error_for_status()
-                        .and_then(|resp| self.rt.block_on(resp.text()))
-                        .map_err(reqwest_middleware::Error::from)
-                })?;
+                let body = block_on_interruptible(&self.rt, async {
+                    let resp = client.get(&url).send().await?.error_for_status()?;
+                    resp.text().await.map_err(reqwest_middleware::Error::from)
+                })??;
 
                 // Security: Check response size to prevent DoS
                 if body.len() > self.max_response_size {
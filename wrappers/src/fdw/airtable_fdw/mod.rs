@@ -4,10 +4,13 @@ mod result;
 mod tests;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError, sanitize_error_message};
+use super::http_error::{reqwest_middleware_table_error_category, reqwest_table_error_category};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+    sanitize_error_message,
+};
 
 #[derive(Error, Debug)]
 enum AirtableFdwError {
@@ -38,6 +41,9 @@ enum AirtableFdwError {
     #[error("{0}")]
     OptionsError(#[from] OptionsError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("{0}")]
     NumericConversionError(#[from] pgrx::numeric::Error),
 
@@ -45,16 +51,36 @@ enum AirtableFdwError {
     ResponseTooLarge(usize, usize),
 }
 
+impl CategorizedError for AirtableFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::UnsupportedColumnType(_) => ErrorCategory::Unsupported,
+            Self::CreateRuntimeError(e) => e.category(),
+            Self::OptionsError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::UrlParseError(_) | Self::InvalidApiKeyHeader => ErrorCategory::InvalidOption,
+            Self::RequestError(e) => reqwest_table_error_category(e),
+            Self::RequestMiddlewareError(e) => reqwest_middleware_table_error_category(e),
+            Self::ColumnTypeNotMatch(_)
+            | Self::SerdeError(_)
+            | Self::NumericConversionError(_)
+            | Self::ResponseTooLarge(_, _) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<AirtableFdwError> for ErrorReport {
     fn from(value: AirtableFdwError) -> Self {
         match value {
             AirtableFdwError::CreateRuntimeError(e) => e.into(),
             AirtableFdwError::OptionsError(e) => e.into(),
+            AirtableFdwError::Interrupted(e) => e.into(),
             // SECURITY: Sanitize error messages to prevent credential leakage
             // HTTP errors may contain Authorization headers or API keys
             _ => {
                 let error_message = sanitize_error_message(&format!("{value}"));
-                ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+                value.category().error_report(error_message)
             }
         }
     }
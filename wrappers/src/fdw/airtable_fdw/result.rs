@@ -90,12 +90,12 @@ impl AirtableRecord {
         let mut row = Row::new();
 
         for col in columns.iter() {
-            if col.name == "id" {
-                row.push("id", Some(Cell::String(self.id.clone())));
+            if col.remote_name == "id" {
+                row.push(&col.name, Some(Cell::String(self.id.clone())));
                 continue;
             }
 
-            let col_name_lowercase = col.name.to_lowercase();
+            let col_name_lowercase = col.remote_name.to_lowercase();
             let cell = match col.type_oid {
                 pg_sys::BOOLOID => self.fields.0.get(&col_name_lowercase).map_or_else(
                     || Ok(None),
@@ -1,6 +1,6 @@
 use crate::fdw::auth0_fdw::auth0_client::row::ResultPayload;
+use crate::fdw::http_error::{reqwest_error_category, reqwest_middleware_error_category};
 use http::{HeaderMap, HeaderName, HeaderValue};
-use pgrx::PgSqlErrorCode;
 use pgrx::pg_sys::panic::ErrorReport;
 use reqwest::Url;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
@@ -57,7 +57,7 @@ impl Auth0Client {
     ) -> Result<ResultPayload, Auth0ClientError> {
         let rt = create_async_runtime()?;
 
-        rt.block_on(async {
+        block_on_interruptible(&rt, async {
             let mut url = self.url.clone();
             url.query_pairs_mut().append_pair("page", &page.to_string());
             if let Some(per_page) = per_page {
@@ -72,7 +72,7 @@ impl Auth0Client {
             let payload = response.json::<ResultPayload>().await?;
 
             Ok(payload)
-        })
+        })?
     }
 }
 #[derive(Error, Debug)]
@@ -80,6 +80,9 @@ pub(crate) enum Auth0ClientError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("invalid api_key header")]
     InvalidApiKeyHeader,
 
@@ -96,10 +99,25 @@ pub(crate) enum Auth0ClientError {
     UrlParseError(#[from] ParseError),
 }
 
+impl CategorizedError for Auth0ClientError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::InvalidApiKeyHeader | Self::UrlParseError(_) => ErrorCategory::InvalidOption,
+            Self::ReqwestError(e) => reqwest_error_category(e),
+            Self::ReqwestMiddlewareError(e) => reqwest_middleware_error_category(e),
+            Self::SerdeError(_) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<Auth0ClientError> for ErrorReport {
     fn from(value: Auth0ClientError) -> Self {
         match value {
             Auth0ClientError::CreateRuntimeError(e) => e.into(),
+            Auth0ClientError::Interrupted(e) => e.into(),
             // SECURITY: Sanitize error messages to prevent credential leakage
             // HTTP errors may contain Authorization headers with Bearer tokens
             Auth0ClientError::UrlParseError(_)
@@ -108,7 +126,7 @@ impl From<Auth0ClientError> for ErrorReport {
             | Auth0ClientError::ReqwestMiddlewareError(_)
             | Auth0ClientError::SerdeError(_) => {
                 let error_message = sanitize_error_message(&format!("{value}"));
-                ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+                value.category().error_report(error_message)
             }
         }
     }
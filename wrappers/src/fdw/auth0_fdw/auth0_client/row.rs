@@ -75,7 +75,7 @@ impl Auth0User {
     pub(crate) fn into_row(mut self, columns: &[Column]) -> Row {
         let mut row = Row::new();
         for tgt_col in columns {
-            let cell = match tgt_col.name.as_str() {
+            let cell = match tgt_col.remote_name.as_str() {
                 "user_id" => Some(Cell::String(self.user_id.clone())),
                 "email" => self.email.take().map(Cell::String),
                 "email_verified" => self.email_verified.take().map(Cell::Bool),
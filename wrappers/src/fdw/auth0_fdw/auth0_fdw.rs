@@ -7,13 +7,13 @@ use std::collections::HashMap;
 use supabase_wrappers::prelude::*;
 
 use crate::fdw::auth0_fdw::auth0_client::Auth0ClientError;
-use pgrx::PgSqlErrorCode;
+use crate::fdw::http_error::{reqwest_error_category, reqwest_middleware_error_category};
 use pgrx::pg_sys::panic::ErrorReport;
 use supabase_wrappers::utils::sanitize_error_message;
 use thiserror::Error;
 
 #[wrappers_fdw(
-    version = "0.1.2",
+    version = "0.1.3",
     author = "Joel",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/auth0_fdw",
     error_type = "Auth0FdwError"
@@ -60,6 +60,23 @@ enum Auth0FdwError {
     SetOneOfApiKeyAndApiKeyIdSet,
 }
 
+impl CategorizedError for Auth0FdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::Auth0ClientError(e) => e.category(),
+            Self::CreateRuntimeError(e) => e.category(),
+            Self::OptionsError(e) => e.category(),
+            Self::UrlParseError(_)
+            | Self::SecretNotFound(_)
+            | Self::URLOptionMissing
+            | Self::SetOneOfApiKeyAndApiKeyIdSet => ErrorCategory::InvalidOption,
+            Self::RequestError(e) => reqwest_error_category(e),
+            Self::RequestMiddlewareError(e) => reqwest_middleware_error_category(e),
+            Self::SerdeError(_) | Self::NumericConversionError(_) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<Auth0FdwError> for ErrorReport {
     fn from(value: Auth0FdwError) -> Self {
         match value {
@@ -71,7 +88,7 @@ impl From<Auth0FdwError> for ErrorReport {
             // tokens, or other credentials in request/response details
             _ => {
                 let error_message = sanitize_error_message(&format!("{value}"));
-                ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+                value.category().error_report(error_message)
             }
         }
     }
@@ -0,0 +1,25 @@
+//! Error categories of AWS SDK requests, shared by the FDWs using AWS SDK
+
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::SdkError;
+use supabase_wrappers::prelude::ErrorCategory;
+
+/// Classify an AWS SDK error by its response status or failure kind, the
+/// response status is mapped by `from_status`, e.g.
+/// [`ErrorCategory::from_table_http_status`] for the requests to the remote
+/// object which a foreign table represents
+pub(crate) fn sdk_error_category<E>(
+    err: &SdkError<E, HttpResponse>,
+    from_status: fn(u16) -> ErrorCategory,
+) -> ErrorCategory {
+    match err {
+        SdkError::ConstructionFailure(_) => ErrorCategory::InvalidOption,
+        SdkError::TimeoutError(_) => ErrorCategory::Timeout,
+        SdkError::DispatchFailure(e) if e.is_timeout() => ErrorCategory::Timeout,
+        SdkError::DispatchFailure(_) => ErrorCategory::Network,
+        _ => err
+            .raw_response()
+            .map(|resp| from_status(resp.status().as_u16()))
+            .unwrap_or(ErrorCategory::Remote),
+    }
+}
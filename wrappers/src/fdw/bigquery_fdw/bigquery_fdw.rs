@@ -4,22 +4,24 @@ use gcp_bigquery_client::{
     Client,
     client_builder::ClientBuilder,
     model::{
-        get_query_results_parameters::GetQueryResultsParameters, job_reference::JobReference,
-        query_request::QueryRequest, query_response::ResultSet,
+        get_query_results_parameters::GetQueryResultsParameters, job::Job,
+        job_configuration::JobConfiguration, job_configuration_query::JobConfigurationQuery,
+        job_reference::JobReference, query_request::QueryRequest, query_response::ResultSet,
         table_data_insert_all_request::TableDataInsertAllRequest,
     },
 };
-use pgrx::prelude::{AnyNumeric, Date, PgSqlErrorCode, Timestamp};
+use pgrx::prelude::{AnyNumeric, Date, Timestamp};
 use pgrx::{JsonB, pg_sys};
 use serde_json::json;
 use std::collections::HashMap;
 use std::str::FromStr;
+use uuid::Uuid;
 
 use crate::setup_rustls_default_crypto_provider;
 use supabase_wrappers::prelude::*;
 
 #[wrappers_fdw(
-    version = "0.1.6",
+    version = "0.1.7",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/bigquery_fdw",
     error_type = "BigQueryFdwError"
@@ -38,6 +40,23 @@ pub(crate) struct BigQueryFdw {
     auth_mock: Option<GoogleAuthMock>,
 }
 
+struct Formatter;
+
+impl CellFormatter for Formatter {
+    fn fmt_cell(&mut self, cell: &Cell) -> String {
+        format!("{cell}")
+    }
+
+    // quote identifier with backticks if it is not a plain identifier, see
+    // https://cloud.google.com/bigquery/docs/reference/standard-sql/lexical#quoted_identifiers
+    fn fmt_ident(&mut self, ident: &str) -> String {
+        if is_plain_ident(ident) {
+            return ident.to_owned();
+        }
+        format!("`{}`", ident.replace('\\', "\\\\").replace('`', "\\`"))
+    }
+}
+
 impl BigQueryFdw {
     const FDW_NAME: &'static str = "BigQueryFdw";
 
@@ -48,12 +67,13 @@ impl BigQueryFdw {
         sorts: &[Sort],
         limit: &Option<Limit>,
     ) -> String {
+        let mut formatter = Formatter;
         let tgts = if columns.is_empty() {
             "*".to_string()
         } else {
             columns
                 .iter()
-                .map(|c| c.name.clone())
+                .map(|c| formatter.fmt_ident(&c.remote_name))
                 .collect::<Vec<String>>()
                 .join(", ")
         };
@@ -68,7 +88,7 @@ impl BigQueryFdw {
         } else {
             let cond = quals
                 .iter()
-                .map(|q| q.deparse())
+                .map(|q| q.deparse_with_fmt(&mut formatter))
                 .collect::<Vec<String>>()
                 .join(" and ");
             format!("select {tgts} from {table} where {cond}")
@@ -78,7 +98,7 @@ impl BigQueryFdw {
         if !sorts.is_empty() {
             let order_by = sorts
                 .iter()
-                .map(|sort| sort.deparse())
+                .map(|sort| sort.deparse_with_fmt(&mut formatter))
                 .collect::<Vec<String>>()
                 .join(", ");
             sql.push_str(&format!(" order by {order_by}"));
@@ -109,44 +129,48 @@ impl BigQueryFdw {
         // convert BigQuery field to Cell
         for tgt_col in tgt_cols {
             let cell = match tgt_col.type_oid {
-                pg_sys::BOOLOID => rs.get_bool_by_name(&tgt_col.name)?.map(Cell::Bool),
-                pg_sys::CHAROID => rs.get_i64_by_name(&tgt_col.name)?.map(|v| Cell::I8(v as _)),
+                pg_sys::BOOLOID => rs.get_bool_by_name(&tgt_col.remote_name)?.map(Cell::Bool),
+                pg_sys::CHAROID => rs
+                    .get_i64_by_name(&tgt_col.remote_name)?
+                    .map(|v| Cell::I8(v as _)),
                 pg_sys::INT2OID => rs
-                    .get_i64_by_name(&tgt_col.name)?
+                    .get_i64_by_name(&tgt_col.remote_name)?
                     .map(|v| Cell::I16(v as _)),
                 pg_sys::INT4OID => rs
-                    .get_i64_by_name(&tgt_col.name)?
+                    .get_i64_by_name(&tgt_col.remote_name)?
                     .map(|v| Cell::I32(v as _)),
-                pg_sys::INT8OID => rs.get_i64_by_name(&tgt_col.name)?.map(Cell::I64),
+                pg_sys::INT8OID => rs.get_i64_by_name(&tgt_col.remote_name)?.map(Cell::I64),
                 pg_sys::FLOAT4OID => rs
-                    .get_f64_by_name(&tgt_col.name)?
+                    .get_f64_by_name(&tgt_col.remote_name)?
                     .map(|v| Cell::F32(v as _)),
-                pg_sys::FLOAT8OID => rs.get_f64_by_name(&tgt_col.name)?.map(Cell::F64),
-                pg_sys::NUMERICOID => match rs.get_f64_by_name(&tgt_col.name)? {
+                pg_sys::FLOAT8OID => rs.get_f64_by_name(&tgt_col.remote_name)?.map(Cell::F64),
+                pg_sys::NUMERICOID => match rs.get_f64_by_name(&tgt_col.remote_name)? {
                     Some(v) => Some(Cell::Numeric(AnyNumeric::try_from(v)?)),
                     None => None,
                 },
-                pg_sys::TEXTOID => rs.get_string_by_name(&tgt_col.name)?.map(Cell::String),
-                pg_sys::DATEOID => match rs.get_string_by_name(&tgt_col.name)? {
+                pg_sys::TEXTOID => rs
+                    .get_string_by_name(&tgt_col.remote_name)?
+                    .map(Cell::String),
+                pg_sys::DATEOID => match rs.get_string_by_name(&tgt_col.remote_name)? {
                     Some(v) => Some(Cell::Date(Date::from_str(&v)?)),
                     None => None,
                 },
                 pg_sys::TIMESTAMPOID | pg_sys::TIMESTAMPTZOID => {
                     // try convert BigQuery source field FieldType::Timestamp first,
                     // then try convert from FieldType::Datetime
-                    match rs.get_f64_by_name(&tgt_col.name) {
+                    match rs.get_f64_by_name(&tgt_col.remote_name) {
                         Ok(val) => val.map(|v| {
                             let ts = pgrx::prelude::to_timestamp(v);
                             Cell::Timestamp(ts.to_utc())
                         }),
-                        Err(_) => match rs.get_string_by_name(&tgt_col.name)? {
+                        Err(_) => match rs.get_string_by_name(&tgt_col.remote_name)? {
                             Some(v) => Some(Cell::Timestamp(Timestamp::from_str(&v)?)),
                             None => None,
                         },
                     }
                 }
                 pg_sys::JSONBOID => rs
-                    .get_json_value_by_name(&tgt_col.name)?
+                    .get_json_value_by_name(&tgt_col.remote_name)?
                     .map(|v| Cell::Json(JsonB(v))),
                 _ => {
                     return Err(BigQueryFdwError::UnsupportedFieldType(tgt_col.name.clone()));
@@ -218,22 +242,23 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
             Ok(sa_key) => sa_key,
             Err(err) => {
                 report_error(
-                    PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                    ErrorCategory::InvalidOption.sqlstate(),
                     &format!("parse service account key JSON failed: {err}"),
                 );
                 return Ok(ret);
             }
         };
 
-        ret.client = match ret.rt.block_on(
+        ret.client = match block_on_interruptible(
+            &ret.rt,
             ClientBuilder::new()
                 .with_v2_base_url(api_endpoint)
                 .build_from_service_account_key(sa_key, false),
-        ) {
+        )? {
             Ok(client) => Some(client),
             Err(err) => {
                 report_error(
-                    PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                    bq_error_category(&err).sqlstate(),
                     &format!("create client failed: {err}"),
                 );
                 None
@@ -277,24 +302,65 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
             match timeout_str.parse::<i32>() {
                 Ok(t) => timeout = t,
                 Err(_) => report_error(
-                    PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                    ErrorCategory::InvalidOption.sqlstate(),
                     &format!("invalid timeout value: {timeout_str}"),
                 ),
             }
         }
 
+        // don't wait longer than the remaining statement timeout
+        if let Some(remaining) = remaining_statement_timeout() {
+            timeout = timeout.min(remaining.as_millis().try_into().unwrap_or(i32::MAX));
+        }
+
         if let Some(client) = &self.client {
             let sql = self.deparse(quals, columns, sorts, limit);
-            let mut req = QueryRequest::new(sql);
-            req.location = Some(location);
-            req.timeout_ms = Some(timeout);
+            // use a client generated job id, so the job can be canceled even
+            // before BigQuery responds
+            let job_ref = JobReference {
+                job_id: Some(format!("wrappers_{}", Uuid::now_v7().simple())),
+                location: Some(location.clone()),
+                project_id: Some(self.project_id.clone()),
+            };
+            self.job_ref = Some(job_ref.clone());
+            let job = Job {
+                configuration: Some(JobConfiguration {
+                    query: Some(JobConfigurationQuery {
+                        query: sql,
+                        use_legacy_sql: Some(false),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                job_reference: Some(job_ref.clone()),
+                ..Default::default()
+            };
+            let job_id = job_ref.job_id.as_deref().unwrap_or_default();
+            let params = GetQueryResultsParameters {
+                location: Some(location),
+                timeout_ms: Some(timeout),
+                ..Default::default()
+            };
 
-            // execute query on BigQuery
-            match self.rt.block_on(client.job().query(&self.project_id, req)) {
+            // execute query on BigQuery and wait for its first page
+            let query_job = async {
+                match client.job().insert(&self.project_id, job).await {
+                    Ok(_) => {
+                        client
+                            .job()
+                            .get_query_results(&self.project_id, job_id, params)
+                            .await
+                    }
+                    Err(err) => Err(err),
+                }
+            };
+            match block_on_interruptible(&self.rt, query_job)? {
                 Ok(resp) => {
                     if resp.job_complete == Some(false) {
+                        // don't leave the job running on BigQuery
+                        self.cancel()?;
                         report_error(
-                            PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                            ErrorCategory::Timeout.sqlstate(),
                             &format!("query timeout {timeout}ms expired"),
                         );
                     } else {
@@ -324,14 +390,14 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
                         );
                         self.job_ref = resp.job_reference.clone();
                         self.page_token = resp.page_token.clone();
-                        let rs = ResultSet::new_from_query_response(resp);
+                        let rs = ResultSet::new_from_get_query_results_response(resp);
                         self.scan_result = Some(rs);
                     }
                 }
                 Err(err) => {
                     self.scan_result = None;
                     report_error(
-                        PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                        bq_error_category(&err).sqlstate(),
                         &format!("query failed: {err}"),
                     );
                 }
@@ -358,15 +424,18 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
             if let Some(job_ref) = &self.job_ref
                 && let Some(job_id) = &job_ref.job_id
             {
-                match self.rt.block_on(client.job().get_query_results(
-                    &self.project_id,
-                    job_id,
-                    GetQueryResultsParameters {
-                        location: job_ref.location.clone(),
-                        page_token: self.page_token.clone(),
-                        ..Default::default()
-                    },
-                )) {
+                match block_on_interruptible(
+                    &self.rt,
+                    client.job().get_query_results(
+                        &self.project_id,
+                        job_id,
+                        GetQueryResultsParameters {
+                            location: job_ref.location.clone(),
+                            page_token: self.page_token.clone(),
+                            ..Default::default()
+                        },
+                    ),
+                )? {
                     Ok(resp) => {
                         // replace result set with data from the new page
                         self.job_ref = resp.job_reference.clone();
@@ -379,7 +448,7 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
                     Err(err) => {
                         self.scan_result = None;
                         report_error(
-                            PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                            bq_error_category(&err).sqlstate(),
                             &format!("fetch query result failed: {err}"),
                         );
                     }
@@ -391,6 +460,23 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
 
     fn end_scan(&mut self) -> Result<(), BigQueryFdwError> {
         self.scan_result.take();
+        self.job_ref.take();
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Result<(), BigQueryFdwError> {
+        // stop the in-flight query job on BigQuery, cancellation is already
+        // pending so the call cannot be interruptible
+        if let Some(client) = &self.client
+            && let Some(job_ref) = self.job_ref.take()
+            && let Some(job_id) = &job_ref.job_id
+        {
+            self.rt.block_on(client.job().cancel_job(
+                &self.project_id,
+                job_id,
+                job_ref.location.as_deref(),
+            ))?;
+        }
         Ok(())
     }
 
@@ -434,15 +520,18 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
             insert_request.add_row(None, row_json)?;
 
             // execute insert job on BigQuery
-            let resp = self.rt.block_on(client.tabledata().insert_all(
-                &self.project_id,
-                &self.dataset_id,
-                &self.table,
-                insert_request,
-            ))?;
+            let resp = block_on_interruptible(
+                &self.rt,
+                client.tabledata().insert_all(
+                    &self.project_id,
+                    &self.dataset_id,
+                    &self.table,
+                    insert_request,
+                ),
+            )??;
             if let Some(errors) = resp.insert_errors {
                 report_error(
-                    PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                    ErrorCategory::InvalidData.sqlstate(),
                     &format!("insert failed: {errors:?}"),
                 );
             }
@@ -480,9 +569,9 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
             let query_job = client.job().query(&self.project_id, QueryRequest::new(sql));
 
             // execute update on BigQuery
-            if let Err(err) = self.rt.block_on(query_job) {
+            if let Err(err) = block_on_interruptible(&self.rt, query_job)? {
                 report_error(
-                    PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                    bq_error_category(&err).sqlstate(),
                     &format!("update failed: {err}"),
                 );
             }
@@ -500,9 +589,9 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
             let query_job = client.job().query(&self.project_id, QueryRequest::new(sql));
 
             // execute delete on BigQuery
-            if let Err(err) = self.rt.block_on(query_job) {
+            if let Err(err) = block_on_interruptible(&self.rt, query_job)? {
                 report_error(
-                    PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                    bq_error_category(&err).sqlstate(),
                     &format!("update failed: {err}"),
                 );
             }
@@ -511,7 +600,7 @@ impl ForeignDataWrapper<BigQueryFdwError> for BigQueryFdw {
     }
 }
 
-use crate::fdw::bigquery_fdw::{BigQueryFdwError, BigQueryFdwResult};
+use crate::fdw::bigquery_fdw::{BigQueryFdwError, BigQueryFdwResult, bq_error_category};
 use auth_mock::GoogleAuthMock;
 
 mod auth_mock {
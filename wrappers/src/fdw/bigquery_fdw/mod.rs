@@ -2,8 +2,10 @@
 
 use gcp_bigquery_client::error::BQError;
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::{PgSqlErrorCode, prelude::DateTimeConversionError};
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError};
+use pgrx::prelude::DateTimeConversionError;
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 use thiserror::Error;
 
 mod bigquery_fdw;
@@ -17,6 +19,9 @@ enum BigQueryFdwError {
     #[error("{0}")]
     OptionsError(#[from] OptionsError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("big query error: {0}")]
     BigQueryError(#[from] Box<BQError>),
 
@@ -30,17 +35,48 @@ enum BigQueryFdwError {
     DateTimeConversionError(#[from] DateTimeConversionError),
 }
 
+// classify a BigQuery client error by its response status or failure kind
+fn bq_error_category(err: &BQError) -> ErrorCategory {
+    match err {
+        BQError::InvalidServiceAccountKey(_) | BQError::InvalidServiceAccountAuthenticator(_) => {
+            ErrorCategory::InvalidOption
+        }
+        BQError::AuthError(_) | BQError::YupAuthError(_) => ErrorCategory::Auth,
+        BQError::RequestError(e) if e.is_timeout() => ErrorCategory::Timeout,
+        BQError::RequestError(e) => e
+            .status()
+            .map(|status| ErrorCategory::from_http_status(status.as_u16()))
+            .unwrap_or(ErrorCategory::Network),
+        BQError::ResponseError { error } => u16::try_from(error.error.code)
+            .map(ErrorCategory::from_http_status)
+            .unwrap_or(ErrorCategory::Remote),
+        _ => ErrorCategory::Remote,
+    }
+}
+
+impl CategorizedError for BigQueryFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::CreateRuntimeError(e) => e.category(),
+            Self::OptionsError(e) => e.category(),
+            // interrupted queries are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::BigQueryError(e) => bq_error_category(e),
+            Self::UnsupportedFieldType(_) => ErrorCategory::Unsupported,
+            Self::NumericConversionError(_) | Self::DateTimeConversionError(_) => {
+                ErrorCategory::InvalidData
+            }
+        }
+    }
+}
+
 impl From<BigQueryFdwError> for ErrorReport {
     fn from(value: BigQueryFdwError) -> Self {
         match value {
             BigQueryFdwError::CreateRuntimeError(e) => e.into(),
             BigQueryFdwError::OptionsError(e) => e.into(),
-            BigQueryFdwError::BigQueryError(e) => ErrorReport::new(
-                PgSqlErrorCode::ERRCODE_FDW_INVALID_DATA_TYPE,
-                format!("{e}"),
-                "",
-            ),
-            _ => ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), ""),
+            BigQueryFdwError::Interrupted(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
         }
     }
 }
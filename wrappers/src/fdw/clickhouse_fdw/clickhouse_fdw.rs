@@ -17,6 +17,7 @@ use regex::{Captures, Regex};
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::{
     runtime::{Builder, Runtime},
     task::JoinHandle,
@@ -27,6 +28,9 @@ use supabase_wrappers::prelude::*;
 
 use super::{ClickHouseFdwError, ClickHouseFdwResult};
 
+// how often the pending interrupts are checked while waiting for the next row
+const RECV_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
 struct Formatter;
 
 impl CellFormatter for Formatter {
@@ -36,6 +40,14 @@ impl CellFormatter for Formatter {
             _ => format!("{cell}"),
         }
     }
+
+    // quote identifier with backticks if it is not a plain identifier
+    fn fmt_ident(&mut self, ident: &str) -> String {
+        if is_plain_ident(ident) {
+            return ident.to_owned();
+        }
+        format!("`{}`", ident.replace('\\', "\\\\").replace('`', "\\`"))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,7 +66,7 @@ fn convert_row_simple(
         // find the column index in the source row
         let mut src_idx = None;
         for i in 0..src_row.len() {
-            if src_row.name(i)? == tgt_col.name {
+            if src_row.name(i)? == tgt_col.remote_name {
                 src_idx = Some(i);
                 break;
             }
@@ -363,7 +375,7 @@ where
 }
 
 #[wrappers_fdw(
-    version = "0.1.10",
+    version = "0.1.11",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/clickhouse_fdw",
     error_type = "ClickHouseFdwError"
@@ -442,13 +454,14 @@ impl ClickHouseFdw {
             self.table.clone()
         };
 
+        let mut formatter = Formatter {};
         let tgts = if columns.is_empty() {
             "*".to_string()
         } else {
             columns
                 .iter()
                 .filter(|c| !self.params.iter().any(|p| p.field == c.name))
-                .map(|c| c.name.clone())
+                .map(|c| formatter.fmt_ident(&c.remote_name))
                 .collect::<Vec<String>>()
                 .join(", ")
         };
@@ -456,7 +469,6 @@ impl ClickHouseFdw {
         let mut sql = format!("select {} from {}", tgts, &table);
 
         if !quals.is_empty() {
-            let mut formatter = Formatter {};
             let cond = quals
                 .iter()
                 .filter(|q| {
@@ -480,7 +492,7 @@ impl ClickHouseFdw {
         if !sorts.is_empty() {
             let order_by = sorts
                 .iter()
-                .map(|sort| sort.deparse())
+                .map(|sort| sort.deparse_with_fmt(&mut formatter))
                 .collect::<Vec<String>>()
                 .join(", ");
             sql.push_str(&format!(" order by {order_by}"));
@@ -500,7 +512,7 @@ impl ClickHouseFdw {
 
     fn fetch_next_row(&mut self) -> ClickHouseFdwResult<()> {
         if let Some(ref rx) = self.row_receiver {
-            match rx.recv() {
+            match recv_interruptible(rx)? {
                 Ok(row_result) => match row_result {
                     Ok(Some(mut row)) => {
                         // got a valid row from sender, and then do the numeric
@@ -645,7 +657,8 @@ impl ForeignDataWrapper<ClickHouseFdwError> for ClickHouseFdw {
         if let Some(converted_row) = self.current_row_data.take() {
             // process current row
             for (i, tgt_col) in self.tgt_cols.iter().enumerate() {
-                // check if this is a parameter column
+                // check if this is a parameter column, which is not in the remote
+                // table so it is matched by the local column name
                 if let Some(param) = self.params.iter().find(|&p| p.field == tgt_col.name) {
                     if let Value::Cell(cell) = &param.value {
                         row.push(&tgt_col.name, Some(cell.clone()));
@@ -668,6 +681,15 @@ impl ForeignDataWrapper<ClickHouseFdwError> for ClickHouseFdw {
         }
     }
 
+    fn cancel(&mut self) -> ClickHouseFdwResult<()> {
+        // dropping the streaming task closes its connection, which stops the
+        // query on ClickHouse
+        if let Some(task) = self.streaming_task.take() {
+            task.abort();
+        }
+        Ok(())
+    }
+
     fn end_scan(&mut self) -> ClickHouseFdwResult<()> {
         // clean up channel and state
         self.current_row_data = None;
@@ -704,11 +726,11 @@ impl ForeignDataWrapper<ClickHouseFdwError> for ClickHouseFdw {
     fn insert(&mut self, src: &Row) -> ClickHouseFdwResult<()> {
         // create a client for insert operations
         let pool = Pool::new(self.conn_str.clone());
-        let mut client = self.rt.block_on(pool.get_handle())?;
+        let mut client = block_on_interruptible(&self.rt, pool.get_handle())??;
 
         // use a dummy query to probe column types
         let sql = format!("select * from {} where false", self.table);
-        let probe = self.rt.block_on(client.query(&sql).fetch_all())?;
+        let probe = block_on_interruptible(&self.rt, client.query(&sql).fetch_all())??;
 
         // add row to block
         let mut row = Vec::new();
@@ -945,14 +967,14 @@ impl ForeignDataWrapper<ClickHouseFdwError> for ClickHouseFdw {
         block.push(row)?;
 
         // execute query on ClickHouse
-        self.rt.block_on(client.insert(&self.table, block))?;
+        block_on_interruptible(&self.rt, client.insert(&self.table, block))??;
         Ok(())
     }
 
     fn update(&mut self, rowid: &Cell, new_row: &Row) -> ClickHouseFdwResult<()> {
         // create a client for update operations
         let pool = Pool::new(self.conn_str.clone());
-        let mut client = self.rt.block_on(pool.get_handle())?;
+        let mut client = block_on_interruptible(&self.rt, pool.get_handle())??;
 
         let mut formatter = Formatter {};
         let mut sets = Vec::new();
@@ -976,14 +998,14 @@ impl ForeignDataWrapper<ClickHouseFdwError> for ClickHouseFdw {
         );
 
         // execute query on ClickHouse
-        self.rt.block_on(client.execute(&sql))?;
+        block_on_interruptible(&self.rt, client.execute(&sql))??;
         Ok(())
     }
 
     fn delete(&mut self, rowid: &Cell) -> ClickHouseFdwResult<()> {
         // create a client for delete operations
         let pool = Pool::new(self.conn_str.clone());
-        let mut client = self.rt.block_on(pool.get_handle())?;
+        let mut client = block_on_interruptible(&self.rt, pool.get_handle())??;
 
         let sql = format!(
             "alter table {} delete where {} = {}",
@@ -991,11 +1013,29 @@ impl ForeignDataWrapper<ClickHouseFdwError> for ClickHouseFdw {
         );
 
         // execute query on ClickHouse
-        self.rt.block_on(client.execute(&sql))?;
+        block_on_interruptible(&self.rt, client.execute(&sql))??;
         Ok(())
     }
 }
 
+// wait for the next message from the streaming task, unless the statement is
+// canceled
+fn recv_interruptible<T>(
+    rx: &channel::Receiver<T>,
+) -> ClickHouseFdwResult<Result<T, channel::RecvError>> {
+    loop {
+        match rx.recv_timeout(RECV_CHECK_INTERVAL) {
+            Ok(msg) => return Ok(Ok(msg)),
+            Err(channel::RecvTimeoutError::Disconnected) => return Ok(Err(channel::RecvError)),
+            Err(channel::RecvTimeoutError::Timeout) => {
+                if cancel_pending() {
+                    return Err(Interrupted.into());
+                }
+            }
+        }
+    }
+}
+
 async fn stream_data_to_channel(
     conn_str: String,
     sql: String,
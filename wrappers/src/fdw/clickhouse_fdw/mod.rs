@@ -2,12 +2,14 @@
 mod clickhouse_fdw;
 mod tests;
 
+use clickhouse_rs::errors::Error as ClickHouseError;
 use pgrx::datum::datetime_support::DateTimeConversionError;
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 
 #[derive(Error, Debug)]
 enum ClickHouseFdwError {
@@ -41,13 +43,64 @@ enum ClickHouseFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("{0}")]
     ClickHouseError(#[from] clickhouse_rs::errors::Error),
 }
 
+// classify a ClickHouse client error, server errors are mapped by the
+// ClickHouse error codes
+fn clickhouse_error_category(err: &ClickHouseError) -> ErrorCategory {
+    match err {
+        ClickHouseError::Io(_) | ClickHouseError::Connection(_) => ErrorCategory::Network,
+        ClickHouseError::Url(_) => ErrorCategory::InvalidOption,
+        ClickHouseError::FromSql(_) => ErrorCategory::InvalidData,
+        ClickHouseError::Server(e) => match e.code {
+            // AUTHENTICATION_FAILED, UNKNOWN_USER, WRONG_PASSWORD
+            516 | 192 | 193 => ErrorCategory::Auth,
+            // ACCESS_DENIED
+            497 => ErrorCategory::PermissionDenied,
+            // UNKNOWN_TABLE, UNKNOWN_DATABASE
+            60 | 81 => ErrorCategory::NotFound,
+            // TIMEOUT_EXCEEDED
+            159 => ErrorCategory::Timeout,
+            // TOO_MANY_SIMULTANEOUS_QUERIES
+            202 => ErrorCategory::RateLimited,
+            _ => ErrorCategory::Remote,
+        },
+        _ => ErrorCategory::Remote,
+    }
+}
+
+impl CategorizedError for ClickHouseFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::NoArrayParameter(_) | Self::UnmatchedParameter(_) => ErrorCategory::InvalidOption,
+            Self::UnsupportedColumnType(_) => ErrorCategory::Unsupported,
+            Self::ParseIntError(_)
+            | Self::NumericConversionError(_)
+            | Self::DatetimeConversionError(_)
+            | Self::DatetimeParseError(_)
+            | Self::UuidParseError(_) => ErrorCategory::InvalidData,
+            Self::OptionsError(e) => e.category(),
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted queries are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::ClickHouseError(e) => clickhouse_error_category(e),
+        }
+    }
+}
+
 impl From<ClickHouseFdwError> for ErrorReport {
     fn from(value: ClickHouseFdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            ClickHouseFdwError::OptionsError(e) => e.into(),
+            ClickHouseFdwError::CreateRuntimeError(e) => e.into(),
+            ClickHouseFdwError::Interrupted(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
@@ -35,39 +35,39 @@ impl IntoRow for UserType {
         let mut row = Row::new();
 
         for column in columns {
-            match column.name.as_str() {
+            match column.remote_name.as_str() {
                 "username" => {
-                    row.push("username", self.username.clone().map(Cell::String));
+                    row.push(&column.name, self.username.clone().map(Cell::String));
                 }
                 "attributes" => {
                     if let Some(ref attributes) = self.attributes {
                         let serialized_attributes = serialize_attributes(attributes);
                         let attributes_json_b = pgrx::JsonB(serialized_attributes);
-                        row.push("attributes", Some(Cell::Json(attributes_json_b)));
+                        row.push(&column.name, Some(Cell::Json(attributes_json_b)));
                     }
                 }
                 "created_at" => {
                     row.push(
-                        "created_at",
+                        &column.name,
                         self.user_create_date.map(convert_to_timestamp),
                     );
                 }
                 "updated_at" => {
                     row.push(
-                        "updated_at",
+                        &column.name,
                         self.user_last_modified_date.map(convert_to_timestamp),
                     );
                 }
                 "email" => {
                     let value = self.extract_attribute_value("email").map(Cell::String);
-                    row.push("email", value);
+                    row.push(&column.name, value);
                 }
                 "enabled" => {
-                    row.push("enabled", Some(Cell::Bool(self.enabled)));
+                    row.push(&column.name, Some(Cell::Bool(self.enabled)));
                 }
                 "status" => {
                     row.push(
-                        "status",
+                        &column.name,
                         self.user_status
                             .clone()
                             .map(|s| Cell::String(s.as_str().to_owned())),
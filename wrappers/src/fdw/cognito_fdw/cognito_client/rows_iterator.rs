@@ -3,7 +3,7 @@ use aws_sdk_cognitoidentityprovider::Client;
 use std::collections::VecDeque;
 use std::sync::Arc;
 
-use supabase_wrappers::prelude::{Column, Row, Runtime};
+use supabase_wrappers::prelude::{Column, Row, Runtime, block_on_interruptible};
 
 use super::super::CognitoFdwResult;
 use super::row::IntoRow;
@@ -48,9 +48,7 @@ impl RowsIterator {
             request = request.pagination_token(token.clone());
         }
 
-        let resp = self
-            .rt
-            .block_on(request.send())
+        let resp = block_on_interruptible(&self.rt, request.send())?
             .map_err(aws_sdk_cognitoidentityprovider::Error::from)?;
         self.pagination_token.clone_from(&resp.pagination_token);
         self.rows = resp
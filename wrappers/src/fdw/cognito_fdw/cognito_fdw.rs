@@ -12,7 +12,7 @@ use super::cognito_client::rows_iterator::RowsIterator;
 use super::{CognitoFdwError, CognitoFdwResult};
 
 #[wrappers_fdw(
-    version = "0.1.4",
+    version = "0.1.5",
     author = "Joel",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/cognito_fdw",
     error_type = "CognitoFdwError"
@@ -117,19 +117,18 @@ impl ForeignDataWrapper<CognitoFdwError> for CognitoFdw {
         &mut self,
         stmt: ImportForeignSchemaStmt,
     ) -> CognitoFdwResult<Vec<String>> {
-        Ok(vec![format!(
-            r#"create foreign table if not exists users (
-                username text,
-                email text,
-                status text,
-                enabled boolean,
-                created_at timestamp,
-                updated_at timestamp,
-                attributes jsonb
-            )
-            server {} options (object 'users')"#,
-            stmt.server_name
-        )])
+        let users = ForeignTableDef::new("users")
+            .columns([
+                ColumnDef::new("username", "text"),
+                ColumnDef::new("email", "text"),
+                ColumnDef::new("status", "text"),
+                ColumnDef::new("enabled", "boolean"),
+                ColumnDef::new("created_at", "timestamp"),
+                ColumnDef::new("updated_at", "timestamp"),
+                ColumnDef::new("attributes", "jsonb"),
+            ])
+            .option("object", "users");
+        Ok(stmt.build_ddl([users]))
     }
 
     fn validator(
@@ -3,11 +3,15 @@ mod cognito_client;
 mod cognito_fdw;
 mod tests;
 
-use pgrx::PgSqlErrorCode;
+use aws_sdk_cognitoidentityprovider::Error as CognitoError;
 use pgrx::pg_sys::panic::ErrorReport;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError, sanitize_error_message};
+use super::http_error::{reqwest_error_category, reqwest_middleware_error_category};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+    sanitize_error_message,
+};
 
 #[derive(Error, Debug)]
 enum CognitoFdwError {
@@ -20,6 +24,9 @@ enum CognitoFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("parse url failed: {0}")]
     UrlParseError(#[from] url::ParseError),
 
@@ -48,12 +55,41 @@ enum CognitoFdwError {
     SetOneOfSecretKeyAndApiKeyIdSet,
 }
 
+impl CategorizedError for CognitoFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::CognitoClientError(e) => match e {
+                CognitoError::NotAuthorizedException(_) => ErrorCategory::Auth,
+                CognitoError::ResourceNotFoundException(_) => ErrorCategory::NotFound,
+                CognitoError::TooManyRequestsException(_) => ErrorCategory::RateLimited,
+                CognitoError::InternalErrorException(_) => ErrorCategory::Unavailable,
+                _ => ErrorCategory::Remote,
+            },
+            Self::UnsupportedColumn(_) => ErrorCategory::Unsupported,
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::OptionsError(e) => e.category(),
+            Self::UrlParseError(_)
+            | Self::SecretNotFound(_)
+            | Self::ApiKeyAndSecretKeySet
+            | Self::SetOneOfSecretKeyAndApiKeyIdSet => ErrorCategory::InvalidOption,
+            Self::RequestError(e) => reqwest_error_category(e),
+            Self::RequestMiddlewareError(e) => reqwest_middleware_error_category(e),
+            Self::SerdeError(_) | Self::NumericConversionError(_) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<CognitoFdwError> for ErrorReport {
     fn from(value: CognitoFdwError) -> Self {
+        if let CognitoFdwError::Interrupted(e) = value {
+            return e.into();
+        }
         // SECURITY: Sanitize error messages to prevent credential leakage
         // Cognito errors may contain AWS credentials or tokens
         let error_message = sanitize_error_message(&format!("{value}"));
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+        value.category().error_report(error_message)
     }
 }
 
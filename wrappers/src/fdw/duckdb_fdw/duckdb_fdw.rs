@@ -8,8 +8,25 @@ use supabase_wrappers::prelude::*;
 
 use super::{DuckdbFdwError, DuckdbFdwResult, mapper, server_type::ServerType};
 
+struct Formatter;
+
+impl CellFormatter for Formatter {
+    fn fmt_cell(&mut self, cell: &Cell) -> String {
+        format!("{cell}")
+    }
+
+    // quote identifier with double quotes if it is not a plain identifier,
+    // DuckDB identifiers are case insensitive even when they are quoted
+    fn fmt_ident(&mut self, ident: &str) -> String {
+        if is_plain_ident(ident) {
+            return ident.to_owned();
+        }
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    }
+}
+
 #[wrappers_fdw(
-    version = "0.1.2",
+    version = "0.1.3",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/duckdb_fdw",
     error_type = "DuckdbFdwError"
@@ -53,12 +70,13 @@ impl DuckdbFdw {
         sorts: &[Sort],
         limit: &Option<Limit>,
     ) -> DuckdbFdwResult<String> {
+        let mut formatter = Formatter;
         let tgts = if columns.is_empty() {
             "*".to_string()
         } else {
             columns
                 .iter()
-                .map(|c| c.name.clone())
+                .map(|c| formatter.fmt_ident(&c.remote_name))
                 .collect::<Vec<String>>()
                 .join(", ")
         };
@@ -68,7 +86,7 @@ impl DuckdbFdw {
         if !quals.is_empty() {
             let cond = quals
                 .iter()
-                .map(|q| q.deparse())
+                .map(|q| q.deparse_with_fmt(&mut formatter))
                 .collect::<Vec<String>>()
                 .join(" and ");
 
@@ -82,7 +100,7 @@ impl DuckdbFdw {
             let order_by = sorts
                 .iter()
                 .map(|sort| {
-                    let mut clause = sort.field.to_string();
+                    let mut clause = formatter.fmt_ident(&sort.remote_field);
                     if sort.reversed {
                         clause.push_str(" desc");
                     } else {
@@ -107,14 +125,17 @@ impl DuckdbFdw {
         Ok(sql)
     }
 
-    fn get_table_ddl(
+    fn get_table_def(
         &self,
         tbl_duckdb: &str,
         tbl_pg: &str,
-        server_name: &str,
         is_strict: bool,
-    ) -> DuckdbFdwResult<String> {
-        let mut fields: Vec<String> = Vec::new();
+    ) -> DuckdbFdwResult<ForeignTableDef> {
+        // the table and column names were unquoted in the DDL before, so
+        // Postgres folded them to lower case, keep doing that as DuckDB
+        // identifiers are case insensitive anyway
+        let mut table =
+            ForeignTableDef::new(tbl_pg.to_ascii_lowercase()).option("table", tbl_duckdb);
 
         // 'information_schema.columns' table won't have the external table
         // column info as for now, so we use 'show' statement to fetch it from remote
@@ -124,32 +145,17 @@ impl DuckdbFdw {
         while let Some(col) = columns.next()? {
             let col_name = col.get::<_, String>("column_name")?;
             let col_type = col.get::<_, String>("column_type")?;
-            let is_null = if col.get::<_, String>("null")? == "YES" {
-                ""
-            } else {
-                "not null"
-            };
+            let not_null = col.get::<_, String>("null")? != "YES";
 
             if let Some(pg_type) = mapper::map_column_type(tbl_pg, &col_name, &col_type, is_strict)?
             {
-                fields.push(format!("{col_name} {pg_type} {is_null}"));
+                table = table.column(
+                    ColumnDef::new(col_name.to_ascii_lowercase(), pg_type).not_null(not_null),
+                );
             }
         }
 
-        let ret = if !fields.is_empty() {
-            format!(
-                r#"create foreign table if not exists {} ({})
-                server {} options (table '{}')"#,
-                tbl_pg,
-                fields.join(","),
-                server_name,
-                tbl_duckdb.replace("'", "''"),
-            )
-        } else {
-            String::default()
-        };
-
-        Ok(ret)
+        Ok(table)
     }
 }
 
@@ -189,19 +195,24 @@ impl ForeignDataWrapper<DuckdbFdwError> for DuckdbFdw {
             log_debug1(&format!("sql on DuckDB: {sql}"));
         }
 
-        // run sql query on DuckDB
-        let mut stmt = self.conn.prepare(&sql)?;
-        let tgt_rows: Result<Vec<_>, DuckdbFdwError> = stmt
-            .query_and_then([], |src_row| {
-                let mut tgt_row = Row::new();
-                for (col_idx, tgt_col) in columns.iter().enumerate() {
-                    let cell = mapper::map_cell(src_row, col_idx, tgt_col)?;
-                    tgt_row.push(&tgt_col.name, cell);
-                }
-                Ok(tgt_row)
-            })?
-            .collect();
-        self.scan_result = tgt_rows?;
+        // run sql query on DuckDB, it is interrupted if the statement is
+        // canceled
+        let interrupt = self.conn.interrupt_handle();
+        self.scan_result = run_interruptible(
+            || -> DuckdbFdwResult<Vec<Row>> {
+                let mut stmt = self.conn.prepare(&sql)?;
+                stmt.query_and_then([], |src_row| {
+                    let mut tgt_row = Row::new();
+                    for (col_idx, tgt_col) in columns.iter().enumerate() {
+                        let cell = mapper::map_cell(src_row, col_idx, tgt_col)?;
+                        tgt_row.push(&tgt_col.name, cell);
+                    }
+                    Ok(tgt_row)
+                })?
+                .collect()
+            },
+            move || interrupt.interrupt(),
+        )?;
 
         stats::inc_stats(
             Self::FDW_NAME,
@@ -244,8 +255,6 @@ impl ForeignDataWrapper<DuckdbFdwError> for DuckdbFdw {
     ) -> DuckdbFdwResult<Vec<String>> {
         let is_strict =
             require_option_or("strict", &import_stmt.options, "false").to_lowercase() == "true";
-        let mut ret: Vec<String> = Vec::new();
-
         // initialise DuckDB
         self.init_duckdb()?;
 
@@ -347,14 +356,13 @@ impl ForeignDataWrapper<DuckdbFdwError> for DuckdbFdw {
                 .collect()
         };
 
-        // get each table DDL
+        // get each table definition
+        let mut table_defs = Vec::new();
         for (tbl_duckdb, tbl_pg) in tables {
-            let ddl =
-                self.get_table_ddl(&tbl_duckdb, &tbl_pg, &import_stmt.server_name, is_strict)?;
-            ret.push(ddl);
+            table_defs.push(self.get_table_def(&tbl_duckdb, &tbl_pg, is_strict)?);
         }
 
-        Ok(ret)
+        Ok(import_stmt.build_ddl(table_defs))
     }
 
     fn validator(
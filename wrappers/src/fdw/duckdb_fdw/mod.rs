@@ -5,10 +5,11 @@ mod server_type;
 mod tests;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError, sanitize_error_message};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, OptionsError, sanitize_error_message,
+};
 
 #[derive(Error, Debug)]
 enum DuckdbFdwError {
@@ -46,12 +47,31 @@ enum DuckdbFdwError {
     CreateRuntime(#[from] CreateRuntimeError),
 }
 
+impl CategorizedError for DuckdbFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::ImportColumnError(_, _) | Self::UnsupportedColumnType(_) => {
+                ErrorCategory::Unsupported
+            }
+            Self::InvalidServerType(_) => ErrorCategory::InvalidOption,
+            Self::DatetimeConversionError(_)
+            | Self::NumericError(_)
+            | Self::ArrowError(_)
+            | Self::UuidConversionError(_)
+            | Self::JsonError(_) => ErrorCategory::InvalidData,
+            Self::Duckdb(_) => ErrorCategory::Remote,
+            Self::Options(e) => e.category(),
+            Self::CreateRuntime(e) => e.category(),
+        }
+    }
+}
+
 impl From<DuckdbFdwError> for ErrorReport {
     fn from(value: DuckdbFdwError) -> Self {
         // SECURITY: Sanitize error messages to prevent credential leakage
         // DuckDB errors may contain SQL statements with embedded secrets
         let error_message = sanitize_error_message(&format!("{value}"));
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+        value.category().error_report(error_message)
     }
 }
 
@@ -19,7 +19,7 @@ const DEFAULT_MAX_RESPONSE_SIZE: usize = 10 * 1024 * 1024;
 
 fn get_oauth2_token(sa_key: &str, rt: &Runtime) -> FirebaseFdwResult<AccessToken> {
     let creds = yup_oauth2::parse_service_account_key(sa_key.as_bytes())?;
-    let sa = rt.block_on(ServiceAccountAuthenticator::builder(creds).build())?;
+    let sa = block_on_interruptible(rt, ServiceAccountAuthenticator::builder(creds).build())??;
     let scopes = &[
         "https://www.googleapis.com/auth/cloud-platform",
         "https://www.googleapis.com/auth/firebase.database",
@@ -27,7 +27,7 @@ fn get_oauth2_token(sa_key: &str, rt: &Runtime) -> FirebaseFdwResult<AccessToken
         "https://www.googleapis.com/auth/identitytoolkit",
         "https://www.googleapis.com/auth/userinfo.email",
     ];
-    Ok(rt.block_on(sa.token(scopes))?)
+    Ok(block_on_interruptible(rt, sa.token(scopes))??)
 }
 
 fn body_to_rows(
@@ -49,8 +49,9 @@ fn body_to_rows(
 
         // extract normal columns
         for tgt_col in tgt_cols {
-            if let Some((src_name, col_name, col_type)) =
-                normal_cols.iter().find(|(_, c, _)| c == &tgt_col.name)
+            if let Some((src_name, col_name, col_type)) = normal_cols
+                .iter()
+                .find(|(_, c, _)| c == &tgt_col.remote_name)
             {
                 let v = obj
                     .as_object()
@@ -80,14 +81,14 @@ fn body_to_rows(
                         )));
                     }
                 };
-                row.push(col_name, cell);
+                row.push(&tgt_col.name, cell);
             }
         }
 
         // put all properties into 'attrs' JSON column
-        if tgt_cols.iter().any(|c| &c.name == "attrs") {
+        if let Some(attrs_col) = tgt_cols.iter().find(|c| &c.remote_name == "attrs") {
             let attrs = serde_json::from_str(&obj.to_string())?;
-            row.push("attrs", Some(Cell::Json(JsonB(attrs))));
+            row.push(&attrs_col.name, Some(Cell::Json(JsonB(attrs))));
         }
 
         result.push(row);
@@ -131,7 +132,7 @@ fn resp_to_rows(obj: &str, resp: &JsonValue, tgt_cols: &[Column]) -> FirebaseFdw
 }
 
 #[wrappers_fdw(
-    version = "0.1.3",
+    version = "0.1.4",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/firebase_fdw",
     error_type = "FirebaseFdwError"
@@ -305,17 +306,17 @@ impl ForeignDataWrapper<FirebaseFdwError> for FirebaseFdw {
             loop {
                 let url = self.build_url(obj, &next_page, options);
 
-                let body = self.rt.block_on(client.get(&url).send()).and_then(|resp| {
+                let body = block_on_interruptible(&self.rt, async {
+                    let resp = client.get(&url).send().await?;
                     stats::inc_stats(
                         Self::FDW_NAME,
                         stats::Metric::BytesIn,
                         resp.content_length().unwrap_or(0) as i64,
                     );
 
-                    resp.error_for_status()
-                        .and_then(|resp| self.rt.block_on(resp.text()))
-                        .map_err(reqwest_middleware::Error::from)
-                })?;
+                    let resp = resp.error_for_status()?;
+                    resp.text().await.map_err(reqwest_middleware::Error::from)
+                })??;
 
                 // Security: Check response size to prevent DoS
                 if body.len() > self.max_response_size {
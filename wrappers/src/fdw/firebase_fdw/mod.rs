@@ -3,11 +3,14 @@ mod firebase_fdw;
 mod tests;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use std::num::ParseIntError;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError, sanitize_error_message};
+use super::http_error::{reqwest_middleware_table_error_category, reqwest_table_error_category};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+    sanitize_error_message,
+};
 
 #[derive(Error, Debug)]
 enum FirebaseFdwError {
@@ -35,6 +38,9 @@ enum FirebaseFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("{0}")]
     OptionsError(#[from] OptionsError),
 
@@ -57,12 +63,40 @@ enum FirebaseFdwError {
     ResponseTooLarge(usize, usize),
 }
 
+impl CategorizedError for FirebaseFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::InvalidServiceAccount(_)
+            | Self::NoTokenFound(_)
+            | Self::OAuthTokenError(_)
+            | Self::InvalidApiKeyHeader => ErrorCategory::Auth,
+            Self::ObjectNotImplemented(_) | Self::UnsupportedColumnType(_) => {
+                ErrorCategory::Unsupported
+            }
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::OptionsError(e) => e.category(),
+            Self::LimitOptionParseError(_) => ErrorCategory::InvalidOption,
+            Self::RequestError(e) => reqwest_table_error_category(e),
+            Self::RequestMiddlewareError(e) => reqwest_middleware_table_error_category(e),
+            Self::InvalidTimestampFormat(_)
+            | Self::InvalidResponse(_)
+            | Self::JsonParseError(_)
+            | Self::ResponseTooLarge(_, _) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<FirebaseFdwError> for ErrorReport {
     fn from(value: FirebaseFdwError) -> Self {
+        if let FirebaseFdwError::Interrupted(e) = value {
+            return e.into();
+        }
         // SECURITY: Sanitize error messages to prevent credential leakage
         // Firebase errors may contain service account keys or OAuth tokens
         let error_message = sanitize_error_message(&format!("{value}"));
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+        value.category().error_report(error_message)
     }
 }
 
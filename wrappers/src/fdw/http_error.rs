@@ -0,0 +1,50 @@
+//! Error categories of HTTP requests, shared by the FDWs using reqwest
+
+use supabase_wrappers::prelude::ErrorCategory;
+
+// classify a reqwest error, the response status is mapped by `from_status`
+fn categorize(err: &reqwest::Error, from_status: fn(u16) -> ErrorCategory) -> ErrorCategory {
+    if err.is_timeout() {
+        ErrorCategory::Timeout
+    } else if let Some(status) = err.status() {
+        from_status(status.as_u16())
+    } else if err.is_connect() || err.is_request() {
+        ErrorCategory::Network
+    } else if err.is_decode() {
+        ErrorCategory::InvalidData
+    } else {
+        ErrorCategory::Remote
+    }
+}
+
+/// Classify a reqwest error by its response status or failure kind
+///
+/// A `404` response is not mapped to `NotFound`, because the request may
+/// target a single remote object rather than the table.
+pub(crate) fn reqwest_error_category(err: &reqwest::Error) -> ErrorCategory {
+    categorize(err, ErrorCategory::from_http_status)
+}
+
+/// Classify a reqwest error of a table-level endpoint, where a `404`
+/// response means the remote table doesn't exist
+pub(crate) fn reqwest_table_error_category(err: &reqwest::Error) -> ErrorCategory {
+    categorize(err, ErrorCategory::from_table_http_status)
+}
+
+/// Classify a reqwest middleware error, e.g. from a retried request
+pub(crate) fn reqwest_middleware_error_category(err: &reqwest_middleware::Error) -> ErrorCategory {
+    match err {
+        reqwest_middleware::Error::Reqwest(err) => reqwest_error_category(err),
+        reqwest_middleware::Error::Middleware(_) => ErrorCategory::Remote,
+    }
+}
+
+/// Classify a reqwest middleware error of a table-level endpoint
+pub(crate) fn reqwest_middleware_table_error_category(
+    err: &reqwest_middleware::Error,
+) -> ErrorCategory {
+    match err {
+        reqwest_middleware::Error::Reqwest(err) => reqwest_table_error_category(err),
+        reqwest_middleware::Error::Middleware(_) => ErrorCategory::Remote,
+    }
+}
@@ -16,7 +16,7 @@ use iceberg_catalog_rest::{RestCatalog, RestCatalogConfig};
 use iceberg_catalog_s3tables::{S3TablesCatalog, S3TablesCatalogConfig};
 use parquet::file::properties::WriterProperties;
 use pgrx::pg_sys;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 use supabase_wrappers::prelude::*;
@@ -32,7 +32,7 @@ use super::{
 use crate::stats;
 
 #[wrappers_fdw(
-    version = "0.1.4",
+    version = "0.1.5",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/iceberg_fdw",
     error_type = "IcebergFdwError"
@@ -79,7 +79,7 @@ impl IcebergFdw {
     // and convert it local cached row data
     fn next_batch(&mut self) -> IcebergFdwResult<()> {
         if let Some(stream) = &mut self.stream {
-            if let Some(result) = self.rt.block_on(stream.next()) {
+            if let Some(result) = block_on_interruptible(&self.rt, stream.next())? {
                 let batch = result?;
                 self.record_batch_to_row_data(&batch)?;
                 if batch.num_rows() > 0 {
@@ -100,7 +100,7 @@ impl IcebergFdw {
         self.row_data = VecDeque::with_capacity(batch.num_rows());
 
         for tgt_col in &self.tgt_cols {
-            let col_name = &tgt_col.name;
+            let col_name = &tgt_col.remote_name;
             let array = batch
                 .column_by_name(col_name)
                 .ok_or_else(|| IcebergFdwError::ColumnNotFound(col_name.into()))?;
@@ -141,7 +141,7 @@ impl IcebergFdw {
         if let Some(table) = &self.table {
             let mut scan_builder = table
                 .scan()
-                .select(self.tgt_cols.iter().map(|c| c.name.clone()))
+                .select(self.tgt_cols.iter().map(|c| c.remote_name.clone()))
                 .with_batch_size(Some(self.batch_size));
             if let Some(predicate) = &self.predicate {
                 scan_builder = scan_builder.with_filter(predicate.clone());
@@ -150,8 +150,8 @@ impl IcebergFdw {
 
             // debug the record count and data files has been scanned
             if cfg!(debug_assertions) {
-                let mut scan_files = self.rt.block_on(scan.plan_files())?;
-                while let Some(sf) = self.rt.block_on(scan_files.next()) {
+                let mut scan_files = block_on_interruptible(&self.rt, scan.plan_files())??;
+                while let Some(sf) = block_on_interruptible(&self.rt, scan_files.next())? {
                     let sf = sf.unwrap();
                     report_info(&format!(
                         "file scan: {:?}, {}",
@@ -161,7 +161,7 @@ impl IcebergFdw {
             }
 
             // convert to record stream and cache it locally
-            self.stream = self.rt.block_on(scan.to_arrow())?.into();
+            self.stream = block_on_interruptible(&self.rt, scan.to_arrow())??.into();
         }
 
         Ok(())
@@ -183,10 +183,11 @@ impl IcebergFdw {
             .name(tbl_ident.name().to_string())
             .schema(schema)
             .build();
-        let _ = self.rt.block_on(
+        let _ = block_on_interruptible(
+            &self.rt,
             self.catalog
                 .create_table(tbl_ident.namespace(), table_creation),
-        )?;
+        )??;
         Ok(())
     }
 
@@ -273,13 +274,14 @@ impl IcebergFdw {
                     partition_value,
                     metadata.default_partition_spec().spec_id(),
                 );
-                let mut data_file_writer = self.rt.block_on(data_file_writer_builder.build())?;
+                let mut data_file_writer =
+                    block_on_interruptible(&self.rt, data_file_writer_builder.build())??;
 
                 // write the record batch to Iceberg and close the writer and get
                 // the data file
-                self.rt
-                    .block_on(data_file_writer.write(record_batch.clone()))?;
-                let mut part_data_files = self.rt.block_on(data_file_writer.close())?;
+                block_on_interruptible(&self.rt, data_file_writer.write(record_batch.clone()))??;
+                let mut part_data_files =
+                    block_on_interruptible(&self.rt, data_file_writer.close())??;
 
                 data_files.append(&mut part_data_files);
             }
@@ -288,7 +290,8 @@ impl IcebergFdw {
             let tx = Transaction::new(table);
             let append_action = tx.fast_append().add_data_files(data_files.clone());
             let tx = append_action.apply(tx)?;
-            updated_table = self.rt.block_on(tx.commit(self.catalog.as_ref()))?.into();
+            updated_table =
+                block_on_interruptible(&self.rt, tx.commit(self.catalog.as_ref()))??.into();
         }
 
         // update the cached table reference with the new metadata
@@ -437,7 +440,10 @@ impl ForeignDataWrapper<IcebergFdwError> for IcebergFdw {
                     .table_bucket_arn(aws_s3table_arn.into())
                     .properties(props)
                     .build();
-                Box::new(rt.block_on(S3TablesCatalog::new(catalog_config))?)
+                Box::new(block_on_interruptible(
+                    &rt,
+                    S3TablesCatalog::new(catalog_config),
+                )??)
             } else {
                 let catalog_uri = require_option("catalog_uri", &props)?;
                 let warehouse = require_option_or("warehouse", &props, "warehouse");
@@ -480,11 +486,11 @@ impl ForeignDataWrapper<IcebergFdwError> for IcebergFdw {
         options: &HashMap<String, String>,
     ) -> IcebergFdwResult<()> {
         let tbl_ident = TableIdent::from_strs(require_option("table", options)?.split("."))?;
-        let table = self.rt.block_on(self.catalog.load_table(&tbl_ident))?;
+        let table = block_on_interruptible(&self.rt, self.catalog.load_table(&tbl_ident))??;
 
         let schema = table.metadata().current_schema();
         for tgt_col in columns {
-            let col_name = &tgt_col.name;
+            let col_name = &tgt_col.remote_name;
             let field = schema
                 .field_by_name_case_insensitive(col_name)
                 .ok_or_else(|| IcebergFdwError::ColumnNotFound(col_name.into()))?;
@@ -537,13 +543,15 @@ impl ForeignDataWrapper<IcebergFdwError> for IcebergFdw {
             .clamp(1, 65536);
 
         // create target table in Iceberg if needed
-        if create_table && !self.rt.block_on(self.catalog.table_exists(&tbl_ident))? {
+        if create_table
+            && !block_on_interruptible(&self.rt, self.catalog.table_exists(&tbl_ident))??
+        {
             let ftable_oid = require_option("wrappers.ftable_oid", options)?.parse::<u32>()?;
             self.create_iceberg_table(&tbl_ident, ftable_oid)?;
         }
 
         // load Iceberg table
-        let table = self.rt.block_on(self.catalog.load_table(&tbl_ident))?;
+        let table = block_on_interruptible(&self.rt, self.catalog.load_table(&tbl_ident))??;
 
         self.table = table.into();
         self.input_rows.clear();
@@ -586,39 +594,32 @@ impl ForeignDataWrapper<IcebergFdwError> for IcebergFdw {
 
         // get table list under specified remote schema
         let ns = NamespaceIdent::from_strs(stmt.remote_schema.split('.'))?;
-        let tbl_idents = self.rt.block_on(self.catalog.list_tables(&ns))?;
-
-        // filter out selected table name list
-        let all_tables: HashSet<&str> =
-            HashSet::from_iter(tbl_idents.iter().map(|i| i.name.as_str()));
-        let table_list = stmt.table_list.iter().map(|t| t.as_str()).collect();
-        let selected = match stmt.list_type {
-            ImportSchemaType::FdwImportSchemaAll => all_tables,
-            ImportSchemaType::FdwImportSchemaLimitTo => {
-                all_tables.intersection(&table_list).copied().collect()
-            }
-            ImportSchemaType::FdwImportSchemaExcept => {
-                all_tables.difference(&table_list).copied().collect()
-            }
-        };
+        let tbl_idents = block_on_interruptible(&self.rt, self.catalog.list_tables(&ns))??;
 
         // get selected table instances
         let tbls: Vec<Table> = tbl_idents
             .iter()
-            .filter(|t| selected.contains(t.name.as_str()))
-            .map(|t| self.rt.block_on(self.catalog.load_table(t)))
-            .collect::<Result<Vec<_>, _>>()?;
+            .filter(|t| stmt.is_table_selected(&t.name))
+            .map(|t| {
+                Ok(block_on_interruptible(
+                    &self.rt,
+                    self.catalog.load_table(t),
+                )??)
+            })
+            .collect::<IcebergFdwResult<Vec<_>>>()?;
 
-        let mut ret: Vec<String> = Vec::new();
+        let mut table_defs: Vec<ForeignTableDef> = Vec::new();
 
-        // generate DDL for each table
+        // generate table definition for each table
         for tbl in tbls {
             let schema = tbl.metadata().current_schema();
-            let mut fields: Vec<String> = Vec::new();
+            // the table name was unquoted in the DDL before, so keep it folded
+            // to lower case like Postgres did
+            let mut table_def = ForeignTableDef::new(tbl.identifier().name.to_ascii_lowercase())
+                .option("table", tbl.identifier().to_string());
 
             for field in schema.as_struct().fields() {
-                let field_name = pgrx::spi::quote_identifier(&field.name);
-                let not_null = if field.required { "not null" } else { "" };
+                let field_name = &field.name;
 
                 match *field.field_type {
                     Type::Primitive(ref p) => {
@@ -648,39 +649,27 @@ impl ForeignDataWrapper<IcebergFdwError> for IcebergFdw {
                                 continue;
                             }
                         };
-                        fields.push(format!("{field_name} {pg_type} {not_null}"));
+                        table_def = table_def
+                            .column(ColumnDef::new(field_name, pg_type).not_null(field.required));
                     }
                     Type::Struct(_) | Type::List(_) | Type::Map(_) => {
-                        fields.push(format!("{field_name} jsonb {not_null}"));
+                        table_def = table_def
+                            .column(ColumnDef::new(field_name, "jsonb").not_null(field.required));
                     }
                 }
             }
 
-            if !fields.is_empty() {
-                let ident_field_ids: Vec<i32> = schema.identifier_field_ids().collect();
-                let rowid_column = if ident_field_ids.len() == 1 {
-                    schema
-                        .field_by_id(ident_field_ids[0])
-                        .map(|field| format!(", rowid_column '{}'", field.name))
-                } else {
-                    None
-                };
-
-                ret.push(format!(
-                    r#"create foreign table if not exists {} (
-                        {}
-                    )
-                    server {} options (table '{}'{})"#,
-                    tbl.identifier().name,
-                    fields.join(","),
-                    stmt.server_name,
-                    tbl.identifier(),
-                    rowid_column.unwrap_or_default(),
-                ));
+            let ident_field_ids: Vec<i32> = schema.identifier_field_ids().collect();
+            if ident_field_ids.len() == 1
+                && let Some(field) = schema.field_by_id(ident_field_ids[0])
+            {
+                table_def = table_def.option("rowid_column", &field.name);
             }
+
+            table_defs.push(table_def);
         }
 
-        Ok(ret)
+        Ok(stmt.build_ddl(table_defs))
     }
 
     fn validator(
@@ -99,7 +99,7 @@ impl Mapper {
         rec_offset: usize,
     ) -> IcebergFdwResult<Cell> {
         let mut cell: Option<Cell> = None;
-        let col_name = &tgt_col.name;
+        let col_name = &tgt_col.remote_name;
         let array = src_array.as_any();
 
         // map source field to target column
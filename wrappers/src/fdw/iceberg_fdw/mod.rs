@@ -8,10 +8,11 @@ mod utils;
 mod writer;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{Cell, CreateRuntimeError, OptionsError};
+use supabase_wrappers::prelude::{
+    CategorizedError, Cell, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 
 #[derive(Error, Debug)]
 enum IcebergFdwError {
@@ -66,6 +67,9 @@ enum IcebergFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("{0}")]
     OptionsError(#[from] OptionsError),
 
@@ -73,9 +77,45 @@ enum IcebergFdwError {
     IoError(#[from] std::io::Error),
 }
 
+impl CategorizedError for IcebergFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::UnsupportedType(_) | Self::ImportColumnError(_, _) => ErrorCategory::Unsupported,
+            Self::VaultError(_) => ErrorCategory::InvalidOption,
+            Self::ColumnNotFound(_)
+            | Self::IncompatibleColumnType(_, _)
+            | Self::DecimalConversionError(_)
+            | Self::ParseIntError(_)
+            | Self::ParseFloatError(_)
+            | Self::DatetimeConversionError(_)
+            | Self::DatumConversionError(_)
+            | Self::UuidConversionError(_)
+            | Self::NumericError(_)
+            | Self::ArrowError(_)
+            | Self::JsonError(_) => ErrorCategory::InvalidData,
+            Self::IcebergError(e) => match e.kind() {
+                iceberg::ErrorKind::DataInvalid => ErrorCategory::InvalidData,
+                iceberg::ErrorKind::FeatureUnsupported => ErrorCategory::Unsupported,
+                _ => ErrorCategory::Remote,
+            },
+            Self::IoError(_) => ErrorCategory::Network,
+            Self::SpiError(_) => ErrorCategory::Remote,
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::OptionsError(e) => e.category(),
+        }
+    }
+}
+
 impl From<IcebergFdwError> for ErrorReport {
     fn from(value: IcebergFdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            IcebergFdwError::CreateRuntimeError(e) => e.into(),
+            IcebergFdwError::Interrupted(e) => e.into(),
+            IcebergFdwError::OptionsError(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
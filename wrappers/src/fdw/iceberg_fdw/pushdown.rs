@@ -103,8 +103,8 @@ pub(super) fn try_pushdown(table: &Table, quals: &[Qual]) -> IcebergFdwResult<Op
     let mut preds: Vec<Predicate> = Vec::new();
 
     for qual in quals {
-        if let Some(field) = schema.field_by_name(&qual.field) {
-            let term = Reference::new(&qual.field);
+        if let Some(field) = schema.field_by_name(&qual.remote_field) {
+            let term = Reference::new(&qual.remote_field);
             let tgt_type = field.field_type.as_ref();
 
             match &qual.value {
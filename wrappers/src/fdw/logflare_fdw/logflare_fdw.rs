@@ -91,7 +91,7 @@ fn json_value_to_cell(tgt_col: &Column, v: &JsonValue) -> LogflareFdwResult<Cell
 }
 
 #[wrappers_fdw(
-    version = "0.1.1",
+    version = "0.1.2",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/logflare_fdw",
     error_type = "LogflareFdwError"
@@ -175,7 +175,7 @@ impl LogflareFdw {
                                     })
                                 } else {
                                     // add normal cell
-                                    if let Some(s) = r.get(&tgt_col.name) {
+                                    if let Some(s) = r.get(&tgt_col.remote_name) {
                                         match json_value_to_cell(tgt_col, s) {
                                             Ok(cell) => Some(cell),
                                             Err(err) => return Err(err),
@@ -265,7 +265,8 @@ impl ForeignDataWrapper<LogflareFdwError> for LogflareFdw {
             let url = url.unwrap();
 
             // make api call
-            let body_text = self.rt.block_on(client.get(url).send()).and_then(|resp| {
+            let body_text = block_on_interruptible(&self.rt, async {
+                let resp = client.get(url).send().await?;
                 stats::inc_stats(
                     Self::FDW_NAME,
                     stats::Metric::BytesIn,
@@ -278,10 +279,9 @@ impl ForeignDataWrapper<LogflareFdwError> for LogflareFdw {
                     return Ok(String::new());
                 }
 
-                resp.error_for_status()
-                    .and_then(|resp| self.rt.block_on(resp.text()))
-                    .map_err(reqwest_middleware::Error::from)
-            })?;
+                let resp = resp.error_for_status()?;
+                resp.text().await.map_err(reqwest_middleware::Error::from)
+            })??;
             if body_text.is_empty() {
                 return Ok(());
             }
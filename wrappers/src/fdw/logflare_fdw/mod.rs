@@ -4,10 +4,13 @@ mod tests;
 
 use http::header::InvalidHeaderValue;
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError, sanitize_error_message};
+use super::http_error::{reqwest_middleware_table_error_category, reqwest_table_error_category};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+    sanitize_error_message,
+};
 
 #[derive(Error, Debug)]
 enum LogflareFdwError {
@@ -32,6 +35,9 @@ enum LogflareFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("parse url failed: {0}")]
     UrlParseError(#[from] url::ParseError),
 
@@ -51,12 +57,36 @@ enum LogflareFdwError {
     ResponseTooLarge(usize, usize),
 }
 
+impl CategorizedError for LogflareFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::NoEqualParameter(_)
+            | Self::NoArrayParameter(_)
+            | Self::UnsupportedColumnType(_) => ErrorCategory::Unsupported,
+            Self::OptionsError(e) => e.category(),
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::UrlParseError(_) | Self::InvalidApiKeyHeader(_) => ErrorCategory::InvalidOption,
+            Self::RequestError(e) => reqwest_table_error_category(e),
+            Self::RequestMiddlewareError(e) => reqwest_middleware_table_error_category(e),
+            Self::ColumnTypeNotMatch(_)
+            | Self::InvalidResponse(_)
+            | Self::JsonParseError(_)
+            | Self::ResponseTooLarge(_, _) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<LogflareFdwError> for ErrorReport {
     fn from(value: LogflareFdwError) -> Self {
+        if let LogflareFdwError::Interrupted(e) = value {
+            return e.into();
+        }
         // SECURITY: Sanitize error messages to prevent credential leakage
         // Logflare errors may contain API keys in headers or request details
         let error_message = sanitize_error_message(&format!("{value}"));
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+        value.category().error_report(error_message)
     }
 }
 
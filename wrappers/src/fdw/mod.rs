@@ -1,6 +1,23 @@
+#[cfg(any(
+    feature = "stripe_fdw",
+    feature = "firebase_fdw",
+    feature = "airtable_fdw",
+    feature = "logflare_fdw",
+    feature = "auth0_fdw",
+    feature = "cognito_fdw",
+    feature = "wasm_fdw"
+))]
+mod http_error;
+
+#[cfg(any(feature = "s3_fdw", feature = "s3vectors_fdw"))]
+mod aws_error;
+
 #[cfg(feature = "helloworld_fdw")]
 mod helloworld_fdw;
 
+#[cfg(any(test, feature = "pg_test"))]
+mod test_fdw;
+
 #[cfg(feature = "bigquery_fdw")]
 mod bigquery_fdw;
 
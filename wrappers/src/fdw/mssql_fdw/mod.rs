@@ -3,10 +3,11 @@ mod mssql_fdw;
 mod tests;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 
 #[derive(Error, Debug)]
 enum MssqlFdwError {
@@ -28,6 +29,9 @@ enum MssqlFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("{0}")]
     OptionsError(#[from] OptionsError),
 
@@ -35,9 +39,50 @@ enum MssqlFdwError {
     IoError(#[from] std::io::Error),
 }
 
+// classify a SQL Server client error, server errors are mapped by the SQL
+// Server error numbers
+fn tiberius_error_category(err: &tiberius::error::Error) -> ErrorCategory {
+    use tiberius::error::Error;
+
+    match err {
+        Error::Io { .. } => ErrorCategory::Network,
+        Error::Conversion(_) => ErrorCategory::InvalidData,
+        Error::Server(e) => match e.code() {
+            // login failed
+            18456 => ErrorCategory::Auth,
+            // permission denied on object or database
+            229 | 230 | 262 => ErrorCategory::PermissionDenied,
+            // invalid object name
+            208 => ErrorCategory::NotFound,
+            _ => ErrorCategory::Remote,
+        },
+        _ => ErrorCategory::Remote,
+    }
+}
+
+impl CategorizedError for MssqlFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::SyntaxError(_) | Self::UnsupportedColumnType(_) => ErrorCategory::Unsupported,
+            Self::ConversionError(_) | Self::PgrxNumericError(_) => ErrorCategory::InvalidData,
+            Self::TiberiusError(e) => tiberius_error_category(e),
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted queries are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::OptionsError(e) => e.category(),
+            Self::IoError(_) => ErrorCategory::Network,
+        }
+    }
+}
+
 impl From<MssqlFdwError> for ErrorReport {
     fn from(value: MssqlFdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            MssqlFdwError::CreateRuntimeError(e) => e.into(),
+            MssqlFdwError::Interrupted(e) => e.into(),
+            MssqlFdwError::OptionsError(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
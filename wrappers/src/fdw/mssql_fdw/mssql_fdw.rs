@@ -16,7 +16,7 @@ use super::{MssqlFdwError, MssqlFdwResult};
 
 // convert a source field to a wrappers cell
 fn field_to_cell(src_row: &tiberius::Row, tgt_col: &Column) -> MssqlFdwResult<Option<Cell>> {
-    let col_name = tgt_col.name.as_str();
+    let col_name = tgt_col.remote_name.as_str();
 
     let ret = match PgOid::from(tgt_col.type_oid) {
         PgOid::BuiltIn(PgBuiltInOids::BOOLOID) => {
@@ -91,10 +91,18 @@ impl CellFormatter for MssqlCellFormatter {
             _ => format!("{cell}"),
         }
     }
+
+    // quote identifier with brackets if it is not a plain identifier
+    fn fmt_ident(&mut self, ident: &str) -> String {
+        if is_plain_ident(ident) {
+            return ident.to_owned();
+        }
+        format!("[{}]", ident.replace(']', "]]"))
+    }
 }
 
 #[wrappers_fdw(
-    version = "0.1.3",
+    version = "0.1.4",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/mssql_fdw",
     error_type = "MssqlFdwError"
@@ -118,12 +126,13 @@ impl MssqlFdw {
         sorts: &[Sort],
         limit: &Option<Limit>,
     ) -> MssqlFdwResult<String> {
+        let mut formatter = MssqlCellFormatter {};
         let tgts = if columns.is_empty() {
             "*".to_string()
         } else {
             columns
                 .iter()
-                .map(|c| c.name.clone())
+                .map(|c| formatter.fmt_ident(&c.remote_name))
                 .collect::<Vec<String>>()
                 .join(", ")
         };
@@ -140,9 +149,17 @@ impl MssqlFdw {
                         // deparse boolean test qual, e.g. "bool_col is true" => "bool_col = 1"
                         if let Cell::Bool(_) = cell {
                             if oper == "is" {
-                                return format!("{} = {}", q.field, fmt.fmt_cell(cell));
+                                return format!(
+                                    "{} = {}",
+                                    fmt.fmt_ident(&q.remote_field),
+                                    fmt.fmt_cell(cell)
+                                );
                             } else if oper == "is not" {
-                                return format!("{} <> {}", q.field, fmt.fmt_cell(cell));
+                                return format!(
+                                    "{} <> {}",
+                                    fmt.fmt_ident(&q.remote_field),
+                                    fmt.fmt_cell(cell)
+                                );
                             }
                         }
                     }
@@ -161,7 +178,7 @@ impl MssqlFdw {
             let order_by = sorts
                 .iter()
                 .map(|sort| {
-                    let mut clause = sort.field.to_string();
+                    let mut clause = formatter.fmt_ident(&sort.remote_field);
                     if sort.reversed {
                         clause.push_str(" desc");
                     } else {
@@ -231,23 +248,20 @@ impl ForeignDataWrapper<MssqlFdwError> for MssqlFdw {
         self.iter_idx = 0;
 
         // create sql server client
-        let tcp = self
-            .rt
-            .block_on(TcpStream::connect(self.config.get_addr()))?;
+        let tcp = block_on_interruptible(&self.rt, TcpStream::connect(self.config.get_addr()))??;
         tcp.set_nodelay(true)?;
-        let mut client = self
-            .rt
-            .block_on(Client::connect(self.config.clone(), tcp.compat_write()))?;
+        let mut client = block_on_interruptible(
+            &self.rt,
+            Client::connect(self.config.clone(), tcp.compat_write()),
+        )??;
 
         // compile sql query to run on remote
         let sql = self.deparse(quals, columns, sorts, limit)?;
 
         // run query on remote sql server and store full result set locally
-        self.scan_result = self.rt.block_on(
-            self.rt
-                .block_on(client.simple_query(sql))?
-                .into_first_result(),
-        )?;
+        self.scan_result = block_on_interruptible(&self.rt, async {
+            client.simple_query(sql).await?.into_first_result().await
+        })??;
 
         stats::inc_stats(
             Self::FDW_NAME,
@@ -3,11 +3,10 @@ mod redis_fdw;
 mod tests;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
-use redis::RedisError;
+use redis::{ErrorKind, RedisError};
 use thiserror::Error;
 
-use supabase_wrappers::prelude::OptionsError;
+use supabase_wrappers::prelude::{CategorizedError, ErrorCategory, OptionsError};
 
 #[derive(Error, Debug)]
 enum RedisFdwError {
@@ -36,9 +35,37 @@ enum RedisFdwError {
     IoError(#[from] std::io::Error),
 }
 
+impl CategorizedError for RedisFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::UnsupportedSourceType(_)
+            | Self::OnlyOneColumn(_)
+            | Self::OnlyTwoColumn(_)
+            | Self::UnsupportedColumnName(_)
+            | Self::UnsupportedColumnType(_) => ErrorCategory::Unsupported,
+            Self::RedisError(e) if e.kind() == ErrorKind::AuthenticationFailed => {
+                ErrorCategory::Auth
+            }
+            Self::RedisError(e) if e.kind() == ErrorKind::TypeError => ErrorCategory::InvalidData,
+            Self::RedisError(e) if e.is_timeout() => ErrorCategory::Timeout,
+            Self::RedisError(e)
+                if e.is_connection_refusal() || e.is_connection_dropped() || e.is_io_error() =>
+            {
+                ErrorCategory::Network
+            }
+            Self::RedisError(_) => ErrorCategory::Remote,
+            Self::OptionsError(e) => e.category(),
+            Self::IoError(_) => ErrorCategory::Network,
+        }
+    }
+}
+
 impl From<RedisFdwError> for ErrorReport {
     fn from(value: RedisFdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            RedisFdwError::OptionsError(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
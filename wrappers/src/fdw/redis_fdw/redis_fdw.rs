@@ -4,6 +4,7 @@ use redis::{Client, Commands, Connection, TlsCertificates};
 use serde_json::json;
 use serde_json::value::Value as JsonValue;
 use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::setup_rustls_default_crypto_provider;
 use supabase_wrappers::prelude::*;
@@ -28,7 +29,9 @@ fn check_target_columns(
     }
 
     for col in cols {
-        let pos = expect_names.iter().position(|&name| name == col.name);
+        let pos = expect_names
+            .iter()
+            .position(|&name| name == col.remote_name);
         if let Some(pos) = pos {
             if col.type_oid != expect_types[pos].value() {
                 return Err(RedisFdwError::UnsupportedColumnType(col.name.clone()));
@@ -42,7 +45,7 @@ fn check_target_columns(
 }
 
 #[wrappers_fdw(
-    version = "0.1.1",
+    version = "0.1.2",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/redis_fdw",
     error_type = "RedisFdwError"
@@ -75,6 +78,21 @@ impl RedisFdw {
     const FDW_NAME: &'static str = "RedisFdw";
     const BUF_SIZE: isize = 256;
 
+    // open a connection whose calls cannot run past the statement timeout,
+    // the synchronous Redis client cannot be interrupted by query cancellation
+    fn connect(&self) -> RedisFdwResult<Connection> {
+        let Some(timeout) = remaining_statement_timeout() else {
+            return Ok(self.client.get_connection()?);
+        };
+
+        // zero timeout is not accepted by sockets
+        let timeout = timeout.max(Duration::from_millis(1));
+        let conn = self.client.get_connection_with_timeout(timeout)?;
+        conn.set_read_timeout(Some(timeout))?;
+        conn.set_write_timeout(Some(timeout))?;
+        Ok(conn)
+    }
+
     fn reset(&mut self) {
         self.iter_idx = 0;
         self.scan_result.clear();
@@ -139,10 +157,10 @@ impl RedisFdw {
         let val = &self.scan_result[self.iter_idx as usize * 2 + 1];
         let mut tgt_row = Row::new();
         for tgt_col in &self.tgt_cols {
-            if tgt_col.name == "key" {
+            if tgt_col.remote_name == "key" {
                 tgt_row.push(&tgt_col.name, Some(Cell::String(key.to_owned())));
             }
-            if tgt_col.name == "value" {
+            if tgt_col.remote_name == "value" {
                 tgt_row.push(&tgt_col.name, Some(Cell::String(val.to_owned())));
             }
         }
@@ -180,10 +198,10 @@ impl RedisFdw {
 
             let mut tgt_row = Row::new();
             for tgt_col in &self.tgt_cols {
-                if tgt_col.name == "id" {
+                if tgt_col.remote_name == "id" {
                     tgt_row.push(&tgt_col.name, Some(Cell::String(id.to_owned())));
                 }
-                if tgt_col.name == "items" {
+                if tgt_col.remote_name == "items" {
                     tgt_row.push(&tgt_col.name, Some(Cell::Json(JsonB(items.clone()))));
                 }
             }
@@ -226,10 +244,10 @@ impl RedisFdw {
 
             let mut tgt_row = Row::new();
             for tgt_col in &self.tgt_cols {
-                if tgt_col.name == "key" {
+                if tgt_col.remote_name == "key" {
                     tgt_row.push(&tgt_col.name, Some(Cell::String(key.to_owned())));
                 }
-                if tgt_col.name == "items" {
+                if tgt_col.remote_name == "items" {
                     tgt_row.push(&tgt_col.name, Some(Cell::Json(JsonB(items.clone()))));
                 }
             }
@@ -290,7 +308,7 @@ impl ForeignDataWrapper<RedisFdwError> for RedisFdw {
         let src_type = require_option("src_type", options)?.to_string();
         let src_key = require_option("src_key", options)?.to_string();
 
-        let mut conn = self.client.get_connection()?;
+        let mut conn = self.connect()?;
 
         self.reset();
 
@@ -7,10 +7,12 @@ use aws_sdk_s3::config::http::HttpResponse;
 use aws_sdk_s3::error::SdkError;
 use aws_sdk_s3::operation::get_object::GetObjectError;
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError};
+use super::aws_error::sdk_error_category;
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 
 #[derive(Error, Debug)]
 enum S3FdwError {
@@ -53,6 +55,9 @@ enum S3FdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("parse uri failed: {0}")]
     UriParseError(#[from] http::uri::InvalidUri),
 
@@ -66,9 +71,41 @@ enum S3FdwError {
     NumericConversionError(#[from] pgrx::numeric::Error),
 }
 
+impl CategorizedError for S3FdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::InvalidS3Uri(_)
+            | Self::InvalidFormatOption(_)
+            | Self::InvalidDelimiterOption(_)
+            | Self::InvalidCompressOption(_)
+            | Self::UriParseError(_) => ErrorCategory::InvalidOption,
+            Self::ReadLineError(_) => ErrorCategory::Network,
+            Self::ReadCsvError(_)
+            | Self::ReadJsonlError(_)
+            | Self::ReadParquetError(_)
+            | Self::ColumnTypeNotMatch(_)
+            | Self::ColumnNotFound(_)
+            | Self::JsonParseError(_)
+            | Self::NumericConversionError(_) => ErrorCategory::InvalidData,
+            Self::UnsupportedColumnType(_) => ErrorCategory::Unsupported,
+            Self::OptionsError(e) => e.category(),
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            // the S3 object is the remote table
+            Self::RequestError(e) => sdk_error_category(e, ErrorCategory::from_table_http_status),
+        }
+    }
+}
+
 impl From<S3FdwError> for ErrorReport {
     fn from(value: S3FdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            S3FdwError::OptionsError(e) => e.into(),
+            S3FdwError::CreateRuntimeError(e) => e.into(),
+            S3FdwError::Interrupted(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
@@ -209,9 +209,9 @@ impl S3Parquet {
                     .iter()
                     .map(|tgt_col| {
                         cols.iter()
-                            .position(|col| col.name() == tgt_col.name)
+                            .position(|col| col.name() == tgt_col.remote_name)
                             .unwrap_or_else(|| {
-                                panic!("column '{}' not found in parquet file", tgt_col.name)
+                                panic!("column '{}' not found in parquet file", tgt_col.remote_name)
                             })
                     })
                     .collect::<Vec<usize>>();
@@ -270,8 +270,8 @@ impl S3Parquet {
         if let Some(batch) = &self.batch {
             for tgt_col in tgt_cols {
                 let col = batch
-                    .column_by_name(&tgt_col.name)
-                    .ok_or(S3FdwError::ColumnNotFound(tgt_col.name.clone()))?;
+                    .column_by_name(&tgt_col.remote_name)
+                    .ok_or(S3FdwError::ColumnNotFound(tgt_col.remote_name.clone()))?;
 
                 macro_rules! col_to_cell {
                     ($array_type:ident, $cell_type:ident) => {{
@@ -25,7 +25,7 @@ enum Parser {
 }
 
 #[wrappers_fdw(
-    version = "0.1.6",
+    version = "0.1.7",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/s3_fdw",
     error_type = "S3FdwError"
@@ -65,7 +65,7 @@ impl S3Fdw {
             let mut total_lines = 0;
             let mut total_bytes = 0;
             loop {
-                let num_bytes = self.rt.block_on(rdr.read_line(&mut self.buf))?;
+                let num_bytes = block_on_interruptible(&self.rt, rdr.read_line(&mut self.buf))??;
                 total_lines += 1;
                 total_bytes += num_bytes;
                 if num_bytes == 0 || self.buf.len() > Self::BUF_SIZE {
@@ -188,7 +188,7 @@ impl ForeignDataWrapper<S3FdwError> for S3Fdw {
         let path_style_url =
             server.options.get("path_style_url").map(|s| s.as_str()) == Some("true");
 
-        let config = ret.rt.block_on(config_loader.load());
+        let config = block_on_interruptible(&ret.rt, config_loader.load())?;
 
         stats::inc_stats(Self::FDW_NAME, stats::Metric::CreateTimes, 1);
 
@@ -250,11 +250,12 @@ impl ForeignDataWrapper<S3FdwError> for S3Fdw {
                 _ => return Err(S3FdwError::InvalidFormatOption(format.to_string())),
             }
 
-            let stream = self
-                .rt
-                .block_on(client.get_object().bucket(&bucket).key(&object).send())?
-                .body
-                .into_async_read();
+            let stream = block_on_interruptible(
+                &self.rt,
+                client.get_object().bucket(&bucket).key(&object).send(),
+            )??
+            .body
+            .into_async_read();
 
             let mut boxed_stream: Pin<Box<dyn AsyncRead>> =
                 if let Some(compress) = options.get("compress") {
@@ -276,18 +277,15 @@ impl ForeignDataWrapper<S3FdwError> for S3Fdw {
                 if options.get("compress").is_some() {
                     // read all contents to local
                     let mut buf = Vec::new();
-                    self.rt
-                        .block_on(boxed_stream.read_to_end(&mut buf))
+                    block_on_interruptible(&self.rt, boxed_stream.read_to_end(&mut buf))?
                         .expect("read compressed parquet file failed");
-                    self.rt.block_on(s3parquet.open_local_stream(buf))?;
+                    block_on_interruptible(&self.rt, s3parquet.open_local_stream(buf))??;
                 } else {
                     // open async read stream
-                    self.rt.block_on(s3parquet.open_async_stream(
-                        client,
-                        &bucket,
-                        &object,
-                        &self.tgt_cols,
-                    ))?;
+                    block_on_interruptible(
+                        &self.rt,
+                        s3parquet.open_async_stream(client, &bucket, &object, &self.tgt_cols),
+                    )??;
                 }
                 return Ok(());
             }
@@ -299,7 +297,7 @@ impl ForeignDataWrapper<S3FdwError> for S3Fdw {
                 && has_header
             {
                 let mut header = String::new();
-                self.rt.block_on(rdr.read_line(&mut header))?;
+                block_on_interruptible(&self.rt, rdr.read_line(&mut header))??;
             }
 
             self.rdr = Some(rdr);
@@ -311,7 +309,7 @@ impl ForeignDataWrapper<S3FdwError> for S3Fdw {
     fn iter_scan(&mut self, row: &mut Row) -> S3FdwResult<Option<()>> {
         // read parquet record
         if let Parser::Parquet(s3parquet) = &mut self.parser {
-            if self.rt.block_on(s3parquet.refill())?.is_none() {
+            if block_on_interruptible(&self.rt, s3parquet.refill())??.is_none() {
                 return Ok(None);
             }
             let ret = s3parquet.read_into_row(row, &self.tgt_cols)?;
@@ -352,7 +350,7 @@ impl ForeignDataWrapper<S3FdwError> for S3Fdw {
                             if let Some(obj) = record.as_object() {
                                 for col in &self.tgt_cols {
                                     let cell = obj
-                                        .get(&col.name)
+                                        .get(&col.remote_name)
                                         .map(|val| match val {
                                             JsonValue::Null => None,
                                             JsonValue::Bool(v) => Some(Cell::String(v.to_string())),
@@ -13,10 +13,12 @@ use aws_sdk_s3vectors::{
     },
 };
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError};
+use super::aws_error::sdk_error_category;
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 
 #[derive(Error, Debug)]
 enum S3VectorsFdwError {
@@ -32,6 +34,9 @@ enum S3VectorsFdwError {
     #[error("rowid must be a string, but got: {0}")]
     InvalidRowId(String),
 
+    #[error("only 'on conflict (key) do update' which sets all the other columns is supported")]
+    UpsertNotSupported,
+
     #[error("{0:?}")]
     S3VectorListIndexesError(#[from] Box<SdkError<ListIndexesError>>),
 
@@ -56,6 +61,9 @@ enum S3VectorsFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("{0}")]
     OptionsError(#[from] OptionsError),
 
@@ -63,9 +71,42 @@ enum S3VectorsFdwError {
     IoError(#[from] std::io::Error),
 }
 
+impl CategorizedError for S3VectorsFdwError {
+    fn category(&self) -> ErrorCategory {
+        // the vector index is the remote table, but the bucket listing indexes
+        // is not
+        let from_table_status = ErrorCategory::from_table_http_status;
+        match self {
+            Self::QueryNotSupported | Self::UpsertNotSupported => ErrorCategory::Unsupported,
+            Self::InvalidS3Vec(_) | Self::InvalidInsertValue(_) | Self::InvalidRowId(_) => {
+                ErrorCategory::InvalidData
+            }
+            Self::S3VectorListIndexesError(e) => {
+                sdk_error_category(e, ErrorCategory::from_http_status)
+            }
+            Self::S3VectorGetVectorsError(e) => sdk_error_category(e, from_table_status),
+            Self::S3VectorQueryVectorsError(e) => sdk_error_category(e, from_table_status),
+            Self::S3VectorListVectorsError(e) => sdk_error_category(e, from_table_status),
+            Self::S3VectorPutVectorsError(e) => sdk_error_category(e, from_table_status),
+            Self::S3VectorDeleteVectorsError(e) => sdk_error_category(e, from_table_status),
+            Self::S3VectorsBuilderError(_) => ErrorCategory::InvalidData,
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::OptionsError(e) => e.category(),
+            Self::IoError(_) => ErrorCategory::Remote,
+        }
+    }
+}
+
 impl From<S3VectorsFdwError> for ErrorReport {
     fn from(value: S3VectorsFdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            S3VectorsFdwError::CreateRuntimeError(e) => e.into(),
+            S3VectorsFdwError::Interrupted(e) => e.into(),
+            S3VectorsFdwError::OptionsError(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
@@ -19,7 +19,7 @@ use super::s3vec::S3Vec;
 use super::{S3VectorsFdwError, S3VectorsFdwResult};
 
 #[wrappers_fdw(
-    version = "0.1.2",
+    version = "0.1.3",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/s3vectors_fdw",
     error_type = "S3VectorsFdwError"
@@ -53,7 +53,7 @@ impl S3VectorsFdw {
         self.has_next_page = false;
 
         if let Some(stream) = self.vectors_stream.as_mut()
-            && let Some(next_batch) = self.rt.block_on(stream.try_next())?
+            && let Some(next_batch) = block_on_interruptible(&self.rt, stream.try_next())??
         {
             self.has_next_page = next_batch.next_token.is_some();
             self.curr_vectors = next_batch.vectors.iter().map(S3Vec::from).collect();
@@ -101,7 +101,8 @@ impl S3VectorsFdw {
             }
         }
 
-        let result = self.rt.block_on(
+        let result = block_on_interruptible(
+            &self.rt,
             self.client
                 .get_vectors()
                 .set_vector_bucket_name(self.bucket_name.clone())
@@ -110,7 +111,7 @@ impl S3VectorsFdw {
                 .set_return_data(Some(true))
                 .set_return_metadata(Some(true))
                 .send(),
-        )?;
+        )??;
 
         self.curr_vectors = result.vectors.iter().map(S3Vec::from).collect();
 
@@ -121,7 +122,7 @@ impl S3VectorsFdw {
     // ref: https://docs.aws.amazon.com/AmazonS3/latest/API/API_S3VectorBuckets_QueryVectors.html
     fn query_vectors(&mut self) -> S3VectorsFdwResult<()> {
         let query_vector = {
-            let qual = self.quals.iter().find(|q| q.field == "data");
+            let qual = self.quals.iter().find(|q| q.remote_field == "data");
             if let Some(q) = qual {
                 if let Value::Cell(Cell::Bytea(bytea)) = &q.value {
                     let s3vec = if let Some(param) = &q.param {
@@ -146,7 +147,7 @@ impl S3VectorsFdw {
             }
         };
         let metadata_filter = self.quals.iter().find_map(|q| {
-            if q.field == "metadata"
+            if q.remote_field == "metadata"
                 && let Value::Cell(Cell::Json(json)) = &q.value
             {
                 let document = json_value_to_document(&json.0);
@@ -157,7 +158,8 @@ impl S3VectorsFdw {
         // return top 3 vectors by default
         let top_k = self.row_limit.map(|v| v as i32).or(Some(3));
 
-        let result = self.rt.block_on(
+        let result = block_on_interruptible(
+            &self.rt,
             self.client
                 .query_vectors()
                 .set_vector_bucket_name(self.bucket_name.clone())
@@ -168,7 +170,7 @@ impl S3VectorsFdw {
                 .set_return_distance(Some(true))
                 .set_return_metadata(Some(true))
                 .send(),
-        )?;
+        )??;
 
         self.curr_vectors = result.vectors.iter().map(S3Vec::from).collect();
 
@@ -182,14 +184,14 @@ impl S3VectorsFdw {
             return self.list_vectors();
         } else if self.quals.len() == 1 {
             let qual = &self.quals[0];
-            if qual.field == "key" && qual.operator.as_str() == "=" {
+            if qual.remote_field == "key" && qual.operator.as_str() == "=" {
                 return self.get_vectors();
-            } else if qual.field == "data" && qual.operator.as_str() == "<==>" {
+            } else if qual.remote_field == "data" && qual.operator.as_str() == "<==>" {
                 return self.query_vectors();
             }
         } else if self.quals.len() == 2
             && self.quals.iter().all(|qual| {
-                (qual.field == "data" || qual.field == "metadata")
+                (qual.remote_field == "data" || qual.remote_field == "metadata")
                     && qual.operator.as_str() == "<==>"
             })
         {
@@ -204,14 +206,15 @@ impl S3VectorsFdw {
             return Ok(());
         }
 
-        let _ = self.rt.block_on(
+        let _ = block_on_interruptible(
+            &self.rt,
             self.client
                 .put_vectors()
                 .set_vector_bucket_name(self.bucket_name.clone())
                 .set_index_name(self.index_name.clone())
                 .set_vectors(Some(self.insert_vectors.clone()))
                 .send(),
-        )?;
+        )??;
 
         self.insert_vectors.clear();
 
@@ -273,7 +276,7 @@ impl ForeignDataWrapper<S3VectorsFdwError> for S3VectorsFdw {
 
         // load AWS config and create client
         let config_loader = aws_config::defaults(BehaviorVersion::latest());
-        let config = rt.block_on(config_loader.load());
+        let config = block_on_interruptible(&rt, config_loader.load())?;
         let client = Client::new(&config);
 
         stats::inc_stats(Self::FDW_NAME, stats::Metric::CreateTimes, 1);
@@ -328,17 +331,17 @@ impl ForeignDataWrapper<S3VectorsFdwError> for S3VectorsFdw {
             // convert a vector to a row
             if let Some(vector) = self.curr_vectors.pop() {
                 for tgt_col in &self.tgt_cols {
-                    match tgt_col.name.as_str() {
+                    match tgt_col.remote_name.as_str() {
                         "key" => {
-                            row.push("key", Some(Cell::String(vector.key.clone())));
+                            row.push(&tgt_col.name, Some(Cell::String(vector.key.clone())));
                         }
                         "data" => {
                             let cbor_data = unsafe { pgrx::datum::cbor_encode(&vector) };
-                            row.push("data", Some(Cell::Bytea(cbor_data.cast_mut())));
+                            row.push(&tgt_col.name, Some(Cell::Bytea(cbor_data.cast_mut())));
                         }
                         "metadata" => {
                             row.push(
-                                "metadata",
+                                &tgt_col.name,
                                 vector.metadata.clone().map(|m| Cell::Json(JsonB(m))),
                             );
                         }
@@ -428,18 +431,46 @@ impl ForeignDataWrapper<S3VectorsFdwError> for S3VectorsFdw {
         Ok(())
     }
 
+    fn upsert_supported() -> bool {
+        true
+    }
+
+    fn upsert(
+        &mut self,
+        src: &Row,
+        conflict_columns: &[String],
+        action: &ConflictAction,
+    ) -> S3VectorsFdwResult<()> {
+        // PutVectors overwrites the whole existing vector with the same key,
+        // so all the other columns must be updated from the new row
+        let is_key_conflict = conflict_columns == ["key"];
+        let is_full_update = match action {
+            ConflictAction::DoUpdate { columns } => src
+                .cols
+                .iter()
+                .filter(|col| *col != "key")
+                .all(|col| columns.contains(col)),
+            ConflictAction::DoNothing => false,
+        };
+        if !is_key_conflict || !is_full_update {
+            return Err(S3VectorsFdwError::UpsertNotSupported);
+        }
+        self.insert(src)
+    }
+
     fn delete(&mut self, rowid: &Cell) -> S3VectorsFdwResult<()> {
         match rowid {
             Cell::String(key) => {
                 // delete the vector using the key
-                let _resp = self.rt.block_on(
+                let _resp = block_on_interruptible(
+                    &self.rt,
                     self.client
                         .delete_vectors()
                         .set_vector_bucket_name(self.bucket_name.clone())
                         .set_index_name(self.index_name.clone())
                         .set_keys(Some(vec![key.to_owned()]))
                         .send(),
-                )?;
+                )??;
             }
             _ => return Err(S3VectorsFdwError::InvalidRowId(format!("{rowid:?}"))),
         };
@@ -458,7 +489,7 @@ impl ForeignDataWrapper<S3VectorsFdwError> for S3VectorsFdw {
     ) -> S3VectorsFdwResult<Vec<String>> {
         let bucket_name = &import_stmt.remote_schema;
         let mut next_token: Option<String> = None;
-        let mut ret: Vec<String> = Vec::new();
+        let mut table_defs: Vec<ForeignTableDef> = Vec::new();
 
         loop {
             let mut request = self
@@ -471,26 +502,22 @@ impl ForeignDataWrapper<S3VectorsFdwError> for S3VectorsFdw {
                 request = request.set_next_token(Some(token));
             }
 
-            let resp = self.rt.block_on(request.send())?;
+            let resp = block_on_interruptible(&self.rt, request.send())??;
 
             for index in resp.indexes {
                 // for PostgreSQL table name compatibility
                 let table_name = index.index_name.replace('-', "_");
 
-                let table_sql = format!(
-                    r#"create foreign table if not exists {} (
-                        key text not null,
-                        data s3vec not null,
-                        metadata jsonb
-                    )
-                    server {} options (
-                        bucket_name '{}',
-                        index_name '{}',
-                        rowid_column 'key'
-                    )"#,
-                    table_name, import_stmt.server_name, bucket_name, index.index_name
-                );
-                ret.push(table_sql);
+                let table_def = ForeignTableDef::new(table_name)
+                    .columns([
+                        ColumnDef::new("key", "text").not_null(true),
+                        ColumnDef::new("data", "s3vec").not_null(true),
+                        ColumnDef::new("metadata", "jsonb"),
+                    ])
+                    .option("bucket_name", bucket_name)
+                    .option("index_name", index.index_name)
+                    .option("rowid_column", "key");
+                table_defs.push(table_def);
             }
 
             // check if there are more pages
@@ -500,7 +527,7 @@ impl ForeignDataWrapper<S3VectorsFdwError> for S3VectorsFdw {
             }
         }
 
-        Ok(ret)
+        Ok(import_stmt.build_ddl(table_defs))
     }
 
     fn validator(
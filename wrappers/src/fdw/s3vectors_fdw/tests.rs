@@ -2,38 +2,43 @@
 #[pgrx::pg_schema]
 mod tests {
     use pgrx::prelude::*;
+    use pgrx::spi::SpiClient;
+
+    fn create_foreign_tables(c: &mut SpiClient<'_>) {
+        c.update(
+            r#"CREATE FOREIGN DATA WRAPPER s3_vectors_wrapper
+                 HANDLER s3_vectors_fdw_handler VALIDATOR s3_vectors_fdw_validator"#,
+            None,
+            &[],
+        )
+        .unwrap();
+        c.update(
+            r#"CREATE SERVER s3_vectors_server
+                 FOREIGN DATA WRAPPER s3_vectors_wrapper
+                 OPTIONS (
+                   aws_access_key_id 'test',
+                   aws_secret_access_key 'test',
+                   endpoint_url 'http://localhost:4444',
+                   batch_size '200'
+                 )"#,
+            None,
+            &[],
+        )
+        .unwrap();
+        c.update(r#"CREATE SCHEMA IF NOT EXISTS s3_vectors"#, None, &[])
+            .unwrap();
+        c.update(
+            r#"IMPORT FOREIGN SCHEMA "my-vector-bucket" FROM SERVER s3_vectors_server INTO s3_vectors"#,
+            None,
+            &[],
+        )
+        .unwrap();
+    }
 
     #[pg_test]
     fn s3vectors_smoketest() {
         Spi::connect_mut(|c| {
-            c.update(
-                r#"CREATE FOREIGN DATA WRAPPER s3_vectors_wrapper
-                     HANDLER s3_vectors_fdw_handler VALIDATOR s3_vectors_fdw_validator"#,
-                None,
-                &[],
-            )
-            .unwrap();
-            c.update(
-                r#"CREATE SERVER s3_vectors_server
-                     FOREIGN DATA WRAPPER s3_vectors_wrapper
-                     OPTIONS (
-                       aws_access_key_id 'test',
-                       aws_secret_access_key 'test',
-                       endpoint_url 'http://localhost:4444',
-                       batch_size '200'
-                     )"#,
-                None,
-                &[],
-            )
-            .unwrap();
-            c.update(r#"CREATE SCHEMA IF NOT EXISTS s3_vectors"#, None, &[])
-                .unwrap();
-            c.update(
-                r#"IMPORT FOREIGN SCHEMA "my-vector-bucket" FROM SERVER s3_vectors_server INTO s3_vectors"#,
-                None,
-                &[],
-            )
-            .unwrap();
+            create_foreign_tables(c);
 
             let results = c
                 .select(
@@ -81,4 +86,60 @@ mod tests {
             .unwrap();
         });
     }
+
+    #[pg_test]
+    fn s3vectors_upsert_test() {
+        Spi::connect_mut(|c| {
+            create_foreign_tables(c);
+
+            c.update(
+                r#"INSERT INTO s3_vectors.my_vector_index (key, data, metadata)
+                    VALUES (
+                        'test-key1',
+                        '[1.0, 2.0, 1.0]',
+                        '{"model": "test1"}'::jsonb
+                    )
+                    ON CONFLICT (key) DO UPDATE
+                    SET data = EXCLUDED.data, metadata = EXCLUDED.metadata"#,
+                None,
+                &[],
+            )
+            .unwrap();
+        });
+    }
+
+    #[pg_test]
+    #[should_panic]
+    fn s3vectors_upsert_partial_update() {
+        Spi::connect_mut(|c| {
+            create_foreign_tables(c);
+
+            // metadata would be overwritten by PutVectors, so it must be set
+            c.update(
+                r#"INSERT INTO s3_vectors.my_vector_index (key, data, metadata)
+                    VALUES ('test-key1', '[1.0, 2.0, 1.0]', '{"model": "test1"}'::jsonb)
+                    ON CONFLICT (key) DO UPDATE SET data = EXCLUDED.data"#,
+                None,
+                &[],
+            )
+            .unwrap();
+        });
+    }
+
+    #[pg_test]
+    #[should_panic]
+    fn s3vectors_upsert_do_nothing() {
+        Spi::connect_mut(|c| {
+            create_foreign_tables(c);
+
+            c.update(
+                r#"INSERT INTO s3_vectors.my_vector_index (key, data, metadata)
+                    VALUES ('test-key1', '[1.0, 2.0, 1.0]', '{"model": "test1"}'::jsonb)
+                    ON CONFLICT (key) DO NOTHING"#,
+                None,
+                &[],
+            )
+            .unwrap();
+        });
+    }
 }
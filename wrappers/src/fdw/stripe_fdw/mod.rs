@@ -4,10 +4,13 @@ mod tests;
 
 use http::header::InvalidHeaderValue;
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError, sanitize_error_message};
+use super::http_error::{reqwest_error_category, reqwest_middleware_error_category};
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+    sanitize_error_message,
+};
 
 #[derive(Error, Debug)]
 enum StripeFdwError {
@@ -23,6 +26,9 @@ enum StripeFdwError {
     #[error("{0}")]
     CreateRuntimeError(#[from] CreateRuntimeError),
 
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
+
     #[error("parse url failed: {0}")]
     UrlParseError(#[from] url::ParseError),
 
@@ -48,12 +54,36 @@ enum StripeFdwError {
     ResponseTooLarge(usize, usize),
 }
 
+impl CategorizedError for StripeFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::UnsupportedColumnType(_) | Self::ObjectNotImplemented(_) => {
+                ErrorCategory::Unsupported
+            }
+            Self::OptionsError(e) => e.category(),
+            Self::CreateRuntimeError(e) => e.category(),
+            // interrupted requests are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+            Self::UrlParseError(_) | Self::InvalidApiKeyHeader(_) => ErrorCategory::InvalidOption,
+            Self::RequestError(e) => reqwest_error_category(e),
+            Self::RequestMiddlewareError(e) => reqwest_middleware_error_category(e),
+            Self::JsonParseError(_)
+            | Self::InvalidResponse
+            | Self::InvalidStats(_)
+            | Self::ResponseTooLarge(_, _) => ErrorCategory::InvalidData,
+        }
+    }
+}
+
 impl From<StripeFdwError> for ErrorReport {
     fn from(value: StripeFdwError) -> Self {
+        if let StripeFdwError::Interrupted(e) = value {
+            return e.into();
+        }
         // SECURITY: Sanitize error messages to prevent credential leakage
         // Stripe errors may contain API keys in headers or request details
         let error_message = sanitize_error_message(&format!("{value}"));
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, error_message, "")
+        value.category().error_report(error_message)
     }
 }
 
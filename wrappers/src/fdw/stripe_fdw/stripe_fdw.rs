@@ -4,7 +4,7 @@ use reqwest::{self, StatusCode, Url, header};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
 use serde_json::{Map as JsonMap, Number, Value as JsonValue, json};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use supabase_wrappers::prelude::*;
 
@@ -505,7 +505,8 @@ fn body_to_rows(
 
         // extract normal columns
         for tgt_col in tgt_cols {
-            if let Some((col_name, col_type)) = normal_cols.iter().find(|(c, _)| c == &tgt_col.name)
+            if let Some((col_name, col_type)) =
+                normal_cols.iter().find(|(c, _)| c == &tgt_col.remote_name)
             {
                 let cell = obj
                     .as_object()
@@ -520,11 +521,11 @@ fn body_to_rows(
                         }),
                         _ => None,
                     });
-                row.push(col_name, cell);
-            } else if &tgt_col.name == "attrs" {
+                row.push(&tgt_col.name, cell);
+            } else if &tgt_col.remote_name == "attrs" {
                 // put all properties into 'attrs' JSON column
                 let attrs = serde_json::from_str(&obj.to_string())?;
-                row.push("attrs", Some(Cell::Json(JsonB(attrs))));
+                row.push(&tgt_col.name, Some(Cell::Json(JsonB(attrs))));
             }
         }
 
@@ -596,7 +597,7 @@ fn pushdown_quals(
     // for scan with a single id query param, optimized to single object GET request
     if quals.len() == 1 {
         let qual = &quals[0];
-        if qual.field == "id"
+        if qual.remote_field == "id"
             && qual.operator == "="
             && !qual.use_or
             && let Value::Cell(Cell::String(id)) = &qual.value
@@ -611,7 +612,7 @@ fn pushdown_quals(
     // pushdown quals
     for qual in quals {
         for field in &fields {
-            if qual.field == *field
+            if qual.remote_field == *field
                 && qual.operator == "="
                 && !qual.use_or
                 && let Value::Cell(cell) = &qual.value
@@ -667,7 +668,7 @@ fn inc_stats_request_cnt(stats_metadata: &mut JsonB) -> StripeFdwResult<()> {
 }
 
 #[wrappers_fdw(
-    version = "0.1.12",
+    version = "0.1.13",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/stripe_fdw",
     error_type = "StripeFdwError"
@@ -826,7 +827,8 @@ impl ForeignDataWrapper<StripeFdwError> for StripeFdw {
                 inc_stats_request_cnt(&mut stats_metadata)?;
 
                 // make api call
-                let body = self.rt.block_on(client.get(url).send()).and_then(|resp| {
+                let body = block_on_interruptible(&self.rt, async {
+                    let resp = client.get(url).send().await?;
                     stats::inc_stats(
                         Self::FDW_NAME,
                         stats::Metric::BytesIn,
@@ -839,10 +841,9 @@ impl ForeignDataWrapper<StripeFdwError> for StripeFdw {
                         return Ok(String::new());
                     }
 
-                    resp.error_for_status()
-                        .and_then(|resp| self.rt.block_on(resp.text()))
-                        .map_err(reqwest_middleware::Error::from)
-                })?;
+                    let resp = resp.error_for_status()?;
+                    resp.text().await.map_err(reqwest_middleware::Error::from)
+                })??;
                 if body.is_empty() {
                     break;
                 }
@@ -925,21 +926,20 @@ impl ForeignDataWrapper<StripeFdwError> for StripeFdw {
             inc_stats_request_cnt(&mut stats_metadata)?;
 
             // call Stripe API
-            let body = self
-                .rt
-                .block_on(client.post(url).form(&body).send())
-                .and_then(|resp| {
-                    resp.error_for_status()
-                        .and_then(|resp| {
-                            stats::inc_stats(
-                                Self::FDW_NAME,
-                                stats::Metric::BytesIn,
-                                resp.content_length().unwrap_or(0) as i64,
-                            );
-                            self.rt.block_on(resp.text())
-                        })
-                        .map_err(reqwest_middleware::Error::from)
-                })?;
+            let body = block_on_interruptible(&self.rt, async {
+                let resp = client
+                    .post(url)
+                    .form(&body)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                stats::inc_stats(
+                    Self::FDW_NAME,
+                    stats::Metric::BytesIn,
+                    resp.content_length().unwrap_or(0) as i64,
+                );
+                resp.text().await.map_err(reqwest_middleware::Error::from)
+            })??;
 
             // Security: Check response size to prevent DoS
             if body.len() > self.max_response_size {
@@ -974,21 +974,20 @@ impl ForeignDataWrapper<StripeFdwError> for StripeFdw {
                     inc_stats_request_cnt(&mut stats_metadata)?;
 
                     // call Stripe API
-                    let body = self
-                        .rt
-                        .block_on(client.post(url).form(&body).send())
-                        .and_then(|resp| {
-                            resp.error_for_status()
-                                .and_then(|resp| {
-                                    stats::inc_stats(
-                                        Self::FDW_NAME,
-                                        stats::Metric::BytesIn,
-                                        resp.content_length().unwrap_or(0) as i64,
-                                    );
-                                    self.rt.block_on(resp.text())
-                                })
-                                .map_err(reqwest_middleware::Error::from)
-                        })?;
+                    let body = block_on_interruptible(&self.rt, async {
+                        let resp = client
+                            .post(url)
+                            .form(&body)
+                            .send()
+                            .await?
+                            .error_for_status()?;
+                        stats::inc_stats(
+                            Self::FDW_NAME,
+                            stats::Metric::BytesIn,
+                            resp.content_length().unwrap_or(0) as i64,
+                        );
+                        resp.text().await.map_err(reqwest_middleware::Error::from)
+                    })??;
 
                     // Security: Check response size to prevent DoS
                     if body.len() > self.max_response_size {
@@ -1022,21 +1021,15 @@ impl ForeignDataWrapper<StripeFdwError> for StripeFdw {
                     inc_stats_request_cnt(&mut stats_metadata)?;
 
                     // call Stripe API
-                    let body = self
-                        .rt
-                        .block_on(client.delete(url).send())
-                        .and_then(|resp| {
-                            resp.error_for_status()
-                                .and_then(|resp| {
-                                    stats::inc_stats(
-                                        Self::FDW_NAME,
-                                        stats::Metric::BytesIn,
-                                        resp.content_length().unwrap_or(0) as i64,
-                                    );
-                                    self.rt.block_on(resp.text())
-                                })
-                                .map_err(reqwest_middleware::Error::from)
-                        })?;
+                    let body = block_on_interruptible(&self.rt, async {
+                        let resp = client.delete(url).send().await?.error_for_status()?;
+                        stats::inc_stats(
+                            Self::FDW_NAME,
+                            stats::Metric::BytesIn,
+                            resp.content_length().unwrap_or(0) as i64,
+                        );
+                        resp.text().await.map_err(reqwest_middleware::Error::from)
+                    })??;
 
                     // Security: Check response size to prevent DoS
                     if body.len() > self.max_response_size {
@@ -1063,44 +1056,14 @@ impl ForeignDataWrapper<StripeFdwError> for StripeFdw {
         &mut self,
         stmt: ImportForeignSchemaStmt,
     ) -> StripeFdwResult<Vec<String>> {
-        let tbl_config: TableConfig = HashMap::from_iter(
-            self.table_config
-                .iter()
-                .map(|(k, v)| (v.0, (*k, v.1.clone(), v.2.clone()))),
-        );
-        let all_tables: HashSet<&str> = HashSet::from_iter(tbl_config.keys().copied());
-        let table_list = stmt.table_list.iter().map(|t| t.as_str()).collect();
-        let selected = match stmt.list_type {
-            ImportSchemaType::FdwImportSchemaAll => all_tables,
-            ImportSchemaType::FdwImportSchemaLimitTo => {
-                all_tables.intersection(&table_list).copied().collect()
-            }
-            ImportSchemaType::FdwImportSchemaExcept => {
-                all_tables.difference(&table_list).copied().collect()
-            }
-        };
-        let ret = selected
-            .iter()
-            .map(|tbl| {
-                tbl_config
-                    .get(tbl)
-                    .map(|cfg| {
-                        let cols = cfg
-                            .2
-                            .iter()
-                            .map(|col| format!("{} {}", col.0, col.1))
-                            .collect::<Vec<String>>()
-                            .join(",");
-                        format!(
-                            r#"create foreign table if not exists {} ({},attrs jsonb)
-                            server {} options (object '{}', rowid_column 'id')"#,
-                            tbl, cols, stmt.server_name, cfg.0,
-                        )
-                    })
-                    .unwrap_or_default()
-            })
-            .collect();
-        Ok(ret)
+        let tables = self.table_config.iter().map(|(obj, (tbl, _, cols))| {
+            ForeignTableDef::new(*tbl)
+                .columns(cols.iter().map(|(name, typ)| ColumnDef::new(*name, *typ)))
+                .column(ColumnDef::new("attrs", "jsonb"))
+                .option("object", *obj)
+                .option("rowid_column", "id")
+        });
+        Ok(stmt.build_ddl(tables))
     }
 
     fn validator(
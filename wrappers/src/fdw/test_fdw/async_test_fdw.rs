@@ -0,0 +1,212 @@
+use pgrx::PgSqlErrorCode;
+use pgrx::pg_sys::panic::ErrorReport;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::thread;
+use supabase_wrappers::prelude::*;
+
+// calls made to the instances of this FDW, in the order of calls
+pub(super) static ASYNC_EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record(event: impl Into<String>) {
+    ASYNC_EVENTS.lock().unwrap().push(event.into());
+}
+
+fn format_row(row: &Row) -> String {
+    row.iter()
+        .map(|(col, cell)| match cell {
+            Some(cell) => format!("{col}={cell}"),
+            None => format!("{col}=null"),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// row ids of a page, and if the page was fetched off the backend thread
+type Page = Option<(Vec<i64>, bool)>;
+
+// An async FDW driven through the blanket ForeignDataWrapper adapter. It scans
+// `rows` rows in pages of `batch_size` rows, which are fetched by tasks spawned
+// on the Wrappers async runtime, and fails in the callback named by the
+// `fail_on` option.
+#[wrappers_fdw(
+    version = "0.1.0",
+    author = "Supabase",
+    website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/test_fdw",
+    error_type = "AsyncTestFdwError"
+)]
+pub(crate) struct AsyncTestFdw {
+    batch: RowBatch,
+    columns: Vec<Column>,
+    rows: i64,
+    batch_size: i64,
+    next_id: i64,
+    fail_on: Option<String>,
+    // request of the next page, running on the async runtime
+    next_page: Option<Pin<Box<dyn Future<Output = Page>>>>,
+}
+
+enum AsyncTestFdwError {
+    Interrupted(Interrupted),
+    Failed(&'static str),
+}
+
+impl From<Interrupted> for AsyncTestFdwError {
+    fn from(value: Interrupted) -> Self {
+        Self::Interrupted(value)
+    }
+}
+
+impl From<AsyncTestFdwError> for ErrorReport {
+    fn from(value: AsyncTestFdwError) -> Self {
+        match value {
+            AsyncTestFdwError::Interrupted(e) => e.into(),
+            AsyncTestFdwError::Failed(callback) => ErrorReport::new(
+                PgSqlErrorCode::ERRCODE_FDW_ERROR,
+                format!("async test fdw failed in {callback}"),
+                "",
+            ),
+        }
+    }
+}
+
+type AsyncTestFdwResult<T> = Result<T, AsyncTestFdwError>;
+
+impl AsyncTestFdw {
+    fn check_fail(&self, callback: &'static str) -> AsyncTestFdwResult<()> {
+        if self.fail_on.as_deref() == Some(callback) {
+            return Err(AsyncTestFdwError::Failed(callback));
+        }
+        Ok(())
+    }
+
+    // spawn the request of the next page, so it is fetched while Postgres is
+    // consuming the current page
+    fn request_next_page(&mut self) {
+        let start = self.next_id;
+        let end = (start + self.batch_size).min(self.rows);
+        self.next_id = end;
+
+        let backend_thread = thread::current().id();
+        let handle = async_runtime().report_unwrap().spawn(async move {
+            (start < end).then(|| {
+                let ids = (start..end).collect::<Vec<_>>();
+                (ids, thread::current().id() != backend_thread)
+            })
+        });
+        self.next_page = Some(Box::pin(async move { handle.await.unwrap() }));
+    }
+
+    fn make_row(&self, id: i64) -> Row {
+        let mut row = Row::new();
+        for col in &self.columns {
+            let cell = match col.name.as_str() {
+                "id" => Some(Cell::I64(id)),
+                "name" => Some(Cell::String(format!("row {id}"))),
+                _ => None,
+            };
+            row.push(&col.name, cell);
+        }
+        row
+    }
+}
+
+impl AsyncForeignDataWrapper<AsyncTestFdwError> for AsyncTestFdw {
+    fn new(_server: ForeignServer) -> AsyncTestFdwResult<Self> {
+        Ok(Self {
+            batch: RowBatch::new(),
+            columns: Vec::new(),
+            rows: 0,
+            batch_size: 1,
+            next_id: 0,
+            fail_on: None,
+            next_page: None,
+        })
+    }
+
+    fn row_batch(&mut self) -> &mut RowBatch {
+        &mut self.batch
+    }
+
+    async fn begin_scan(
+        &mut self,
+        _quals: &[Qual],
+        columns: &[Column],
+        _sorts: &[Sort],
+        _limit: &Option<Limit>,
+        options: &HashMap<String, String>,
+    ) -> AsyncTestFdwResult<()> {
+        self.fail_on = options.get("fail_on").cloned();
+        self.check_fail("begin_scan")?;
+        record("begin_scan");
+
+        self.columns = columns.to_vec();
+        self.rows = options.get("rows").map_or(0, |v| v.parse().unwrap());
+        self.batch_size = options.get("batch_size").map_or(1, |v| v.parse().unwrap());
+        self.next_id = 0;
+        self.request_next_page();
+        Ok(())
+    }
+
+    async fn next_batch(&mut self) -> AsyncTestFdwResult<Option<Vec<Row>>> {
+        self.check_fail("next_batch")?;
+
+        let Some(page) = self.next_page.take() else {
+            return Ok(None);
+        };
+        let Some((ids, off_backend)) = page.await else {
+            return Ok(None);
+        };
+        if !off_backend {
+            record("page fetched on backend thread");
+        }
+        record(format!("batch {}", ids.len()));
+
+        self.request_next_page();
+        Ok(Some(ids.into_iter().map(|id| self.make_row(id)).collect()))
+    }
+
+    async fn re_scan(&mut self) -> AsyncTestFdwResult<()> {
+        record("re_scan");
+        self.next_id = 0;
+        self.request_next_page();
+        Ok(())
+    }
+
+    async fn end_scan(&mut self) -> AsyncTestFdwResult<()> {
+        record("end_scan");
+        self.next_page = None;
+        Ok(())
+    }
+
+    async fn begin_modify(&mut self, options: &HashMap<String, String>) -> AsyncTestFdwResult<()> {
+        self.fail_on = options.get("fail_on").cloned();
+        self.check_fail("begin_modify")?;
+        record("begin_modify");
+        Ok(())
+    }
+
+    async fn insert(&mut self, row: &Row) -> AsyncTestFdwResult<()> {
+        self.check_fail("insert")?;
+        record(format!("insert {}", format_row(row)));
+        Ok(())
+    }
+
+    async fn update(&mut self, rowid: &Cell, new_row: &Row) -> AsyncTestFdwResult<()> {
+        self.check_fail("update")?;
+        record(format!("update {rowid} {}", format_row(new_row)));
+        Ok(())
+    }
+
+    async fn delete(&mut self, rowid: &Cell) -> AsyncTestFdwResult<()> {
+        self.check_fail("delete")?;
+        record(format!("delete {rowid}"));
+        Ok(())
+    }
+
+    async fn end_modify(&mut self) -> AsyncTestFdwResult<()> {
+        record("end_modify");
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+//! Foreign data wrappers only built for tests, they cover the framework
+//! features which can't be tested without a remote service otherwise
+mod async_test_fdw;
+mod tests;
+mod xact_test_fdw;
@@ -0,0 +1,345 @@
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::super::async_test_fdw::ASYNC_EVENTS;
+    use super::super::xact_test_fdw::XACT_EVENTS;
+    use pgrx::prelude::*;
+    use pgrx::spi::SpiClient;
+
+    fn take_events() -> Vec<String> {
+        std::mem::take(&mut *XACT_EVENTS.lock().unwrap())
+    }
+
+    fn take_async_events() -> Vec<String> {
+        std::mem::take(&mut *ASYNC_EVENTS.lock().unwrap())
+    }
+
+    // events of the async FDW without the scan ones, e.g. the scan of the
+    // target rows of an UPDATE
+    fn take_async_modify_events() -> Vec<String> {
+        take_async_events()
+            .into_iter()
+            .filter(|e| {
+                !["begin_scan", "batch", "re_scan", "end_scan"]
+                    .iter()
+                    .any(|p| e.starts_with(p))
+            })
+            .collect()
+    }
+
+    fn create_async_test_server(c: &mut SpiClient<'_>) {
+        c.update(
+            r#"CREATE FOREIGN DATA WRAPPER async_test_wrapper
+                 HANDLER async_test_fdw_handler VALIDATOR async_test_fdw_validator"#,
+            None,
+            &[],
+        )
+        .unwrap();
+        c.update(
+            r#"CREATE SERVER async_test_server FOREIGN DATA WRAPPER async_test_wrapper"#,
+            None,
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn create_async_test_table(c: &mut SpiClient<'_>, table: &str, options: &str) {
+        c.update(
+            &format!(
+                r#"CREATE FOREIGN TABLE {table} (id bigint, name text)
+                     SERVER async_test_server OPTIONS ({options})"#
+            ),
+            None,
+            &[],
+        )
+        .unwrap();
+    }
+
+    // run a statement which must fail with the given FDW error
+    fn assert_fdw_error(c: &mut SpiClient<'_>, stmt: &str, message: &str) {
+        c.update(
+            &format!(
+                r#"DO $$
+                   DECLARE
+                     msg text;
+                   BEGIN
+                     {stmt};
+                     RAISE EXCEPTION 'no error';
+                   EXCEPTION WHEN fdw_error THEN
+                     GET STACKED DIAGNOSTICS msg = MESSAGE_TEXT;
+                     IF msg <> '{message}' THEN
+                       RAISE EXCEPTION 'unexpected error: %', msg;
+                     END IF;
+                   END $$"#
+            ),
+            None,
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[pg_test]
+    fn xact_hooks_test() {
+        Spi::connect_mut(|c| {
+            c.update(
+                r#"CREATE FOREIGN DATA WRAPPER xact_test_wrapper
+                     HANDLER xact_test_fdw_handler VALIDATOR xact_test_fdw_validator"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            c.update(
+                r#"CREATE SERVER xact_test_server FOREIGN DATA WRAPPER xact_test_wrapper"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            c.update(
+                r#"CREATE FOREIGN TABLE xact_test_table (id bigint)
+                     SERVER xact_test_server OPTIONS (rowid_column 'id')"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            take_events();
+
+            // the error is raised by Postgres in the middle of the insert, so
+            // end_modify is skipped but abort is still called
+            c.update(
+                r#"DO $$
+                   BEGIN
+                     INSERT INTO xact_test_table
+                       SELECT 10 / (2 - g) FROM generate_series(1, 2) g;
+                   EXCEPTION WHEN division_by_zero THEN
+                     NULL;
+                   END $$"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                take_events(),
+                vec!["begin_modify", "insert id=10", "abort", "drop"]
+            );
+
+            // the error is raised by Postgres after the insert ends
+            c.update(
+                r#"DO $$
+                   BEGIN
+                     INSERT INTO xact_test_table VALUES (1);
+                     RAISE EXCEPTION 'rollback';
+                   EXCEPTION WHEN raise_exception THEN
+                     NULL;
+                   END $$"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                take_events(),
+                vec!["begin_modify", "insert id=1", "end_modify", "abort", "drop"]
+            );
+
+            // the instance is kept until the transaction ends
+            c.update("INSERT INTO xact_test_table VALUES (2)", None, &[])
+                .unwrap();
+            assert_eq!(
+                take_events(),
+                vec!["begin_modify", "insert id=2", "end_modify"]
+            );
+        });
+    }
+
+    #[pg_test]
+    fn remote_column_name_test() {
+        Spi::connect_mut(|c| {
+            c.update(
+                r#"CREATE FOREIGN DATA WRAPPER xact_test_wrapper
+                     HANDLER xact_test_fdw_handler VALIDATOR xact_test_fdw_validator"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            c.update(
+                r#"CREATE SERVER xact_test_server FOREIGN DATA WRAPPER xact_test_wrapper"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            c.update(
+                r#"CREATE FOREIGN TABLE xact_test_table (
+                     id bigint OPTIONS (column_name 'Id'),
+                     name text
+                   )
+                     SERVER xact_test_server OPTIONS (rowid_column 'id')"#,
+                None,
+                &[],
+            )
+            .unwrap();
+            take_events();
+
+            // the FDW gets the remote column names of the new row
+            c.update("INSERT INTO xact_test_table VALUES (3, 'foo')", None, &[])
+                .unwrap();
+            assert_eq!(
+                take_events(),
+                vec![
+                    "begin_modify",
+                    "insert Id=3",
+                    "insert name='foo'",
+                    "end_modify"
+                ]
+            );
+        });
+    }
+
+    #[pg_test]
+    fn async_scan_test() {
+        Spi::connect_mut(|c| {
+            create_async_test_server(c);
+            create_async_test_table(c, "async_test_table", "rows '5', batch_size '2'");
+            create_async_test_table(c, "async_test_table2", "rows '3', batch_size '3'");
+            create_async_test_table(c, "async_test_empty", "rows '0'");
+            take_async_events();
+
+            // rows are fetched in pages by tasks on the async runtime
+            let results = c
+                .select("SELECT name FROM async_test_table", None, &[])
+                .unwrap()
+                .filter_map(|r| r.get_by_name::<&str, _>("name").unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(results, vec!["row 0", "row 1", "row 2", "row 3", "row 4"]);
+            assert_eq!(
+                take_async_events(),
+                vec!["begin_scan", "batch 2", "batch 2", "batch 1", "end_scan"]
+            );
+
+            let results = c
+                .select("SELECT name FROM async_test_empty", None, &[])
+                .unwrap()
+                .collect::<Vec<_>>();
+            assert!(results.is_empty());
+            assert_eq!(take_async_events(), vec!["begin_scan", "end_scan"]);
+
+            // instances scanned in the same query share the async runtime
+            let count = c
+                .select(
+                    "SELECT count(*) AS cnt FROM async_test_table a, async_test_table2 b",
+                    None,
+                    &[],
+                )
+                .unwrap()
+                .filter_map(|r| r.get_by_name::<i64, _>("cnt").unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(count, vec![15]);
+            assert!(
+                !take_async_events()
+                    .iter()
+                    .any(|e| e == "page fetched on backend thread")
+            );
+        });
+    }
+
+    #[pg_test]
+    fn async_modify_test() {
+        Spi::connect_mut(|c| {
+            create_async_test_server(c);
+            create_async_test_table(
+                c,
+                "async_test_table",
+                "rowid_column 'id', rows '2', batch_size '2'",
+            );
+            take_async_events();
+
+            c.update(
+                "INSERT INTO async_test_table VALUES (10, 'foo'), (11, 'bar')",
+                None,
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                take_async_modify_events(),
+                vec![
+                    "begin_modify",
+                    "insert id=10 name='foo'",
+                    "insert id=11 name='bar'",
+                    "end_modify"
+                ]
+            );
+
+            c.update(
+                "UPDATE async_test_table SET name = 'baz' WHERE id = 1",
+                None,
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                take_async_modify_events(),
+                vec!["begin_modify", "update 1 name='baz'", "end_modify"]
+            );
+
+            c.update("DELETE FROM async_test_table WHERE id = 0", None, &[])
+                .unwrap();
+            assert_eq!(
+                take_async_modify_events(),
+                vec!["begin_modify", "delete 0", "end_modify"]
+            );
+        });
+    }
+
+    #[pg_test]
+    fn async_error_test() {
+        Spi::connect_mut(|c| {
+            create_async_test_server(c);
+            for callback in [
+                "begin_scan",
+                "next_batch",
+                "begin_modify",
+                "insert",
+                "update",
+            ] {
+                create_async_test_table(
+                    c,
+                    &format!("async_test_{callback}"),
+                    &format!("rowid_column 'id', rows '1', fail_on '{callback}'"),
+                );
+            }
+            create_async_test_table(c, "async_test_table", "rows '3', batch_size '2'");
+
+            // errors returned by the async callbacks are reported by Postgres
+            assert_fdw_error(
+                c,
+                "PERFORM * FROM async_test_begin_scan",
+                "async test fdw failed in begin_scan",
+            );
+            assert_fdw_error(
+                c,
+                "PERFORM * FROM async_test_next_batch",
+                "async test fdw failed in next_batch",
+            );
+            assert_fdw_error(
+                c,
+                "INSERT INTO async_test_begin_modify VALUES (1, 'foo')",
+                "async test fdw failed in begin_modify",
+            );
+            assert_fdw_error(
+                c,
+                "INSERT INTO async_test_insert VALUES (1, 'foo')",
+                "async test fdw failed in insert",
+            );
+            assert_fdw_error(
+                c,
+                "UPDATE async_test_update SET name = 'foo'",
+                "async test fdw failed in update",
+            );
+
+            // the async runtime keeps working after the errors
+            let results = c
+                .select("SELECT id FROM async_test_table", None, &[])
+                .unwrap()
+                .filter_map(|r| r.get_by_name::<i64, _>("id").unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(results, vec![0, 1, 2]);
+        });
+    }
+}
@@ -0,0 +1,97 @@
+use pgrx::PgSqlErrorCode;
+use pgrx::pg_sys::panic::ErrorReport;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use supabase_wrappers::prelude::*;
+
+// calls made to the instances of this FDW, in the order of calls
+pub(super) static XACT_EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record(event: impl Into<String>) {
+    XACT_EVENTS.lock().unwrap().push(event.into());
+}
+
+// A FDW supporting transaction hooks, which records modify and transaction
+// hook calls
+#[wrappers_fdw(
+    version = "0.1.0",
+    author = "Supabase",
+    website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/test_fdw",
+    error_type = "XactTestFdwError"
+)]
+pub(crate) struct XactTestFdw;
+
+enum XactTestFdwError {}
+
+impl From<XactTestFdwError> for ErrorReport {
+    fn from(_value: XactTestFdwError) -> Self {
+        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, "", "")
+    }
+}
+
+type XactTestFdwResult<T> = Result<T, XactTestFdwError>;
+
+impl ForeignDataWrapper<XactTestFdwError> for XactTestFdw {
+    fn new(_server: ForeignServer) -> XactTestFdwResult<Self> {
+        Ok(Self)
+    }
+
+    fn begin_scan(
+        &mut self,
+        _quals: &[Qual],
+        _columns: &[Column],
+        _sorts: &[Sort],
+        _limit: &Option<Limit>,
+        _options: &HashMap<String, String>,
+    ) -> XactTestFdwResult<()> {
+        Ok(())
+    }
+
+    fn iter_scan(&mut self, _row: &mut Row) -> XactTestFdwResult<Option<()>> {
+        Ok(None)
+    }
+
+    fn end_scan(&mut self) -> XactTestFdwResult<()> {
+        Ok(())
+    }
+
+    fn begin_modify(&mut self, _options: &HashMap<String, String>) -> XactTestFdwResult<()> {
+        record("begin_modify");
+        Ok(())
+    }
+
+    fn insert(&mut self, row: &Row) -> XactTestFdwResult<()> {
+        for (col, cell) in row.iter() {
+            match cell {
+                Some(cell) => record(format!("insert {col}={cell}")),
+                None => record(format!("insert {col}=null")),
+            }
+        }
+        Ok(())
+    }
+
+    fn end_modify(&mut self) -> XactTestFdwResult<()> {
+        record("end_modify");
+        Ok(())
+    }
+
+    fn transaction_hooks_supported(&self) -> bool {
+        true
+    }
+
+    fn pre_commit(&mut self) -> XactTestFdwResult<()> {
+        record("pre_commit");
+        Ok(())
+    }
+
+    fn abort(&mut self) -> XactTestFdwResult<()> {
+        record("abort");
+        Ok(())
+    }
+}
+
+impl Drop for XactTestFdw {
+    fn drop(&mut self) {
+        record("drop");
+    }
+}
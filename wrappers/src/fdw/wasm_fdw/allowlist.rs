@@ -0,0 +1,358 @@
+use reqwest::{Url, redirect};
+use std::collections::HashMap;
+
+use supabase_wrappers::prelude::OptionsError;
+
+use super::WasmFdwResult;
+
+// max number of redirects can be followed, same as reqwest's default policy
+const MAX_REDIRECTS: usize = 10;
+
+// one entry of the `allowed_hosts` option, in the form of
+// `[scheme://]host[:port]`, host can have `*` wildcards. Scheme can be `http`,
+// `https` or `tcp`, the last one only matches TCP connections and must have an
+// explicit port.
+#[derive(Debug, Clone, PartialEq)]
+struct HostPattern {
+    scheme: Option<String>,
+    host: String,
+    port: Option<u16>,
+}
+
+impl HostPattern {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        let (scheme, rest) = match s.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme.to_string()), rest.to_string()),
+            None => (None, s),
+        };
+        if let Some(scheme) = &scheme
+            && scheme != "http"
+            && scheme != "https"
+            && scheme != "tcp"
+        {
+            return None;
+        }
+
+        // IPv6 address must be in brackets if port is specified, e.g. '[::1]:8080'
+        let (host, port) = match rest.strip_prefix('[') {
+            Some(rest) => {
+                let (host, port) = rest.split_once(']')?;
+                let port = match port {
+                    "" => None,
+                    port => Some(port.strip_prefix(':')?),
+                };
+                (host.to_string(), port)
+            }
+            None if rest.matches(':').count() > 1 => (rest, None),
+            None => match rest.rsplit_once(':') {
+                Some((host, port)) => (host.to_string(), Some(port)),
+                None => (rest, None),
+            },
+        };
+        let port = match port {
+            None | Some("*") => None,
+            Some(port) => Some(port.parse::<u16>().ok()?),
+        };
+        if host.is_empty() || host.contains(['/', '[', ']']) {
+            return None;
+        }
+        if scheme.as_deref() == Some("tcp") && port.is_none() {
+            return None;
+        }
+
+        Some(Self { scheme, host, port })
+    }
+
+    fn matches_addr(&self, scheme: &str, host: &str, port: Option<u16>) -> bool {
+        // IPv6 address in url is in brackets, e.g. '[::1]'
+        let host = host
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+            .unwrap_or(host);
+        // TCP connections are only matched by explicit 'tcp://' patterns
+        let scheme_matches = match &self.scheme {
+            Some(s) => s == scheme,
+            None => scheme != "tcp",
+        };
+        scheme_matches
+            && self.port.is_none_or(|p| Some(p) == port)
+            && glob_match(self.host.as_bytes(), host.to_lowercase().as_bytes())
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        self.matches_addr(url.scheme(), host, url.port_or_known_default())
+    }
+}
+
+// match text against a glob pattern, in which `*` matches any characters
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((bp, bt)) = backtrack {
+            p = bp + 1;
+            t = bt + 1;
+            backtrack = Some((bp, bt + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+/// Outbound host allowlist of a Wasm FDW, set by the server options below
+///
+/// - `allowed_hosts` - comma separated list of hosts the guest can send HTTP
+///   requests or open TCP connections to, each in the form of
+///   `[scheme://]host[:port]`, e.g. `https://*.example.com, tcp://db:5432`.
+///   Scheme and port match any HTTP request if not specified, IPv6 address
+///   must be in brackets if port is specified, e.g. `[::1]:8080`. TCP
+///   connections are only allowed by `tcp://host:port` patterns.
+/// - `default_deny` - when it is `true` and `allowed_hosts` is not set, all
+///   HTTP requests are blocked. It has no effect on local `file://` packages.
+///
+/// All HTTP requests are allowed and all TCP connections are blocked if
+/// neither of them is set.
+#[derive(Debug, Clone, Default)]
+pub(super) struct HostAllowlist {
+    patterns: Option<Vec<HostPattern>>,
+}
+
+impl HostAllowlist {
+    pub(super) fn from_options(options: &HashMap<String, String>) -> WasmFdwResult<Self> {
+        let is_local = options
+            .get("fdw_package_url")
+            .is_some_and(|url| url.starts_with("file://"));
+        let default_deny = match options.get("default_deny").map(|s| s.as_str()) {
+            None | Some("false") => false,
+            Some("true") => true,
+            Some(_) => {
+                return Err(OptionsError::OptionParsingError {
+                    option_name: "default_deny".to_string(),
+                    type_name: "boolean",
+                }
+                .into());
+            }
+        };
+
+        let patterns = match options.get("allowed_hosts") {
+            Some(hosts) => Some(
+                hosts
+                    .split(',')
+                    .filter(|s| !s.trim().is_empty())
+                    .map(|s| {
+                        HostPattern::parse(s).ok_or_else(|| OptionsError::OptionParsingError {
+                            option_name: "allowed_hosts".to_string(),
+                            type_name: "host pattern list",
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None if default_deny && !is_local => Some(Vec::new()),
+            None => None,
+        };
+
+        Ok(Self { patterns })
+    }
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        self.patterns
+            .as_ref()
+            .is_none_or(|patterns| patterns.iter().any(|p| p.matches(url)))
+    }
+
+    // check if the guest is allowed to send request to the url
+    pub(super) fn check(&self, url: &str) -> Result<(), String> {
+        let url = Url::parse(url).map_err(|e| format!("invalid url: {e}"))?;
+        if self.is_allowed(&url) {
+            return Ok(());
+        }
+
+        // only log the origin, as url path and query may contain secrets
+        let origin = url.origin().ascii_serialization();
+        pgrx::log!("wasm fdw: blocked HTTP request to '{origin}' not in allowed_hosts");
+        Err(format!(
+            "request to '{origin}' is not allowed by 'allowed_hosts'"
+        ))
+    }
+
+    // check if the guest is allowed to open TCP connection to the host, which
+    // must be listed in allowed_hosts explicitly
+    pub(super) fn check_tcp(&self, host: &str, port: u16) -> Result<(), String> {
+        let allowed = self.patterns.as_ref().is_some_and(|patterns| {
+            patterns
+                .iter()
+                .any(|p| p.matches_addr("tcp", host, Some(port)))
+        });
+        if allowed {
+            return Ok(());
+        }
+
+        pgrx::log!("wasm fdw: blocked TCP connection to '{host}:{port}' not in allowed_hosts");
+        Err(format!(
+            "connection to '{host}:{port}' is not allowed, add 'tcp://{host}:{port}' to 'allowed_hosts' to allow it"
+        ))
+    }
+
+    // redirect policy which stops redirecting to hosts not allowed
+    pub(super) fn redirect_policy(&self) -> redirect::Policy {
+        if self.patterns.is_none() {
+            return redirect::Policy::default();
+        }
+
+        let allowlist = self.clone();
+        redirect::Policy::custom(move |attempt| {
+            match allowlist.check_redirect(attempt.url(), attempt.previous().len()) {
+                Ok(_) => attempt.follow(),
+                Err(err) => attempt.error(err),
+            }
+        })
+    }
+
+    // check if a redirect can be followed, `redirects` is the number of
+    // redirects already followed
+    fn check_redirect(&self, url: &Url, redirects: usize) -> Result<(), String> {
+        if redirects >= MAX_REDIRECTS {
+            return Err("too many redirects".to_string());
+        }
+        if self.is_allowed(url) {
+            return Ok(());
+        }
+
+        let origin = url.origin().ascii_serialization();
+        pgrx::log!("wasm fdw: blocked HTTP redirect to '{origin}' not in allowed_hosts");
+        Err(format!(
+            "redirect to '{origin}' is not allowed by 'allowed_hosts'"
+        ))
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use pgrx::prelude::*;
+
+    fn allowlist(allowed_hosts: &str) -> HostAllowlist {
+        let options = HashMap::from([("allowed_hosts".to_string(), allowed_hosts.to_string())]);
+        HostAllowlist::from_options(&options).unwrap()
+    }
+
+    #[pg_test]
+    fn glob_match_test() {
+        assert!(glob_match(b"api.example.com", b"api.example.com"));
+        assert!(!glob_match(b"api.example.com", b"api.example.co"));
+        assert!(glob_match(b"*.example.com", b"api.example.com"));
+        assert!(glob_match(b"*.example.com", b"a.b.example.com"));
+        assert!(!glob_match(b"*.example.com", b"example.com"));
+        assert!(!glob_match(b"*.example.com", b"api.example.com.evil.io"));
+        assert!(glob_match(b"api-*.example.*", b"api-eu.example.io"));
+        assert!(glob_match(b"*", b"anything"));
+        assert!(glob_match(b"**", b""));
+        assert!(!glob_match(b"", b"a"));
+    }
+
+    #[pg_test]
+    fn host_pattern_parse_test() {
+        let parse = |s| HostPattern::parse(s).map(|p| (p.scheme, p.host, p.port));
+
+        assert_eq!(
+            parse(" HTTPS://*.Example.com:8443 "),
+            Some((Some("https".into()), "*.example.com".into(), Some(8443)))
+        );
+        assert_eq!(
+            parse("example.com"),
+            Some((None, "example.com".into(), None))
+        );
+        assert_eq!(
+            parse("example.com:*"),
+            Some((None, "example.com".into(), None))
+        );
+        assert_eq!(
+            parse("tcp://db:5432"),
+            Some((Some("tcp".into()), "db".into(), Some(5432)))
+        );
+        assert_eq!(parse("[::1]:8080"), Some((None, "::1".into(), Some(8080))));
+        assert_eq!(
+            parse("http://[::1]"),
+            Some((Some("http".into()), "::1".into(), None))
+        );
+        assert_eq!(parse("::1"), Some((None, "::1".into(), None)));
+        assert_eq!(parse("ftp://example.com"), None);
+        assert_eq!(parse("example.com:port"), None);
+        assert_eq!(parse("example.com:70000"), None);
+        assert_eq!(parse("https://example.com/path"), None);
+        assert_eq!(parse("[::1"), None);
+        assert_eq!(parse("[::1]8080"), None);
+        assert_eq!(parse("tcp://db"), None);
+        assert_eq!(parse("tcp://db:*"), None);
+        assert_eq!(parse(":8080"), None);
+    }
+
+    #[pg_test]
+    fn allowlist_check_test() {
+        let list = allowlist("https://*.example.com, http://localhost:8080, [::1]:9000");
+        assert!(list.check("https://api.example.com/v1?key=secret").is_ok());
+        assert!(list.check("http://api.example.com/v1").is_err());
+        assert!(list.check("http://localhost:8080/").is_ok());
+        assert!(list.check("http://localhost:8081/").is_err());
+        assert!(list.check("http://[::1]:9000/").is_ok());
+        assert!(list.check("http://[::1]:9001/").is_err());
+        assert!(list.check("not a url").is_err());
+
+        // all hosts are allowed if allowed_hosts is not set
+        let list = HostAllowlist::from_options(&HashMap::new()).unwrap();
+        assert!(list.check("https://anywhere.io/").is_ok());
+
+        let options = HashMap::from([("default_deny".to_string(), "true".to_string())]);
+        let list = HostAllowlist::from_options(&options).unwrap();
+        assert!(list.check("https://anywhere.io/").is_err());
+    }
+
+    #[pg_test]
+    fn allowlist_check_tcp_test() {
+        let list = allowlist("tcp://db.example.com:5432, tcp://*.cache.io:6379, *.example.com");
+        assert!(list.check_tcp("db.example.com", 5432).is_ok());
+        assert!(list.check_tcp("db.example.com", 5433).is_err());
+        assert!(list.check_tcp("eu.cache.io", 6379).is_ok());
+        assert!(list.check("https://eu.cache.io/").is_err());
+
+        // patterns without scheme don't match TCP connections
+        assert!(list.check("https://api.example.com/").is_ok());
+        assert!(list.check_tcp("api.example.com", 443).is_err());
+
+        // TCP connections are blocked if allowed_hosts is not set
+        let list = HostAllowlist::from_options(&HashMap::new()).unwrap();
+        assert!(list.check_tcp("db.example.com", 5432).is_err());
+    }
+
+    #[pg_test]
+    fn allowlist_redirect_test() {
+        let list = allowlist("https://*.example.com");
+        let url = |s| Url::parse(s).unwrap();
+
+        assert!(
+            list.check_redirect(&url("https://cdn.example.com/a"), 0)
+                .is_ok()
+        );
+        assert!(list.check_redirect(&url("https://evil.io/a"), 0).is_err());
+        assert!(
+            list.check_redirect(&url("http://cdn.example.com/a"), 0)
+                .is_err()
+        );
+        assert_eq!(
+            list.check_redirect(&url("https://cdn.example.com/a"), MAX_REDIRECTS),
+            Err("too many redirects".to_string())
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use pgrx::{
+    AnyNumeric, JsonB,
+    datum::datetime_support::to_timestamp,
+    prelude::{Date, Interval, Time, Timestamp, TimestampWithTimeZone},
+    varlena,
+};
+use std::str::FromStr;
+use uuid::Uuid;
+use wasmtime::Error as WasmError;
+use wasmtime::component::bindgen;
+
+use super::{PG_EPOCH_MS, PG_EPOCH_SEC};
+use crate::stats::Metric as HostMetric;
+use supabase_wrappers::prelude::{
+    Cell as HostCell, ImportForeignSchemaStmt as HostImportForeignSchemaStmt,
+    ImportSchemaType as HostImportSchemaType, Param as HostParam,
+    ScanCapabilities as HostScanCapabilities, Value as HostValue,
+};
+
+bindgen!("wrappers" in "../wasm-wrappers/wit/v3");
+
+use self::supabase::wrappers::{
+    stats::Metric as GuestMetric,
+    types::{
+        Cell as GuestCell, ImportForeignSchemaStmt as GuestImportForeignSchemaStmt,
+        ImportSchemaType as GuestImportSchemaType, Interval as GuestInterval, Param as GuestParam,
+        ScanCapabilities as GuestScanCapabilities, Value as GuestValue,
+    },
+};
+
+impl TryFrom<GuestCell> for HostCell {
+    type Error = WasmError;
+
+    fn try_from(value: GuestCell) -> Result<Self, Self::Error> {
+        match value {
+            GuestCell::Bool(v) => Ok(Self::Bool(v)),
+            GuestCell::I8(v) => Ok(Self::I8(v)),
+            GuestCell::I16(v) => Ok(Self::I16(v)),
+            GuestCell::F32(v) => Ok(Self::F32(v)),
+            GuestCell::I32(v) => Ok(Self::I32(v)),
+            GuestCell::F64(v) => Ok(Self::F64(v)),
+            GuestCell::I64(v) => Ok(Self::I64(v)),
+            GuestCell::Numeric(v) => AnyNumeric::from_str(&v)
+                .map(Self::Numeric)
+                .map_err(Self::Error::msg),
+            GuestCell::String(v) => Ok(Self::String(v.clone())),
+            GuestCell::Date(v) => {
+                let ts = to_timestamp(v as f64);
+                Ok(Self::Date(Date::from(ts)))
+            }
+            // convert 'pg epoch' (2000-01-01 00:00:00) to unix epoch
+            GuestCell::Timestamp(v) => Timestamp::try_from(v - PG_EPOCH_MS)
+                .map(Self::Timestamp)
+                .map_err(Self::Error::msg),
+            GuestCell::Timestamptz(v) => TimestampWithTimeZone::try_from(v - PG_EPOCH_MS)
+                .map(Self::Timestamptz)
+                .map_err(Self::Error::msg),
+            GuestCell::Json(v) => {
+                let ret = serde_json::from_str(&v).map(|j| Self::Json(JsonB(j)))?;
+                Ok(ret)
+            }
+            GuestCell::Uuid(v) => Uuid::try_parse(&v)
+                .map(|u| Self::Uuid(pgrx::Uuid::from_bytes(*u.as_bytes())))
+                .map_err(Self::Error::msg),
+            GuestCell::Time(v) => Time::try_from(v).map(Self::Time).map_err(Self::Error::msg),
+            GuestCell::Interval(v) => Interval::new(v.months, v.days, v.micros)
+                .map(Self::Interval)
+                .map_err(Self::Error::msg),
+            GuestCell::Bytea(v) => Ok(Self::Bytea(varlena::rust_byte_slice_to_bytea(&v).into_pg())),
+            GuestCell::BoolArray(v) => Ok(Self::BoolArray(v)),
+            GuestCell::I16Array(v) => Ok(Self::I16Array(v)),
+            GuestCell::I32Array(v) => Ok(Self::I32Array(v)),
+            GuestCell::I64Array(v) => Ok(Self::I64Array(v)),
+            GuestCell::F32Array(v) => Ok(Self::F32Array(v)),
+            GuestCell::F64Array(v) => Ok(Self::F64Array(v)),
+            GuestCell::StringArray(v) => Ok(Self::StringArray(v)),
+            GuestCell::Other(v) => Err(Self::Error::msg(format!(
+                "cell type '{v}' is not supported"
+            ))),
+        }
+    }
+}
+
+impl From<&HostCell> for GuestCell {
+    fn from(value: &HostCell) -> Self {
+        match value {
+            HostCell::Bool(v) => Self::Bool(*v),
+            HostCell::I8(v) => Self::I8(*v),
+            HostCell::I16(v) => Self::I16(*v),
+            HostCell::F32(v) => Self::F32(*v),
+            HostCell::I32(v) => Self::I32(*v),
+            HostCell::F64(v) => Self::F64(*v),
+            HostCell::I64(v) => Self::I64(*v),
+            HostCell::Numeric(v) => Self::Numeric(v.to_string()),
+            HostCell::String(v) => Self::String(v.clone()),
+            HostCell::Date(v) => {
+                // convert 'pg epoch' (2000-01-01 00:00:00) to unix epoch
+                let ts = Timestamp::from(*v);
+                Self::Date(ts.into_inner() / 1_000_000 + PG_EPOCH_SEC)
+            }
+            HostCell::Timestamp(v) => {
+                // convert 'pg epoch' (2000-01-01 00:00:00) in macroseconds to unix epoch
+                Self::Timestamp(v.into_inner() + PG_EPOCH_MS)
+            }
+            HostCell::Timestamptz(v) => {
+                // convert 'pg epoch' (2000-01-01 00:00:00) in macroseconds to unix epoch
+                Self::Timestamptz(v.into_inner() + PG_EPOCH_MS)
+            }
+            HostCell::Json(v) => Self::Json(v.0.to_string()),
+            HostCell::Uuid(v) => Self::Uuid(v.to_string()),
+            HostCell::Time(v) => Self::Time(i64::from(*v)),
+            HostCell::Interval(v) => Self::Interval(GuestInterval {
+                months: v.months(),
+                days: v.days(),
+                micros: v.micros(),
+            }),
+            HostCell::Bytea(v) => {
+                let bytes = unsafe { varlena::varlena_to_byte_slice(*v) };
+                Self::Bytea(bytes.to_vec())
+            }
+            HostCell::BoolArray(v) => Self::BoolArray(v.clone()),
+            HostCell::I16Array(v) => Self::I16Array(v.clone()),
+            HostCell::I32Array(v) => Self::I32Array(v.clone()),
+            HostCell::I64Array(v) => Self::I64Array(v.clone()),
+            HostCell::F32Array(v) => Self::F32Array(v.clone()),
+            HostCell::F64Array(v) => Self::F64Array(v.clone()),
+            HostCell::StringArray(v) => Self::StringArray(v.clone()),
+        }
+    }
+}
+
+impl From<HostValue> for GuestValue {
+    fn from(value: HostValue) -> Self {
+        match value {
+            HostValue::Cell(c) => Self::Cell(GuestCell::from(&c)),
+            HostValue::Array(a) => {
+                let a: Vec<GuestCell> = a.iter().map(GuestCell::from).collect();
+                Self::Array(a)
+            }
+        }
+    }
+}
+
+impl From<HostParam> for GuestParam {
+    fn from(value: HostParam) -> Self {
+        Self {
+            id: value.id as u32,
+            type_oid: value.type_oid.to_u32(),
+        }
+    }
+}
+
+impl From<GuestMetric> for HostMetric {
+    fn from(value: GuestMetric) -> Self {
+        match value {
+            GuestMetric::CreateTimes => HostMetric::CreateTimes,
+            GuestMetric::RowsIn => HostMetric::RowsIn,
+            GuestMetric::RowsOut => HostMetric::RowsOut,
+            GuestMetric::BytesIn => HostMetric::BytesIn,
+            GuestMetric::BytesOut => HostMetric::BytesOut,
+        }
+    }
+}
+
+impl From<HostImportSchemaType> for GuestImportSchemaType {
+    fn from(value: HostImportSchemaType) -> Self {
+        match value {
+            HostImportSchemaType::FdwImportSchemaAll => GuestImportSchemaType::All,
+            HostImportSchemaType::FdwImportSchemaLimitTo => GuestImportSchemaType::LimitTo,
+            HostImportSchemaType::FdwImportSchemaExcept => GuestImportSchemaType::Except,
+        }
+    }
+}
+
+impl From<HostImportForeignSchemaStmt> for GuestImportForeignSchemaStmt {
+    fn from(value: HostImportForeignSchemaStmt) -> Self {
+        Self {
+            server_name: value.server_name.clone(),
+            remote_schema: value.remote_schema.clone(),
+            local_schema: value.local_schema.clone(),
+            list_type: GuestImportSchemaType::from(value.list_type),
+            table_list: value.table_list.clone(),
+            options: value.options.into_iter().collect(),
+        }
+    }
+}
+
+impl From<GuestScanCapabilities> for HostScanCapabilities {
+    fn from(value: GuestScanCapabilities) -> Self {
+        Self {
+            quals: value.quals,
+            sorts: value.sorts,
+            limit: value.limit,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use pgrx::prelude::*;
+
+    // convert a guest cell to host cell and back
+    fn round_trip(cell: GuestCell) -> GuestCell {
+        let cell = HostCell::try_from(cell).unwrap();
+        GuestCell::from(&cell)
+    }
+
+    #[pg_test]
+    fn numeric_cell_test() {
+        let cell = HostCell::try_from(GuestCell::Numeric("123.45".to_string())).unwrap();
+        assert!(matches!(&cell, HostCell::Numeric(v) if v.to_string() == "123.45"));
+        assert!(matches!(
+            GuestCell::from(&cell),
+            GuestCell::Numeric(v) if v == "123.45"
+        ));
+
+        assert!(HostCell::try_from(GuestCell::Numeric("12a".to_string())).is_err());
+        assert!(HostCell::try_from(GuestCell::Numeric(String::new())).is_err());
+    }
+
+    #[pg_test]
+    fn time_cell_test() {
+        // 12:34:56.789 in microseconds
+        let micros = ((12 * 60 + 34) * 60 + 56) * 1_000_000 + 789_000;
+        let cell = HostCell::try_from(GuestCell::Time(micros)).unwrap();
+        assert!(matches!(&cell, HostCell::Time(v) if v.to_string() == "12:34:56.789"));
+        assert!(matches!(round_trip(GuestCell::Time(micros)), GuestCell::Time(v) if v == micros));
+
+        assert!(HostCell::try_from(GuestCell::Time(-1)).is_err());
+        assert!(HostCell::try_from(GuestCell::Time(25 * 3_600 * 1_000_000)).is_err());
+    }
+
+    #[pg_test]
+    fn interval_cell_test() {
+        let interval = GuestInterval {
+            months: 14,
+            days: -3,
+            micros: 3_723_000_004,
+        };
+        let cell = HostCell::try_from(GuestCell::Interval(interval)).unwrap();
+        assert!(matches!(
+            &cell,
+            HostCell::Interval(v) if v.months() == 14 && v.days() == -3 && v.micros() == 3_723_000_004
+        ));
+        assert!(matches!(
+            GuestCell::from(&cell),
+            GuestCell::Interval(GuestInterval {
+                months: 14,
+                days: -3,
+                micros: 3_723_000_004
+            })
+        ));
+    }
+
+    #[pg_test]
+    fn bytea_cell_test() {
+        let bytes = vec![0xde, 0xad, 0x00, 0xbe, 0xef];
+        assert!(matches!(
+            round_trip(GuestCell::Bytea(bytes.clone())),
+            GuestCell::Bytea(v) if v == bytes
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::Bytea(Vec::new())),
+            GuestCell::Bytea(v) if v.is_empty()
+        ));
+    }
+
+    #[pg_test]
+    fn array_cell_test() {
+        assert!(matches!(
+            round_trip(GuestCell::BoolArray(vec![Some(true), None, Some(false)])),
+            GuestCell::BoolArray(v) if v == [Some(true), None, Some(false)]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::I16Array(vec![Some(i16::MIN), None])),
+            GuestCell::I16Array(v) if v == [Some(i16::MIN), None]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::I32Array(vec![None, Some(42)])),
+            GuestCell::I32Array(v) if v == [None, Some(42)]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::I64Array(vec![Some(i64::MAX)])),
+            GuestCell::I64Array(v) if v == [Some(i64::MAX)]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::F32Array(vec![Some(1.5), None])),
+            GuestCell::F32Array(v) if v == [Some(1.5), None]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::F64Array(vec![Some(-2.25)])),
+            GuestCell::F64Array(v) if v == [Some(-2.25)]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::StringArray(vec![Some("a".to_string()), None])),
+            GuestCell::StringArray(v) if v == [Some("a".to_string()), None]
+        ));
+        assert!(matches!(
+            round_trip(GuestCell::I32Array(Vec::new())),
+            GuestCell::I32Array(v) if v.is_empty()
+        ));
+    }
+
+    #[pg_test]
+    fn unsupported_cell_test() {
+        let err = HostCell::try_from(GuestCell::Other("geometry".to_string())).unwrap_err();
+        assert_eq!(err.to_string(), "cell type 'geometry' is not supported");
+    }
+}
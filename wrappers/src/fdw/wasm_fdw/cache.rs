@@ -0,0 +1,361 @@
+use pgrx::pg_sys;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::CStr;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::SystemTime;
+use wasmtime::component::Component;
+use wasmtime::{Config, Engine};
+
+use super::WasmFdwResult;
+use super::limits::ResourceLimits;
+use super::signature::PackageVerifier;
+
+// engine config which affects compiled code, components compiled by one
+// engine cannot be used by an engine with different config
+type EngineKey = (bool, bool);
+
+thread_local! {
+    // engines and compiled components cached in this backend
+    static ENGINES: RefCell<HashMap<EngineKey, Engine>> = RefCell::new(HashMap::new());
+    static COMPONENTS: RefCell<HashMap<String, Component>> = RefCell::new(HashMap::new());
+}
+
+fn engine_key(limits: &ResourceLimits) -> EngineKey {
+    (limits.max_fuel.is_some(), limits.call_timeout_ms.is_some())
+}
+
+/// Get the engine for the resource limits, it is created once and cached in
+/// the backend
+pub(super) fn get_engine(limits: &ResourceLimits) -> WasmFdwResult<Engine> {
+    let key = engine_key(limits);
+    if let Some(engine) = ENGINES.with(|e| e.borrow().get(&key).cloned()) {
+        return Ok(engine);
+    }
+
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    limits.configure(&mut config);
+    let engine = Engine::new(&config)?;
+    limits.start_epoch_ticker(&engine);
+
+    ENGINES.with(|e| e.borrow_mut().insert(key, engine.clone()));
+    Ok(engine)
+}
+
+// get modified time of a local package file, so the cached component is
+// reloaded after the package is rebuilt
+fn local_file_mtime(url: &str) -> Option<SystemTime> {
+    let file_path = url.strip_prefix("file://")?;
+    fs::metadata(file_path).and_then(|m| m.modified()).ok()
+}
+
+// length of the SHA-256 digest put before the precompiled component
+const DIGEST_LEN: usize = 32;
+
+// check the directory is owned by the Postgres OS user, i.e. the owner of the
+// data directory, and is not accessible by any other users
+fn is_private_dir(dir: &Path) -> bool {
+    let data_dir = unsafe { pg_sys::DataDir };
+    if data_dir.is_null() {
+        return false;
+    }
+    let data_dir = unsafe { CStr::from_ptr(data_dir) }.to_string_lossy();
+    let (Ok(meta), Ok(data_meta)) = (fs::symlink_metadata(dir), fs::metadata(&*data_dir)) else {
+        return false;
+    };
+    meta.is_dir() && meta.uid() == data_meta.uid() && meta.mode() & 0o077 == 0
+}
+
+// get the directory of precompiled components, which is created in the
+// user's cache directory and must be private to the Postgres OS user
+fn get_cwasm_dir() -> Option<PathBuf> {
+    let mut dir = dirs::cache_dir()?;
+    dir.push("wrappers_wasm");
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .ok()?;
+    is_private_dir(&dir).then_some(dir)
+}
+
+// get path of the precompiled component in the private cache directory,
+// which is different for each wasmtime version and engine config
+fn get_cwasm_path(engine: &Engine, key: &str) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    engine.precompile_compatibility_hash().hash(&mut hasher);
+
+    let hash = Sha256::digest(format!(
+        "{}:{}:{:x}",
+        unsafe { pg_sys::GetUserId().to_u32() },
+        key,
+        hasher.finish()
+    ));
+
+    let mut path = get_cwasm_dir()?;
+    path.push(hex::encode(hash));
+    path.set_extension("cwasm");
+    Some(path)
+}
+
+// load the precompiled component, the file is removed if its digest doesn't
+// match or it cannot be loaded
+fn load_cwasm(engine: &Engine, path: &Path) -> Option<Component> {
+    let bytes = fs::read(path).ok()?;
+
+    let component = (bytes.len() > DIGEST_LEN)
+        .then(|| bytes.split_at(DIGEST_LEN))
+        .filter(|(digest, data)| Sha256::digest(data).as_slice() == *digest)
+        // SAFETY: the precompiled file is only written by this host to a
+        // directory private to the Postgres OS user, its digest is checked
+        // above, and wasmtime checks it is compiled by a compatible engine
+        .and_then(|(_, data)| unsafe { Component::deserialize(engine, data) }.ok());
+    if component.is_none() {
+        let _ = fs::remove_file(path);
+    }
+    component
+}
+
+// save the precompiled component with its digest, it is written to a
+// temporary file first so other backends never load a partially written file
+fn save_cwasm(component: &Component, path: &Path) {
+    // failing to save the precompiled component only makes next load slower,
+    // so errors are ignored
+    let Ok(data) = component.serialize() else {
+        return;
+    };
+    let tmp_path = path.with_extension(format!("cwasm.{}", process::id()));
+    let saved = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(&Sha256::digest(&data))?;
+            file.write_all(&data)
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if saved.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+}
+
+// check if the package can be precompiled to a `.cwasm` file. Only packages
+// verified by checksum are precompiled, local packages are not as they are
+// often rebuilt. Signed packages are not precompiled either, as the signature
+// can only be verified on the original package.
+fn can_precompile(url: &str, checksum: Option<&str>, verifier: &PackageVerifier) -> bool {
+    checksum.is_some() && !url.starts_with("file://") && !verifier.has_trusted_keys()
+}
+
+/// Get the compiled component of a package, call `load` to download and
+/// compile it if it is not cached
+///
+/// Compiled components are cached in backend memory, and remote packages with
+/// checksum are also precompiled to a `.cwasm` file in a cache directory
+/// private to the Postgres OS user, unless trusted keys are set. The cache key
+/// includes package url, name, version, checksum and signature verifier, so a
+/// new package version is always loaded again and a changed trusted key or
+/// signature is always verified again.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn get_component<F>(
+    engine: &Engine,
+    limits: &ResourceLimits,
+    url: &str,
+    name: &str,
+    version: &str,
+    checksum: Option<&str>,
+    verifier: &PackageVerifier,
+    load: F,
+) -> WasmFdwResult<Component>
+where
+    F: FnOnce() -> WasmFdwResult<Component>,
+{
+    let mtime = local_file_mtime(url);
+    let key = format!(
+        "{:?}:{}:{}:{}:{}:{:?}:{}",
+        engine_key(limits),
+        url,
+        name,
+        version,
+        checksum.unwrap_or_default(),
+        mtime,
+        verifier.cache_key()
+    );
+    if let Some(component) = COMPONENTS.with(|c| c.borrow().get(&key).cloned()) {
+        return Ok(component);
+    }
+
+    let cwasm_path = if can_precompile(url, checksum, verifier) {
+        get_cwasm_path(engine, &key)
+    } else {
+        None
+    };
+
+    let component = match cwasm_path.as_ref().and_then(|p| load_cwasm(engine, p)) {
+        Some(component) => component,
+        None => {
+            let component = load()?;
+            if let Some(path) = &cwasm_path {
+                save_cwasm(&component, path);
+            }
+            component
+        }
+    };
+
+    COMPONENTS.with(|c| c.borrow_mut().insert(key, component.clone()));
+    Ok(component)
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::super::WasmFdwError;
+    use super::*;
+    use pgrx::prelude::*;
+    use std::cell::Cell;
+    use std::os::unix::fs::PermissionsExt;
+
+    // binary of an empty component
+    const EMPTY_COMPONENT: &[u8] = b"\0asm\x0d\0\x01\0";
+
+    // create an empty directory for the test, which is private to the
+    // Postgres OS user
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wrappers_{name}_{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::DirBuilder::new().mode(0o700).create(&dir).unwrap();
+        dir
+    }
+
+    #[pg_test]
+    fn engine_cache_test() {
+        let limits = ResourceLimits::default();
+        let engine = get_engine(&limits).unwrap();
+        assert!(Engine::same(&engine, &get_engine(&limits).unwrap()));
+
+        // engine config is different if fuel is enabled
+        let fuel_limits = ResourceLimits {
+            max_fuel: Some(1000),
+            ..Default::default()
+        };
+        let fuel_engine = get_engine(&fuel_limits).unwrap();
+        assert!(!Engine::same(&engine, &fuel_engine));
+
+        // memory limit doesn't affect engine config
+        let mem_limits = ResourceLimits {
+            max_memory_mb: Some(64),
+            ..Default::default()
+        };
+        assert!(Engine::same(&engine, &get_engine(&mem_limits).unwrap()));
+    }
+
+    #[pg_test]
+    fn component_cache_test() {
+        let limits = ResourceLimits::default();
+        let engine = get_engine(&limits).unwrap();
+        let verifier = PackageVerifier::default();
+        let loads = Cell::new(0);
+        let get = |version: &str| {
+            get_component(
+                &engine,
+                &limits,
+                "file:///nonexistent/component_cache_test.wasm",
+                "test:component-cache",
+                version,
+                None,
+                &verifier,
+                || {
+                    loads.set(loads.get() + 1);
+                    Ok(Component::new(&engine, EMPTY_COMPONENT)?)
+                },
+            )
+        };
+
+        get("0.1.0").unwrap();
+        get("0.1.0").unwrap();
+        assert_eq!(loads.get(), 1);
+
+        // a new package version is loaded again
+        get("0.2.0").unwrap();
+        assert_eq!(loads.get(), 2);
+
+        // failed load is not cached
+        let err = get_component(
+            &engine,
+            &limits,
+            "file:///nonexistent/component_cache_test.wasm",
+            "test:component-cache",
+            "0.3.0",
+            None,
+            &verifier,
+            || Err(WasmFdwError::InvalidWasmComponent),
+        );
+        assert!(err.is_err());
+    }
+
+    #[pg_test]
+    fn cwasm_cache_test() {
+        let engine = get_engine(&ResourceLimits::default()).unwrap();
+        let component = Component::new(&engine, EMPTY_COMPONENT).unwrap();
+        let dir = test_dir("cwasm_cache_test");
+        let path = dir.join("test.cwasm");
+
+        assert!(load_cwasm(&engine, &path).is_none());
+
+        save_cwasm(&component, &path);
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(load_cwasm(&engine, &path).is_some());
+
+        // the file is removed if its digest doesn't match
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        assert!(load_cwasm(&engine, &path).is_none());
+        assert!(!path.exists());
+
+        // truncated file is removed as well
+        fs::write(&path, &bytes[..DIGEST_LEN]).unwrap();
+        assert!(load_cwasm(&engine, &path).is_none());
+        assert!(!path.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[pg_test]
+    fn private_dir_test() {
+        let dir = test_dir("private_dir_test");
+        assert!(is_private_dir(&dir));
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(!is_private_dir(&dir));
+
+        assert!(!is_private_dir(&dir.join("nonexistent")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[pg_test]
+    fn can_precompile_test() {
+        let verifier = PackageVerifier::default();
+        let url = "https://example.com/fdw.wasm";
+        assert!(can_precompile(url, Some("abc"), &verifier));
+        assert!(!can_precompile(url, None, &verifier));
+        assert!(!can_precompile(
+            "file:///tmp/fdw.wasm",
+            Some("abc"),
+            &verifier
+        ));
+    }
+}
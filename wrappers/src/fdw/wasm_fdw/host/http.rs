@@ -1,14 +1,22 @@
 use reqwest::{
-    StatusCode,
+    Response, StatusCode,
     header::{HeaderMap, HeaderName, HeaderValue},
 };
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+use std::time::Duration;
+use wasmtime::Result as WasmResult;
+use wasmtime::component::Resource;
 
+use supabase_wrappers::prelude::{ErrorCategory, Runtime};
+
+use super::super::allowlist::HostAllowlist;
 use super::super::bindings::v1::supabase::wrappers::http::{
     Headers as GuestHeaders, HttpError as GuestHttpError,
 };
-use super::FdwHost;
+use super::super::http_options::HttpOptions;
+use super::{FdwHost, block_on_remote, set_request_error};
+use crate::fdw::http_error::{reqwest_error_category, reqwest_middleware_error_category};
 
 // convert guest headers to HeaderMap
 fn guest_to_header_map(headers: &GuestHeaders) -> HeaderMap {
@@ -36,55 +44,184 @@ fn header_map_to_guest(headers: &HeaderMap) -> GuestHeaders {
 }
 
 // create http request client with backoff retry
-fn create_client(headers: &GuestHeaders) -> Result<ClientWithMiddleware, GuestHttpError> {
+fn create_client(
+    headers: &GuestHeaders,
+    http_opts: &HttpOptions,
+    allowlist: &HostAllowlist,
+) -> Result<ClientWithMiddleware, GuestHttpError> {
     let headers = guest_to_header_map(headers);
-    let client = reqwest::Client::builder()
+    let builder = reqwest::Client::builder()
         .default_headers(headers)
-        .gzip(true)
+        .redirect(allowlist.redirect_policy())
+        .gzip(true);
+    let client = http_opts
+        .apply(builder)
         .build()
         .map_err(|e| e.to_string())?;
-    let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+    let retry_policy = ExponentialBackoff::builder().build_with_max_retries(http_opts.max_retries);
     Ok(ClientBuilder::new(client)
         .with(RetryTransientMiddleware::new_with_policy(retry_policy))
         .build())
 }
 
+// max length of a line read by read-line, so an endless line won't be
+// buffered in memory
+const MAX_LINE_SIZE: usize = 8 * 1024 * 1024;
+
+// convert the error of reading response body
+fn body_error(err: reqwest::Error) -> GuestHttpError {
+    set_request_error(reqwest_error_category(&err));
+    err.to_string()
+}
+
 // raise error for http status code
 fn error_for_status(status_code: u16, url: &str) -> Result<(), GuestHttpError> {
     let status = StatusCode::from_u16(status_code).map_err(|e| e.to_string())?;
     if status.is_client_error() || status.is_server_error() {
+        set_request_error(ErrorCategory::from_http_status(status_code));
         Err(format!("HTTP status error ({status}) for url ({url})"))
     } else {
         Ok(())
     }
 }
 
+// a http response whose body is read incrementally by the guest
+#[derive(Debug)]
+pub(super) struct HttpStream {
+    resp: Response,
+    url: String,
+    status_code: u16,
+    headers: GuestHeaders,
+    // body data received but not read by the guest yet
+    buf: Vec<u8>,
+}
+
+impl HttpStream {
+    fn new(resp: Response) -> Self {
+        Self {
+            url: resp.url().to_string(),
+            status_code: resp.status().as_u16(),
+            headers: header_map_to_guest(resp.headers()),
+            resp,
+            buf: Vec::new(),
+        }
+    }
+
+    // receive next chunk of body, returns none at the end of body
+    fn recv_chunk(resp: &mut Response, rt: &Runtime) -> Result<Option<Vec<u8>>, GuestHttpError> {
+        block_on_remote(rt, resp.chunk())?
+            .map(|chunk| chunk.map(|c| c.to_vec()))
+            .map_err(body_error)
+    }
+
+    fn read_chunk(&mut self, rt: &Runtime) -> Result<Option<Vec<u8>>, GuestHttpError> {
+        if !self.buf.is_empty() {
+            return Ok(Some(std::mem::take(&mut self.buf)));
+        }
+        Self::recv_chunk(&mut self.resp, rt)
+    }
+
+    fn read_line(&mut self, rt: &Runtime) -> Result<Option<String>, GuestHttpError> {
+        let resp = &mut self.resp;
+        take_line(&mut self.buf, || Self::recv_chunk(resp, rt))
+    }
+}
+
+// take the next line out of the buffered body without the line ending, more
+// body chunks are received by `recv` until a line ending is found. The buffer
+// is kept unchanged if the line is longer than MAX_LINE_SIZE.
+fn take_line(
+    buf: &mut Vec<u8>,
+    mut recv: impl FnMut() -> Result<Option<Vec<u8>>, GuestHttpError>,
+) -> Result<Option<String>, GuestHttpError> {
+    let too_long = || format!("line is longer than {MAX_LINE_SIZE} bytes");
+
+    // number of buffered bytes already searched for line ending
+    let mut searched = 0;
+    let mut line = loop {
+        if let Some(pos) = buf[searched..].iter().position(|b| *b == b'\n') {
+            let end = searched + pos;
+            if end > MAX_LINE_SIZE {
+                return Err(too_long());
+            }
+            let mut line: Vec<u8> = buf.drain(..=end).collect();
+            line.pop();
+            break line;
+        }
+        searched = buf.len();
+        if searched > MAX_LINE_SIZE {
+            return Err(too_long());
+        }
+        match recv()? {
+            Some(chunk) => buf.extend_from_slice(&chunk),
+            // the last line may not have line ending
+            None if buf.is_empty() => return Ok(None),
+            None => break std::mem::take(buf),
+        }
+    };
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map(Some).map_err(|e| e.to_string())
+}
+
 const _: () = {
     use super::super::bindings::v1::supabase::wrappers::http;
 
     impl FdwHost {
+        // send a http request and return the response, its body is not read.
+        // The failed request is recorded to categorize the guest error, a
+        // response with error status is recorded as well since the guest may
+        // return error for it. The request is aborted if the statement is
+        // canceled.
+        fn send_request(
+            &mut self,
+            method: http::Method,
+            url: &str,
+            headers: &GuestHeaders,
+            body: impl Into<reqwest::Body>,
+            timeout: Option<Duration>,
+        ) -> Result<reqwest::Response, GuestHttpError> {
+            self.allowlist.check(url).inspect_err(|_| {
+                set_request_error(ErrorCategory::PermissionDenied);
+            })?;
+            let client = create_client(headers, &self.http_opts, &self.allowlist)?;
+            let mut req = match method {
+                http::Method::Get => client.get(url),
+                http::Method::Post => client.post(url),
+                http::Method::Put => client.put(url),
+                http::Method::Patch => client.patch(url),
+                http::Method::Delete => client.delete(url),
+            }
+            .body(body);
+            // the request, including reading its body, cannot run past the
+            // deadline of the current guest call
+            let timeout = match (timeout, self.call_time_left()) {
+                (Some(timeout), Some(left)) => Some(timeout.min(left)),
+                (timeout, left) => timeout.or(left),
+            };
+            if let Some(timeout) = timeout {
+                req = req.timeout(timeout);
+            }
+            let resp = block_on_remote(&self.rt, req.send())?.map_err(|e| {
+                set_request_error(reqwest_middleware_error_category(&e));
+                e.to_string()
+            })?;
+            let status = resp.status();
+            if status.is_client_error() || status.is_server_error() {
+                set_request_error(ErrorCategory::from_http_status(status.as_u16()));
+            }
+            Ok(resp)
+        }
+
         // make a http request
         fn http_request(&mut self, req: http::Request) -> http::HttpResult {
-            let client = create_client(&req.headers)?;
-            let resp = self
-                .rt
-                .block_on(
-                    match req.method {
-                        http::Method::Get => client.get(req.url),
-                        http::Method::Post => client.post(req.url),
-                        http::Method::Put => client.put(req.url),
-                        http::Method::Patch => client.patch(req.url),
-                        http::Method::Delete => client.delete(req.url),
-                    }
-                    .body(req.body)
-                    .send(),
-                )
-                .map_err(|e| e.to_string())?;
+            let resp = self.send_request(req.method, &req.url, &req.headers, req.body, None)?;
 
             let url = resp.url().to_string();
             let status_code = resp.status().as_u16();
             let headers = header_map_to_guest(resp.headers());
-            let body = self.rt.block_on(resp.text()).map_err(|e| e.to_string())?;
+            let body = block_on_remote(&self.rt, resp.text())?.map_err(body_error)?;
             Ok(http::Response {
                 url,
                 status_code,
@@ -185,3 +322,211 @@ const _: () = {
         }
     }
 };
+
+const _: () = {
+    use super::super::bindings::v1::supabase::wrappers::http as http_v1;
+    use super::super::bindings::v3::supabase::wrappers::http;
+
+    impl From<http::Method> for http_v1::Method {
+        fn from(m: http::Method) -> Self {
+            match m {
+                http::Method::Get => http_v1::Method::Get,
+                http::Method::Post => http_v1::Method::Post,
+                http::Method::Put => http_v1::Method::Put,
+                http::Method::Patch => http_v1::Method::Patch,
+                http::Method::Delete => http_v1::Method::Delete,
+            }
+        }
+    }
+
+    impl FdwHost {
+        // make a http request with binary body
+        fn http_request_bytes(&mut self, req: http::Request) -> http::HttpResult {
+            let resp = self.send_request(
+                req.method.into(),
+                &req.url,
+                &req.headers,
+                req.body,
+                req.timeout_ms.map(|t| Duration::from_millis(t as u64)),
+            )?;
+
+            let url = resp.url().to_string();
+            let status_code = resp.status().as_u16();
+            let headers = header_map_to_guest(resp.headers());
+            let body = block_on_remote(&self.rt, resp.bytes())?.map_err(body_error)?;
+            Ok(http::Response {
+                url,
+                status_code,
+                headers,
+                body: body.to_vec(),
+            })
+        }
+    }
+
+    impl FdwHost {
+        fn http_stream(&self, rep: &Resource<http::ResponseStream>) -> &HttpStream {
+            self.http_streams
+                .get(&rep.rep())
+                .expect("response stream should exist")
+        }
+    }
+
+    impl http::HostResponseStream for FdwHost {
+        fn url(&mut self, rep: Resource<http::ResponseStream>) -> String {
+            self.http_stream(&rep).url.clone()
+        }
+
+        fn status_code(&mut self, rep: Resource<http::ResponseStream>) -> u16 {
+            self.http_stream(&rep).status_code
+        }
+
+        fn headers(&mut self, rep: Resource<http::ResponseStream>) -> http::Headers {
+            self.http_stream(&rep).headers.clone()
+        }
+
+        fn read_chunk(
+            &mut self,
+            rep: Resource<http::ResponseStream>,
+        ) -> Result<Option<http::Body>, http::HttpError> {
+            let stream = self
+                .http_streams
+                .get_mut(&rep.rep())
+                .expect("response stream should exist");
+            stream.read_chunk(&self.rt)
+        }
+
+        fn read_line(
+            &mut self,
+            rep: Resource<http::ResponseStream>,
+        ) -> Result<Option<String>, http::HttpError> {
+            let stream = self
+                .http_streams
+                .get_mut(&rep.rep())
+                .expect("response stream should exist");
+            stream.read_line(&self.rt)
+        }
+
+        fn drop(&mut self, rep: Resource<http::ResponseStream>) -> WasmResult<()> {
+            self.http_streams.remove(&rep.rep());
+            Ok(())
+        }
+    }
+
+    impl http::Host for FdwHost {
+        fn get(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn post(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn put(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn patch(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn delete(&mut self, req: http::Request) -> http::HttpResult {
+            self.http_request_bytes(req)
+        }
+
+        fn send_stream(
+            &mut self,
+            req: http::Request,
+        ) -> Result<Resource<http::ResponseStream>, http::HttpError> {
+            let resp = self.send_request(
+                req.method.into(),
+                &req.url,
+                &req.headers,
+                req.body,
+                req.timeout_ms.map(|t| Duration::from_millis(t as u64)),
+            )?;
+            let rep = self.next_stream_rep;
+            self.next_stream_rep = rep.wrapping_add(1);
+            self.http_streams.insert(rep, HttpStream::new(resp));
+            Ok(Resource::new_own(rep))
+        }
+
+        fn error_for_status(&mut self, resp: http::Response) -> Result<(), http::HttpError> {
+            error_for_status(resp.status_code, &resp.url)
+        }
+    }
+};
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use pgrx::prelude::*;
+
+    // read all lines of body chunks
+    fn read_lines(chunks: &[&[u8]]) -> Result<Vec<String>, GuestHttpError> {
+        let mut chunks = chunks.iter().map(|c| c.to_vec());
+        let mut buf = Vec::new();
+        let mut lines = Vec::new();
+        while let Some(line) = take_line(&mut buf, || Ok(chunks.next()))? {
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+
+    #[pg_test]
+    fn take_line_test() {
+        assert_eq!(read_lines(&[]).unwrap(), Vec::<String>::new());
+        assert_eq!(
+            read_lines(&[b"a\nb", b"c\r\n", b"\n", b"d"]).unwrap(),
+            vec!["a", "bc", "", "d"]
+        );
+        assert_eq!(read_lines(&[b"a\n"]).unwrap(), vec!["a"]);
+        assert!(read_lines(&[b"\xff\n"]).is_err());
+    }
+
+    #[pg_test]
+    fn take_line_too_long_test() {
+        let long = vec![b'x'; MAX_LINE_SIZE + 1];
+
+        // line ending is not found within the max size
+        let mut buf = Vec::new();
+        let mut chunks = vec![long.clone(), b"\n".to_vec()].into_iter();
+        assert!(take_line(&mut buf, || Ok(chunks.next())).is_err());
+
+        // line ending is found but beyond the max size
+        let mut buf = [&long[..], b"\nnext\n"].concat();
+        assert!(take_line(&mut buf, || Ok(None)).is_err());
+        assert_eq!(buf.len(), long.len() + 6);
+
+        // a line of max size is fine
+        let mut buf = [&long[1..], b"\n"].concat();
+        let line = take_line(&mut buf, || Ok(None)).unwrap().unwrap();
+        assert_eq!(line.len(), MAX_LINE_SIZE);
+    }
+
+    #[pg_test]
+    fn request_error_category_test() {
+        use super::super::super::WasmFdwError;
+        use super::super::clear_request_error;
+        use supabase_wrappers::prelude::CategorizedError;
+
+        let url = "https://example.com";
+        let guest_error = |msg: &str| WasmFdwError::from(msg.to_string()).category();
+
+        // guest error is categorized by the last failed request
+        clear_request_error();
+        assert!(error_for_status(200, url).is_ok());
+        assert_eq!(guest_error("no request"), ErrorCategory::Remote);
+        assert!(error_for_status(503, url).is_err());
+        assert!(error_for_status(429, url).is_err());
+        assert_eq!(guest_error("rate limited"), ErrorCategory::RateLimited);
+
+        // the failed request is only used once
+        assert_eq!(guest_error("other"), ErrorCategory::Remote);
+
+        // and forgotten before next guest call
+        assert!(error_for_status(401, url).is_err());
+        clear_request_error();
+        assert_eq!(guest_error("next call"), ErrorCategory::Remote);
+    }
+}
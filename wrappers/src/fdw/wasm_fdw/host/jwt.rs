@@ -1,4 +1,6 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use jwt_simple::prelude::*;
+use serde_json::{Map as JsonMap, Value as JsonValue};
 
 use super::FdwHost;
 
@@ -52,3 +54,178 @@ const _: () = {
         }
     }
 };
+
+type VerifiedClaims = JWTClaims<JsonMap<String, JsonValue>>;
+
+// decode token payload as JSON string without verifying it
+fn decode_token(token: &str) -> Result<String, String> {
+    let payload = token
+        .split('.')
+        .nth(1)
+        .ok_or("invalid token format".to_string())?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| "invalid token payload encoding".to_string())?;
+    let claims: JsonValue =
+        serde_json::from_slice(&payload).map_err(|_| "invalid token payload".to_string())?;
+    Ok(claims.to_string())
+}
+
+// verify token signature and time claims, returns claims as JSON string
+fn verify_token(token: &str, algo: &str, key: &str) -> Result<String, String> {
+    let claims: Result<VerifiedClaims, _> = match algo {
+        "HS256" => HS256Key::from_bytes(key.as_bytes()).verify_token(token, None),
+        "HS384" => HS384Key::from_bytes(key.as_bytes()).verify_token(token, None),
+        "HS512" => HS512Key::from_bytes(key.as_bytes()).verify_token(token, None),
+        "RS256" => RS256PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "RS384" => RS384PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "RS512" => RS512PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "ES256" => ES256PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "ES384" => ES384PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        "EdDSA" => Ed25519PublicKey::from_pem(key).and_then(|k| k.verify_token(token, None)),
+        _ => return Err(format!("algorithm {algo} not implemented")),
+    };
+    let claims = claims.map_err(|e| e.to_string())?;
+    serde_json::to_string(&claims).map_err(|e| e.to_string())
+}
+
+const _: () = {
+    use super::super::bindings::v1::supabase::wrappers::jwt as jwt_v1;
+    use super::super::bindings::v3::supabase::wrappers::jwt;
+
+    impl jwt::Host for FdwHost {
+        fn encode(
+            &mut self,
+            payload: Vec<(String, String)>,
+            algo: String,
+            key: String,
+            ttl_hours: u32,
+        ) -> jwt::JwtResult {
+            jwt_v1::Host::encode(self, payload, algo, key, ttl_hours)
+        }
+
+        fn decode(&mut self, token: String) -> jwt::JwtResult {
+            decode_token(&token)
+        }
+
+        fn verify(&mut self, token: String, algo: String, key: String) -> jwt::JwtResult {
+            verify_token(&token, &algo, &key)
+        }
+    }
+};
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use pgrx::prelude::*;
+
+    const HS256_KEY: &str = "secret";
+
+    const RSA_PUBLIC_KEY: &str = r#"-----BEGIN PUBLIC KEY-----
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEApAKd28DfNW4ijjHlGgtx
+PRuQU37Z82Ad+g3f4JJSfYPtt3t9wZXrYCvSpHr8k7sk6s7ihoRyR0DiDMxg4Xth
+DulnHll0L/5keRR5RhWCta4FdugXgqtHVm3bOJVK3hYIDZ78zUYmclD2SvBszpA/
+YJ3a+wfVwGgoCXmEAVlYWb0pFalKlOFhgduu8wYDdaEwBrmbQtUbmR1ABGdDSO4i
+D0GrpSMxE+9OvlTkuuDlDpbbFta+AEv+kE0vGHoGyY0aPGsSMZX5h+RLCqEhmnPy
+u8i94N1GJKG7OmT0Op08jcBHdUbrnvt7EUKym4zUWfYoHX3Ybe/39kdE0nq0iorG
+SwIDAQAB
+-----END PUBLIC KEY-----
+"#;
+
+    const EC_PUBLIC_KEY: &str = r#"-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE/I0UecxUBtBohvQoi6VqtgzvVohP
+OqLSDuJ+yIlsshJkt8JbDZY2SPm96YTZW0vZyj0IJopXi9hPz4IxWe8afA==
+-----END PUBLIC KEY-----
+"#;
+
+    const ED_PUBLIC_KEY: &str = r#"-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEARk5I/0UpKSJdwmzMtpIf6vGmzt4oTSBvlffQr+T4IhQ=
+-----END PUBLIC KEY-----
+"#;
+
+    // tokens of the same claims signed by each algorithm, which expire in 2100
+    const HS256_TOKEN: &str = concat!(
+        "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.",
+        "eyJpc3MiOiJzdXBhYmFzZSIsInN1YiI6InVzZXItMSIsImlhdCI6MTcwMDAwMDAwMCwiZXhwIjo0MTAyNDQ0ODAwfQ.",
+        "CzZ506AHkqYcMO-ocV4FOuMe8nEpZ3MGGXXhm9Pdy48"
+    );
+    const RS256_TOKEN: &str = concat!(
+        "eyJhbGciOiJSUzI1NiIsInR5cCI6IkpXVCJ9.",
+        "eyJpc3MiOiJzdXBhYmFzZSIsInN1YiI6InVzZXItMSIsImlhdCI6MTcwMDAwMDAwMCwiZXhwIjo0MTAyNDQ0ODAwfQ.",
+        "l_qSApg-ZXTsk5wcaCudGr5fiHNvzrmV9Veah1vrKxCqV4p2vbP5DhTOu-1vTWgzoO_JcG0YKu67d-tS2T52YwjJ19i3kkSIS-6QXCDeC2EMBKO0afRpDMqYnBNZPJcsskgSWMfALUPkEc9OAALBZuHB4adtAJieaMTjIOpAyZusg48rN646B3ZrW6BLl70qJs49WjGpMBVFasSi76nantbm_rgCq0Sy8N5nJ0UC1o7DQmQ5hfUYAMutLppz6jexRFVe_VHesUpE_TTE7OGvc7v6Q6tba5zAv6wHrdDF4UzPH7NDJBRQa_JBdBiruCKnxZMOqNrq6JLT1WJYjxTHbQ"
+    );
+    const ES256_TOKEN: &str = concat!(
+        "eyJhbGciOiJFUzI1NiIsInR5cCI6IkpXVCJ9.",
+        "eyJpc3MiOiJzdXBhYmFzZSIsInN1YiI6InVzZXItMSIsImlhdCI6MTcwMDAwMDAwMCwiZXhwIjo0MTAyNDQ0ODAwfQ.",
+        "-FXFNfe8RwXM2bD4X3XsZJ8x-ThF-Z4c4Gd7kMnDxq6NzsHEg6FHM8MVuZ88Pmt8WHWyRD-dkAPPx0aTuY2LwQ"
+    );
+    const EDDSA_TOKEN: &str = concat!(
+        "eyJhbGciOiJFZERTQSIsInR5cCI6IkpXVCJ9.",
+        "eyJpc3MiOiJzdXBhYmFzZSIsInN1YiI6InVzZXItMSIsImlhdCI6MTcwMDAwMDAwMCwiZXhwIjo0MTAyNDQ0ODAwfQ.",
+        "BgBS1gssDI-O5Unsbkq_qEjrONeTE6aKeojb9b1WAAYYn9JcuvwWOQh1VZOnxHldjoRx-HoEA_WnXs81967VDw"
+    );
+
+    // token expired in 2020
+    const EXPIRED_TOKEN: &str = concat!(
+        "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.",
+        "eyJzdWIiOiJ1c2VyLTEiLCJpYXQiOjE2MDAwMDAwMDAsImV4cCI6MTYwMDAwMzYwMH0.",
+        "apE6Ii8PZyE3wSfMaKjKEBsiWpPcpadDutOnx372peA"
+    );
+
+    fn assert_claims(claims: &str) {
+        let claims: JsonValue = serde_json::from_str(claims).unwrap();
+        assert_eq!(claims["iss"], "supabase");
+        assert_eq!(claims["sub"], "user-1");
+    }
+
+    #[pg_test]
+    fn decode_token_test() {
+        let claims: JsonValue = serde_json::from_str(&decode_token(HS256_TOKEN).unwrap()).unwrap();
+        assert_eq!(
+            claims,
+            serde_json::json!({
+                "iss": "supabase",
+                "sub": "user-1",
+                "iat": 1700000000,
+                "exp": 4102444800u64,
+            })
+        );
+
+        // expired token can still be decoded
+        let claims: JsonValue =
+            serde_json::from_str(&decode_token(EXPIRED_TOKEN).unwrap()).unwrap();
+        assert_eq!(claims["exp"], 1600003600);
+
+        assert!(decode_token("not-a-token").is_err());
+        assert!(decode_token("header.!!!.signature").is_err());
+        assert!(decode_token("header.bm90IGpzb24.signature").is_err());
+    }
+
+    #[pg_test]
+    fn verify_token_test() {
+        assert_claims(&verify_token(HS256_TOKEN, "HS256", HS256_KEY).unwrap());
+        assert_claims(&verify_token(RS256_TOKEN, "RS256", RSA_PUBLIC_KEY).unwrap());
+        assert_claims(&verify_token(ES256_TOKEN, "ES256", EC_PUBLIC_KEY).unwrap());
+        assert_claims(&verify_token(EDDSA_TOKEN, "EdDSA", ED_PUBLIC_KEY).unwrap());
+
+        // wrong key or algorithm
+        assert!(verify_token(HS256_TOKEN, "HS256", "wrong secret").is_err());
+        assert!(verify_token(RS256_TOKEN, "ES256", EC_PUBLIC_KEY).is_err());
+        assert!(verify_token(ES256_TOKEN, "EdDSA", ED_PUBLIC_KEY).is_err());
+        assert!(verify_token(EDDSA_TOKEN, "none", "").is_err());
+
+        // tampered payload
+        let parts: Vec<&str> = HS256_TOKEN.split('.').collect();
+        let tampered = format!(
+            "{}.{}.{}",
+            parts[0],
+            EXPIRED_TOKEN.split('.').nth(1).unwrap(),
+            parts[2]
+        );
+        assert!(verify_token(&tampered, "HS256", HS256_KEY).is_err());
+
+        // expired token
+        assert!(verify_token(EXPIRED_TOKEN, "HS256", HS256_KEY).is_err());
+    }
+}
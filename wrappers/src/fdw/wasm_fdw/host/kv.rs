@@ -0,0 +1,24 @@
+use super::FdwHost;
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::kv;
+
+    impl kv::Host for FdwHost {
+        fn get(&mut self, key: String) -> Result<Option<String>, kv::KvError> {
+            self.kv.get(&key)
+        }
+
+        fn set(
+            &mut self,
+            key: String,
+            value: String,
+            ttl_secs: Option<u32>,
+        ) -> Result<(), kv::KvError> {
+            self.kv.set(&key, &value, ttl_secs)
+        }
+
+        fn delete(&mut self, key: String) -> Result<(), kv::KvError> {
+            self.kv.delete(&key)
+        }
+    }
+};
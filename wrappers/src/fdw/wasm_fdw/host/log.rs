@@ -0,0 +1,42 @@
+use pgrx::{PgLogLevel, PgSqlErrorCode, ereport};
+
+use super::FdwHost;
+
+// format a field value, it is quoted if it contains spaces, quotes or '='
+fn format_value(value: &str) -> String {
+    if value.is_empty() || value.contains([' ', '"', '=']) {
+        format!("{value:?}")
+    } else {
+        value.to_owned()
+    }
+}
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::log::{self, Level};
+
+    impl log::Host for FdwHost {
+        fn write(&mut self, level: Level, msg: String, fields: Vec<(String, String)>) {
+            let mut line = format!("[{}@{}] {}", self.pkg_name, self.pkg_version, msg);
+            for (key, value) in &fields {
+                line.push_str(&format!(" {}={}", key, format_value(value)));
+            }
+
+            let level = match level {
+                Level::Debug5 => PgLogLevel::DEBUG5,
+                Level::Debug4 => PgLogLevel::DEBUG4,
+                Level::Debug3 => PgLogLevel::DEBUG3,
+                Level::Debug2 => PgLogLevel::DEBUG2,
+                Level::Debug1 => PgLogLevel::DEBUG1,
+                Level::Log => PgLogLevel::LOG,
+                Level::Info => PgLogLevel::INFO,
+                Level::Warning => PgLogLevel::WARNING,
+            };
+            let code = if matches!(level, PgLogLevel::WARNING) {
+                PgSqlErrorCode::ERRCODE_WARNING
+            } else {
+                PgSqlErrorCode::ERRCODE_SUCCESSFUL_COMPLETION
+            };
+            ereport!(level, code, line, "Wrappers");
+        }
+    }
+};
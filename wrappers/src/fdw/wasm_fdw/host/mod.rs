@@ -1,19 +1,88 @@
+mod crypto;
 mod http;
 mod jwt;
+mod kv;
+mod log;
 mod stats;
+mod tcp;
 mod time;
 mod utils;
 
 use pgrx::pg_sys;
 use std::collections::HashMap;
-use wasmtime::Result as WasmResult;
+use std::time::{Duration, Instant};
+use thiserror::Error;
 use wasmtime::component::*;
+use wasmtime::{CallHook, Result as WasmResult, Store};
 
 use supabase_wrappers::prelude::*;
 
+use super::allowlist::HostAllowlist;
+use super::http_options::HttpOptions;
+use super::kv_store::KvStore;
+use super::limits::MemoryLimiter;
+use http::HttpStream;
+use tcp::TcpConnection;
+
+/// Error of converting a cell sent by the guest, such as a numeric string
+/// that doesn't parse
+#[derive(Error, Debug)]
+#[error("invalid cell from guest: {0}")]
+pub(super) struct InvalidGuestCell(pub(super) String);
+
+thread_local! {
+    // category of the last failed remote request made by the guest in the
+    // current guest call, it is used to categorize the error returned by the
+    // guest
+    static REQUEST_ERROR: std::cell::Cell<Option<ErrorCategory>> =
+        const { std::cell::Cell::new(None) };
+}
+
+// record a failed remote request made by the guest
+fn set_request_error(category: ErrorCategory) {
+    REQUEST_ERROR.set(Some(category));
+}
+
+/// Forget the failed remote requests, called before each call into the guest
+pub(super) fn clear_request_error() {
+    REQUEST_ERROR.set(None);
+}
+
+/// Take the category of the last failed remote request made by the guest
+pub(super) fn take_request_error() -> Option<ErrorCategory> {
+    REQUEST_ERROR.take()
+}
+
+// run a remote call made by the guest, unless the statement is canceled. The
+// interrupted call returns error to the guest, and the cancellation is
+// reported to Postgres once the guest call returns.
+fn block_on_remote<F: Future>(rt: &Runtime, future: F) -> Result<F::Output, String> {
+    block_on_interruptible(rt, future).map_err(|e| e.to_string())
+}
+
+// return error to the guest if the statement is canceled, it is checked
+// before the blocking socket operations which cannot be interrupted
+fn check_canceled() -> Result<(), String> {
+    if cancel_pending() {
+        Err(Interrupted.to_string())
+    } else {
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct FdwHost {
     pub rt: Runtime,
+    pub limiter: MemoryLimiter,
+    pub allowlist: HostAllowlist,
+    pub http_opts: HttpOptions,
+    pub tcp_timeout: Duration,
+    // deadline of the current guest call set by 'call_timeout_ms', remote
+    // calls made by the guest cannot run past it
+    pub call_deadline: Option<Instant>,
+    pub kv: KvStore,
+    pub pkg_name: String,
+    pub pkg_version: String,
     pub svr_opts: HashMap<String, String>,
     pub tbl_opts: HashMap<String, String>,
     pub import_schema_opts: HashMap<String, String>,
@@ -22,6 +91,15 @@ pub(super) struct FdwHost {
     pub quals: Vec<Qual>,
     pub sorts: Vec<Sort>,
     pub limit: Option<Limit>,
+    // open streaming http responses, keyed by resource rep
+    http_streams: HashMap<u32, HttpStream>,
+    next_stream_rep: u32,
+    // open tcp connections, keyed by resource rep
+    tcp_conns: HashMap<u32, TcpConnection>,
+    next_conn_rep: u32,
+    // cell conversion error in the current host call, which is raised as a
+    // trap when the host call returns
+    cell_error: Option<InvalidGuestCell>,
 }
 
 impl FdwHost {
@@ -29,6 +107,7 @@ impl FdwHost {
     const SVR_OPTS_REP: u32 = 2000;
     const TBL_OPTS_REP: u32 = 3000;
     const IMPORT_SCHEMA_OPTS_REP: u32 = 3100;
+    const OTHER_OPTS_REP: u32 = 3200;
     const ROW_REP: u32 = 4000;
     const COLUMN_REP: u32 = 5000;
     const QUAL_REP: u32 = 6000;
@@ -38,6 +117,14 @@ impl FdwHost {
     pub(super) fn new(rt: Runtime) -> Self {
         Self {
             rt,
+            limiter: MemoryLimiter::default(),
+            allowlist: HostAllowlist::default(),
+            http_opts: HttpOptions::default(),
+            tcp_timeout: HttpOptions::default().tcp_timeout(None),
+            call_deadline: None,
+            kv: KvStore::default(),
+            pkg_name: String::new(),
+            pkg_version: String::new(),
             svr_opts: HashMap::new(),
             tbl_opts: HashMap::new(),
             import_schema_opts: HashMap::new(),
@@ -46,6 +133,50 @@ impl FdwHost {
             quals: Vec::new(),
             sorts: Vec::new(),
             limit: None,
+            http_streams: HashMap::new(),
+            next_stream_rep: 0,
+            tcp_conns: HashMap::new(),
+            next_conn_rep: 0,
+            cell_error: None,
+        }
+    }
+
+    // time left before the deadline of the current guest call
+    fn call_time_left(&self) -> Option<Duration> {
+        self.call_deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    // convert a guest cell, the error is saved and raised when the host call
+    // returns, as host calls like 'row.push' cannot return errors to the guest
+    fn convert_cell<C>(&mut self, cell: C) -> Option<Cell>
+    where
+        Cell: TryFrom<C, Error = wasmtime::Error>,
+    {
+        Cell::try_from(cell)
+            .map_err(|err| self.cell_error = Some(InvalidGuestCell(err.to_string())))
+            .ok()
+    }
+
+    /// Trap the guest when a host call failed to convert a guest cell
+    pub(super) fn trap_cell_errors(store: &mut Store<Self>) {
+        store.call_hook(|mut ctx, hook| match hook {
+            CallHook::ReturningFromHost => match ctx.data_mut().cell_error.take() {
+                Some(err) => Err(err.into()),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        });
+    }
+
+    // get the options of an options resource, the unsupported 'other' options
+    // type has no options
+    fn options(&self, rep: u32) -> Option<&HashMap<String, String>> {
+        match rep {
+            Self::SVR_OPTS_REP => Some(&self.svr_opts),
+            Self::TBL_OPTS_REP => Some(&self.tbl_opts),
+            Self::IMPORT_SCHEMA_OPTS_REP => Some(&self.import_schema_opts),
+            _ => None,
         }
     }
 }
@@ -79,7 +210,7 @@ const _: () = {
         }
 
         fn push(&mut self, _rep: Resource<GuestRow>, cell: Option<GuestCell>) {
-            let cell = cell.map(|c| Cell::try_from(c).expect("convert cell failed"));
+            let cell = cell.and_then(|c| self.convert_cell(c));
             let idx = self.row.cols.len();
             let col = &self.columns[idx];
             self.row.push(&col.name, cell);
@@ -97,7 +228,9 @@ const _: () = {
 
         fn name(&mut self, rep: Resource<GuestColumn>) -> String {
             let index = (rep.rep() - Self::COLUMN_REP) as usize;
-            self.columns[index].name.clone()
+            // expose the remote name, so guest FDWs honor the `column_name`
+            // column option without any changes
+            self.columns[index].remote_name.clone()
         }
 
         fn num(&mut self, rep: Resource<GuestColumn>) -> u32 {
@@ -137,7 +270,7 @@ const _: () = {
 
         fn field(&mut self, rep: Resource<GuestQual>) -> String {
             let index = (rep.rep() - Self::QUAL_REP) as usize;
-            self.quals[index].field.clone()
+            self.quals[index].remote_field.clone()
         }
 
         fn operator(&mut self, rep: Resource<GuestQual>) -> String {
@@ -177,7 +310,7 @@ const _: () = {
 
         fn field(&mut self, rep: Resource<GuestSort>) -> String {
             let index = (rep.rep() - Self::SORT_REP) as usize;
-            self.sorts[index].field.clone()
+            self.sorts[index].remote_field.clone()
         }
 
         fn field_no(&mut self, rep: Resource<GuestSort>) -> u32 {
@@ -366,7 +499,7 @@ const _: () = {
         }
 
         fn push(&mut self, _rep: Resource<GuestRow>, cell: Option<GuestCell>) {
-            let cell = cell.map(|c| Cell::try_from(c).expect("convert cell failed"));
+            let cell = cell.and_then(|c| self.convert_cell(c));
             let idx = self.row.cols.len();
             let col = &self.columns[idx];
             self.row.push(&col.name, cell);
@@ -384,7 +517,9 @@ const _: () = {
 
         fn name(&mut self, rep: Resource<GuestColumn>) -> String {
             let index = (rep.rep() - Self::COLUMN_REP) as usize;
-            self.columns[index].name.clone()
+            // expose the remote name, so guest FDWs honor the `column_name`
+            // column option without any changes
+            self.columns[index].remote_name.clone()
         }
 
         fn num(&mut self, rep: Resource<GuestColumn>) -> u32 {
@@ -425,7 +560,7 @@ const _: () = {
 
         fn field(&mut self, rep: Resource<GuestQual>) -> String {
             let index = (rep.rep() - Self::QUAL_REP) as usize;
-            self.quals[index].field.clone()
+            self.quals[index].remote_field.clone()
         }
 
         fn operator(&mut self, rep: Resource<GuestQual>) -> String {
@@ -465,7 +600,7 @@ const _: () = {
 
         fn field(&mut self, rep: Resource<GuestSort>) -> String {
             let index = (rep.rep() - Self::SORT_REP) as usize;
-            self.sorts[index].field.clone()
+            self.sorts[index].remote_field.clone()
         }
 
         fn field_no(&mut self, rep: Resource<GuestSort>) -> u32 {
@@ -531,19 +666,16 @@ const _: () = {
                 OptionsType::Server => Self::SVR_OPTS_REP,
                 OptionsType::Table => Self::TBL_OPTS_REP,
                 OptionsType::ImportSchema => Self::IMPORT_SCHEMA_OPTS_REP,
-                OptionsType::Other(_) => todo!("Add support for more options type"),
+                // not supported, an error is returned when its option is required
+                OptionsType::Other(_) => Self::OTHER_OPTS_REP,
             };
             Resource::new_own(opt_type)
         }
 
         fn get(&mut self, rep: Resource<Options>, key: String) -> Option<String> {
-            let opts = match rep.rep() {
-                Self::SVR_OPTS_REP => &self.svr_opts,
-                Self::TBL_OPTS_REP => &self.tbl_opts,
-                Self::IMPORT_SCHEMA_OPTS_REP => &self.import_schema_opts,
-                _ => unreachable!(),
-            };
-            opts.get(&key).map(|s| s.to_owned())
+            self.options(rep.rep())
+                .and_then(|opts| opts.get(&key))
+                .map(|s| s.to_owned())
         }
 
         fn require(
@@ -551,11 +683,10 @@ const _: () = {
             rep: Resource<Options>,
             key: String,
         ) -> Result<String, GuestFdwError> {
-            let opts = match rep.rep() {
-                Self::SVR_OPTS_REP => &self.svr_opts,
-                Self::TBL_OPTS_REP => &self.tbl_opts,
-                Self::IMPORT_SCHEMA_OPTS_REP => &self.import_schema_opts,
-                _ => unreachable!(),
+            let Some(opts) = self.options(rep.rep()) else {
+                return Err(format!(
+                    "required option '{key}' is not found, the options type is not supported"
+                ));
             };
             require_option(&key, opts)
                 .map(|s| s.to_owned())
@@ -563,13 +694,308 @@ const _: () = {
         }
 
         fn require_or(&mut self, rep: Resource<Options>, key: String, default: String) -> String {
-            let opts = match rep.rep() {
-                Self::SVR_OPTS_REP => &self.svr_opts,
-                Self::TBL_OPTS_REP => &self.tbl_opts,
-                Self::IMPORT_SCHEMA_OPTS_REP => &self.import_schema_opts,
-                _ => unreachable!(),
+            match self.options(rep.rep()) {
+                Some(opts) => require_option_or(&key, opts, &default).to_owned(),
+                None => default,
+            }
+        }
+
+        fn drop(&mut self, _rep: Resource<Options>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostContext for FdwHost {
+        fn new(&mut self) -> Resource<Context> {
+            Resource::new_borrow(Self::CTX_REP)
+        }
+
+        fn get_options(
+            &mut self,
+            _rep: Resource<Context>,
+            options_type: OptionsType,
+        ) -> Resource<Options> {
+            HostOptions::new(self, options_type)
+        }
+
+        fn get_quals(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestQual>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.quals.len() {
+                ret.push(HostQual::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_columns(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestColumn>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.columns.len() {
+                ret.push(HostColumn::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_sorts(&mut self, _rep: Resource<Context>) -> Vec<Resource<GuestSort>> {
+            let mut ret = Vec::new();
+            for idx in 0..self.sorts.len() {
+                ret.push(HostSort::new(self, idx as u32));
+            }
+            ret
+        }
+
+        fn get_limit(&mut self, _rep: Resource<Context>) -> Option<Resource<GuestLimit>> {
+            if self.limit.is_some() {
+                Some(HostLimit::new(self))
+            } else {
+                None
+            }
+        }
+
+        fn drop(&mut self, _rep: Resource<Context>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Host for FdwHost {}
+};
+
+const _: () = {
+    use super::bindings::v3::{
+        exports::supabase::wrappers::routines::Context,
+        supabase::wrappers::types::{
+            Cell as GuestCell, Column as GuestColumn, FdwError as GuestFdwError, Host, HostColumn,
+            HostContext, HostLimit, HostOptions, HostQual, HostRow, HostSort, Limit as GuestLimit,
+            Options, OptionsType, Param as GuestParam, Qual as GuestQual, Row as GuestRow,
+            Sort as GuestSort, TypeOid, Value as GuestValue,
+        },
+    };
+
+    impl HostRow for FdwHost {
+        fn new(&mut self) -> Resource<GuestRow> {
+            Resource::new_own(Self::ROW_REP)
+        }
+
+        fn cols(&mut self, _rep: Resource<GuestRow>) -> Vec<String> {
+            self.row.cols.clone()
+        }
+
+        fn cells(&mut self, _rep: Resource<GuestRow>) -> Vec<Option<GuestCell>> {
+            self.row
+                .cells
+                .iter()
+                .map(|c| c.as_ref().map(GuestCell::from))
+                .collect()
+        }
+
+        fn push(&mut self, _rep: Resource<GuestRow>, cell: Option<GuestCell>) {
+            let cell = cell.and_then(|c| self.convert_cell(c));
+            let idx = self.row.cols.len();
+            let col = &self.columns[idx];
+            self.row.push(&col.name, cell);
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestRow>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostColumn for FdwHost {
+        fn new(&mut self, index: u32) -> Resource<GuestColumn> {
+            Resource::new_own(Self::COLUMN_REP + index)
+        }
+
+        fn name(&mut self, rep: Resource<GuestColumn>) -> String {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            // expose the remote name, so guest FDWs honor the `column_name`
+            // column option without any changes
+            self.columns[index].remote_name.clone()
+        }
+
+        fn num(&mut self, rep: Resource<GuestColumn>) -> u32 {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            self.columns[index].num as u32
+        }
+
+        fn type_oid(&mut self, rep: Resource<GuestColumn>) -> TypeOid {
+            let index = (rep.rep() - Self::COLUMN_REP) as usize;
+            match self.columns[index].type_oid {
+                pg_sys::BOOLOID => TypeOid::Bool,
+                pg_sys::CHAROID => TypeOid::I8,
+                pg_sys::INT2OID => TypeOid::I16,
+                pg_sys::FLOAT4OID => TypeOid::F32,
+                pg_sys::INT4OID => TypeOid::I32,
+                pg_sys::FLOAT8OID => TypeOid::F64,
+                pg_sys::INT8OID => TypeOid::I64,
+                pg_sys::NUMERICOID => TypeOid::Numeric,
+                pg_sys::TEXTOID => TypeOid::String,
+                pg_sys::DATEOID => TypeOid::Date,
+                pg_sys::TIMESTAMPOID => TypeOid::Timestamp,
+                pg_sys::TIMESTAMPTZOID => TypeOid::Timestamptz,
+                pg_sys::JSONBOID => TypeOid::Json,
+                pg_sys::UUIDOID => TypeOid::Uuid,
+                pg_sys::TIMEOID => TypeOid::Time,
+                pg_sys::INTERVALOID => TypeOid::Interval,
+                pg_sys::BYTEAOID => TypeOid::Bytea,
+                pg_sys::BOOLARRAYOID => TypeOid::BoolArray,
+                pg_sys::INT2ARRAYOID => TypeOid::I16Array,
+                pg_sys::INT4ARRAYOID => TypeOid::I32Array,
+                pg_sys::INT8ARRAYOID => TypeOid::I64Array,
+                pg_sys::FLOAT4ARRAYOID => TypeOid::F32Array,
+                pg_sys::FLOAT8ARRAYOID => TypeOid::F64Array,
+                pg_sys::TEXTARRAYOID => TypeOid::StringArray,
+                other => TypeOid::Other(other.to_u32().to_string()),
+            }
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestColumn>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostQual for FdwHost {
+        fn new(&mut self, index: u32) -> Resource<GuestQual> {
+            Resource::new_own(Self::QUAL_REP + index)
+        }
+
+        fn field(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].remote_field.clone()
+        }
+
+        fn operator(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].operator.clone()
+        }
+
+        fn value(&mut self, rep: Resource<GuestQual>) -> GuestValue {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            GuestValue::from(self.quals[index].value.clone())
+        }
+
+        fn use_or(&mut self, rep: Resource<GuestQual>) -> bool {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].use_or
+        }
+
+        fn param(&mut self, rep: Resource<GuestQual>) -> Option<GuestParam> {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].param.clone().map(GuestParam::from)
+        }
+
+        fn deparse(&mut self, rep: Resource<GuestQual>) -> String {
+            let index = (rep.rep() - Self::QUAL_REP) as usize;
+            self.quals[index].deparse()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestQual>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostSort for FdwHost {
+        fn new(&mut self, index: u32) -> Resource<GuestSort> {
+            Resource::new_own(Self::SORT_REP + index)
+        }
+
+        fn field(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].remote_field.clone()
+        }
+
+        fn field_no(&mut self, rep: Resource<GuestSort>) -> u32 {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].field_no as u32
+        }
+
+        fn reversed(&mut self, rep: Resource<GuestSort>) -> bool {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].reversed
+        }
+
+        fn nulls_first(&mut self, rep: Resource<GuestSort>) -> bool {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].nulls_first
+        }
+
+        fn collate(&mut self, rep: Resource<GuestSort>) -> Option<String> {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].collate.clone()
+        }
+
+        fn deparse(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].deparse()
+        }
+
+        fn deparse_with_collate(&mut self, rep: Resource<GuestSort>) -> String {
+            let index = (rep.rep() - Self::SORT_REP) as usize;
+            self.sorts[index].deparse_with_collate()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestSort>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostLimit for FdwHost {
+        fn new(&mut self) -> Resource<GuestLimit> {
+            Resource::new_own(Self::LIMIT_REP)
+        }
+
+        fn count(&mut self, _rep: Resource<GuestLimit>) -> i64 {
+            self.limit.as_ref().map(|a| a.count).unwrap()
+        }
+
+        fn offset(&mut self, _rep: Resource<GuestLimit>) -> i64 {
+            self.limit.as_ref().map(|a| a.offset).unwrap()
+        }
+
+        fn deparse(&mut self, _rep: Resource<GuestLimit>) -> String {
+            self.limit.as_ref().map(|a| a.deparse()).unwrap()
+        }
+
+        fn drop(&mut self, _rep: Resource<GuestLimit>) -> WasmResult<()> {
+            Ok(())
+        }
+    }
+
+    impl HostOptions for FdwHost {
+        fn new(&mut self, options_type: OptionsType) -> Resource<Options> {
+            let opt_type = match options_type {
+                OptionsType::Server => Self::SVR_OPTS_REP,
+                OptionsType::Table => Self::TBL_OPTS_REP,
+                OptionsType::ImportSchema => Self::IMPORT_SCHEMA_OPTS_REP,
+                // not supported, an error is returned when its option is required
+                OptionsType::Other(_) => Self::OTHER_OPTS_REP,
             };
-            require_option_or(&key, opts, &default).to_owned()
+            Resource::new_own(opt_type)
+        }
+
+        fn get(&mut self, rep: Resource<Options>, key: String) -> Option<String> {
+            self.options(rep.rep())
+                .and_then(|opts| opts.get(&key))
+                .map(|s| s.to_owned())
+        }
+
+        fn require(
+            &mut self,
+            rep: Resource<Options>,
+            key: String,
+        ) -> Result<String, GuestFdwError> {
+            let Some(opts) = self.options(rep.rep()) else {
+                return Err(format!(
+                    "required option '{key}' is not found, the options type is not supported"
+                ));
+            };
+            require_option(&key, opts)
+                .map(|s| s.to_owned())
+                .map_err(|e| e.to_string())
+        }
+
+        fn require_or(&mut self, rep: Resource<Options>, key: String, default: String) -> String {
+            match self.options(rep.rep()) {
+                Some(opts) => require_option_or(&key, opts, &default).to_owned(),
+                None => default,
+            }
         }
 
         fn drop(&mut self, _rep: Resource<Options>) -> WasmResult<()> {
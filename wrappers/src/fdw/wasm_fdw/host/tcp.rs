@@ -0,0 +1,258 @@
+use rustls::pki_types::{CertificateDer, ServerName, pem::PemObject};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+use wasmtime::Result as WasmResult;
+use wasmtime::component::Resource;
+
+use supabase_wrappers::prelude::ErrorCategory;
+
+use super::super::WasmFdwError;
+use super::super::http_options::HttpOptions;
+use super::{FdwHost, check_canceled, set_request_error};
+use crate::setup_rustls_default_crypto_provider;
+
+// max number of bytes can be read from a connection at once
+const MAX_READ_SIZE: u32 = 1024 * 1024;
+
+// max number of open connections of a foreign server
+const MAX_CONNECTIONS: usize = 16;
+
+// create TLS client config which trusts the public root certificates and
+// the 'ca_cert' server option
+fn create_tls_config(http_opts: &HttpOptions) -> Result<Arc<ClientConfig>, String> {
+    setup_rustls_default_crypto_provider();
+
+    let mut roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(pem) = http_opts.ca_cert_pem() {
+        for cert in CertificateDer::pem_slice_iter(pem.as_bytes()) {
+            let cert = cert.map_err(|e| format!("invalid ca_cert: {e}"))?;
+            roots
+                .add(cert)
+                .map_err(|e| format!("invalid ca_cert: {e}"))?;
+        }
+    }
+
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+// tcp connection opened by the guest, which can be upgraded to TLS
+#[derive(Debug)]
+pub(super) enum TcpConnection {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+    // closed by the guest or failed to upgrade to TLS
+    Closed,
+}
+
+impl TcpConnection {
+    fn connect(host: &str, port: u16, timeout: Duration) -> Result<Self, String> {
+        let addrs = (host, port).to_socket_addrs().map_err(|e| e.to_string())?;
+
+        // try each resolved address until one of them is connected
+        let mut last_err = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream
+                        .set_read_timeout(Some(timeout))
+                        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+                        .and_then(|_| stream.set_nodelay(true))
+                        .map_err(|e| e.to_string())?;
+                    return Ok(Self::Plain(stream));
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| format!("cannot resolve host '{host}'")))
+    }
+
+    // set the read and write timeout of the socket
+    fn set_timeout(&self, timeout: Duration) -> Result<(), String> {
+        let sock = match self {
+            Self::Plain(stream) => stream,
+            Self::Tls(stream) => &stream.sock,
+            Self::Closed => return Err("connection is closed".to_string()),
+        };
+        sock.set_read_timeout(Some(timeout))
+            .and_then(|_| sock.set_write_timeout(Some(timeout)))
+            .map_err(|e| e.to_string())
+    }
+
+    fn read(&mut self, max_bytes: u32) -> Result<Vec<u8>, String> {
+        let mut buf = vec![0u8; max_bytes.min(MAX_READ_SIZE) as usize];
+        let len = match self {
+            Self::Plain(stream) => stream.read(&mut buf),
+            Self::Tls(stream) => stream.read(&mut buf),
+            Self::Closed => return Err("connection is closed".to_string()),
+        }
+        .map_err(|e| e.to_string())?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        match self {
+            Self::Plain(stream) => stream.write_all(data).and_then(|_| stream.flush()),
+            Self::Tls(stream) => stream.write_all(data).and_then(|_| stream.flush()),
+            Self::Closed => return Err("connection is closed".to_string()),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    fn upgrade_tls(&mut self, server_name: &str, config: Arc<ClientConfig>) -> Result<(), String> {
+        let mut sock = match std::mem::replace(self, Self::Closed) {
+            Self::Plain(sock) => sock,
+            Self::Tls(stream) => {
+                *self = Self::Tls(stream);
+                return Err("connection is already upgraded to TLS".to_string());
+            }
+            Self::Closed => return Err("connection is closed".to_string()),
+        };
+
+        let server_name = ServerName::try_from(server_name.to_owned())
+            .map_err(|e| format!("invalid server name: {e}"))?;
+        let mut conn = ClientConnection::new(config, server_name).map_err(|e| e.to_string())?;
+
+        // finish the handshake now so its errors are returned to the guest
+        while conn.is_handshaking() {
+            conn.complete_io(&mut sock).map_err(|e| e.to_string())?;
+        }
+
+        *self = Self::Tls(Box::new(StreamOwned::new(conn, sock)));
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        match std::mem::replace(self, Self::Closed) {
+            Self::Plain(sock) => sock.shutdown(Shutdown::Both),
+            Self::Tls(mut stream) => {
+                stream.conn.send_close_notify();
+                stream
+                    .flush()
+                    .and_then(|_| stream.sock.shutdown(Shutdown::Both))
+            }
+            Self::Closed => Ok(()),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+// record a failed socket operation to categorize the guest error
+fn network_error() {
+    set_request_error(ErrorCategory::Network);
+}
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::tcp;
+
+    impl FdwHost {
+        fn tcp_conn(&mut self, rep: &Resource<tcp::Connection>) -> &mut TcpConnection {
+            self.tcp_conns
+                .get_mut(&rep.rep())
+                .expect("tcp connection should exist")
+        }
+
+        // timeout of a socket operation, it cannot run past the deadline of
+        // the current guest call
+        fn tcp_op_timeout(&self) -> Result<Duration, String> {
+            match self.call_time_left() {
+                Some(left) if left.is_zero() => {
+                    set_request_error(ErrorCategory::LimitExceeded);
+                    Err(WasmFdwError::CallTimeout.to_string())
+                }
+                Some(left) => Ok(left.min(self.tcp_timeout)),
+                None => Ok(self.tcp_timeout),
+            }
+        }
+
+        // get a connection whose timeout is set for the next operation
+        fn tcp_conn_for_op(
+            &mut self,
+            rep: &Resource<tcp::Connection>,
+        ) -> Result<&mut TcpConnection, String> {
+            check_canceled()?;
+            let timeout = self.tcp_op_timeout()?;
+            let conn = self.tcp_conn(rep);
+            conn.set_timeout(timeout).inspect_err(|_| network_error())?;
+            Ok(conn)
+        }
+    }
+
+    impl tcp::HostConnection for FdwHost {
+        fn read(
+            &mut self,
+            rep: Resource<tcp::Connection>,
+            max_bytes: u32,
+        ) -> Result<Vec<u8>, tcp::TcpError> {
+            self.tcp_conn_for_op(&rep)?
+                .read(max_bytes)
+                .inspect_err(|_| network_error())
+        }
+
+        fn write(
+            &mut self,
+            rep: Resource<tcp::Connection>,
+            data: Vec<u8>,
+        ) -> Result<(), tcp::TcpError> {
+            self.tcp_conn_for_op(&rep)?
+                .write(&data)
+                .inspect_err(|_| network_error())
+        }
+
+        fn upgrade_tls(
+            &mut self,
+            rep: Resource<tcp::Connection>,
+            server_name: String,
+        ) -> Result<(), tcp::TcpError> {
+            let config = create_tls_config(&self.http_opts)?;
+            self.tcp_conn_for_op(&rep)?
+                .upgrade_tls(&server_name, config)
+                .inspect_err(|_| network_error())
+        }
+
+        fn close(&mut self, rep: Resource<tcp::Connection>) -> Result<(), tcp::TcpError> {
+            self.tcp_conn(&rep).close()
+        }
+
+        fn drop(&mut self, rep: Resource<tcp::Connection>) -> WasmResult<()> {
+            self.tcp_conns.remove(&rep.rep());
+            Ok(())
+        }
+    }
+
+    impl tcp::Host for FdwHost {
+        fn connect(
+            &mut self,
+            host: String,
+            port: u16,
+        ) -> Result<Resource<tcp::Connection>, tcp::TcpError> {
+            check_canceled()?;
+            self.allowlist.check_tcp(&host, port).inspect_err(|_| {
+                set_request_error(ErrorCategory::PermissionDenied);
+            })?;
+            if self.tcp_conns.len() >= MAX_CONNECTIONS {
+                set_request_error(ErrorCategory::LimitExceeded);
+                return Err(format!(
+                    "too many open tcp connections, max is {MAX_CONNECTIONS}"
+                ));
+            }
+
+            let timeout = self.tcp_op_timeout()?;
+            let conn =
+                TcpConnection::connect(&host, port, timeout).inspect_err(|_| network_error())?;
+            let rep = self.next_conn_rep;
+            self.next_conn_rep = rep.wrapping_add(1);
+            self.tcp_conns.insert(rep, conn);
+            Ok(Resource::new_own(rep))
+        }
+    }
+};
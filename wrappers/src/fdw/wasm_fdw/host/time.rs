@@ -64,3 +64,30 @@ const _: () = {
         }
     }
 };
+
+const _: () = {
+    use super::super::bindings::v1::supabase::wrappers::time as time_v1;
+    use super::super::bindings::v3::supabase::wrappers::time;
+
+    impl time::Host for FdwHost {
+        fn epoch_secs(&mut self) -> i64 {
+            time_v1::Host::epoch_secs(self)
+        }
+
+        fn parse_from_rfc3339(&mut self, s: String) -> time::TimeResult {
+            time_v1::Host::parse_from_rfc3339(self, s)
+        }
+
+        fn parse_from_str(&mut self, s: String, fmt: String) -> time::TimeResult {
+            time_v1::Host::parse_from_str(self, s, fmt)
+        }
+
+        fn epoch_ms_to_rfc3339(&mut self, msecs: i64) -> Result<String, time::TimeError> {
+            time_v1::Host::epoch_ms_to_rfc3339(self, msecs)
+        }
+
+        fn sleep(&mut self, millis: u64) {
+            time_v1::Host::sleep(self, millis)
+        }
+    }
+};
@@ -26,9 +26,10 @@ const _: () = {
 
         fn cell_to_string(&mut self, cell: Option<GuestCell>) -> String {
             match cell {
-                Some(c) => Cell::try_from(c)
+                Some(c) => self
+                    .convert_cell(c)
                     .map(|a| a.to_string())
-                    .expect("convert cell failed"),
+                    .unwrap_or_default(),
                 None => "null".to_string(),
             }
         }
@@ -65,9 +66,50 @@ const _: () = {
 
         fn cell_to_string(&mut self, cell: Option<GuestCell>) -> String {
             match cell {
-                Some(c) => Cell::try_from(c)
+                Some(c) => self
+                    .convert_cell(c)
                     .map(|a| a.to_string())
-                    .expect("convert cell failed"),
+                    .unwrap_or_default(),
+                None => "null".to_string(),
+            }
+        }
+
+        fn get_vault_secret(&mut self, secret_id: String) -> Option<String> {
+            get_vault_secret(&secret_id)
+        }
+
+        fn get_vault_secret_by_name(&mut self, secret_name: String) -> Option<String> {
+            get_vault_secret_by_name(&secret_name)
+        }
+    }
+};
+
+const _: () = {
+    use super::super::bindings::v3::supabase::wrappers::{types::Cell as GuestCell, utils};
+
+    impl utils::Host for FdwHost {
+        fn report_info(&mut self, msg: String) {
+            report_info(&msg);
+        }
+
+        fn report_notice(&mut self, msg: String) {
+            report_notice(&msg);
+        }
+
+        fn report_warning(&mut self, msg: String) {
+            report_warning(&msg);
+        }
+
+        fn report_error(&mut self, msg: String) {
+            report_error(PgSqlErrorCode::ERRCODE_FDW_ERROR, &msg);
+        }
+
+        fn cell_to_string(&mut self, cell: Option<GuestCell>) -> String {
+            match cell {
+                Some(c) => self
+                    .convert_cell(c)
+                    .map(|a| a.to_string())
+                    .unwrap_or_default(),
                 None => "null".to_string(),
             }
         }
@@ -0,0 +1,126 @@
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use supabase_wrappers::prelude::OptionsError;
+
+use super::WasmFdwResult;
+
+// default max number of retries on transient errors
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+// default connect, read and write timeout of TCP connections, used when
+// neither `http_timeout_ms` nor `call_timeout_ms` is set
+const DEFAULT_TCP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP client options of a Wasm FDW, set by the server options below
+///
+/// - `http_timeout_ms` - timeout of each HTTP request, in milliseconds. It is
+///   not set by default and guests can override it for each request. It is
+///   also the connect, read and write timeout of TCP connections, which
+///   defaults to `call_timeout_ms` or 30 seconds if that is not set either.
+/// - `http_max_retries` - max number of retries on transient errors, default
+///   is 3
+/// - `http_proxy` - proxy URL all HTTP requests are sent through, e.g.
+///   `http://proxy.example.com:3128`
+/// - `ca_cert` - additional root certificate in PEM format, for servers using
+///   a private certificate authority. It is also trusted by TLS upgraded TCP
+///   connections.
+#[derive(Debug, Clone)]
+pub(super) struct HttpOptions {
+    timeout: Option<Duration>,
+    pub max_retries: u32,
+    proxy: Option<Proxy>,
+    ca_cert: Option<Certificate>,
+    // the original PEM of `ca_cert`, used by TCP connections
+    ca_cert_pem: Option<String>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            proxy: None,
+            ca_cert: None,
+            ca_cert_pem: None,
+        }
+    }
+}
+
+fn parse_error(option_name: &str, type_name: &'static str) -> OptionsError {
+    OptionsError::OptionParsingError {
+        option_name: option_name.to_string(),
+        type_name,
+    }
+}
+
+impl HttpOptions {
+    pub(super) fn from_options(options: &HashMap<String, String>) -> WasmFdwResult<Self> {
+        let timeout = options
+            .get("http_timeout_ms")
+            .map(|v| {
+                v.parse::<u64>()
+                    .ok()
+                    .filter(|v| *v > 0)
+                    .map(Duration::from_millis)
+                    .ok_or_else(|| parse_error("http_timeout_ms", "positive integer"))
+            })
+            .transpose()?;
+        let max_retries = options
+            .get("http_max_retries")
+            .map(|v| {
+                v.parse::<u32>()
+                    .map_err(|_| parse_error("http_max_retries", "non-negative integer"))
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        let proxy = options
+            .get("http_proxy")
+            .map(|v| Proxy::all(v).map_err(|_| parse_error("http_proxy", "proxy url")))
+            .transpose()?;
+        let ca_cert = options
+            .get("ca_cert")
+            .map(|v| {
+                Certificate::from_pem(v.as_bytes())
+                    .map_err(|_| parse_error("ca_cert", "PEM certificate"))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            timeout,
+            max_retries,
+            proxy,
+            ca_cert,
+            ca_cert_pem: options.get("ca_cert").cloned(),
+        })
+    }
+
+    // connect, read and write timeout of TCP connections, which is capped by
+    // the call timeout, as a blocking read cannot be interrupted
+    pub(super) fn tcp_timeout(&self, call_timeout_ms: Option<u64>) -> Duration {
+        let call_timeout = call_timeout_ms.map(Duration::from_millis);
+        match (self.timeout, call_timeout) {
+            (Some(timeout), Some(call_timeout)) => timeout.min(call_timeout),
+            (timeout, call_timeout) => timeout.or(call_timeout).unwrap_or(DEFAULT_TCP_TIMEOUT),
+        }
+    }
+
+    pub(super) fn ca_cert_pem(&self) -> Option<&str> {
+        self.ca_cert_pem.as_deref()
+    }
+
+    // apply the options to a http client builder
+    pub(super) fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            builder = builder.add_root_certificate(ca_cert.clone());
+        }
+        builder
+    }
+}
@@ -0,0 +1,228 @@
+use pgrx::pg_sys::{self, Oid};
+use pgrx::spi::{self, Spi};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use supabase_wrappers::prelude::*;
+
+use super::WasmFdwResult;
+use crate::stats::is_txn_read_only;
+
+// name of the table to persist key-value pairs
+const WRAPPERS_KV_TABLE_NAME: &str = "wrappers_fdw_kv";
+
+#[derive(Debug, Clone)]
+struct KvEntry {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl KvEntry {
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|t| t <= Instant::now())
+    }
+}
+
+thread_local! {
+    // key-value pairs cached in backend memory, keyed by server oid and user id
+    static KV_CACHE: RefCell<HashMap<(u32, u32), HashMap<String, KvEntry>>> =
+        RefCell::new(HashMap::new());
+}
+
+// get the fully qualified name of the key-value table
+fn get_kv_table() -> Result<String, String> {
+    let sql = format!(
+        "select b.nspname || '.{WRAPPERS_KV_TABLE_NAME}'
+         from pg_catalog.pg_extension a join pg_namespace b on a.extnamespace = b.oid
+         where a.extname = 'wrappers'"
+    );
+
+    Spi::get_one(&sql)
+        .map_err(|_| "wrappers extension is not installed".to_string())?
+        .ok_or("key-value table is not created".to_string())
+}
+
+/// Key-value store for a Wasm FDW server, set by the server option below
+///
+/// - `kv_persist` - when it is `true`, key-value pairs are also saved in the
+///   `wrappers_fdw_kv` table, so they survive backend restarts and are shared
+///   by all backends. Default is `false`.
+///
+/// Key-value pairs are scoped to the foreign server and the current user, so
+/// a user cannot see the pairs, like access tokens, saved by other users.
+/// They are always cached in backend memory, so they survive across
+/// statements in the same session.
+///
+/// Persisted values are stored in plain text. The calling user needs
+/// `select`, `insert`, `update` and `delete` privileges on the table, whose
+/// row level security policy only exposes the user's own rows.
+#[derive(Debug, Clone, Default)]
+pub(super) struct KvStore {
+    server_oid: u32,
+    user_id: u32,
+    persist: bool,
+}
+
+impl KvStore {
+    pub(super) fn new(server_oid: Oid, options: &HashMap<String, String>) -> WasmFdwResult<Self> {
+        let persist = match options.get("kv_persist").map(|s| s.as_str()) {
+            None | Some("false") => false,
+            Some("true") => true,
+            Some(_) => {
+                return Err(OptionsError::OptionParsingError {
+                    option_name: "kv_persist".to_string(),
+                    type_name: "boolean",
+                }
+                .into());
+            }
+        };
+        Ok(Self {
+            server_oid: server_oid.to_u32(),
+            user_id: unsafe { pg_sys::GetUserId().to_u32() },
+            persist,
+        })
+    }
+
+    fn cache_key(&self) -> (u32, u32) {
+        (self.server_oid, self.user_id)
+    }
+
+    // server oid and user id the key-value pairs belong to
+    fn owner(&self) -> (Oid, Oid) {
+        (Oid::from(self.server_oid), Oid::from(self.user_id))
+    }
+
+    fn get_cached(&self, key: &str) -> Option<KvEntry> {
+        KV_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let entries = cache.get_mut(&self.cache_key())?;
+            match entries.get(key) {
+                Some(entry) if entry.is_expired() => {
+                    entries.remove(key);
+                    None
+                }
+                entry => entry.cloned(),
+            }
+        })
+    }
+
+    fn set_cached(&self, key: &str, entry: Option<KvEntry>) {
+        KV_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let entries = cache.entry(self.cache_key()).or_default();
+            match entry {
+                Some(entry) => entries.insert(key.to_owned(), entry),
+                None => entries.remove(key),
+            };
+        })
+    }
+
+    // get value from the table, returns the value and its remaining ttl
+    fn get_persisted(&self, key: &str) -> Result<Option<(String, Option<f64>)>, String> {
+        let kv_table = get_kv_table()?;
+        let sql = format!(
+            "select value, extract(epoch from expires_at - now())::float8
+             from {kv_table}
+             where server_oid = $1 and user_id = $2 and key = $3
+               and (expires_at is null or expires_at > now())"
+        );
+        let (server_oid, user_id) = self.owner();
+        Spi::connect(|client| -> Result<_, spi::Error> {
+            let tup_table = client.select(
+                &sql,
+                Some(1),
+                &[server_oid.into(), user_id.into(), key.into()],
+            )?;
+            match tup_table.into_iter().next() {
+                Some(row) => {
+                    let value: Option<String> = row.get(1)?;
+                    let ttl: Option<f64> = row.get(2)?;
+                    Ok(value.map(|v| (v, ttl)))
+                }
+                None => Ok(None),
+            }
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Get value of a key, returns `None` if it doesn't exist or is expired
+    pub(super) fn get(&self, key: &str) -> Result<Option<String>, String> {
+        if let Some(entry) = self.get_cached(key) {
+            return Ok(Some(entry.value));
+        }
+        if !self.persist {
+            return Ok(None);
+        }
+
+        let ret = self.get_persisted(key)?;
+        if let Some((value, ttl)) = &ret {
+            let expires_at = ttl.map(|t| Instant::now() + Duration::from_secs_f64(t.max(0.0)));
+            self.set_cached(
+                key,
+                Some(KvEntry {
+                    value: value.clone(),
+                    expires_at,
+                }),
+            );
+        }
+        Ok(ret.map(|(value, _)| value))
+    }
+
+    /// Set value of a key, it expires after `ttl_secs` seconds if specified
+    pub(super) fn set(&self, key: &str, value: &str, ttl_secs: Option<u32>) -> Result<(), String> {
+        let expires_at = ttl_secs.map(|t| Instant::now() + Duration::from_secs(t as u64));
+        self.set_cached(
+            key,
+            Some(KvEntry {
+                value: value.to_owned(),
+                expires_at,
+            }),
+        );
+
+        // persisted pairs are only saved in read-write transactions, they are
+        // still cached in memory otherwise
+        if !self.persist || is_txn_read_only() {
+            return Ok(());
+        }
+
+        let kv_table = get_kv_table()?;
+        let sql = format!(
+            "insert into {kv_table} (server_oid, user_id, key, value, expires_at)
+             values ($1, $2, $3, $4, now() + make_interval(secs => $5))
+             on conflict (server_oid, user_id, key)
+             do update set
+                value = excluded.value,
+                expires_at = excluded.expires_at,
+                updated_at = timezone('utc'::text, now())"
+        );
+        let (server_oid, user_id) = self.owner();
+        Spi::run_with_args(
+            &sql,
+            &[
+                server_oid.into(),
+                user_id.into(),
+                key.into(),
+                value.into(),
+                ttl_secs.map(|t| t as f64).into(),
+            ],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// Delete a key
+    pub(super) fn delete(&self, key: &str) -> Result<(), String> {
+        self.set_cached(key, None);
+
+        if !self.persist || is_txn_read_only() {
+            return Ok(());
+        }
+
+        let kv_table = get_kv_table()?;
+        let sql =
+            format!("delete from {kv_table} where server_oid = $1 and user_id = $2 and key = $3");
+        let (server_oid, user_id) = self.owner();
+        Spi::run_with_args(&sql, &[server_oid.into(), user_id.into(), key.into()])
+            .map_err(|e| e.to_string())
+    }
+}
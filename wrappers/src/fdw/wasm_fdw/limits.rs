@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use wasmtime::{Config, Engine, ResourceLimiter, Store};
+
+use supabase_wrappers::prelude::OptionsError;
+
+use super::host::FdwHost;
+use super::{WasmFdwError, WasmFdwResult};
+
+// interval of the epoch ticks used to enforce call timeout
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Error raised by the memory limiter when the guest memory grows beyond the
+/// `max_memory_mb` limit
+#[derive(Error, Debug)]
+#[error("memory limit exceeded")]
+pub(super) struct MemoryLimitExceeded;
+
+/// Resource limiter which traps when the guest memory exceeds a limit
+#[derive(Debug, Default)]
+pub(super) struct MemoryLimiter {
+    max_memory: Option<usize>,
+}
+
+impl ResourceLimiter for MemoryLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        match self.max_memory {
+            Some(max_memory) if desired > max_memory => Err(MemoryLimitExceeded.into()),
+            _ => Ok(true),
+        }
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Resource limits of a Wasm FDW, set by the server options below, all of
+/// them are unlimited if not specified
+///
+/// - `max_memory_mb` - maximum linear memory size of the guest, in MiB
+/// - `max_fuel` - maximum fuel, roughly the number of Wasm instructions,
+///   consumed by each call into the guest
+/// - `call_timeout_ms` - maximum wall-clock time of each call into the guest,
+///   in milliseconds. The guest code is interrupted by epoch, and the HTTP
+///   requests and TCP socket operations made by the guest cannot run past the
+///   deadline. Other host functions, like the key-value store, are not
+///   interrupted but their time is still counted.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ResourceLimits {
+    pub max_memory_mb: Option<u64>,
+    pub max_fuel: Option<u64>,
+    pub call_timeout_ms: Option<u64>,
+}
+
+fn parse_option(options: &HashMap<String, String>, name: &str) -> WasmFdwResult<Option<u64>> {
+    options
+        .get(name)
+        .map(|v| {
+            v.parse::<u64>().ok().filter(|v| *v > 0).ok_or_else(|| {
+                OptionsError::OptionParsingError {
+                    option_name: name.to_string(),
+                    type_name: "positive integer",
+                }
+            })
+        })
+        .transpose()
+        .map_err(WasmFdwError::from)
+}
+
+impl ResourceLimits {
+    pub(super) fn from_options(options: &HashMap<String, String>) -> WasmFdwResult<Self> {
+        Ok(Self {
+            max_memory_mb: parse_option(options, "max_memory_mb")?,
+            max_fuel: parse_option(options, "max_fuel")?,
+            call_timeout_ms: parse_option(options, "call_timeout_ms")?,
+        })
+    }
+
+    // enable fuel metering and epoch interruption in engine config
+    pub(super) fn configure(&self, config: &mut Config) {
+        config.consume_fuel(self.max_fuel.is_some());
+        config.epoch_interruption(self.call_timeout_ms.is_some());
+    }
+
+    // start a thread to tick the engine epoch, it stops after the engine is
+    // dropped
+    pub(super) fn start_epoch_ticker(&self, engine: &Engine) {
+        if self.call_timeout_ms.is_none() {
+            return;
+        }
+
+        let engine = engine.weak();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(EPOCH_TICK);
+                match engine.upgrade() {
+                    Some(engine) => engine.increment_epoch(),
+                    None => break,
+                }
+            }
+        });
+    }
+
+    // set up memory limiter for the store
+    pub(super) fn limit_store(&self, store: &mut Store<FdwHost>) {
+        if let Some(max_memory_mb) = self.max_memory_mb {
+            store.data_mut().limiter = MemoryLimiter {
+                max_memory: Some((max_memory_mb as usize).saturating_mul(1024 * 1024)),
+            };
+            store.limiter(|host| &mut host.limiter);
+        }
+    }
+
+    // refill fuel and reset deadline before each call into the guest
+    pub(super) fn reset(&self, store: &mut Store<FdwHost>) -> WasmFdwResult<()> {
+        if let Some(max_fuel) = self.max_fuel {
+            store.set_fuel(max_fuel)?;
+        }
+        if let Some(call_timeout_ms) = self.call_timeout_ms {
+            let ticks = call_timeout_ms.div_ceil(EPOCH_TICK.as_millis() as u64);
+            store.set_epoch_deadline(ticks);
+            store.data_mut().call_deadline =
+                Some(Instant::now() + Duration::from_millis(call_timeout_ms));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use pgrx::prelude::*;
+    use supabase_wrappers::prelude::{CategorizedError, ErrorCategory, create_async_runtime};
+    use wasmtime::{Instance, Module};
+
+    // (module (func (export "run") (loop (br 0))))
+    const LOOP_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03,
+        0x02, 0x01, 0x00, 0x07, 0x07, 0x01, 0x03, b'r', b'u', b'n', 0x00, 0x00, 0x0a, 0x09, 0x01,
+        0x07, 0x00, 0x03, 0x40, 0x0c, 0x00, 0x0b, 0x0b,
+    ];
+
+    // (module (memory 1) (func (export "run") (drop (memory.grow (i32.const 100)))))
+    const GROW_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03,
+        0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x07, 0x01, 0x03, b'r', b'u', b'n',
+        0x00, 0x00, 0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0xe4, 0x00, 0x40, 0x00, 0x1a, 0x0b,
+    ];
+
+    fn parse_limits(options: &[(&str, &str)]) -> WasmFdwResult<ResourceLimits> {
+        let options = options
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ResourceLimits::from_options(&options)
+    }
+
+    // call the exported 'run' function of a core module under the limits
+    fn run(wasm: &[u8], limits: &ResourceLimits) -> WasmFdwResult<()> {
+        let mut config = Config::new();
+        limits.configure(&mut config);
+        let engine = Engine::new(&config)?;
+        limits.start_epoch_ticker(&engine);
+
+        let mut store = Store::new(&engine, FdwHost::new(create_async_runtime()?));
+        limits.limit_store(&mut store);
+        limits.reset(&mut store)?;
+
+        let module = Module::new(&engine, wasm)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+        Ok(run.call(&mut store, ())?)
+    }
+
+    #[pg_test]
+    fn options_test() {
+        let limits = parse_limits(&[("max_memory_mb", "64"), ("call_timeout_ms", "500")]).unwrap();
+        assert_eq!(limits.max_memory_mb, Some(64));
+        assert_eq!(limits.max_fuel, None);
+        assert_eq!(limits.call_timeout_ms, Some(500));
+
+        for value in ["0", "-1", "abc"] {
+            assert!(matches!(
+                parse_limits(&[("max_fuel", value)]),
+                Err(WasmFdwError::Options(_))
+            ));
+        }
+    }
+
+    #[pg_test]
+    fn memory_limit_test() {
+        // 100 more pages are 6.4 MiB
+        assert!(run(GROW_WASM, &parse_limits(&[]).unwrap()).is_ok());
+        assert!(
+            run(
+                GROW_WASM,
+                &parse_limits(&[("max_memory_mb", "10")]).unwrap()
+            )
+            .is_ok()
+        );
+
+        let err = run(GROW_WASM, &parse_limits(&[("max_memory_mb", "1")]).unwrap()).unwrap_err();
+        assert!(matches!(err, WasmFdwError::MemoryLimitExceeded));
+        assert_eq!(err.category(), ErrorCategory::LimitExceeded);
+    }
+
+    #[pg_test]
+    fn fuel_limit_test() {
+        let err = run(LOOP_WASM, &parse_limits(&[("max_fuel", "10000")]).unwrap()).unwrap_err();
+        assert!(matches!(err, WasmFdwError::FuelExhausted));
+        assert_eq!(err.category(), ErrorCategory::LimitExceeded);
+    }
+
+    #[pg_test]
+    fn call_timeout_test() {
+        let started = Instant::now();
+        let err = run(
+            LOOP_WASM,
+            &parse_limits(&[("call_timeout_ms", "100")]).unwrap(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, WasmFdwError::CallTimeout));
+        assert_eq!(err.category(), ErrorCategory::LimitExceeded);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[pg_test]
+    fn call_deadline_test() {
+        let limits = parse_limits(&[("call_timeout_ms", "1000")]).unwrap();
+        let mut config = Config::new();
+        limits.configure(&mut config);
+        let engine = Engine::new(&config).unwrap();
+        let mut store = Store::new(&engine, FdwHost::new(create_async_runtime().unwrap()));
+        assert!(store.data().call_deadline.is_none());
+
+        // the deadline is moved forward before each call
+        limits.reset(&mut store).unwrap();
+        let deadline = store.data().call_deadline.unwrap();
+        assert!(deadline <= Instant::now() + Duration::from_millis(1000));
+        std::thread::sleep(Duration::from_millis(10));
+        limits.reset(&mut store).unwrap();
+        assert!(store.data().call_deadline.unwrap() > deadline);
+    }
+}
@@ -1,16 +1,26 @@
 #![allow(clippy::module_inception)]
+mod allowlist;
 mod bindings;
+mod cache;
 mod host;
+mod http_options;
+mod kv_store;
+mod limits;
+mod signature;
 mod tests;
 mod wasm_fdw;
 
 use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::prelude::PgSqlErrorCode;
 use thiserror::Error;
 
-use supabase_wrappers::prelude::{CreateRuntimeError, OptionsError};
+use super::http_error::reqwest_error_category;
+use supabase_wrappers::prelude::{
+    CategorizedError, CreateRuntimeError, ErrorCategory, Interrupted, OptionsError,
+};
 
 use self::bindings::v1::supabase::wrappers::types::FdwError as GuestFdwError;
+use self::host::{InvalidGuestCell, take_request_error};
+use self::limits::MemoryLimitExceeded;
 
 #[derive(Error, Debug)]
 enum WasmFdwError {
@@ -18,13 +28,25 @@ enum WasmFdwError {
     InvalidWasmComponent,
 
     #[error("guest fdw error: {0}")]
-    GuestFdw(GuestFdwError),
+    GuestFdw(GuestFdwError, ErrorCategory),
 
     #[error("semver error: {0}")]
     Semver(#[from] semver::Error),
 
     #[error("wasmtime error: {0}")]
-    Wasmtime(#[from] wasmtime::Error),
+    Wasmtime(wasmtime::Error),
+
+    #[error("wasm guest exceeded the memory limit set by 'max_memory_mb'")]
+    MemoryLimitExceeded,
+
+    #[error("wasm guest ran out of fuel set by 'max_fuel'")]
+    FuelExhausted,
+
+    #[error("wasm guest call exceeded the time limit set by 'call_timeout_ms'")]
+    CallTimeout,
+
+    #[error("package signature verification failed: {0}")]
+    PackageSignature(String),
 
     #[error("uuid error: {0}")]
     Uuid(#[from] uuid::Error),
@@ -43,17 +65,72 @@ enum WasmFdwError {
 
     #[error("{0}")]
     Options(#[from] OptionsError),
+
+    #[error("{0}")]
+    Interrupted(#[from] Interrupted),
 }
 
 impl From<GuestFdwError> for WasmFdwError {
+    // the guest error is categorized by the last failed remote request made by
+    // the guest, e.g. a HTTP 429 response is rate limited
     fn from(value: GuestFdwError) -> Self {
-        Self::GuestFdw(value)
+        Self::GuestFdw(value, take_request_error().unwrap_or(ErrorCategory::Remote))
+    }
+}
+
+impl From<InvalidGuestCell> for WasmFdwError {
+    fn from(value: InvalidGuestCell) -> Self {
+        Self::GuestFdw(value.to_string(), ErrorCategory::InvalidData)
+    }
+}
+
+impl From<wasmtime::Error> for WasmFdwError {
+    fn from(value: wasmtime::Error) -> Self {
+        // invalid guest cells in host calls are reported as traps too
+        if let Some(err) = value.downcast_ref::<InvalidGuestCell>() {
+            return Self::GuestFdw(err.to_string(), ErrorCategory::InvalidData);
+        }
+        // resource limit violations are reported as traps
+        if value.downcast_ref::<MemoryLimitExceeded>().is_some() {
+            return Self::MemoryLimitExceeded;
+        }
+        match value.downcast_ref::<wasmtime::Trap>() {
+            Some(wasmtime::Trap::OutOfFuel) => Self::FuelExhausted,
+            Some(wasmtime::Trap::Interrupt) => Self::CallTimeout,
+            _ => Self::Wasmtime(value),
+        }
+    }
+}
+
+impl CategorizedError for WasmFdwError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Self::GuestFdw(_, category) => *category,
+            Self::InvalidWasmComponent
+            | Self::Semver(_)
+            | Self::PackageSignature(_)
+            | Self::Uuid(_) => ErrorCategory::InvalidData,
+            Self::MemoryLimitExceeded | Self::FuelExhausted | Self::CallTimeout => {
+                ErrorCategory::LimitExceeded
+            }
+            Self::Request(e) => reqwest_error_category(e),
+            Self::Wasmtime(_) | Self::WargClient(_) | Self::Io(_) => ErrorCategory::Remote,
+            Self::CreateRuntime(e) => e.category(),
+            Self::Options(e) => e.category(),
+            // interrupted downloads are reported as canceled, see below
+            Self::Interrupted(_) => ErrorCategory::Remote,
+        }
     }
 }
 
 impl From<WasmFdwError> for ErrorReport {
     fn from(value: WasmFdwError) -> Self {
-        ErrorReport::new(PgSqlErrorCode::ERRCODE_FDW_ERROR, format!("{value}"), "")
+        match value {
+            WasmFdwError::CreateRuntime(e) => e.into(),
+            WasmFdwError::Options(e) => e.into(),
+            WasmFdwError::Interrupted(e) => e.into(),
+            _ => value.category().error_report(format!("{value}")),
+        }
     }
 }
 
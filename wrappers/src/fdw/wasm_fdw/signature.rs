@@ -0,0 +1,224 @@
+use ed25519_compact::{PublicKey, Signature};
+use std::collections::HashMap;
+
+use supabase_wrappers::prelude::*;
+
+use super::{WasmFdwError, WasmFdwResult};
+
+fn parse_error(option_name: &str, type_name: &'static str) -> OptionsError {
+    OptionsError::OptionParsingError {
+        option_name: option_name.to_string(),
+        type_name,
+    }
+}
+
+// parse comma separated hex encoded ed25519 public keys
+fn parse_trusted_keys(options: &HashMap<String, String>) -> WasmFdwResult<Vec<PublicKey>> {
+    let Some(keys) = options.get("fdw_package_trusted_keys") else {
+        return Ok(Vec::new());
+    };
+    keys.split(',')
+        .map(|k| k.trim())
+        .filter(|k| !k.is_empty())
+        .map(|k| {
+            hex::decode(k)
+                .ok()
+                .and_then(|k| PublicKey::from_slice(&k).ok())
+                .ok_or_else(|| parse_error("fdw_package_trusted_keys", "ed25519 public key").into())
+        })
+        .collect()
+}
+
+// parse hex encoded ed25519 signature
+fn parse_signature(options: &HashMap<String, String>) -> WasmFdwResult<Option<Signature>> {
+    options
+        .get("fdw_package_signature")
+        .map(|s| {
+            hex::decode(s.trim())
+                .ok()
+                .and_then(|s| Signature::from_slice(&s).ok())
+                .ok_or_else(|| parse_error("fdw_package_signature", "ed25519 signature").into())
+        })
+        .transpose()
+}
+
+/// Signature verifier of Wasm FDW packages
+///
+/// Trusted public keys are set by the `fdw_package_trusted_keys` option of
+/// the foreign data wrapper, which is a comma separated list of hex encoded
+/// ed25519 public keys. When it is set, every package must be signed by one
+/// of the trusted keys, and its hex encoded detached ed25519 signature must
+/// be set in the `fdw_package_signature` server option.
+#[derive(Debug, Clone, Default)]
+pub(super) struct PackageVerifier {
+    trusted_keys: Vec<PublicKey>,
+    signature: Option<Signature>,
+}
+
+impl PackageVerifier {
+    pub(super) fn new(server: &ForeignServer) -> WasmFdwResult<Self> {
+        let fdw_options = server.fdw_options()?;
+        Ok(Self {
+            trusted_keys: parse_trusted_keys(&fdw_options)?,
+            signature: parse_signature(&server.options)?,
+        })
+    }
+
+    // validate the foreign data wrapper options
+    pub(super) fn validate_fdw_options(options: &HashMap<String, String>) -> WasmFdwResult<()> {
+        parse_trusted_keys(options).map(|_| ())
+    }
+
+    // validate the foreign server options
+    pub(super) fn validate_server_options(options: &HashMap<String, String>) -> WasmFdwResult<()> {
+        parse_signature(options).map(|_| ())
+    }
+
+    // if packages must be signed by the trusted keys
+    pub(super) fn has_trusted_keys(&self) -> bool {
+        !self.trusted_keys.is_empty()
+    }
+
+    /// Verify the package bytes is signed by one of the trusted keys
+    pub(super) fn verify(&self, bytes: &[u8]) -> WasmFdwResult<()> {
+        if self.trusted_keys.is_empty() {
+            if self.signature.is_some() {
+                return Err(WasmFdwError::PackageSignature(
+                    "no trusted keys are set for 'fdw_package_signature'".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        let signature = self.signature.as_ref().ok_or_else(|| {
+            WasmFdwError::PackageSignature(
+                "package signature must be specified by 'fdw_package_signature'".to_string(),
+            )
+        })?;
+        if self
+            .trusted_keys
+            .iter()
+            .any(|key| key.verify(bytes, signature).is_ok())
+        {
+            Ok(())
+        } else {
+            Err(WasmFdwError::PackageSignature(
+                "package is not signed by any trusted key".to_string(),
+            ))
+        }
+    }
+
+    // a string identifies the trusted keys and signature, it is part of the
+    // compiled component cache key, so cached components are verified again
+    // when any of them changes
+    pub(super) fn cache_key(&self) -> String {
+        let keys: Vec<String> = self.trusted_keys.iter().map(|k| hex::encode(**k)).collect();
+        format!(
+            "{}:{}",
+            keys.join(","),
+            self.signature
+                .as_ref()
+                .map(|s| hex::encode(**s))
+                .unwrap_or_default()
+        )
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use ed25519_compact::{KeyPair, Seed};
+    use pgrx::prelude::*;
+
+    const PACKAGE: &[u8] = b"wasm package bytes";
+
+    fn key_pair(seed: u8) -> KeyPair {
+        KeyPair::from_seed(Seed::new([seed; 32]))
+    }
+
+    fn verifier(keys: &[&KeyPair], signature: Option<Signature>) -> PackageVerifier {
+        PackageVerifier {
+            trusted_keys: keys.iter().map(|kp| kp.pk).collect(),
+            signature,
+        }
+    }
+
+    #[pg_test]
+    fn verify_signature_test() {
+        let kp = key_pair(1);
+        let other = key_pair(2);
+        let signature = kp.sk.sign(PACKAGE, None);
+
+        // signed by the only trusted key, or one of the trusted keys
+        assert!(verifier(&[&kp], Some(signature)).verify(PACKAGE).is_ok());
+        assert!(
+            verifier(&[&other, &kp], Some(signature))
+                .verify(PACKAGE)
+                .is_ok()
+        );
+
+        // tampered package
+        assert!(
+            verifier(&[&kp], Some(signature))
+                .verify(b"tampered package bytes")
+                .is_err()
+        );
+
+        // invalid signature
+        let invalid = other.sk.sign(b"other package bytes", None);
+        assert!(verifier(&[&kp], Some(invalid)).verify(PACKAGE).is_err());
+
+        // signed by a key which is not trusted
+        let wrong = other.sk.sign(PACKAGE, None);
+        assert!(verifier(&[&kp], Some(wrong)).verify(PACKAGE).is_err());
+    }
+
+    #[pg_test]
+    fn verify_options_test() {
+        let kp = key_pair(1);
+        let signature = kp.sk.sign(PACKAGE, None);
+
+        // no trusted keys and no signature
+        let v = verifier(&[], None);
+        assert!(!v.has_trusted_keys());
+        assert!(v.verify(PACKAGE).is_ok());
+
+        // signature without trusted keys
+        assert!(verifier(&[], Some(signature)).verify(PACKAGE).is_err());
+
+        // trusted keys without signature
+        let v = verifier(&[&kp], None);
+        assert!(v.has_trusted_keys());
+        assert!(v.verify(PACKAGE).is_err());
+    }
+
+    #[pg_test]
+    fn parse_options_test() {
+        let kp = key_pair(1);
+        let other = key_pair(2);
+        let signature = kp.sk.sign(PACKAGE, None);
+
+        let options = HashMap::from([
+            (
+                "fdw_package_trusted_keys".to_string(),
+                format!(" {}, {} ,", hex::encode(*kp.pk), hex::encode(*other.pk)),
+            ),
+            ("fdw_package_signature".to_string(), hex::encode(*signature)),
+        ]);
+        assert_eq!(parse_trusted_keys(&options).unwrap(), vec![kp.pk, other.pk]);
+        assert_eq!(parse_signature(&options).unwrap(), Some(signature));
+        assert!(parse_trusted_keys(&HashMap::new()).unwrap().is_empty());
+        assert!(parse_signature(&HashMap::new()).unwrap().is_none());
+
+        let options = HashMap::from([
+            (
+                "fdw_package_trusted_keys".to_string(),
+                "not hex".to_string(),
+            ),
+            ("fdw_package_signature".to_string(), "abcd".to_string()),
+        ]);
+        assert!(PackageVerifier::validate_fdw_options(&options).is_err());
+        assert!(PackageVerifier::validate_server_options(&options).is_err());
+    }
+}
@@ -7,10 +7,11 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use warg_client as warg;
 use wasmtime::component::*;
-use wasmtime::{Config, Engine, Store};
+use wasmtime::{Engine, Store};
 
 use supabase_wrappers::prelude::*;
 
+use super::allowlist::HostAllowlist;
 use super::bindings::v1::{
     Wrappers as WrappersV1,
     supabase::wrappers::types::{
@@ -21,15 +22,31 @@ use super::bindings::v2::{
     Wrappers as WrappersV2,
     supabase::wrappers::types::{
         Cell as GuestCellV2, HostContext as HostContextV2, HostRow as HostRowV2,
-        ImportForeignSchemaStmt as GuestImportForeignSchemaStmt,
+        ImportForeignSchemaStmt as GuestImportForeignSchemaStmtV2,
     },
 };
-use super::host::FdwHost;
+use super::bindings::v3::{
+    Wrappers as WrappersV3,
+    supabase::wrappers::types::{
+        Cell as GuestCellV3, HostContext as HostContextV3, HostRow as HostRowV3,
+        ImportForeignSchemaStmt as GuestImportForeignSchemaStmtV3,
+    },
+};
+use super::cache;
+use super::host::{FdwHost, InvalidGuestCell, clear_request_error};
+use super::http_options::HttpOptions;
+use super::kv_store::KvStore;
+use super::limits::ResourceLimits;
+use super::signature::PackageVerifier;
 use super::{WasmFdwError, WasmFdwResult};
 
+// default max number of rows fetched by each iter-scan-batch call
+const DEFAULT_SCAN_BATCH_SIZE: usize = 100;
+
 enum Bindings {
     V1(WrappersV1),
     V2(WrappersV2),
+    V3(WrappersV3),
 }
 
 // check minimal host version requirement, e.g, ">=1.2.3"
@@ -39,17 +56,24 @@ fn check_version_requirement(ver_req: &str) -> WasmFdwResult<()> {
     let host_ver = meta.get("version").expect("version should be defined");
     let version = Version::parse(host_ver)?;
     if !req.matches(&version) {
-        return Err(format!("host version {host_ver} not match requirement {ver_req}").into());
+        return Err(WasmFdwError::GuestFdw(
+            format!("host version {host_ver} not match requirement {ver_req}"),
+            ErrorCategory::Unsupported,
+        ));
     }
     Ok(())
 }
 
-// compiles a new WebAssembly component from a wasm file
+// compiles a new WebAssembly component from a wasm file, the file content is
+// verified against the package signature before compiling
 fn load_component_from_file(
     engine: &Engine,
     file_path: impl AsRef<std::path::Path>,
+    verifier: &PackageVerifier,
 ) -> WasmFdwResult<Component> {
-    Component::from_file(engine, file_path).map_err(|_| WasmFdwError::InvalidWasmComponent)
+    let bytes = fs::read(file_path)?;
+    verifier.verify(&bytes)?;
+    Component::new(engine, &bytes).map_err(|_| WasmFdwError::InvalidWasmComponent)
 }
 
 fn download_component(
@@ -59,19 +83,20 @@ fn download_component(
     name: &str,
     version: &str,
     checksum: Option<&str>,
+    verifier: &PackageVerifier,
 ) -> WasmFdwResult<Component> {
     // handle local file paths
     if let Some(file_path) = url.strip_prefix("file://") {
-        return load_component_from_file(engine, file_path);
+        return load_component_from_file(engine, file_path, verifier);
     }
 
     // handle warg registry URLs
     if url.starts_with("warg://") || url.starts_with("wargs://") {
-        return download_from_warg(rt, engine, url, name, version);
+        return download_from_warg(rt, engine, url, name, version, verifier);
     }
 
     // handle direct URLs with caching
-    download_from_url(rt, engine, url, name, version, checksum)
+    download_from_url(rt, engine, url, name, version, checksum, verifier)
 }
 
 fn download_from_warg(
@@ -80,6 +105,7 @@ fn download_from_warg(
     url: &str,
     name: &str,
     version: &str,
+    verifier: &PackageVerifier,
 ) -> WasmFdwResult<Component> {
     let url = url
         .replacen("warg://", "http://", 1)
@@ -90,11 +116,10 @@ fn download_from_warg(
         ..Default::default()
     };
 
-    let client = rt.block_on(warg::FileSystemClient::new_with_config(
-        Some(&url),
-        &config,
-        None,
-    ))?;
+    let client = block_on_interruptible(
+        rt,
+        warg::FileSystemClient::new_with_config(Some(&url), &config, None),
+    )??;
 
     let pkg_name = warg_protocol::registry::PackageName::new(name)
         .map_err(|e| format!("invalid package name '{name}': {e}"))?;
@@ -102,11 +127,10 @@ fn download_from_warg(
     let ver = semver::VersionReq::parse(version)
         .map_err(|e| format!("invalid version requirement '{version}': {e}"))?;
 
-    let pkg = rt
-        .block_on(client.download(&pkg_name, &ver))?
+    let pkg = block_on_interruptible(rt, client.download(&pkg_name, &ver))??
         .ok_or_else(|| format!("{name}@{version} not found on {url}"))?;
 
-    load_component_from_file(engine, pkg.path)
+    load_component_from_file(engine, pkg.path, verifier)
 }
 
 fn download_from_url(
@@ -116,6 +140,7 @@ fn download_from_url(
     name: &str,
     version: &str,
     checksum: Option<&str>,
+    verifier: &PackageVerifier,
 ) -> WasmFdwResult<Component> {
     // validate URL
     let url = url
@@ -127,7 +152,7 @@ fn download_from_url(
 
     // return cached component if it exists and is valid
     if cache_path.exists() {
-        if let Ok(component) = load_component_from_file(engine, &cache_path) {
+        if let Ok(component) = load_component_from_file(engine, &cache_path, verifier) {
             return Ok(component);
         }
         // if loading fails, remove invalid cache file
@@ -145,7 +170,7 @@ fn download_from_url(
     save_to_cache(&cache_path, &bytes)?;
 
     // load component
-    load_component_from_file(engine, &cache_path).inspect_err(|_| {
+    load_component_from_file(engine, &cache_path, verifier).inspect_err(|_| {
         let _ = fs::remove_file(&cache_path);
     })
 }
@@ -173,8 +198,7 @@ fn download_and_verify(
     url: reqwest::Url,
     expected_checksum: &str,
 ) -> WasmFdwResult<Bytes> {
-    let resp = rt
-        .block_on(reqwest::get(url.clone()))
+    let resp = block_on_interruptible(rt, reqwest::get(url.clone()))?
         .map_err(|_| "failed to download component".to_string())?;
 
     if !resp.status().is_success() {
@@ -183,8 +207,7 @@ fn download_and_verify(
             .into());
     }
 
-    let bytes = rt
-        .block_on(resp.bytes())
+    let bytes = block_on_interruptible(rt, resp.bytes())?
         .map_err(|_| "failed to read component data".to_string())?;
 
     let actual_checksum = hex::encode(Sha256::digest(&bytes));
@@ -206,7 +229,7 @@ fn save_to_cache(path: &Path, bytes: &[u8]) -> WasmFdwResult<()> {
 }
 
 #[wrappers_fdw(
-    version = "0.1.5",
+    version = "0.1.8",
     author = "Supabase",
     website = "https://github.com/supabase/wrappers/tree/main/wrappers/src/fdw/wasm_fdw",
     error_type = "WasmFdwError"
@@ -214,10 +237,67 @@ fn save_to_cache(path: &Path, bytes: &[u8]) -> WasmFdwResult<()> {
 pub(crate) struct WasmFdw {
     store: Store<FdwHost>,
     bindings: Bindings,
+    limits: ResourceLimits,
+    // rows fetched by iter-scan-batch but not yet returned to Postgres
+    batch: RowBatch,
+    // max number of rows fetched by each iter-scan-batch call
+    scan_batch_size: u32,
+    // max number of rows sent to insert-batch at once
+    modify_batch_size: usize,
+    // if the guest implements pre-commit and abort, declared by its
+    // modify capabilities
+    transaction_hooks: bool,
+}
+
+// get a batch size option, e.g. 'batch_size', from foreign table options, or
+// from server options if it is not set on the table
+fn get_batch_size(
+    option_name: &str,
+    default: usize,
+    tbl_opts: &HashMap<String, String>,
+    svr_opts: &HashMap<String, String>,
+) -> WasmFdwResult<usize> {
+    tbl_opts
+        .get(option_name)
+        .or_else(|| svr_opts.get(option_name))
+        .map(|v| {
+            v.parse::<usize>().ok().filter(|v| *v > 0).ok_or_else(|| {
+                OptionsError::OptionParsingError {
+                    option_name: option_name.to_string(),
+                    type_name: "positive integer",
+                }
+            })
+        })
+        .transpose()
+        .map(|v| v.unwrap_or(default))
+        .map_err(WasmFdwError::from)
+}
+
+// get the columns of a batch of rows, which are sent to the guest only once,
+// so all rows must have the same columns
+fn get_batch_columns(rows: &[Row]) -> WasmFdwResult<Vec<String>> {
+    let columns = rows.first().map(|r| r.cols.clone()).unwrap_or_default();
+    if let Some(row) = rows.iter().find(|r| r.cols != columns) {
+        return Err(format!(
+            "batch row has columns ({}) but ({}) are expected",
+            row.cols.join(", "),
+            columns.join(", ")
+        )
+        .into());
+    }
+    Ok(columns)
 }
 
 impl WasmFdw {
+    // prepare for a call into the guest, refill its resource limits and forget
+    // the failed requests made in previous calls
+    fn begin_call(&mut self) -> WasmFdwResult<()> {
+        clear_request_error();
+        self.limits.reset(&mut self.store)
+    }
+
     fn call_host_version_requirement(&mut self) -> WasmFdwResult<String> {
+        self.begin_call()?;
         let ret = match &self.bindings {
             Bindings::V1(b) => b
                 .supabase_wrappers_routines()
@@ -225,11 +305,15 @@ impl WasmFdw {
             Bindings::V2(b) => b
                 .supabase_wrappers_routines()
                 .call_host_version_requirement(&mut self.store)?,
+            Bindings::V3(b) => b
+                .supabase_wrappers_routines()
+                .call_host_version_requirement(&mut self.store)?,
         };
         Ok(ret.to_string())
     }
 
     fn call_init(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -241,11 +325,45 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_init(&mut self.store, ctx)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_init(&mut self.store, ctx)??;
+            }
         }
         Ok(())
     }
 
+    fn call_get_rel_size(&mut self) -> WasmFdwResult<(i64, i32)> {
+        self.begin_call()?;
+        let ret = match &self.bindings {
+            // planner callbacks are only available since v3
+            Bindings::V1(_) | Bindings::V2(_) => (0, 0),
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_get_rel_size(&mut self.store, ctx)??
+            }
+        };
+        Ok(ret)
+    }
+
+    fn call_scan_capabilities(&mut self) -> WasmFdwResult<ScanCapabilities> {
+        self.begin_call()?;
+        let ret = match &self.bindings {
+            Bindings::V1(_) | Bindings::V2(_) => ScanCapabilities::default(),
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_get_scan_capabilities(&mut self.store, ctx)??
+                    .into()
+            }
+        };
+        Ok(ret)
+    }
+
     fn call_begin_scan(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -257,29 +375,78 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_begin_scan(&mut self.store, ctx)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_begin_scan(&mut self.store, ctx)??;
+            }
         }
         Ok(())
     }
 
-    fn call_iter_scan(&mut self) -> WasmFdwResult<Option<u32>> {
-        let ret: Option<_> = match &self.bindings {
+    // fetch the next rows, v1 and v2 guests return one row in each call
+    // while v3 guests return rows in batches, an empty list means no more rows
+    fn call_iter_scan(&mut self) -> WasmFdwResult<Vec<Row>> {
+        self.begin_call()?;
+        let rows = match &self.bindings {
             Bindings::V1(b) => {
+                self.store.data_mut().row.clear();
                 let ctx = HostContextV1::new(self.store.data_mut());
                 let host_row = HostRowV1::new(self.store.data_mut());
                 b.supabase_wrappers_routines()
                     .call_iter_scan(&mut self.store, ctx, host_row)??
+                    .map(|_| vec![self.store.data().row.clone()])
+                    .unwrap_or_default()
             }
             Bindings::V2(b) => {
+                self.store.data_mut().row.clear();
                 let ctx = HostContextV2::new(self.store.data_mut());
                 let host_row = HostRowV2::new(self.store.data_mut());
                 b.supabase_wrappers_routines()
                     .call_iter_scan(&mut self.store, ctx, host_row)??
+                    .map(|_| vec![self.store.data().row.clone()])
+                    .unwrap_or_default()
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let rows = b.supabase_wrappers_routines().call_iter_scan_batch(
+                    &mut self.store,
+                    ctx,
+                    self.scan_batch_size,
+                )??;
+
+                // each row has cells in the order of target columns
+                let columns = &self.store.data().columns;
+                rows.into_iter()
+                    .map(|cells| -> WasmFdwResult<Row> {
+                        if cells.len() != columns.len() {
+                            return Err(WasmFdwError::GuestFdw(
+                                format!(
+                                    "batch row has {} cells but {} columns are expected",
+                                    cells.len(),
+                                    columns.len()
+                                ),
+                                ErrorCategory::InvalidData,
+                            ));
+                        }
+                        let mut row = Row::new();
+                        for (col, cell) in columns.iter().zip(cells) {
+                            let cell = cell
+                                .map(Cell::try_from)
+                                .transpose()
+                                .map_err(|err| InvalidGuestCell(err.to_string()))?;
+                            row.push(&col.name, cell);
+                        }
+                        Ok(row)
+                    })
+                    .collect::<WasmFdwResult<_>>()?
             }
         };
-        Ok(ret)
+        Ok(rows)
     }
 
     fn call_re_scan(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -291,11 +458,17 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_re_scan(&mut self.store, ctx)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_re_scan(&mut self.store, ctx)??;
+            }
         }
         Ok(())
     }
 
     fn call_end_scan(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -307,11 +480,17 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_end_scan(&mut self.store, ctx)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_end_scan(&mut self.store, ctx)??;
+            }
         }
         Ok(())
     }
 
     fn call_begin_modify(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -323,11 +502,32 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_begin_modify(&mut self.store, ctx)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_begin_modify(&mut self.store, ctx)??;
+            }
         }
         Ok(())
     }
 
+    fn call_modify_capabilities(&mut self) -> WasmFdwResult<bool> {
+        self.begin_call()?;
+        let ret = match &self.bindings {
+            // transaction hooks are only available since v3
+            Bindings::V1(_) | Bindings::V2(_) => false,
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_get_modify_capabilities(&mut self.store, ctx)??
+                    .transaction_hooks
+            }
+        };
+        Ok(ret)
+    }
+
     fn call_insert(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -341,11 +541,46 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_insert(&mut self.store, ctx, host_row)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let host_row = HostRowV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_insert(&mut self.store, ctx, host_row)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_insert_batch(&mut self, rows: &[Row]) -> WasmFdwResult<()> {
+        self.begin_call()?;
+        match &self.bindings {
+            // batch insert is only available since v3
+            Bindings::V1(_) | Bindings::V2(_) => {}
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let columns = get_batch_columns(rows)?;
+                let rows: Vec<Vec<Option<GuestCellV3>>> = rows
+                    .iter()
+                    .map(|r| {
+                        r.cells
+                            .iter()
+                            .map(|c| c.as_ref().map(GuestCellV3::from))
+                            .collect()
+                    })
+                    .collect();
+                b.supabase_wrappers_routines().call_insert_batch(
+                    &mut self.store,
+                    ctx,
+                    &columns,
+                    &rows,
+                )??;
+            }
         }
         Ok(())
     }
 
     fn call_update(&mut self, rowid: &Cell) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -369,11 +604,23 @@ impl WasmFdw {
                     host_row,
                 )??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let host_row = HostRowV3::new(self.store.data_mut());
+                let cell = GuestCellV3::from(rowid);
+                b.supabase_wrappers_routines().call_update(
+                    &mut self.store,
+                    ctx,
+                    &cell,
+                    host_row,
+                )??;
+            }
         }
         Ok(())
     }
 
     fn call_delete(&mut self, rowid: &Cell) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -387,11 +634,18 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_delete(&mut self.store, ctx, &cell)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let cell = GuestCellV3::from(rowid);
+                b.supabase_wrappers_routines()
+                    .call_delete(&mut self.store, ctx, &cell)??;
+            }
         }
         Ok(())
     }
 
     fn call_end_modify(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
         match &self.bindings {
             Bindings::V1(b) => {
                 let ctx = HostContextV1::new(self.store.data_mut());
@@ -403,6 +657,38 @@ impl WasmFdw {
                 b.supabase_wrappers_routines()
                     .call_end_modify(&mut self.store, ctx)??;
             }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_end_modify(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_pre_commit(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
+        match &self.bindings {
+            // transaction hooks are only available since v3
+            Bindings::V1(_) | Bindings::V2(_) => {}
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_pre_commit(&mut self.store, ctx)??;
+            }
+        }
+        Ok(())
+    }
+
+    fn call_abort(&mut self) -> WasmFdwResult<()> {
+        self.begin_call()?;
+        match &self.bindings {
+            Bindings::V1(_) | Bindings::V2(_) => {}
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                b.supabase_wrappers_routines()
+                    .call_abort(&mut self.store, ctx)??;
+            }
         }
         Ok(())
     }
@@ -411,13 +697,23 @@ impl WasmFdw {
         &mut self,
         stmt: ImportForeignSchemaStmt,
     ) -> WasmFdwResult<Vec<String>> {
+        self.begin_call()?;
         let ret = match &self.bindings {
             Bindings::V1(_) => {
                 return Err("import foreign schema not implemented".to_string().into());
             }
             Bindings::V2(b) => {
                 let ctx = HostContextV2::new(self.store.data_mut());
-                let stmt = GuestImportForeignSchemaStmt::from(stmt);
+                let stmt = GuestImportForeignSchemaStmtV2::from(stmt);
+                b.supabase_wrappers_routines().call_import_foreign_schema(
+                    &mut self.store,
+                    ctx,
+                    &stmt,
+                )??
+            }
+            Bindings::V3(b) => {
+                let ctx = HostContextV3::new(self.store.data_mut());
+                let stmt = GuestImportForeignSchemaStmtV3::from(stmt);
                 b.supabase_wrappers_routines().call_import_foreign_schema(
                     &mut self.store,
                     ctx,
@@ -431,6 +727,9 @@ impl WasmFdw {
 
 impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
     fn new(server: ForeignServer) -> WasmFdwResult<Self> {
+        // the package download errors are not caused by any guest request
+        clear_request_error();
+
         let pkg_url = require_option("fdw_package_url", &server.options)?;
         let pkg_name = require_option("fdw_package_name", &server.options)?;
         let pkg_version = require_option("fdw_package_version", &server.options)?;
@@ -441,28 +740,65 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
 
         let rt = create_async_runtime()?;
 
-        let mut config = Config::new();
-        config.wasm_component_model(true);
-        let engine = Engine::new(&config)?;
-
-        let component =
-            download_component(&rt, &engine, pkg_url, pkg_name, pkg_version, pkg_checksum)?;
+        let limits = ResourceLimits::from_options(&server.options)?;
+        let verifier = PackageVerifier::new(&server)?;
+
+        let engine = cache::get_engine(&limits)?;
+        let component = cache::get_component(
+            &engine,
+            &limits,
+            pkg_url,
+            pkg_name,
+            pkg_version,
+            pkg_checksum,
+            &verifier,
+            || {
+                download_component(
+                    &rt,
+                    &engine,
+                    pkg_url,
+                    pkg_name,
+                    pkg_version,
+                    pkg_checksum,
+                    &verifier,
+                )
+            },
+        )?;
 
         let mut fdw_host = FdwHost::new(rt);
         fdw_host.svr_opts.clone_from(&server.options);
+        fdw_host.allowlist = HostAllowlist::from_options(&server.options)?;
+        fdw_host.http_opts = HttpOptions::from_options(&server.options)?;
+        fdw_host.tcp_timeout = fdw_host.http_opts.tcp_timeout(limits.call_timeout_ms);
+        fdw_host.kv = KvStore::new(server.server_oid, &server.options)?;
+        fdw_host.pkg_name = pkg_name.to_owned();
+        fdw_host.pkg_version = pkg_version.to_owned();
 
         let mut linker = Linker::new(&engine);
         WrappersV1::add_to_linker::<_, HasSelf<_>>(&mut linker, |host: &mut FdwHost| host)?;
         WrappersV2::add_to_linker::<_, HasSelf<_>>(&mut linker, |host: &mut FdwHost| host)?;
+        WrappersV3::add_to_linker::<_, HasSelf<_>>(&mut linker, |host: &mut FdwHost| host)?;
 
         let mut store = Store::new(&engine, fdw_host);
+        limits.limit_store(&mut store);
+        FdwHost::trap_cell_errors(&mut store);
+        limits.reset(&mut store)?;
         let bindings = WrappersV1::instantiate(&mut store, &component, &linker)
             .map(Bindings::V1)
+            .or_else(|_| WrappersV2::instantiate(&mut store, &component, &linker).map(Bindings::V2))
             .or_else(|_| {
-                WrappersV2::instantiate(&mut store, &component, &linker).map(Bindings::V2)
+                WrappersV3::instantiate(&mut store, &component, &linker).map(Bindings::V3)
             })?;
 
-        let mut wasm_fdw = Self { store, bindings };
+        let mut wasm_fdw = Self {
+            store,
+            bindings,
+            limits,
+            batch: RowBatch::new(),
+            scan_batch_size: DEFAULT_SCAN_BATCH_SIZE as u32,
+            modify_batch_size: 1,
+            transaction_hooks: false,
+        };
 
         // check version requirement
         let ver_req = wasm_fdw.call_host_version_requirement()?;
@@ -474,6 +810,36 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
         Ok(wasm_fdw)
     }
 
+    fn get_rel_size(
+        &mut self,
+        quals: &[Qual],
+        columns: &[Column],
+        sorts: &[Sort],
+        limit: &Option<Limit>,
+        options: &HashMap<String, String>,
+    ) -> WasmFdwResult<(i64, i32)> {
+        let fdw_state = self.store.data_mut();
+        fdw_state.quals = quals.to_vec();
+        fdw_state.columns = columns.to_vec();
+        fdw_state.sorts = sorts.to_vec();
+        fdw_state.limit.clone_from(limit);
+        fdw_state.tbl_opts.clone_from(options);
+
+        self.call_get_rel_size()
+    }
+
+    fn scan_capabilities(
+        &mut self,
+        _quals: &[Qual],
+        _columns: &[Column],
+        _sorts: &[Sort],
+        _limit: &Option<Limit>,
+        _options: &HashMap<String, String>,
+    ) -> WasmFdwResult<ScanCapabilities> {
+        // it is called right after get_rel_size, so the context is already set
+        self.call_scan_capabilities()
+    }
+
     fn begin_scan(
         &mut self,
         quals: &[Qual],
@@ -489,32 +855,53 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
         fdw_state.limit.clone_from(limit);
         fdw_state.tbl_opts.clone_from(options);
 
+        let scan_batch_size = get_batch_size(
+            "scan_batch_size",
+            DEFAULT_SCAN_BATCH_SIZE,
+            options,
+            &self.store.data().svr_opts,
+        )?;
+        self.scan_batch_size = u32::try_from(scan_batch_size).unwrap_or(u32::MAX);
+
         self.call_begin_scan()
     }
 
     fn iter_scan(&mut self, row: &mut Row) -> WasmFdwResult<Option<()>> {
-        self.store.data_mut().row.clear();
-
-        let ret: Option<_> = self.call_iter_scan()?;
-        if ret.is_some() {
-            row.replace_with(self.store.data().row.clone());
-            return Ok(Some(()));
+        if self.batch.is_empty() {
+            let rows = self.call_iter_scan()?;
+            if rows.is_empty() {
+                return Ok(None);
+            }
+            self.batch.extend(rows);
+        }
+        if let Some(src) = self.batch.pop() {
+            row.replace_with(src);
         }
-        Ok(None)
+        Ok(Some(()))
     }
 
     fn re_scan(&mut self) -> WasmFdwResult<()> {
+        self.batch.clear();
         self.call_re_scan()
     }
 
     fn end_scan(&mut self) -> WasmFdwResult<()> {
+        self.batch.clear();
         self.call_end_scan()
     }
 
     fn begin_modify(&mut self, options: &HashMap<String, String>) -> WasmFdwResult<()> {
         let fdw_state = self.store.data_mut();
         fdw_state.tbl_opts.clone_from(options);
-        self.call_begin_modify()
+        self.modify_batch_size = match self.bindings {
+            Bindings::V3(_) => {
+                get_batch_size("batch_size", 1, options, &self.store.data().svr_opts)?
+            }
+            _ => 1,
+        };
+        self.call_begin_modify()?;
+        self.transaction_hooks = self.call_modify_capabilities()?;
+        Ok(())
     }
 
     fn insert(&mut self, src: &Row) -> WasmFdwResult<()> {
@@ -522,6 +909,17 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
         self.call_insert()
     }
 
+    fn get_modify_batch_size(&mut self) -> usize {
+        self.modify_batch_size
+    }
+
+    fn insert_batch(&mut self, rows: &[Row]) -> WasmFdwResult<()> {
+        if self.modify_batch_size > 1 {
+            return self.call_insert_batch(rows);
+        }
+        rows.iter().try_for_each(|row| self.insert(row))
+    }
+
     fn update(&mut self, rowid: &Cell, new_row: &Row) -> WasmFdwResult<()> {
         self.store.data_mut().row = new_row.clone();
         self.call_update(rowid)
@@ -535,6 +933,18 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
         self.call_end_modify()
     }
 
+    fn transaction_hooks_supported(&self) -> bool {
+        self.transaction_hooks
+    }
+
+    fn pre_commit(&mut self) -> WasmFdwResult<()> {
+        self.call_pre_commit()
+    }
+
+    fn abort(&mut self) -> WasmFdwResult<()> {
+        self.call_abort()
+    }
+
     fn import_foreign_schema(
         &mut self,
         stmt: ImportForeignSchemaStmt,
@@ -547,10 +957,36 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
 
     fn validator(options: Vec<Option<String>>, catalog: Option<pg_sys::Oid>) -> WasmFdwResult<()> {
         if let Some(oid) = catalog {
-            if oid == FOREIGN_SERVER_RELATION_ID {
+            if oid == FOREIGN_DATA_WRAPPER_RELATION_ID {
+                let opts: HashMap<String, String> = options
+                    .iter()
+                    .flatten()
+                    .filter_map(|o| o.split_once('='))
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                PackageVerifier::validate_fdw_options(&opts)?;
+            } else if oid == FOREIGN_SERVER_RELATION_ID {
                 check_options_contain(&options, "fdw_package_url")?;
                 check_options_contain(&options, "fdw_package_name")?;
                 check_options_contain(&options, "fdw_package_version")?;
+                let opts: HashMap<String, String> = options
+                    .iter()
+                    .flatten()
+                    .filter_map(|o| o.split_once('='))
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                ResourceLimits::from_options(&opts)?;
+                HostAllowlist::from_options(&opts)?;
+                HttpOptions::from_options(&opts)?;
+                get_batch_size("batch_size", 1, &HashMap::new(), &opts)?;
+                get_batch_size(
+                    "scan_batch_size",
+                    DEFAULT_SCAN_BATCH_SIZE,
+                    &HashMap::new(),
+                    &opts,
+                )?;
+                KvStore::new(pg_sys::Oid::INVALID, &opts)?;
+                PackageVerifier::validate_server_options(&opts)?;
                 // Require checksum for remote URLs to prevent supply chain attacks.
                 // Local file:// URLs don't need checksum since they're locally built.
                 let is_local = options
@@ -566,3 +1002,86 @@ impl ForeignDataWrapper<WasmFdwError> for WasmFdw {
         Ok(())
     }
 }
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use pgrx::prelude::*;
+
+    fn options(batch_size: Option<&str>) -> HashMap<String, String> {
+        batch_size
+            .map(|v| HashMap::from([("batch_size".to_string(), v.to_string())]))
+            .unwrap_or_default()
+    }
+
+    #[pg_test]
+    fn get_batch_size_test() {
+        let batch_size =
+            |tbl, svr| get_batch_size("batch_size", 1, &options(tbl), &options(svr)).ok();
+
+        // batch insert is disabled by default
+        assert_eq!(batch_size(None, None), Some(1));
+        assert_eq!(batch_size(None, Some("100")), Some(100));
+        assert_eq!(batch_size(Some("10"), Some("100")), Some(10));
+        assert_eq!(batch_size(Some("10"), None), Some(10));
+        assert_eq!(batch_size(Some("0"), None), None);
+        assert_eq!(batch_size(Some("-1"), None), None);
+        assert_eq!(batch_size(None, Some("many")), None);
+
+        // scan batch size has its own option and default
+        let scan_batch_size = |tbl, svr| {
+            get_batch_size(
+                "scan_batch_size",
+                DEFAULT_SCAN_BATCH_SIZE,
+                &options(tbl),
+                &options(svr),
+            )
+            .ok()
+        };
+        assert_eq!(scan_batch_size(None, None), Some(100));
+        assert_eq!(scan_batch_size(Some("10"), None), Some(100));
+        let opts = HashMap::from([("scan_batch_size".to_string(), "500".to_string())]);
+        assert_eq!(
+            get_batch_size(
+                "scan_batch_size",
+                DEFAULT_SCAN_BATCH_SIZE,
+                &HashMap::new(),
+                &opts
+            )
+            .ok(),
+            Some(500)
+        );
+    }
+
+    #[pg_test]
+    fn get_batch_columns_test() {
+        let row = |cols: &[&str]| {
+            let mut row = Row::new();
+            for col in cols {
+                row.push(col, Some(Cell::I64(1)));
+            }
+            row
+        };
+
+        assert_eq!(get_batch_columns(&[]).ok(), Some(Vec::new()));
+        assert_eq!(
+            get_batch_columns(&[row(&["id", "name"]), row(&["id", "name"])]).ok(),
+            Some(vec!["id".to_string(), "name".to_string()])
+        );
+        assert!(get_batch_columns(&[row(&["id", "name"]), row(&["id"])]).is_err());
+        assert!(get_batch_columns(&[row(&["id", "name"]), row(&["name", "id"])]).is_err());
+    }
+
+    #[pg_test]
+    fn invalid_guest_cell_test() {
+        // the error is raised as a trap in host calls, with wasm backtrace
+        // attached as context
+        let trap = wasmtime::Error::from(InvalidGuestCell("bad numeric".to_string()))
+            .context("wasm backtrace");
+        assert!(matches!(
+            WasmFdwError::from(trap),
+            WasmFdwError::GuestFdw(msg, ErrorCategory::InvalidData) if msg == "invalid cell from guest: bad numeric"
+        ));
+    }
+}
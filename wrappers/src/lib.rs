@@ -26,6 +26,12 @@ fn setup_rustls_default_crypto_provider() {
 
 pg_module_magic!();
 
+#[pg_guard]
+pub extern "C-unwind" fn _PG_init() {
+    stats::init_shared_stats();
+    supabase_wrappers::on_conflict::install_planner_hook();
+}
+
 extension_sql_file!("../sql/bootstrap.sql", bootstrap);
 extension_sql_file!("../sql/finalize.sql", finalize);
 
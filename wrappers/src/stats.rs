@@ -1,14 +1,33 @@
 //! Statistics collection module for Foreign Data Wrappers.
 //! This module provides functionality to track and report various metrics
 //! about FDW usage and performance.
+//!
+//! There are two levels of statistics:
+//! - per FDW statistics stored in the `wrappers_fdw_stats` table
+//! - per foreign table statistics stored in shared memory, which are only
+//!   available when `wrappers` is loaded in `shared_preload_libraries`
 
-use pgrx::{JsonB, prelude::*};
+use pgrx::{JsonB, lwlock::PgLwLock, pg_shmem_init, prelude::*, shmem::*};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use supabase_wrappers::hooks::{self, RequestInfo, RequestKind};
 use supabase_wrappers::prelude::report_warning;
 
 /// The name of the table storing FDW statistics
 const WRAPPERS_STATS_TABLE_NAME: &str = "wrappers_fdw_stats";
 
+/// Maximum number of foreign tables tracked in shared memory
+const MAX_TRACKED_TABLES: usize = 1024;
+
+/// Number of hash slots for the tracked foreign tables, it is larger than
+/// `MAX_TRACKED_TABLES` to keep the probe sequences short
+const STATS_SLOTS: usize = MAX_TRACKED_TABLES * 2;
+
+/// Upper bounds (inclusive, in milliseconds) of the request latency histogram
+/// buckets, the last bucket counts all requests slower than the last bound
+const LATENCY_BUCKETS_MS: [u64; 8] = [1, 5, 10, 50, 100, 500, 1000, 5000];
+const LATENCY_BUCKETS: usize = LATENCY_BUCKETS_MS.len() + 1;
+
 /// Metrics that can be collected for FDWs
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
@@ -50,8 +69,263 @@ fn get_stats_table() -> Result<String, &'static str> {
         .ok_or("fdw stats table is not created")
 }
 
+/// Statistics of one foreign table
+///
+/// The counters are atomic, so they can be updated while holding the shared
+/// memory lock in shared mode.
+#[derive(Debug, Default)]
+struct TableStats {
+    server_oid: u32,
+    // InvalidOid if the slot is not used
+    foreigntable_oid: u32,
+    requests: AtomicI64,
+    errors: AtomicI64,
+    rows_in: AtomicI64,
+    rows_out: AtomicI64,
+    bytes_in: AtomicI64,
+    bytes_out: AtomicI64,
+    total_time_us: AtomicI64,
+    latency_histogram: [AtomicI64; LATENCY_BUCKETS],
+}
+
+impl TableStats {
+    fn record_request(&self, info: &RequestInfo) {
+        let elapsed_us = info.elapsed.as_micros() as i64;
+        let elapsed_ms = info.elapsed.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| elapsed_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        // the scan of an UPDATE or DELETE target is a part of the statement,
+        // which is counted by its modification request
+        if !info.is_modify_target {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            self.latency_histogram[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        if info.is_error {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        match info.kind {
+            RequestKind::Scan => self.rows_in.fetch_add(info.rows as i64, Ordering::Relaxed),
+            RequestKind::Modify => self.rows_out.fetch_add(info.rows as i64, Ordering::Relaxed),
+        };
+        self.total_time_us.fetch_add(elapsed_us, Ordering::Relaxed);
+    }
+
+    fn is_used(&self) -> bool {
+        self.foreigntable_oid != pg_sys::InvalidOid.to_u32()
+    }
+
+    fn is_table(&self, server_oid: u32, foreigntable_oid: u32) -> bool {
+        self.server_oid == server_oid && self.foreigntable_oid == foreigntable_oid
+    }
+}
+
+/// Per foreign table statistics stored in shared memory
+///
+/// It is an open addressing hash table keyed by the server and foreign table
+/// oids. Entries are only added under the exclusive lock and never removed
+/// except by a reset, so they can be looked up under the shared lock.
+#[derive(Debug)]
+struct SharedStats {
+    len: usize,
+    slots: [TableStats; STATS_SLOTS],
+    // number of requests not recorded because the table is full
+    dropped: i64,
+}
+
+impl Default for SharedStats {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            slots: std::array::from_fn(|_| TableStats::default()),
+            dropped: 0,
+        }
+    }
+}
+
+unsafe impl PGRXSharedMemory for SharedStats {}
+
+impl SharedStats {
+    // find the slot of a foreign table, or the empty slot where it should be
+    // added, returns None if there is neither
+    fn probe(&self, server_oid: u32, foreigntable_oid: u32) -> Option<usize> {
+        let key = ((server_oid as u64) << 32) | foreigntable_oid as u64;
+        let start = (key.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32) as usize % STATS_SLOTS;
+        (0..STATS_SLOTS)
+            .map(|i| (start + i) % STATS_SLOTS)
+            .find(|pos| {
+                let slot = &self.slots[*pos];
+                !slot.is_used() || slot.is_table(server_oid, foreigntable_oid)
+            })
+    }
+
+    // find the stats entry of a foreign table
+    fn get(&self, server_oid: u32, foreigntable_oid: u32) -> Option<&TableStats> {
+        self.probe(server_oid, foreigntable_oid)
+            .map(|pos| &self.slots[pos])
+            .filter(|slot| slot.is_used())
+    }
+
+    // find the stats entry of a foreign table, or add a new one if there is
+    // free room, returns None if the table is full
+    fn entry(&mut self, server_oid: u32, foreigntable_oid: u32) -> Option<&TableStats> {
+        let pos = self.probe(server_oid, foreigntable_oid)?;
+        if !self.slots[pos].is_used() {
+            if self.len >= MAX_TRACKED_TABLES {
+                return None;
+            }
+            self.slots[pos].server_oid = server_oid;
+            self.slots[pos].foreigntable_oid = foreigntable_oid;
+            self.len += 1;
+        }
+        Some(&self.slots[pos])
+    }
+
+    // remove all entries in place, the whole struct is too big to be built
+    // on stack for every reset
+    fn reset(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = TableStats::default();
+        }
+        self.len = 0;
+        self.dropped = 0;
+    }
+}
+
+// run a function with the stats entry of a foreign table, it only takes the
+// exclusive lock when the entry needs to be added
+fn with_table_stats(server_oid: u32, foreigntable_oid: u32, f: impl FnOnce(&TableStats)) {
+    if let Some(entry) = SHARED_STATS.share().get(server_oid, foreigntable_oid) {
+        f(entry);
+        return;
+    }
+
+    let mut stats = SHARED_STATS.exclusive();
+    match stats.entry(server_oid, foreigntable_oid) {
+        Some(entry) => f(entry),
+        None => stats.dropped += 1,
+    }
+}
+
+static SHARED_STATS: PgLwLock<SharedStats> = unsafe { PgLwLock::new(c"wrappers_shared_stats") };
+
+// if the shared memory statistics is enabled in this cluster
+static SHARED_STATS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Initializes shared memory statistics
+///
+/// Shared memory can only be allocated when the extension library is loaded
+/// by `shared_preload_libraries`, otherwise this function is a no-op.
+pub(crate) fn init_shared_stats() {
+    if unsafe { !pg_sys::process_shared_preload_libraries_in_progress } {
+        return;
+    }
+
+    pg_shmem_init!(SHARED_STATS);
+    SHARED_STATS_ENABLED.store(true, Ordering::Relaxed);
+    hooks::set_request_hook(record_request);
+}
+
+// request hook called by Wrappers when a foreign table request is finished
+fn record_request(info: &RequestInfo) {
+    with_table_stats(
+        info.table.server_oid.to_u32(),
+        info.table.foreigntable_oid.to_u32(),
+        |entry| entry.record_request(info),
+    );
+}
+
+// add bytes transferred to the statistics of current foreign table
+fn inc_table_bytes(metric: Metric, inc: i64) {
+    if !matches!(metric, Metric::BytesIn | Metric::BytesOut)
+        || !SHARED_STATS_ENABLED.load(Ordering::Relaxed)
+    {
+        return;
+    }
+    let Some(table) = hooks::current_foreign_table() else {
+        return;
+    };
+
+    with_table_stats(
+        table.server_oid.to_u32(),
+        table.foreigntable_oid.to_u32(),
+        |entry| {
+            let counter = match metric {
+                Metric::BytesIn => &entry.bytes_in,
+                Metric::BytesOut => &entry.bytes_out,
+                _ => return,
+            };
+            counter.fetch_add(inc, Ordering::Relaxed);
+        },
+    );
+}
+
+/// Returns per foreign table statistics collected in shared memory
+#[allow(clippy::type_complexity)]
+#[pg_extern]
+fn wrappers_stat_foreign_tables() -> TableIterator<
+    'static,
+    (
+        name!(server_oid, pg_sys::Oid),
+        name!(foreigntable_oid, pg_sys::Oid),
+        name!(requests, i64),
+        name!(errors, i64),
+        name!(rows_in, i64),
+        name!(rows_out, i64),
+        name!(bytes_in, i64),
+        name!(bytes_out, i64),
+        name!(total_time_ms, f64),
+        name!(latency_histogram, Vec<i64>),
+    ),
+> {
+    if !SHARED_STATS_ENABLED.load(Ordering::Relaxed) {
+        report_warning("per table statistics requires wrappers in shared_preload_libraries");
+        return TableIterator::new(Vec::new());
+    }
+
+    let stats = SHARED_STATS.share();
+    if stats.dropped > 0 {
+        report_warning(&format!(
+            "only {MAX_TRACKED_TABLES} foreign tables can be tracked, {} requests on other foreign tables were not recorded, call wrappers_stat_reset() to start over",
+            stats.dropped
+        ));
+    }
+    let load = |counter: &AtomicI64| counter.load(Ordering::Relaxed);
+    let rows = stats
+        .slots
+        .iter()
+        .filter(|t| t.is_used())
+        .map(|t| {
+            (
+                t.server_oid.into(),
+                t.foreigntable_oid.into(),
+                load(&t.requests),
+                load(&t.errors),
+                load(&t.rows_in),
+                load(&t.rows_out),
+                load(&t.bytes_in),
+                load(&t.bytes_out),
+                load(&t.total_time_us) as f64 / 1000.0,
+                t.latency_histogram.iter().map(load).collect(),
+            )
+        })
+        .collect::<Vec<_>>();
+    TableIterator::new(rows)
+}
+
+/// Resets per foreign table statistics collected in shared memory
+#[pg_extern]
+fn wrappers_stat_reset() {
+    if !SHARED_STATS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    SHARED_STATS.exclusive().reset();
+}
+
 /// Checks if the current transaction is read-only
-fn is_txn_read_only() -> bool {
+pub(crate) fn is_txn_read_only() -> bool {
     Spi::get_one("show transaction_read_only") == Ok(Some("on"))
 }
 
@@ -63,9 +337,13 @@ fn is_txn_read_only() -> bool {
 /// * `inc` - The increment value
 ///
 /// # Note
-/// This function is a no-op in read-only transactions
+/// Bytes are also added to the shared memory statistics of the foreign table
+/// being scanned or modified. Updating the `wrappers_fdw_stats` table is
+/// skipped in read-only transactions.
 #[allow(dead_code)]
 pub(crate) fn inc_stats(fdw_name: &str, metric: Metric, inc: i64) {
+    inc_table_bytes(metric, inc);
+
     if is_txn_read_only() {
         return;
     }
@@ -153,3 +431,106 @@ pub(crate) fn set_metadata(fdw_name: &str, metadata: Option<JsonB>) {
         report_warning(&format!("Failed to set metadata: {err}"));
     }
 }
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use supabase_wrappers::hooks::ForeignTableRef;
+
+    fn load(counter: &AtomicI64) -> i64 {
+        counter.load(Ordering::Relaxed)
+    }
+
+    fn make_info(kind: RequestKind, elapsed_ms: u64, rows: u64) -> RequestInfo {
+        RequestInfo {
+            table: ForeignTableRef {
+                server_oid: pg_sys::Oid::from(1u32),
+                foreigntable_oid: pg_sys::Oid::from(2u32),
+            },
+            kind,
+            elapsed: Duration::from_millis(elapsed_ms),
+            rows,
+            is_error: false,
+            is_modify_target: false,
+        }
+    }
+
+    #[pg_test]
+    fn shared_stats_entry_test() {
+        let mut stats = Box::<SharedStats>::default();
+
+        // same table returns the same entry
+        stats
+            .entry(1, 10)
+            .unwrap()
+            .record_request(&make_info(RequestKind::Scan, 1, 1));
+        stats
+            .entry(1, 10)
+            .unwrap()
+            .record_request(&make_info(RequestKind::Scan, 1, 1));
+        stats
+            .entry(2, 10)
+            .unwrap()
+            .record_request(&make_info(RequestKind::Scan, 1, 1));
+        assert_eq!(stats.len, 2);
+        assert_eq!(load(&stats.get(1, 10).unwrap().requests), 2);
+        assert_eq!(load(&stats.get(2, 10).unwrap().requests), 1);
+        assert!(stats.get(3, 10).is_none());
+
+        // no more entries once the table is full, existing ones still work
+        for oid in 1..=MAX_TRACKED_TABLES as u32 {
+            stats.entry(3, oid);
+        }
+        assert_eq!(stats.len, MAX_TRACKED_TABLES);
+        assert!(stats.entry(4, 1).is_none());
+        assert!(stats.get(4, 1).is_none());
+        assert_eq!(load(&stats.get(1, 10).unwrap().requests), 2);
+        assert!(stats.get(3, MAX_TRACKED_TABLES as u32 - 2).is_some());
+
+        // reset removes all entries
+        stats.reset();
+        assert_eq!(stats.len, 0);
+        assert!(stats.get(1, 10).is_none());
+        assert_eq!(load(&stats.entry(1, 10).unwrap().requests), 0);
+    }
+
+    #[pg_test]
+    fn table_stats_record_request_test() {
+        let stats = TableStats::default();
+
+        stats.record_request(&make_info(RequestKind::Scan, 3, 10));
+        stats.record_request(&make_info(RequestKind::Modify, 7000, 2));
+        let mut info = make_info(RequestKind::Scan, 0, 0);
+        info.is_error = true;
+        stats.record_request(&info);
+
+        assert_eq!(load(&stats.requests), 3);
+        assert_eq!(load(&stats.errors), 1);
+        assert_eq!(load(&stats.rows_in), 10);
+        assert_eq!(load(&stats.rows_out), 2);
+        assert_eq!(load(&stats.total_time_us), 7_003_000);
+        assert_eq!(
+            stats.latency_histogram.iter().map(load).collect::<Vec<_>>(),
+            [1, 1, 0, 0, 0, 0, 0, 0, 1]
+        );
+    }
+
+    #[pg_test]
+    fn table_stats_update_counted_once_test() {
+        let stats = TableStats::default();
+
+        // an UPDATE reports the scan of its target rows and the modification
+        let mut scan = make_info(RequestKind::Scan, 20, 5);
+        scan.is_modify_target = true;
+        stats.record_request(&scan);
+        stats.record_request(&make_info(RequestKind::Modify, 30, 5));
+
+        assert_eq!(load(&stats.requests), 1);
+        assert_eq!(load(&stats.rows_in), 5);
+        assert_eq!(load(&stats.rows_out), 5);
+        assert_eq!(load(&stats.total_time_us), 50_000);
+        assert_eq!(stats.latency_histogram.iter().map(load).sum::<i64>(), 1);
+    }
+}